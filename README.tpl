@@ -40,6 +40,45 @@ Flute Sender python example
         #TODO Send alc_pkt over UDP/IP
 ```
 
+Configure objects and subscribe to sender events in Python
+```python
+    from flute import sender
+
+    class Subscriber:
+        def on_sender_event(self, event, now):
+            # event.kind is "StartTransfer" or "StopTransfer"
+            print(event.kind, event.toi)
+
+    sender_config = sender.Config()
+    sender_config.fdt_publish_mode = sender.FDTPublishMode.ObjectsBeingTransferred
+    sender_config.set_priority_queue(1, 2)
+
+    flute_sender = sender.Sender(1, sender.Oti.new_no_code(1400, 64), sender_config)
+    flute_sender.subscribe(Subscriber())
+
+    obj = sender.ObjectDesc.create_from_file("/path/to/file", "application/octet-stream",
+        carousel_mode=sender.CarouselRepeatMode.delay_between_transfers(1000),
+        cache_control=sender.CacheControl.expires(60000),
+        max_transfer_count=10)
+    flute_sender.add_object(obj, priority=1)
+```
+
+asyncio integration, the sockets are owned by `flute.aio`
+```python
+    import asyncio
+    from flute import sender, aio
+
+    async def send(flute_sender):
+        # Send the packets to 224.0.0.1:3400 at 10Mbit/s until the task is cancelled
+        async with aio.AsyncSender(flute_sender, "224.0.0.1", 3400, bitrate=10_000_000) as tx:
+            await tx.run()
+
+    async def receive():
+        async with aio.AsyncReceiver([("224.0.0.1", 3400)]) as rx:
+            async for obj in rx:
+                print(obj.meta.content_location, len(obj.data))
+```
+
 Flute Receiver python example
 ```python
     from flute import receiver
@@ -64,3 +103,30 @@ Flute Receiver python example
         # Push the received packet to the FLUTE receiver
         flute_receiver.push(bytes(pkt))
 ```
+
+Process received objects in Python
+```python
+    from flute import receiver
+
+    class Writer:
+        def open(self):
+            self.data = bytearray()
+
+        def write(self, sbn, data):
+            self.data += data
+
+        def complete(self):
+            print("Object received", len(self.data))
+
+        def error(self):
+            print("Reception failed")
+
+    class Builder:
+        def new_object_writer(self, tsi, toi, meta):
+            if meta.content_type != "application/json":
+                return receiver.ObjectWriterBuilderResult.abort()
+            return receiver.ObjectWriterBuilderResult.store(Writer())
+
+    receiver_writer = receiver.ObjectWriterBuilder.new_python(Builder())
+    flute_receiver = receiver.MultiReceiver(receiver_writer, receiver.Config())
+```
@@ -0,0 +1,86 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use flute::benchmark::{BenchmarkCase, BenchmarkMatrix, BlockCodec};
+use flute::core::FECEncodingID;
+
+const FEC_ENCODING_IDS: [FECEncodingID; 5] = [
+    FECEncodingID::NoCode,
+    FECEncodingID::ReedSolomonGF28,
+    FECEncodingID::ReedSolomonGF28UnderSpecified,
+    FECEncodingID::Raptor,
+    FECEncodingID::RaptorQ,
+];
+
+fn case(fec_encoding_id: FECEncodingID, file_size: usize, loss_rate: f64) -> BenchmarkCase {
+    BenchmarkCase {
+        fec_encoding_id,
+        file_size,
+        encoding_symbol_length: 1400,
+        block_length: 64,
+        parity_symbols: match fec_encoding_id {
+            FECEncodingID::NoCode => 0,
+            _ => 16,
+        },
+        loss_rate,
+        mean_burst_length: 1.0,
+    }
+}
+
+fn source_block() -> Vec<u8> {
+    (0..64 * 1400u32).map(|i| (i % 251) as u8).collect()
+}
+
+fn bench_encode(c: &mut Criterion) {
+    let block = source_block();
+    let mut group = c.benchmark_group("encode");
+    group.throughput(Throughput::Bytes(block.len() as u64));
+    for fec_encoding_id in FEC_ENCODING_IDS {
+        let codec =
+            BlockCodec::new(&case(fec_encoding_id, block.len(), 0.0).oti().unwrap()).unwrap();
+        group.bench_function(
+            BenchmarkId::from_parameter(format!("{:?}", fec_encoding_id)),
+            |b| b.iter(|| codec.encode(&block).unwrap()),
+        );
+    }
+    group.finish();
+}
+
+fn bench_decode(c: &mut Criterion) {
+    let block = source_block();
+    let mut group = c.benchmark_group("decode");
+    group.throughput(Throughput::Bytes(block.len() as u64));
+    for fec_encoding_id in FEC_ENCODING_IDS {
+        let codec =
+            BlockCodec::new(&case(fec_encoding_id, block.len(), 0.0).oti().unwrap()).unwrap();
+        let symbols = codec.encode(&block).unwrap();
+        // Half of the repair symbols replace lost source symbols
+        let nb_repair_symbols = symbols.len() - 64;
+        let symbols = &symbols[nb_repair_symbols / 2..];
+        group.bench_function(
+            BenchmarkId::from_parameter(format!("{:?}", fec_encoding_id)),
+            |b| b.iter(|| codec.decode(0, block.len(), symbols).unwrap().unwrap()),
+        );
+    }
+    group.finish();
+}
+
+fn bench_reception(c: &mut Criterion) {
+    let file_size = 256 * 1024;
+    let matrix = BenchmarkMatrix {
+        nb_runs: 1,
+        ..Default::default()
+    };
+    let mut group = c.benchmark_group("reception");
+    group.sample_size(10);
+    group.throughput(Throughput::Bytes(file_size as u64));
+    for fec_encoding_id in FEC_ENCODING_IDS {
+        let case = case(fec_encoding_id, file_size, 0.05);
+        group.bench_function(
+            BenchmarkId::from_parameter(format!("{:?}", fec_encoding_id)),
+            |b| b.iter(|| matrix.simulate_transfers(&case).unwrap()),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_encode, bench_decode, bench_reception);
+criterion_main!(benches);
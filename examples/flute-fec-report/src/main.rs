@@ -0,0 +1,140 @@
+use flute::benchmark::{to_csv, to_markdown, BenchmarkMatrix};
+use flute::core::FECEncodingID;
+use std::str::FromStr;
+
+const USAGE: &str = "Usage: flute-fec-report [OPTIONS]
+
+Compare the FEC schemes: encoding and decoding throughput, overhead
+and probability to receive a file through a lossy channel
+
+Options:
+  --fec LIST             FEC schemes among nocode,rs28,rs28us,raptor,raptorq (default: all)
+  --file-sizes LIST      File sizes in bytes (default: 102400,1048576)
+  --symbol-lengths LIST  Encoding symbol lengths in bytes (default: 1400)
+  --block-lengths LIST   Maximum source block lengths in symbols (default: 50,200)
+  --repair-ratios LIST   Ratios of repair symbols per block (default: 0.25)
+  --loss-rates LIST      Average packet loss rates (default: 0,0.01,0.05,0.1)
+  --burst LENGTH         Mean length of the loss bursts, 1 for independent losses (default: 1)
+  --runs N               Number of simulated transfers per case (default: 10)
+  --seed N               Seed of the channel (default: 0)
+  --csv FILE             Write the report as CSV
+  --markdown FILE        Write the report as a markdown table
+  --help                 Print this help
+
+Without --csv and --markdown, the markdown table is printed";
+
+#[derive(Debug, Default)]
+struct Args {
+    matrix: BenchmarkMatrix,
+    csv: Option<String>,
+    markdown: Option<String>,
+}
+
+fn parse_fec(name: &str) -> Result<FECEncodingID, String> {
+    match name {
+        "nocode" => Ok(FECEncodingID::NoCode),
+        "rs28" => Ok(FECEncodingID::ReedSolomonGF28),
+        "rs28us" => Ok(FECEncodingID::ReedSolomonGF28UnderSpecified),
+        "raptor" => Ok(FECEncodingID::Raptor),
+        "raptorq" => Ok(FECEncodingID::RaptorQ),
+        _ => Err(format!("Unknown FEC scheme {}", name)),
+    }
+}
+
+fn parse_value<T: FromStr>(value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("Invalid value {}", value))
+}
+
+fn parse_list<T: FromStr>(value: &str) -> Result<Vec<T>, String> {
+    value.split(',').map(|v| parse_value(v.trim())).collect()
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = Args::default();
+    let mut it = std::env::args().skip(1);
+    while let Some(arg) = it.next() {
+        let mut value = || it.next().ok_or(format!("Missing value of {}", arg));
+        let matrix = &mut args.matrix;
+        match arg.as_str() {
+            "--fec" => {
+                matrix.fec_encoding_ids = value()?
+                    .split(',')
+                    .map(|name| parse_fec(name.trim()))
+                    .collect::<Result<_, _>>()?
+            }
+            "--file-sizes" => matrix.file_sizes = parse_list(&value()?)?,
+            "--symbol-lengths" => matrix.encoding_symbol_lengths = parse_list(&value()?)?,
+            "--block-lengths" => matrix.block_lengths = parse_list(&value()?)?,
+            "--repair-ratios" => matrix.repair_ratios = parse_list(&value()?)?,
+            "--loss-rates" => matrix.loss_rates = parse_list(&value()?)?,
+            "--burst" => matrix.mean_burst_length = parse_value(&value()?)?,
+            "--runs" => matrix.nb_runs = parse_value(&value()?)?,
+            "--seed" => matrix.seed = parse_value(&value()?)?,
+            "--csv" => args.csv = Some(value()?),
+            "--markdown" => args.markdown = Some(value()?),
+            "--help" | "-h" => {
+                println!("{}", USAGE);
+                std::process::exit(0);
+            }
+            _ => return Err(format!("Unknown option {}", arg)),
+        }
+    }
+    Ok(args)
+}
+
+fn main() {
+    env_logger::builder().try_init().ok();
+
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            std::process::exit(1);
+        }
+    };
+
+    let cases = args.matrix.cases();
+    if cases.is_empty() {
+        eprintln!("No valid case in the matrix");
+        std::process::exit(1);
+    }
+
+    let mut results = Vec::new();
+    for (i, case) in cases.iter().enumerate() {
+        eprintln!(
+            "[{}/{}] {:?} file={} symbol={} block={} parity={} loss={}",
+            i + 1,
+            cases.len(),
+            case.fec_encoding_id,
+            case.file_size,
+            case.encoding_symbol_length,
+            case.block_length,
+            case.parity_symbols,
+            case.loss_rate
+        );
+        match args.matrix.run_case(case) {
+            Ok(result) => results.push(result),
+            Err(e) => eprintln!("Fail to run the case: {}", e.0),
+        }
+    }
+
+    let mut outputs = Vec::new();
+    if let Some(path) = &args.csv {
+        outputs.push((path, to_csv(&results)));
+    }
+    if let Some(path) = &args.markdown {
+        outputs.push((path, to_markdown(&results)));
+    }
+    if outputs.is_empty() {
+        print!("{}", to_markdown(&results));
+    }
+
+    for (path, content) in outputs {
+        if let Err(e) = std::fs::write(path, content) {
+            eprintln!("Fail to write {}: {}", path, e);
+            std::process::exit(1);
+        }
+    }
+}
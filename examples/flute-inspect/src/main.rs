@@ -0,0 +1,196 @@
+use flute::{
+    capture::PcapReader,
+    core::UDPEndpoint,
+    inspect::{InspectEvent, Inspector, ObjectInfo},
+    net::{Interface, ReceiverSocket, SocketConfig},
+};
+use std::net::{IpAddr, SocketAddr};
+use std::time::{Duration, Instant, SystemTime};
+
+const USAGE: &str = "Usage: flute-inspect [OPTIONS] <--pcap FILE | --listen ADDR:PORT>
+
+Decode the ALC/LCT packets of FLUTE sessions
+
+Options:
+  --pcap FILE          Read the packets of a pcap or pcapng capture
+  --listen ADDR:PORT   Receive the packets of a UDP socket, multicast groups are joined
+  --source ADDR        Join the group with source-specific multicast, only the packets sent by ADDR are kept
+  --interface IFACE    Name or address of the interface that joins the multicast group
+  --json               Print the events as JSON lines
+  --quiet              Do not print the packets, only the FDT Instances and the objects
+  --summary SECS       Print the reception state of the objects every SECS seconds (live socket)
+  --help               Print this help";
+
+#[derive(Debug, Default)]
+struct Args {
+    pcap: Option<String>,
+    listen: Option<SocketAddr>,
+    source: Option<IpAddr>,
+    interface: Option<String>,
+    json: bool,
+    quiet: bool,
+    summary: Option<Duration>,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = Args::default();
+    let mut it = std::env::args().skip(1);
+    while let Some(arg) = it.next() {
+        let mut value = || it.next().ok_or(format!("Missing value of {}", arg));
+        match arg.as_str() {
+            "--pcap" => args.pcap = Some(value()?),
+            "--listen" => {
+                let addr = value()?;
+                args.listen = Some(
+                    addr.parse()
+                        .map_err(|_| format!("Invalid address {}", addr))?,
+                );
+            }
+            "--source" => {
+                let addr = value()?;
+                args.source = Some(
+                    addr.parse()
+                        .map_err(|_| format!("Invalid address {}", addr))?,
+                );
+            }
+            "--interface" => args.interface = Some(value()?),
+            "--json" => args.json = true,
+            "--quiet" => args.quiet = true,
+            "--summary" => {
+                let secs = value()?;
+                let secs: f64 = secs
+                    .parse()
+                    .map_err(|_| format!("Invalid duration {}", secs))?;
+                args.summary = Some(
+                    Duration::try_from_secs_f64(secs)
+                        .map_err(|_| format!("Invalid duration {}", secs))?,
+                );
+            }
+            "--help" | "-h" => {
+                println!("{}", USAGE);
+                std::process::exit(0);
+            }
+            _ => return Err(format!("Unknown option {}", arg)),
+        }
+    }
+
+    if args.pcap.is_some() == args.listen.is_some() {
+        return Err("One of --pcap or --listen is required".to_string());
+    }
+    Ok(args)
+}
+
+fn print_event(args: &Args, event: &InspectEvent) {
+    if args.quiet && matches!(event, InspectEvent::Packet(_)) {
+        return;
+    }
+
+    match args.json {
+        true => match serde_json::to_string(event) {
+            Ok(json) => println!("{}", json),
+            Err(e) => log::error!("Fail to serialize event: {}", e),
+        },
+        false => println!("{}", event),
+    }
+}
+
+fn print_objects(args: &Args, objects: &[ObjectInfo]) {
+    for object in objects {
+        match args.json {
+            true => {
+                let mut value = match serde_json::to_value(object) {
+                    Ok(value) => value,
+                    Err(e) => {
+                        log::error!("Fail to serialize object: {}", e);
+                        continue;
+                    }
+                };
+                if let Some(map) = value.as_object_mut() {
+                    map.insert("type".to_string(), "Object".into());
+                }
+                println!("{}", value);
+            }
+            false => println!("{}", object),
+        }
+    }
+}
+
+fn inspect_pcap(args: &Args, path: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let file = std::io::BufReader::new(std::fs::File::open(path)?);
+    let mut reader = PcapReader::new(file).map_err(|e| e.0)?;
+    let mut inspector = Inspector::new();
+
+    while let Some(pkt) = reader.next_packet().map_err(|e| e.0)? {
+        for event in inspector.push(&pkt.endpoint, &pkt.data, pkt.timestamp) {
+            print_event(args, &event);
+        }
+    }
+
+    print_objects(args, &inspector.objects());
+    Ok(())
+}
+
+fn inspect_socket(args: &Args, addr: &SocketAddr) -> Result<(), Box<dyn std::error::Error>> {
+    let endpoint = UDPEndpoint::from_addrs(args.source, addr.ip(), addr.port());
+    let config = SocketConfig {
+        interface: args
+            .interface
+            .as_deref()
+            .map(Interface::parse)
+            .unwrap_or_default(),
+        ..Default::default()
+    };
+    let socket = ReceiverSocket::new(&endpoint, &config).map_err(|e| e.0)?;
+    socket
+        .socket()
+        .set_read_timeout(Some(Duration::from_millis(500)))?;
+    log::info!("Listening on {}", addr);
+
+    let mut inspector = Inspector::new();
+    let mut last_summary = Instant::now();
+    let mut buf = vec![0u8; 65536];
+
+    loop {
+        match socket.recv_from(&mut buf) {
+            Ok((size, _from)) => {
+                for event in inspector.push(&endpoint, &buf[..size], SystemTime::now()) {
+                    print_event(args, &event);
+                }
+            }
+            Err(e)
+                if e.kind() == std::io::ErrorKind::WouldBlock
+                    || e.kind() == std::io::ErrorKind::TimedOut => {}
+            Err(e) => return Err(e.into()),
+        }
+
+        if let Some(interval) = args.summary {
+            if last_summary.elapsed() >= interval {
+                print_objects(args, &inspector.objects());
+                last_summary = Instant::now();
+            }
+        }
+    }
+}
+
+fn main() {
+    env_logger::builder().try_init().ok();
+
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            std::process::exit(1);
+        }
+    };
+
+    let result = match (&args.pcap, &args.listen) {
+        (Some(path), _) => inspect_pcap(&args, path),
+        (_, Some(addr)) => inspect_socket(&args, addr),
+        _ => unreachable!(),
+    };
+
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
@@ -18,7 +18,6 @@ struct AppConfig {
 struct ReceiverConfigSection {
     network: ReceiverNetworkConfig,
     storage: ReceiverStorageConfig,
-    logging: ReceiverLoggingConfig,
     advanced: ReceiverAdvancedConfig,
 }
 
@@ -38,16 +37,10 @@ struct ReceiverStorageConfig {
     enable_md5_check: bool,
 }
 
-#[derive(Debug, Deserialize)]
-struct ReceiverLoggingConfig {
-    progress_interval: u32,
-}
-
 #[derive(Debug, Deserialize)]
 struct ReceiverAdvancedConfig {
     buffer_size: usize,
     cleanup_interval: u32,
-    log_interval: u32,
     max_memory_mb: u64,
 
 }
@@ -270,7 +263,6 @@ fn main() {
     log::info!("  - 缓冲区大小: {} KB", config.receiver.advanced.buffer_size / 1024);
     log::info!("  - 最大内存限制: {} MB", config.receiver.advanced.max_memory_mb);
     log::info!("  - 清理间隔: {} packets", config.receiver.advanced.cleanup_interval);
-    log::info!("  - 日志间隔: {} packets", config.receiver.advanced.log_interval);
     log::info!("  - MD5检查: {}", config.receiver.storage.enable_md5_check);
     log::info!("====================");
 
@@ -325,31 +317,17 @@ fn main() {
     let _max_memory_bytes = config.receiver.advanced.max_memory_mb * 1024 * 1024;
     let mut memory_usage: u64 = 0;
     let mut packet_errors = 0;
-    let start_time = std::time::Instant::now();
 
     log::info!("🚀 Starting packet reception loop with buffer size: {} bytes", config.receiver.advanced.buffer_size);
     log::info!("📡 Waiting for packets from {}...", config.receiver.network.sender_ip.as_ref().unwrap_or(&"any".to_string()));
 
     loop {
         match socket.recv_from(&mut buf) {
-            Ok((n, src)) => {
-                // 第一个包特殊处理
-                if received_packets == 0 {
-                    log::info!("🎉 First packet received! Source: {}, Size: {} bytes", src, n);
-                }
-                
+            Ok((n, _src)) => {
                 received_packets += 1;
                 memory_usage += n as u64;
 
-                // 每100个包输出一次状态
-                if received_packets % 100 == 0 {
-                    let elapsed_secs = start_time.elapsed().as_secs_f64().max(0.001);
-                    let rate_pps = received_packets as f64 / elapsed_secs;
-                    log::info!("📥 Progress: {} packets, {:.0} pps, {:.1} MB total", 
-                              received_packets, rate_pps, memory_usage as f64 / (1024.0 * 1024.0));
-                }
-
-                // 🧠 智能内存管理 - 分级清理策略
+                // Force a cleanup when the memory limit is exceeded
                 let memory_usage_mb = memory_usage / (1024 * 1024);
                 let memory_limit_mb = config.receiver.advanced.max_memory_mb;
                 
@@ -361,35 +339,12 @@ fn main() {
                     let now = SystemTime::now();
                     receiver.cleanup(now);
                     memory_usage = 0;
-                } else if memory_usage_mb > (memory_limit_mb * 3 / 4) {
-                    // 75% 时预警但不清理
-                    if received_packets % (config.receiver.advanced.log_interval * 10) == 0 {
-                        log::info!("⚠️  Memory usage approaching limit: {}MB / {}MB ({:.1}%)", 
-                                  memory_usage_mb, memory_limit_mb, 
-                                  (memory_usage_mb as f64 / memory_limit_mb as f64) * 100.0);
-                    }
                 }
 
-                // if received_packets % config.receiver.advanced.log_interval == 0 {
-                //     let elapsed_secs = match start_time.elapsed() {
-                //         Ok(dur) => dur.as_secs_f64(),
-                //         Err(_) => 1.0,
-                //     }.max(0.001); // 避免除零
-                //     let rate_pps = received_packets as f64 / elapsed_secs;
-                //     let rate_mbps = (memory_usage as f64 * 8.0) / (1024.0 * 1024.0) / elapsed_secs;
-                //     log::info!("📥 Received {} packets from {} | Memory: {:.1}MB | PacketSize: {} | Rate: {:.0} pps ({:.1} Mbps) | Errors: {}", 
-                //               received_packets, src, memory_usage as f64 / (1024.0 * 1024.0), n, rate_pps, rate_mbps, packet_errors);
-                // }
-
                 let now = SystemTime::now();
                 if let Err(e) = receiver.push(&endpoint, &buf[..n], now) {
                     packet_errors += 1;
                     log::warn!("❌ Packet processing error #{}: {:?}", packet_errors, e);
-                    if packet_errors % 100 == 0 {
-                        log::error!("🚨 累计包处理错误: {} | 成功包: {} | 错误率: {:.2}%", 
-                                   packet_errors, received_packets, 
-                                   (packet_errors as f64 / (received_packets + packet_errors) as f64) * 100.0);
-                    }
                     continue;
                 }
 
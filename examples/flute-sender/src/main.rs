@@ -2,7 +2,10 @@ use flute::{
     core::lct::Cenc,
     core::Oti,
     core::UDPEndpoint,
-    sender::{Config as SenderConfig, ObjectDesc, Sender},
+    sender::{
+        CarouselRepeatMode, Config as SenderConfig, DirectoryConfig, DirectorySender, ObjectDesc,
+        Sender,
+    },
 };
 use serde::Deserialize;
 use std::fs;
@@ -21,8 +24,10 @@ struct SenderConfigSection {
     network: SenderNetworkConfig,
     fec: SenderFecConfig,
     flute: SenderFluteConfig,
-    logging: SenderLoggingConfig,
+    #[serde(default)]
     files: Vec<FileConfig>,
+    // Hot-folder mode: broadcast the content of a directory instead of `files`
+    watch: Option<WatchConfig>,
     // New param
     max_rate_kbps: Option<u32>,        // 最大速率限制 (kbps)
 }
@@ -53,11 +58,18 @@ struct SenderFecConfig {
 struct SenderFluteConfig {
     tsi: u32,
     interleave_blocks: u32,
+    split_large_objects: Option<bool>,
 }
 
 #[derive(Debug, Deserialize)]
-struct SenderLoggingConfig {
-    progress_interval: u32,
+struct WatchConfig {
+    path: String,
+    base_url: Option<String>,
+    content_type: Option<String>,
+    scan_interval_ms: Option<u64>,
+    min_file_age_ms: Option<u64>,
+    // Keep the files in a carousel, delay between two transfers
+    carousel_delay_ms: Option<u64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -263,6 +275,57 @@ fn validate_raptorq_transfer_limits(
     log::info!("========================");
 }
 
+/// Broadcast the content of a directory, the FDT is updated when files are added, modified or deleted
+fn run_watch_directory(
+    watch: &WatchConfig,
+    endpoint: UDPEndpoint,
+    tsi: u64,
+    oti: &Oti,
+    sender_config: &SenderConfig,
+    udp_socket: &UdpSocket,
+    send_interval_micros: u64,
+) {
+    let mut directory_config = DirectoryConfig {
+        path: Path::new(&watch.path).to_path_buf(),
+        ..Default::default()
+    };
+    if let Some(base_url) = &watch.base_url {
+        directory_config.base_url = url::Url::parse(base_url).expect("Invalid base_url");
+    }
+    if let Some(content_type) = &watch.content_type {
+        directory_config.content_type = content_type.clone();
+    }
+    if let Some(scan_interval_ms) = watch.scan_interval_ms {
+        directory_config.scan_interval = Duration::from_millis(scan_interval_ms);
+    }
+    if let Some(min_file_age_ms) = watch.min_file_age_ms {
+        directory_config.min_file_age = Duration::from_millis(min_file_age_ms);
+    }
+    if let Some(carousel_delay_ms) = watch.carousel_delay_ms {
+        directory_config.carousel_mode = Some(CarouselRepeatMode::DelayBetweenTransfers(
+            Duration::from_millis(carousel_delay_ms),
+        ));
+    }
+
+    let mut sender = DirectorySender::new(endpoint, tsi, oti, sender_config, directory_config)
+        .expect("Fail to watch directory");
+
+    log::info!("Watching directory {}", watch.path);
+    loop {
+        match sender.read(SystemTime::now()) {
+            Some(pkt) => {
+                if let Err(e) = udp_socket.send(&pkt) {
+                    log::error!("Failed to send packet: {}", e);
+                }
+                if send_interval_micros > 0 {
+                    std::thread::sleep(Duration::from_micros(send_interval_micros));
+                }
+            }
+            None => std::thread::sleep(Duration::from_millis(10)),
+        }
+    }
+}
+
 fn main() {
 
     std::env::set_var("RUST_LOG", "info");
@@ -543,6 +606,23 @@ fn main() {
     log::info!("Max symbol alignment: {}", oti.max_number_of_parity_symbols);
     let mut sender_config = SenderConfig::default();
     sender_config.interleave_blocks = config.sender.flute.interleave_blocks.try_into().unwrap();
+    sender_config.split_large_objects = config.sender.flute.split_large_objects.unwrap_or(false);
+
+    if let Some(watch) = &config.sender.watch {
+        udp_socket
+            .connect(&config.sender.network.destination)
+            .unwrap();
+        run_watch_directory(
+            watch,
+            endpoint,
+            tsi.into(),
+            &oti,
+            &sender_config,
+            &udp_socket,
+            config.sender.network.send_interval_micros,
+        );
+        return;
+    }
 
     let mut sender = Sender::new(endpoint, tsi.into(), &oti, &sender_config);
 
@@ -618,11 +698,6 @@ fn main() {
     // 用“下一次应发送时间”做节拍
     let mut next_send_at = Instant::now();
 
-    // 日志辅助
-    let mut last_log_time = Instant::now();
-    let mut bytes_since_log: u64 = 0;
-    let mut packets_since_log: u64 = 0;
-
     while let Some(pkt) = sender.read(SystemTime::now()) {
         if send_interval_micros > 0 {
             std::thread::sleep(Duration::from_micros(send_interval_micros));
@@ -643,40 +718,12 @@ fn main() {
         match udp_socket.send(&pkt) {
             Ok(bytes_sent) => {
                 total_bytes_sent += bytes_sent as u64;
-                bytes_since_log += bytes_sent as u64;
-                packets_since_log += 1;
                 sent_packets += 1;
-
-                // 按进度间隔打印统计
-                if sent_packets % config.sender.logging.progress_interval as u64 == 0 {
-                    let now = Instant::now();
-                    let dt = now.duration_since(last_log_time).as_secs_f64();
-                    if dt > 0.0 {
-                        let inst_mbps = (bytes_since_log as f64 * 8.0) / dt / 1_000_000.0;
-                        let avg_mbps = (total_bytes_sent as f64 * 8.0)
-                            / now.duration_since(start_time).as_secs_f64() / 1_000_000.0;
-                        let pps = packets_since_log as f64 / dt;
-
-                        log::info!(
-                        "Progress: {} pkts, {} MB | Instant: {:.2} Mbps | Avg: {:.2} Mbps | PPS: {:.0}",
-                        sent_packets,
-                        total_bytes_sent / (1024 * 1024),
-                        inst_mbps,
-                        avg_mbps,
-                        pps
-                    );
-                    }
-                    last_log_time = now;
-                    bytes_since_log = 0;
-                    packets_since_log = 0;
-                }
             }
             Err(e) => {
                 log::error!("Failed to send packet: {}", e);
             }
         }
-
-        // ✅ 这里不要再做额外的 sleep（删除你原来的 network.send_interval_micros 睡眠）
     }
 
     // 传输完成后的详细统计
@@ -80,6 +80,24 @@ class SenderReceiverTestCase(TestCase):
         flute_receiver = receiver.Receiver(udp_endpoint, 1, writer, config)
         print("Flute Receiver created !")
 
+    def test_udp_endpoint(self):
+        from flute import receiver
+        print("------- test_udp_endpoint--------")
+        udp_endpoint = receiver.UDPEndpoint.try_new("FF3E:0::1234", 3400, "0:0::1")
+        self.assertEqual(udp_endpoint.destination_group_address, "ff3e::1234")
+        self.assertEqual(udp_endpoint.source_address, "::1")
+        self.assertEqual(udp_endpoint.port, 3400)
+
+        with self.assertRaises(ValueError):
+            receiver.UDPEndpoint.try_new("224.0.0.1:3400", 3400)
+
+        with self.assertRaises(ValueError):
+            receiver.UDPEndpoint.try_new("224.0.0.1", 3400, "::1")
+
+        # The constructor keeps the addresses as given
+        udp_endpoint = receiver.UDPEndpoint("FF3E:0::1234", 3400)
+        self.assertEqual(udp_endpoint.destination_group_address, "FF3E:0::1234")
+
     def test_create_multireceiver(self):
         from flute import receiver
         print("------- test_create_multireceiver--------")
@@ -192,5 +210,217 @@ class SenderReceiverTestCase(TestCase):
         assert(lct.sbn == 0)
         assert(lct.esi == 0)
 
+    def test_buffer_objects(self):
+        from flute import sender, receiver
+
+        print("------- test_buffer_objects--------")
+
+        tsi = 1
+        flute_sender = sender.Sender(tsi, sender.Oti.new_no_code(1400, 64), sender.Config())
+        writer = receiver.ObjectWriterBuilder.new_buffer()
+        udp_endpoint = receiver.UDPEndpoint("224.0.0.1", 1234)
+        flute_receiver = receiver.Receiver(udp_endpoint, tsi, writer, receiver.Config())
+
+        buf = bytes(b'hello world')
+        flute_sender.add_object_from_buffer(buf, "text/plain", "file:///hello.txt", None)
+        flute_sender.publish()
+
+        while True:
+            pkt = flute_sender.read()
+            if pkt == None:
+                break
+            flute_receiver.push(bytes(pkt))
+
+        objects = writer.objects()
+        assert(len(objects) == 1)
+        assert(objects[0].complete)
+        assert(not objects[0].error)
+        assert(objects[0].data == buf)
+        assert(objects[0].meta.content_location == "file:///hello.txt")
+        assert(objects[0].meta.content_type == "text/plain")
+
+    def test_python_writer(self):
+        from flute import sender, receiver
+
+        print("------- test_python_writer--------")
+
+        class Writer:
+            def __init__(self, meta):
+                self.meta = meta
+                self.data = {}
+                self.state = None
+
+            def open(self):
+                self.state = "open"
+
+            def write(self, sbn, data):
+                self.data[sbn] = self.data.get(sbn, b'') + data
+
+            def complete(self):
+                self.state = "complete"
+
+            def error(self):
+                self.state = "error"
+
+        class Builder:
+            def __init__(self):
+                self.writers = []
+                self.fdts = 0
+
+            def new_object_writer(self, tsi, toi, meta):
+                if meta.content_location.endswith("skip.txt"):
+                    return receiver.ObjectWriterBuilderResult.skip()
+                writer = Writer(meta)
+                self.writers.append(writer)
+                return receiver.ObjectWriterBuilderResult.store(writer)
+
+            def fdt_received(self, tsi, fdt_xml):
+                self.fdts += 1
+
+        tsi = 1
+        flute_sender = sender.Sender(tsi, sender.Oti.new_no_code(1400, 64), sender.Config())
+        builder = Builder()
+        writer = receiver.ObjectWriterBuilder.new_python(builder)
+        flute_receiver = receiver.MultiReceiver(writer, receiver.Config())
+
+        buf = bytes(b'hello world')
+        flute_sender.add_object_from_buffer(buf, "text/plain", "file:///hello.txt", None)
+        flute_sender.add_object_from_buffer(buf, "text/plain", "file:///skip.txt", None)
+        flute_sender.publish()
+
+        udp_endpoint = receiver.UDPEndpoint("224.0.0.1", 1234)
+        while True:
+            pkt = flute_sender.read()
+            if pkt == None:
+                break
+            flute_receiver.push(udp_endpoint, bytes(pkt))
+
+        assert(builder.fdts > 0)
+        assert(len(builder.writers) == 1)
+        writer = builder.writers[0]
+        assert(writer.state == "complete")
+        assert(b''.join(writer.data[sbn] for sbn in sorted(writer.data)) == buf)
+        assert(writer.meta.content_location == "file:///hello.txt")
+        assert(writer.meta.content_length == len(buf))
+
+    def test_sender_config(self):
+        from flute import sender
+        print("------- test_sender_config--------")
+        config = sender.Config()
+        config.fdt_publish_mode = sender.FDTPublishMode.ObjectsBeingTransferred
+        config.fdt_carousel_mode = sender.CarouselRepeatMode.interval_between_start_times(500)
+        config.profile = sender.Profile.RFC3926
+        config.toi_max_length = sender.TOIMaxLength.ToiMax32
+        config.toi_initial_value = 100
+        config.groups = ["group1"]
+        config.set_priority_queue(1, 2)
+        assert(config.priority_queues == {0: 3, 1: 2})
+        config.remove_priority_queue(1)
+        assert(config.priority_queues == {0: 3})
+        assert(config.fdt_publish_mode == sender.FDTPublishMode.ObjectsBeingTransferred)
+        assert(config.profile == sender.Profile.RFC3926)
+        assert(config.toi_max_length == sender.TOIMaxLength.ToiMax32)
+        assert(config.groups == ["group1"])
+
+        flute_sender = sender.Sender(1, sender.Oti.new_no_code(1400, 64), config)
+        toi = flute_sender.allocate_toi()
+        assert(toi.value == 100)
+
+        obj = sender.ObjectDesc.create_from_buffer(b'hello', "text/plain", "file:///hello.txt",
+            max_transfer_count=2,
+            carousel_mode=sender.CarouselRepeatMode.delay_between_transfers(100),
+            target_acquisition=sender.TargetAcquisition.within_duration(1000),
+            cache_control=sender.CacheControl.max_stale(),
+            groups=["group2"])
+        obj.set_toi(toi)
+        assert(obj.toi == 100)
+        assert(obj.content_length == 5)
+        assert(obj.groups == ["group2"])
+
+        assert(flute_sender.add_object(obj, priority=0) == 100)
+        assert(flute_sender.is_added(100))
+        with self.assertRaises(TypeError):
+            flute_sender.add_object(obj)
+        with self.assertRaises(TypeError):
+            obj.groups
+
+    def test_sender_events(self):
+        from flute import sender, receiver
+        print("------- test_sender_events--------")
+
+        class Subscriber:
+            def __init__(self):
+                self.events = []
+
+            def on_sender_event(self, event, now):
+                self.events.append((event.kind, event.toi))
+
+        subscriber = Subscriber()
+        flute_sender = sender.Sender(1, sender.Oti.new_no_code(1400, 64), sender.Config())
+        flute_sender.subscribe(subscriber)
+
+        obj = sender.ObjectDesc.create_from_buffer(b'hello world', "text/plain", "file:///hello.txt")
+        obj.e_tag = "etag"
+        toi = flute_sender.add_object(obj)
+        flute_sender.publish()
+        assert(b'etag' in bytes(flute_sender.fdt_xml_data()))
+
+        writer = receiver.ObjectWriterBuilder.new_buffer()
+        udp_endpoint = receiver.UDPEndpoint("224.0.0.1", 1234)
+        flute_receiver = receiver.Receiver(udp_endpoint, 1, writer, receiver.Config())
+        while True:
+            pkt = flute_sender.read()
+            if pkt == None:
+                break
+            flute_receiver.push(bytes(pkt))
+
+        assert(("StartTransfer", toi) in subscriber.events)
+        assert(("StopTransfer", toi) in subscriber.events)
+        assert(writer.objects()[0].data == b'hello world')
+
+        flute_sender.unsubscribe(subscriber)
+        subscriber.events.clear()
+        obj = sender.ObjectDesc.create_from_buffer(b'hello', "text/plain", "file:///hello2.txt")
+        flute_sender.add_object(obj)
+        flute_sender.publish()
+        while flute_sender.read() != None:
+            pass
+        assert(len(subscriber.events) == 0)
+
+    def test_asyncio(self):
+        import asyncio
+        from flute import sender, aio
+        print("------- test_asyncio--------")
+
+        buf = bytes(b'hello asyncio') * 1000
+
+        async def run(address):
+            async with aio.AsyncReceiver([(address, 0)]) as flute_receiver:
+                port = flute_receiver.local_ports[0]
+                assert(port != 0)
+                flute_sender = sender.Sender(1, sender.Oti.new_no_code(1400, 64), sender.Config())
+                async with aio.AsyncSender(flute_sender, address, port, bitrate=10_000_000) as tx:
+                    flute_sender.add_object_from_buffer(buf, "text/plain", "file:///hello.txt", None)
+                    flute_sender.publish()
+                    await tx.flush()
+                    obj = await asyncio.wait_for(flute_receiver.__anext__(), 5)
+                    assert(obj.data == buf)
+                    assert(obj.tsi == 1)
+                    assert(obj.meta.content_location == "file:///hello.txt")
+
+                flute_receiver.close()
+                remaining = [obj async for obj in flute_receiver]
+                assert(len(remaining) == 0)
+
+        asyncio.run(run("127.0.0.1"))
+
+        import socket
+        if socket.has_ipv6:
+            try:
+                socket.socket(socket.AF_INET6, socket.SOCK_DGRAM).bind(("::1", 0))
+            except OSError:
+                return
+            asyncio.run(run("::1"))
+
 if __name__ == '__main__':
     unittest.main()
@@ -0,0 +1,5 @@
+"""FLUTE - File Delivery over Unidirectional Transport"""
+
+from .flute import receiver, sender
+
+__all__ = ["aio", "receiver", "sender"]
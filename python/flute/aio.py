@@ -0,0 +1,295 @@
+"""asyncio integration of the FLUTE sender and receiver
+
+`AsyncSender` paces the ALC/LCT packets of a `sender.Sender` over a UDP socket.
+`AsyncReceiver` feeds a `receiver.MultiReceiver` from UDP sockets and yields the
+completed objects as an async iterator.
+"""
+
+import asyncio
+import ipaddress
+import logging
+import socket
+import struct
+import sys
+
+from .flute import receiver
+
+__all__ = ["AsyncSender", "AsyncReceiver", "ReceivedObject"]
+
+logger = logging.getLogger(__name__)
+
+
+def _is_multicast(address):
+    try:
+        return ipaddress.ip_address(address).is_multicast
+    except ValueError:
+        return False
+
+
+def _family(address):
+    try:
+        if ipaddress.ip_address(address).version == 6:
+            return socket.AF_INET6
+    except ValueError:
+        pass
+    return socket.AF_INET
+
+
+# Value of IP_ADD_SOURCE_MEMBERSHIP on Linux, not exported by every Python build
+_IP_ADD_SOURCE_MEMBERSHIP = getattr(socket, "IP_ADD_SOURCE_MEMBERSHIP", 39)
+
+
+def _ip_mreq_source(group, interface, source):
+    group = socket.inet_aton(group)
+    interface = socket.inet_aton(interface)
+    source = socket.inet_aton(source)
+    # struct ip_mreq_source: the fields are not in the same order on Linux and on BSD / macOS
+    if sys.platform.startswith("linux"):
+        return group + interface + source
+    return group + source + interface
+
+
+class AsyncSender:
+    """Send the packets of a `sender.Sender` to `address:port`
+
+    `bitrate` (bits/s) limits the output rate, `None` sends as fast as possible.
+    When the sender has no packet to transfer, it is polled every `idle_interval` seconds.
+    `interface` is the IPv4 address of the multicast interface, or its index for IPv6.
+    """
+
+    def __init__(self, flute_sender, address, port, bitrate=None,
+                 idle_interval=0.01, interface=None, ttl=1):
+        self.sender = flute_sender
+        self.address = address
+        self.port = port
+        self.bitrate = bitrate
+        self.idle_interval = idle_interval
+        self.interface = interface
+        self.ttl = ttl
+        self._transport = None
+        self._next_send = None
+
+    async def open(self):
+        loop = asyncio.get_running_loop()
+        family = _family(self.address)
+        sock = socket.socket(family, socket.SOCK_DGRAM)
+        if _is_multicast(self.address) and family == socket.AF_INET6:
+            sock.setsockopt(socket.IPPROTO_IPV6, socket.IPV6_MULTICAST_HOPS, self.ttl)
+            if self.interface is not None:
+                sock.setsockopt(socket.IPPROTO_IPV6, socket.IPV6_MULTICAST_IF, int(self.interface))
+        elif _is_multicast(self.address):
+            sock.setsockopt(socket.IPPROTO_IP, socket.IP_MULTICAST_TTL, self.ttl)
+            if self.interface is not None:
+                sock.setsockopt(socket.IPPROTO_IP, socket.IP_MULTICAST_IF,
+                                socket.inet_aton(self.interface))
+        sock.connect((self.address, self.port))
+        self._transport, _ = await loop.create_datagram_endpoint(
+            asyncio.DatagramProtocol, sock=sock)
+
+    def close(self):
+        if self._transport is not None:
+            self._transport.close()
+            self._transport = None
+
+    async def __aenter__(self):
+        await self.open()
+        return self
+
+    async def __aexit__(self, *exc):
+        self.close()
+
+    async def _send(self, pkt):
+        loop = asyncio.get_running_loop()
+        now = loop.time()
+        if self._next_send is None or self._next_send < now:
+            self._next_send = now
+        elif self._next_send > now:
+            await asyncio.sleep(self._next_send - now)
+
+        self._transport.sendto(bytes(pkt))
+        if self.bitrate:
+            self._next_send += len(pkt) * 8 / self.bitrate
+        else:
+            await asyncio.sleep(0)
+
+    async def flush(self):
+        """Send packets until the sender has nothing more to transfer"""
+        while True:
+            pkt = self.sender.read()
+            if pkt is None:
+                return
+            await self._send(pkt)
+
+    async def run(self):
+        """Send packets until the task is cancelled"""
+        while True:
+            await self.flush()
+            await asyncio.sleep(self.idle_interval)
+
+    async def close_session(self):
+        """Send the close session packet"""
+        await self._send(self.sender.read_close_session())
+
+
+class ReceivedObject:
+    """Object received by `AsyncReceiver`"""
+
+    def __init__(self, tsi, toi, meta, data):
+        self.tsi = tsi
+        self.toi = toi
+        self.meta = meta
+        self.data = data
+
+    def __repr__(self):
+        return "ReceivedObject(tsi={}, toi={}, content_location={!r}, size={})".format(
+            self.tsi, self.toi, self.meta.content_location, len(self.data))
+
+
+class _Writer:
+    def __init__(self, queue, tsi, toi, meta):
+        self.queue = queue
+        self.tsi = tsi
+        self.toi = toi
+        self.meta = meta
+        self.data = bytearray()
+
+    def open(self):
+        pass
+
+    def write(self, sbn, data):
+        self.data += data
+
+    def complete(self):
+        self.queue.put_nowait(ReceivedObject(self.tsi, self.toi, self.meta, bytes(self.data)))
+
+    def error(self):
+        logger.warning("Fail to receive object %s (TOI %d)", self.meta.content_location, self.toi)
+
+    def interrupted(self):
+        logger.warning("Reception of object %s (TOI %d) interrupted",
+                       self.meta.content_location, self.toi)
+
+
+class _WriterBuilder:
+    def __init__(self, queue):
+        self.queue = queue
+
+    def new_object_writer(self, tsi, toi, meta):
+        return _Writer(self.queue, tsi, toi, meta)
+
+
+class _Protocol(asyncio.DatagramProtocol):
+    def __init__(self, owner, address, port, source):
+        self.owner = owner
+        self.endpoint = receiver.UDPEndpoint(address, port, source)
+        self.source = source
+
+    def datagram_received(self, data, addr):
+        if self.source is not None and addr[0] != self.source:
+            return
+        try:
+            self.owner.receiver.push(self.endpoint, data)
+        except Exception as e:
+            logger.debug("Drop packet from %s: %s", addr, e)
+
+
+class AsyncReceiver:
+    """Receive FLUTE objects from UDP sockets
+
+    `endpoints` is a list of `(address, port)` or `(address, port, source_address)`.
+    Port 0 binds a free port, the ports bound are listed by `local_ports` once opened.
+
+    IPv4 multicast groups are joined on the address `interface`, with a source specific
+    membership (SSM) when the source address is given.
+    IPv6 multicast groups are joined on the interface index `interface_index` (0 for the default);
+    the source specific membership is not supported, the packets of other sources are
+    filtered after reception.
+    Completed objects are yielded by `async for obj in async_receiver`.
+    """
+
+    def __init__(self, endpoints, config=None, interface="0.0.0.0", cleanup_interval=1.0,
+                 interface_index=0):
+        self.endpoints = [tuple(endpoint) for endpoint in endpoints]
+        self.config = config if config is not None else receiver.Config()
+        self.interface = interface
+        self.interface_index = interface_index
+        self.cleanup_interval = cleanup_interval
+        self.local_ports = []
+        self.receiver = None
+        self._queue = None
+        self._transports = []
+        self._cleanup_task = None
+        self._closed = object()
+
+    def _socket(self, address, port, source):
+        family = _family(address)
+        sock = socket.socket(family, socket.SOCK_DGRAM)
+        sock.setsockopt(socket.SOL_SOCKET, socket.SO_REUSEADDR, 1)
+        if not _is_multicast(address):
+            sock.bind((address, port))
+        elif family == socket.AF_INET6:
+            sock.bind(("", port))
+            mreq = (socket.inet_pton(socket.AF_INET6, address)
+                    + struct.pack("@I", self.interface_index))
+            sock.setsockopt(socket.IPPROTO_IPV6, socket.IPV6_JOIN_GROUP, mreq)
+        elif source is not None:
+            sock.bind(("", port))
+            mreq = _ip_mreq_source(address, self.interface, source)
+            sock.setsockopt(socket.IPPROTO_IP, _IP_ADD_SOURCE_MEMBERSHIP, mreq)
+        else:
+            sock.bind(("", port))
+            mreq = struct.pack("4s4s", socket.inet_aton(address), socket.inet_aton(self.interface))
+            sock.setsockopt(socket.IPPROTO_IP, socket.IP_ADD_MEMBERSHIP, mreq)
+        return sock
+
+    async def open(self):
+        loop = asyncio.get_running_loop()
+        self._queue = asyncio.Queue()
+        writer = receiver.ObjectWriterBuilder.new_python(_WriterBuilder(self._queue))
+        self.receiver = receiver.MultiReceiver(writer, self.config)
+        self.local_ports = []
+
+        for endpoint in self.endpoints:
+            address, port = endpoint[0], endpoint[1]
+            source = endpoint[2] if len(endpoint) > 2 else None
+            sock = self._socket(address, port, source)
+            # Port 0 is replaced by the port bound by the system
+            port = sock.getsockname()[1]
+            transport, _ = await loop.create_datagram_endpoint(
+                lambda: _Protocol(self, address, port, source),
+                sock=sock)
+            self._transports.append(transport)
+            self.local_ports.append(port)
+
+        self._cleanup_task = loop.create_task(self._cleanup())
+
+    def close(self):
+        for transport in self._transports:
+            transport.close()
+        self._transports = []
+        if self._cleanup_task is not None:
+            self._cleanup_task.cancel()
+            self._cleanup_task = None
+        if self._queue is not None:
+            self._queue.put_nowait(self._closed)
+
+    async def __aenter__(self):
+        await self.open()
+        return self
+
+    async def __aexit__(self, *exc):
+        self.close()
+
+    async def _cleanup(self):
+        while True:
+            await asyncio.sleep(self.cleanup_interval)
+            self.receiver.cleanup()
+
+    def __aiter__(self):
+        return self
+
+    async def __anext__(self):
+        obj = await self._queue.get()
+        if obj is self._closed:
+            raise StopAsyncIteration
+        return obj
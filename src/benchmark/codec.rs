@@ -0,0 +1,200 @@
+use crate::common::oti::{FECEncodingID, Oti, RaptorQSchemeSpecific, SchemeSpecific};
+use crate::common::partition;
+use crate::fec::{self, FecDecoder, FecEncoder, FecShard};
+use crate::tools::error::{FluteError, Result};
+
+/// Encoding symbol of a source block
+#[derive(Debug, Clone)]
+pub struct EncodingSymbol {
+    /// Encoding Symbol ID
+    pub esi: u32,
+    /// Content of the symbol
+    pub data: Vec<u8>,
+}
+
+/// FEC encoder and decoder of the source blocks of an object
+///
+/// Runs the same codecs as the `Sender` and the `MultiReceiver`, without the ALC/LCT layer.
+#[derive(Debug, Clone)]
+pub struct BlockCodec {
+    oti: Oti,
+}
+
+impl BlockCodec {
+    /// Create a codec for the FEC scheme of `oti`
+    pub fn new(oti: &Oti) -> Result<Self> {
+        match oti.fec_encoding_id {
+            FECEncodingID::ReedSolomonGF2M => {
+                return Err(FluteError::new("ReedSolomonGF2M is not implemented"))
+            }
+            FECEncodingID::RaptorQ | FECEncodingID::Raptor if oti.scheme_specific.is_none() => {
+                return Err(FluteError::new(format!(
+                    "Scheme specific of {:?} not defined",
+                    oti.fec_encoding_id
+                )))
+            }
+            _ => {}
+        }
+
+        if oti.encoding_symbol_length == 0 || oti.maximum_source_block_length == 0 {
+            return Err(FluteError::new("Invalid OTI"));
+        }
+
+        Ok(Self { oti: oti.clone() })
+    }
+
+    /// OTI of the codec
+    pub fn oti(&self) -> &Oti {
+        &self.oti
+    }
+
+    /// Split an object into its source blocks, with the block partitioning of RFC 5052
+    pub fn source_blocks<'a>(&self, object: &'a [u8]) -> Vec<&'a [u8]> {
+        let encoding_symbol_length = self.oti.encoding_symbol_length as usize;
+        let (a_large, a_small, nb_a_large, nb_blocks) = partition::block_partitioning(
+            self.oti.maximum_source_block_length as u64,
+            object.len() as u64,
+            encoding_symbol_length as u64,
+        );
+
+        let mut offset = 0;
+        (0..nb_blocks)
+            .map(|sbn| {
+                let nb_source_symbols = match sbn < nb_a_large {
+                    true => a_large,
+                    false => a_small,
+                };
+                let end = (offset + nb_source_symbols as usize * encoding_symbol_length)
+                    .min(object.len());
+                let block = &object[offset..end];
+                offset = end;
+                block
+            })
+            .collect()
+    }
+
+    /// Encode a source block
+    ///
+    /// Return the source symbols followed by `max_number_of_parity_symbols` repair symbols
+    pub fn encode(&self, block: &[u8]) -> Result<Vec<EncodingSymbol>> {
+        let encoding_symbol_length = self.oti.encoding_symbol_length as usize;
+        let nb_source_symbols = self.nb_source_symbols(block.len())?;
+        let nb_parity_symbols = self.oti.max_number_of_parity_symbols as usize;
+
+        let shards: Vec<Box<dyn FecShard>> = match self.oti.fec_encoding_id {
+            FECEncodingID::NoCode => {
+                return Ok(block
+                    .chunks(encoding_symbol_length)
+                    .enumerate()
+                    .map(|(esi, data)| EncodingSymbol {
+                        esi: esi as u32,
+                        data: data.to_vec(),
+                    })
+                    .collect())
+            }
+            FECEncodingID::ReedSolomonGF28 | FECEncodingID::ReedSolomonGF28UnderSpecified => {
+                fec::rscodec::RSGalois8Codec::new(
+                    nb_source_symbols,
+                    nb_parity_symbols,
+                    encoding_symbol_length,
+                )?
+                .encode(block)?
+            }
+            FECEncodingID::RaptorQ => fec::raptorq::RaptorQEncoder::new(
+                nb_source_symbols,
+                nb_parity_symbols,
+                encoding_symbol_length,
+                self.raptorq_scheme()?,
+                0,
+            )
+            .encode(block)?,
+            FECEncodingID::Raptor => {
+                fec::raptor::RaptorEncoder::new(nb_source_symbols, nb_parity_symbols, 0)
+                    .encode(block)?
+            }
+            FECEncodingID::ReedSolomonGF2M => {
+                return Err(FluteError::new("ReedSolomonGF2M is not implemented"))
+            }
+        };
+
+        Ok(shards
+            .iter()
+            .map(|shard| EncodingSymbol {
+                esi: shard.esi(),
+                data: shard.data().to_vec(),
+            })
+            .collect())
+    }
+
+    /// Decode a source block of `block_length` bytes
+    ///
+    /// The symbols are pushed to the decoder in order, until the block is decoded.
+    /// Return `None` if the symbols are not enough to decode the block.
+    pub fn decode(
+        &self,
+        sbn: u32,
+        block_length: usize,
+        symbols: &[EncodingSymbol],
+    ) -> Result<Option<Vec<u8>>> {
+        let mut decoder = self.new_decoder(sbn, block_length)?;
+        for symbol in symbols {
+            decoder.push_symbol(&symbol.data, symbol.esi);
+            if decoder.can_decode() && decoder.decode() {
+                let mut block = decoder.source_block()?.to_vec();
+                block.truncate(block_length);
+                return Ok(Some(block));
+            }
+        }
+        Ok(None)
+    }
+
+    fn new_decoder(&self, sbn: u32, block_length: usize) -> Result<Box<dyn FecDecoder>> {
+        let encoding_symbol_length = self.oti.encoding_symbol_length as usize;
+        let nb_source_symbols = self.nb_source_symbols(block_length)?;
+        let decoder: Box<dyn FecDecoder> = match self.oti.fec_encoding_id {
+            FECEncodingID::NoCode => Box::new(fec::nocode::NoCodeDecoder::new(nb_source_symbols)),
+            FECEncodingID::ReedSolomonGF28 | FECEncodingID::ReedSolomonGF28UnderSpecified => {
+                Box::new(fec::rscodec::RSGalois8Codec::new(
+                    nb_source_symbols,
+                    self.oti.max_number_of_parity_symbols as usize,
+                    encoding_symbol_length,
+                )?)
+            }
+            FECEncodingID::RaptorQ => Box::new(fec::raptorq::RaptorQDecoder::new(
+                sbn,
+                nb_source_symbols,
+                encoding_symbol_length,
+                self.raptorq_scheme()?,
+            )),
+            FECEncodingID::Raptor => Box::new(fec::raptor::RaptorDecoder::new(
+                nb_source_symbols,
+                block_length,
+            )),
+            FECEncodingID::ReedSolomonGF2M => {
+                return Err(FluteError::new("ReedSolomonGF2M is not implemented"))
+            }
+        };
+        Ok(decoder)
+    }
+
+    fn nb_source_symbols(&self, block_length: usize) -> Result<usize> {
+        let nb_source_symbols =
+            num_integer::div_ceil(block_length, self.oti.encoding_symbol_length as usize);
+        if nb_source_symbols == 0
+            || nb_source_symbols > self.oti.maximum_source_block_length as usize
+        {
+            return Err(FluteError::new(format!(
+                "Invalid source block of {} bytes",
+                block_length
+            )));
+        }
+        Ok(nb_source_symbols)
+    }
+
+    fn raptorq_scheme(&self) -> Result<&RaptorQSchemeSpecific> {
+        match self.oti.scheme_specific.as_ref() {
+            Some(SchemeSpecific::RaptorQ(scheme)) => Ok(scheme),
+            _ => Err(FluteError::new("Scheme specific for Raptorq not defined")),
+        }
+    }
+}
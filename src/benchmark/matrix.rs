@@ -0,0 +1,350 @@
+use std::rc::Rc;
+use std::time::{Duration, Instant, SystemTime};
+
+use serde::Serialize;
+
+use super::codec::{BlockCodec, EncodingSymbol};
+use crate::common::lct::Cenc;
+use crate::common::oti::{FECEncodingID, Oti};
+use crate::common::planner;
+use crate::common::udpendpoint::UDPEndpoint;
+use crate::receiver::writer::ObjectWriterBufferBuilder;
+use crate::receiver::MultiReceiver;
+use crate::sender::{Config, ObjectDesc, Sender};
+use crate::simulator::{simulate, ChannelConfig, LossModel, SimulatorConfig};
+use crate::tools::error::{FluteError, Result};
+
+/// Parameters of a benchmark case
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchmarkCase {
+    /// FEC scheme
+    pub fec_encoding_id: FECEncodingID,
+    /// Size of the transferred file in bytes
+    pub file_size: usize,
+    /// Encoding Symbol Length
+    pub encoding_symbol_length: u16,
+    /// Maximum Source Block Length
+    pub block_length: u32,
+    /// Number of repair symbols of each block
+    pub parity_symbols: u32,
+    /// Average packet loss rate of the channel
+    pub loss_rate: f64,
+    /// Mean length of the loss bursts, `1.0` for independent losses
+    pub mean_burst_length: f64,
+}
+
+impl BenchmarkCase {
+    /// OTI of the case
+    pub fn oti(&self) -> Result<Oti> {
+        let oti = planner::new_oti(
+            self.fec_encoding_id,
+            self.encoding_symbol_length,
+            self.block_length,
+            self.parity_symbols,
+        )?;
+        // Some schemes store the parameters in smaller integers
+        if oti.maximum_source_block_length != self.block_length
+            || oti.max_number_of_parity_symbols != self.parity_symbols
+        {
+            return Err(FluteError::new(format!(
+                "{:?} does not support blocks of {} source symbols and {} repair symbols",
+                self.fec_encoding_id, self.block_length, self.parity_symbols
+            )));
+        }
+        Ok(oti)
+    }
+
+    /// Content of the transferred file
+    pub fn file(&self) -> Vec<u8> {
+        (0..self.file_size).map(|i| (i % 251) as u8).collect()
+    }
+
+    /// Loss model of the channel
+    pub fn loss_model(&self) -> LossModel {
+        if self.loss_rate <= 0.0 {
+            LossModel::None
+        } else if self.mean_burst_length <= 1.0 {
+            LossModel::Bernoulli { p: self.loss_rate }
+        } else {
+            LossModel::gilbert(self.loss_rate, self.mean_burst_length)
+        }
+    }
+}
+
+/// Result of a benchmark case
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchmarkResult {
+    /// Parameters of the case
+    #[serde(flatten)]
+    pub case: BenchmarkCase,
+    /// Number of source blocks of the file
+    pub nb_blocks: usize,
+    /// Ratio of repair symbols compared to the source symbols
+    pub overhead: f64,
+    /// Encoding throughput in Mbit/s of source data
+    pub encode_throughput: f64,
+    /// Decoding throughput in Mbit/s of source data
+    pub decode_throughput: f64,
+    /// Average number of packets sent for one transfer, FDT included
+    pub nb_packets: f64,
+    /// Number of simulated transfers
+    pub nb_runs: usize,
+    /// Number of transfers where the file is received
+    pub nb_success: usize,
+    /// Probability to receive the file
+    pub success_probability: f64,
+}
+
+/// Matrix of parameters to benchmark
+///
+/// Each combination of the parameters is a `BenchmarkCase`.
+/// The number of repair symbols is `ceil(block_length * repair_ratio)`,
+/// the combinations that are not supported by a FEC scheme are skipped.
+#[derive(Debug, Clone)]
+pub struct BenchmarkMatrix {
+    /// FEC schemes
+    pub fec_encoding_ids: Vec<FECEncodingID>,
+    /// File sizes in bytes
+    pub file_sizes: Vec<usize>,
+    /// Encoding Symbol Lengths
+    pub encoding_symbol_lengths: Vec<u16>,
+    /// Maximum Source Block Lengths
+    pub block_lengths: Vec<u32>,
+    /// Ratios of repair symbols, ignored for `NoCode`
+    pub repair_ratios: Vec<f64>,
+    /// Average packet loss rates of the channel
+    pub loss_rates: Vec<f64>,
+    /// Mean length of the loss bursts, `1.0` for independent losses
+    pub mean_burst_length: f64,
+    /// Number of simulated transfers per case
+    pub nb_runs: usize,
+    /// Seed of the first transfer, each transfer uses the next seed
+    pub seed: u64,
+    /// Minimum duration of the encoding and decoding measures
+    pub min_measure_duration: Duration,
+}
+
+impl Default for BenchmarkMatrix {
+    fn default() -> Self {
+        Self {
+            fec_encoding_ids: vec![
+                FECEncodingID::NoCode,
+                FECEncodingID::ReedSolomonGF28,
+                FECEncodingID::ReedSolomonGF28UnderSpecified,
+                FECEncodingID::Raptor,
+                FECEncodingID::RaptorQ,
+            ],
+            file_sizes: vec![100 * 1024, 1024 * 1024],
+            encoding_symbol_lengths: vec![1400],
+            block_lengths: vec![50, 200],
+            repair_ratios: vec![0.25],
+            loss_rates: vec![0.0, 0.01, 0.05, 0.1],
+            mean_burst_length: 1.0,
+            nb_runs: 10,
+            seed: 0,
+            min_measure_duration: Duration::from_millis(100),
+        }
+    }
+}
+
+impl BenchmarkMatrix {
+    /// List the cases of the matrix that are supported by their FEC scheme
+    pub fn cases(&self) -> Vec<BenchmarkCase> {
+        let mut cases = Vec::new();
+        for &fec_encoding_id in &self.fec_encoding_ids {
+            let repair_ratios = match fec_encoding_id {
+                FECEncodingID::NoCode => vec![0.0],
+                _ => self.repair_ratios.clone(),
+            };
+            for &file_size in &self.file_sizes {
+                for &encoding_symbol_length in &self.encoding_symbol_lengths {
+                    for &block_length in &self.block_lengths {
+                        for &repair_ratio in &repair_ratios {
+                            for &loss_rate in &self.loss_rates {
+                                let case = BenchmarkCase {
+                                    fec_encoding_id,
+                                    file_size,
+                                    encoding_symbol_length,
+                                    block_length,
+                                    parity_symbols: (block_length as f64 * repair_ratio).ceil()
+                                        as u32,
+                                    loss_rate,
+                                    mean_burst_length: self.mean_burst_length,
+                                };
+                                match case.oti() {
+                                    Ok(_) => cases.push(case),
+                                    Err(e) => log::debug!("Skip {:?}: {:?}", case, e),
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        cases
+    }
+
+    /// Run all the cases of the matrix
+    ///
+    /// The cases that fail are logged and skipped
+    pub fn run(&self) -> Vec<BenchmarkResult> {
+        self.cases()
+            .iter()
+            .filter_map(|case| match self.run_case(case) {
+                Ok(result) => Some(result),
+                Err(e) => {
+                    log::error!("Fail to run {:?}: {:?}", case, e);
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Measure the encoding and decoding throughput of a case
+    /// and simulate `nb_runs` transfers of the file through the lossy channel
+    pub fn run_case(&self, case: &BenchmarkCase) -> Result<BenchmarkResult> {
+        let oti = case.oti()?;
+        let codec = BlockCodec::new(&oti)?;
+        let file = case.file();
+
+        let blocks = codec.source_blocks(&file);
+        let (encoded, encode_duration) = measure(self.min_measure_duration, || {
+            blocks
+                .iter()
+                .map(|block| codec.encode(block))
+                .collect::<Result<Vec<_>>>()
+        })?;
+
+        let nb_source_symbols: usize = blocks
+            .iter()
+            .map(|block| num_integer::div_ceil(block.len(), oti.encoding_symbol_length as usize))
+            .sum();
+        let nb_encoding_symbols: usize = encoded.iter().map(|symbols| symbols.len()).sum();
+
+        // Half of the repair symbols replace lost source symbols
+        let received: Vec<Vec<EncodingSymbol>> = encoded
+            .into_iter()
+            .zip(&blocks)
+            .map(|(symbols, block)| {
+                let nb_source_symbols =
+                    num_integer::div_ceil(block.len(), oti.encoding_symbol_length as usize);
+                let nb_repair_symbols = symbols.len().saturating_sub(nb_source_symbols);
+                symbols.into_iter().skip(nb_repair_symbols / 2).collect()
+            })
+            .collect();
+        let (_, decode_duration) = measure(self.min_measure_duration, || {
+            for (sbn, (block, symbols)) in blocks.iter().zip(&received).enumerate() {
+                match codec.decode(sbn as u32, block.len(), symbols)? {
+                    Some(decoded) if decoded == *block => {}
+                    _ => return Err(FluteError::new(format!("Fail to decode block {}", sbn))),
+                }
+            }
+            Ok(())
+        })?;
+
+        let (nb_success, nb_packets) = self.simulate_transfers(case)?;
+
+        Ok(BenchmarkResult {
+            case: case.clone(),
+            nb_blocks: blocks.len(),
+            overhead: match nb_source_symbols {
+                0 => 0.0,
+                n => (nb_encoding_symbols - n) as f64 / n as f64,
+            },
+            encode_throughput: throughput(file.len(), encode_duration),
+            decode_throughput: throughput(file.len(), decode_duration),
+            nb_packets: match self.nb_runs {
+                0 => 0.0,
+                n => nb_packets as f64 / n as f64,
+            },
+            nb_runs: self.nb_runs,
+            nb_success,
+            success_probability: match self.nb_runs {
+                0 => 0.0,
+                n => nb_success as f64 / n as f64,
+            },
+        })
+    }
+
+    /// Simulate `nb_runs` transfers of the file of a case through the lossy channel
+    ///
+    /// Return the number of transfers where the file is received
+    /// and the total number of packets sent
+    pub fn simulate_transfers(&self, case: &BenchmarkCase) -> Result<(usize, usize)> {
+        let oti = case.oti()?;
+        let file = case.file();
+        let start = SystemTime::now();
+        let mut nb_success = 0;
+        let mut nb_packets = 0;
+
+        for run in 0..self.nb_runs {
+            let endpoint = UDPEndpoint::new(None, "224.0.0.1".to_owned(), 3400);
+            let mut sender = Sender::new(endpoint, 1, &oti, &Config::default());
+            let obj = ObjectDesc::create_from_buffer(
+                file.to_vec(),
+                "application/octet-stream",
+                &url::Url::parse("file:///benchmark.bin").unwrap(),
+                1,
+                None,
+                None,
+                None,
+                None,
+                Cenc::Null,
+                true,
+                None,
+                true,
+            )?;
+            sender.add_object(0, obj)?;
+            sender.publish(start)?;
+
+            let output = Rc::new(ObjectWriterBufferBuilder::new(false));
+            let mut receiver = MultiReceiver::new(output.clone(), None, false);
+            let config = SimulatorConfig {
+                channel: ChannelConfig {
+                    loss: case.loss_model(),
+                    seed: self.seed.wrapping_add(run as u64),
+                    ..Default::default()
+                },
+                start_time: Some(start),
+                ..Default::default()
+            };
+            let stats = simulate(&mut sender, &mut receiver, &config)?;
+            nb_packets += stats.nb_packets;
+
+            let received = output.objects.borrow().iter().any(|object| {
+                let object = object.borrow();
+                object.complete && !object.error && object.data == file
+            });
+            if received {
+                nb_success += 1;
+            }
+        }
+
+        Ok((nb_success, nb_packets))
+    }
+}
+
+/// Run `f` until `min_duration` is elapsed, return the last output and the average duration
+fn measure<T, F>(min_duration: Duration, mut f: F) -> Result<(T, Duration)>
+where
+    F: FnMut() -> Result<T>,
+{
+    let start = Instant::now();
+    let mut iterations = 0u32;
+    loop {
+        let output = f()?;
+        iterations += 1;
+        let elapsed = start.elapsed();
+        if elapsed >= min_duration {
+            return Ok((output, elapsed / iterations));
+        }
+    }
+}
+
+/// Throughput in Mbit/s
+fn throughput(nb_bytes: usize, duration: Duration) -> f64 {
+    match duration.as_secs_f64() {
+        secs if secs > 0.0 => nb_bytes as f64 * 8.0 / secs / 1_000_000.0,
+        _ => 0.0,
+    }
+}
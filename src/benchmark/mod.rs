@@ -0,0 +1,146 @@
+//!
+//! FEC overhead and recovery benchmarks
+//!
+//! `BlockCodec` runs the FEC encoders and decoders of the library on raw source blocks,
+//! it is used by the criterion benchmarks (`cargo bench`).
+//!
+//! `BenchmarkMatrix` compares the FEC schemes over a matrix of file sizes, symbol lengths,
+//! block lengths, repair ratios and loss rates. For each case, it measures the encoding and decoding
+//! throughput, then simulates transfers through a lossy channel (see the `simulator` module) to
+//! estimate the probability to receive the file. The FDT is subject to the same losses as the file.
+//! The results are formatted with `to_csv()` or `to_markdown()`,
+//! the `flute-fec-report` tool runs the matrix from the command line.
+//!
+//! # Example
+//!
+//! ```
+//! use flute::benchmark::{to_markdown, BenchmarkMatrix};
+//! use flute::core::FECEncodingID;
+//! use std::time::Duration;
+//!
+//! let matrix = BenchmarkMatrix {
+//!     fec_encoding_ids: vec![FECEncodingID::RaptorQ],
+//!     file_sizes: vec![10000],
+//!     block_lengths: vec![16],
+//!     loss_rates: vec![0.05],
+//!     nb_runs: 2,
+//!     min_measure_duration: Duration::ZERO,
+//!     ..Default::default()
+//! };
+//! let results = matrix.run();
+//! assert_eq!(results.len(), 1);
+//! println!("{}", to_markdown(&results));
+//! ```
+//!
+
+mod codec;
+mod matrix;
+mod report;
+
+pub use codec::BlockCodec;
+pub use codec::EncodingSymbol;
+pub use matrix::BenchmarkCase;
+pub use matrix::BenchmarkMatrix;
+pub use matrix::BenchmarkResult;
+pub use report::to_csv;
+pub use report::to_markdown;
+
+#[cfg(test)]
+mod tests {
+    use super::{BenchmarkMatrix, BlockCodec};
+    use crate::common::oti::{FECEncodingID, Oti};
+    use std::time::Duration;
+
+    #[test]
+    pub fn test_block_codec() {
+        crate::tests::init();
+        let otis = [
+            Oti::new_no_code(100, 10),
+            Oti::new_reed_solomon_rs28(100, 10, 4).unwrap(),
+            Oti::new_reed_solomon_rs28_under_specified(100, 10, 4).unwrap(),
+            Oti::new_raptor(100, 10, 4, 1, 4).unwrap(),
+            Oti::new_raptorq(100, 10, 4, 1, 4).unwrap(),
+        ];
+        let object: Vec<u8> = (0..2550u32).map(|i| (i % 251) as u8).collect();
+
+        for oti in otis {
+            let codec = BlockCodec::new(&oti).unwrap();
+            let blocks = codec.source_blocks(&object);
+            assert_eq!(blocks.len(), 3);
+            assert_eq!(blocks.concat(), object);
+
+            for (sbn, block) in blocks.iter().enumerate() {
+                let symbols = codec.encode(block).unwrap();
+                let nb_source_symbols = num_integer::div_ceil(block.len(), 100);
+                let nb_repair_symbols = symbols.len() - nb_source_symbols;
+                match oti.fec_encoding_id {
+                    FECEncodingID::NoCode => assert_eq!(nb_repair_symbols, 0),
+                    _ => assert_eq!(nb_repair_symbols, 4),
+                }
+
+                // Half of the repair symbols replace lost source symbols,
+                // Raptor needs a few more symbols than the block length
+                let decoded = codec
+                    .decode(sbn as u32, block.len(), &symbols[nb_repair_symbols / 2..])
+                    .unwrap();
+                assert_eq!(decoded.as_deref(), Some(*block), "{:?}", oti);
+            }
+        }
+    }
+
+    #[test]
+    pub fn test_block_codec_not_enough_symbols() {
+        crate::tests::init();
+        let oti = Oti::new_reed_solomon_rs28(100, 10, 4).unwrap();
+        let codec = BlockCodec::new(&oti).unwrap();
+        let block = vec![1u8; 1000];
+        let symbols = codec.encode(&block).unwrap();
+        assert!(codec
+            .decode(0, block.len(), &symbols[5..])
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    pub fn test_benchmark_matrix() {
+        crate::tests::init();
+        let matrix = BenchmarkMatrix {
+            fec_encoding_ids: vec![FECEncodingID::NoCode, FECEncodingID::ReedSolomonGF28],
+            file_sizes: vec![20000],
+            encoding_symbol_lengths: vec![1000],
+            // RS28 is limited to 255 symbols per encoding block
+            block_lengths: vec![10, 250],
+            repair_ratios: vec![0.5],
+            loss_rates: vec![0.0, 0.1],
+            nb_runs: 4,
+            min_measure_duration: Duration::ZERO,
+            ..Default::default()
+        };
+        let cases = matrix.cases();
+        assert_eq!(cases.len(), 6);
+        assert!(cases
+            .iter()
+            .all(|case| case.fec_encoding_id == FECEncodingID::NoCode || case.block_length == 10));
+
+        let results = matrix.run();
+        assert_eq!(results.len(), 6);
+        for result in &results {
+            log::info!("{:?}", result);
+            assert!(result.encode_throughput > 0.0);
+            if result.case.loss_rate == 0.0 {
+                assert_eq!(result.nb_success, 4);
+            }
+            match result.case.fec_encoding_id {
+                FECEncodingID::NoCode => assert_eq!(result.overhead, 0.0),
+                _ => assert_eq!(result.overhead, 0.5),
+            }
+        }
+
+        let csv = super::to_csv(&results);
+        assert_eq!(csv.lines().count(), 7);
+        assert!(csv.starts_with("fec,file_size,"));
+        let markdown = super::to_markdown(&results);
+        assert_eq!(markdown.lines().count(), 8);
+        assert!(markdown.contains("| ReedSolomonGF28 | 20000 | 1000 | 10 | 5 |"));
+    }
+}
@@ -0,0 +1,61 @@
+use super::matrix::BenchmarkResult;
+
+const COLUMNS: [&str; 15] = [
+    "fec",
+    "file_size",
+    "symbol_length",
+    "block_length",
+    "parity_symbols",
+    "loss_rate",
+    "burst_length",
+    "nb_blocks",
+    "overhead",
+    "encode_mbps",
+    "decode_mbps",
+    "nb_packets",
+    "nb_runs",
+    "nb_success",
+    "success_probability",
+];
+
+fn row(result: &BenchmarkResult) -> [String; 15] {
+    let case = &result.case;
+    [
+        format!("{:?}", case.fec_encoding_id),
+        case.file_size.to_string(),
+        case.encoding_symbol_length.to_string(),
+        case.block_length.to_string(),
+        case.parity_symbols.to_string(),
+        format!("{:.3}", case.loss_rate),
+        format!("{:.1}", case.mean_burst_length),
+        result.nb_blocks.to_string(),
+        format!("{:.3}", result.overhead),
+        format!("{:.1}", result.encode_throughput),
+        format!("{:.1}", result.decode_throughput),
+        format!("{:.1}", result.nb_packets),
+        result.nb_runs.to_string(),
+        result.nb_success.to_string(),
+        format!("{:.3}", result.success_probability),
+    ]
+}
+
+/// Format the results as CSV, with a header line
+pub fn to_csv(results: &[BenchmarkResult]) -> String {
+    let mut output = COLUMNS.join(",");
+    output.push('\n');
+    for result in results {
+        output.push_str(&row(result).join(","));
+        output.push('\n');
+    }
+    output
+}
+
+/// Format the results as a markdown table
+pub fn to_markdown(results: &[BenchmarkResult]) -> String {
+    let mut output = format!("| {} |\n", COLUMNS.join(" | "));
+    output.push_str(&format!("|{}\n", " --- |".repeat(COLUMNS.len())));
+    for result in results {
+        output.push_str(&format!("| {} |\n", row(result).join(" | ")));
+    }
+    output
+}
@@ -0,0 +1,305 @@
+use std::net::IpAddr;
+
+use crate::common::udpendpoint::UDPEndpoint;
+use crate::tools::error::{FluteError, Result};
+
+const IPV4_HEADER_LEN: usize = 20;
+const IPV6_HEADER_LEN: usize = 40;
+const UDP_HEADER_LEN: usize = 8;
+const IPPROTO_UDP: u8 = 17;
+const HOP_LIMIT: u8 = 1;
+
+/// UDP datagram extracted from a captured frame
+#[derive(Debug)]
+pub struct UdpDatagram<'a> {
+    pub source: IpAddr,
+    pub destination: IpAddr,
+    pub source_port: u16,
+    pub destination_port: u16,
+    pub payload: &'a [u8],
+}
+
+fn checksum_add(mut sum: u32, data: &[u8]) -> u32 {
+    let mut chunks = data.chunks_exact(2);
+    for chunk in &mut chunks {
+        sum += u16::from_be_bytes([chunk[0], chunk[1]]) as u32;
+    }
+    if let [last] = chunks.remainder() {
+        sum += (*last as u32) << 8;
+    }
+    sum
+}
+
+fn checksum_finish(mut sum: u32) -> u16 {
+    while sum >> 16 != 0 {
+        sum = (sum & 0xFFFF) + (sum >> 16);
+    }
+    !(sum as u16)
+}
+
+fn udp_checksum(source: &IpAddr, destination: &IpAddr, udp: &[u8]) -> u16 {
+    let mut sum = match (source, destination) {
+        (IpAddr::V4(src), IpAddr::V4(dst)) => {
+            checksum_add(checksum_add(0, &src.octets()), &dst.octets())
+        }
+        (IpAddr::V6(src), IpAddr::V6(dst)) => {
+            checksum_add(checksum_add(0, &src.octets()), &dst.octets())
+        }
+        _ => 0,
+    };
+    sum += IPPROTO_UDP as u32;
+    sum += udp.len() as u32;
+    match checksum_finish(checksum_add(sum, udp)) {
+        0 => 0xFFFF,
+        checksum => checksum,
+    }
+}
+
+/// Build a raw IP frame (IPv4 or IPv6 + UDP) that carries `payload` to `endpoint`
+///
+/// When the endpoint has no source address, the unspecified address is used.
+pub fn encode(endpoint: &UDPEndpoint, payload: &[u8]) -> Result<Vec<u8>> {
+    let (source, destination) = endpoint.addrs()?;
+    let source = source.unwrap_or(match destination {
+        IpAddr::V4(_) => IpAddr::V4(std::net::Ipv4Addr::UNSPECIFIED),
+        IpAddr::V6(_) => IpAddr::V6(std::net::Ipv6Addr::UNSPECIFIED),
+    });
+
+    let udp_len = UDP_HEADER_LEN + payload.len();
+    let ip_header_len = match destination {
+        IpAddr::V4(_) => IPV4_HEADER_LEN,
+        IpAddr::V6(_) => IPV6_HEADER_LEN,
+    };
+    let max_udp_len = match destination {
+        IpAddr::V4(_) => u16::MAX as usize - IPV4_HEADER_LEN,
+        IpAddr::V6(_) => u16::MAX as usize,
+    };
+    if udp_len > max_udp_len {
+        return Err(FluteError::new(format!(
+            "Payload of {} bytes is too large for a UDP datagram",
+            payload.len()
+        )));
+    }
+
+    let mut frame = Vec::with_capacity(ip_header_len + udp_len);
+    match (&source, &destination) {
+        (IpAddr::V4(src), IpAddr::V4(dst)) => {
+            frame.extend(&[0x45, 0]);
+            frame.extend(((IPV4_HEADER_LEN + udp_len) as u16).to_be_bytes());
+            frame.extend(&[0, 0, 0x40, 0, HOP_LIMIT, IPPROTO_UDP, 0, 0]);
+            frame.extend(src.octets());
+            frame.extend(dst.octets());
+            let checksum = checksum_finish(checksum_add(0, &frame));
+            frame[10..12].copy_from_slice(&checksum.to_be_bytes());
+        }
+        (IpAddr::V6(src), IpAddr::V6(dst)) => {
+            frame.extend(&[0x60, 0, 0, 0]);
+            frame.extend((udp_len as u16).to_be_bytes());
+            frame.extend(&[IPPROTO_UDP, HOP_LIMIT]);
+            frame.extend(src.octets());
+            frame.extend(dst.octets());
+        }
+        _ => unreachable!(),
+    }
+
+    let udp_start = frame.len();
+    frame.extend(endpoint.port.to_be_bytes());
+    frame.extend(endpoint.port.to_be_bytes());
+    frame.extend((udp_len as u16).to_be_bytes());
+    frame.extend(&[0, 0]);
+    frame.extend(payload);
+    let checksum = udp_checksum(&source, &destination, &frame[udp_start..]);
+    frame[udp_start + 6..udp_start + 8].copy_from_slice(&checksum.to_be_bytes());
+    Ok(frame)
+}
+
+fn decode_udp(source: IpAddr, destination: IpAddr, udp: &[u8]) -> Option<UdpDatagram<'_>> {
+    if udp.len() < UDP_HEADER_LEN {
+        return None;
+    }
+    let len = u16::from_be_bytes([udp[4], udp[5]]) as usize;
+    // The length of jumbograms is 0, the datagram ends with the frame
+    let len = match len {
+        0 => udp.len(),
+        len if len < UDP_HEADER_LEN || len > udp.len() => return None,
+        len => len,
+    };
+    Some(UdpDatagram {
+        source,
+        destination,
+        source_port: u16::from_be_bytes([udp[0], udp[1]]),
+        destination_port: u16::from_be_bytes([udp[2], udp[3]]),
+        payload: &udp[UDP_HEADER_LEN..len],
+    })
+}
+
+fn decode_ipv4(frame: &[u8]) -> Option<UdpDatagram<'_>> {
+    if frame.len() < IPV4_HEADER_LEN {
+        return None;
+    }
+    let header_len = ((frame[0] & 0x0F) as usize) * 4;
+    let total_len = u16::from_be_bytes([frame[2], frame[3]]) as usize;
+    let fragment = u16::from_be_bytes([frame[6], frame[7]]);
+    if header_len < IPV4_HEADER_LEN || total_len < header_len || total_len > frame.len() {
+        return None;
+    }
+    // Fragmented datagrams are not reassembled
+    if fragment & 0x3FFF != 0 || frame[9] != IPPROTO_UDP {
+        return None;
+    }
+    let source: [u8; 4] = frame[12..16].try_into().ok()?;
+    let destination: [u8; 4] = frame[16..20].try_into().ok()?;
+    decode_udp(
+        IpAddr::from(source),
+        IpAddr::from(destination),
+        &frame[header_len..total_len],
+    )
+}
+
+fn decode_ipv6(frame: &[u8]) -> Option<UdpDatagram<'_>> {
+    if frame.len() < IPV6_HEADER_LEN {
+        return None;
+    }
+    let payload_len = u16::from_be_bytes([frame[4], frame[5]]) as usize;
+    let end = match payload_len {
+        0 => frame.len(),
+        len => IPV6_HEADER_LEN + len,
+    };
+    if end > frame.len() {
+        return None;
+    }
+    let source: [u8; 16] = frame[8..24].try_into().ok()?;
+    let destination: [u8; 16] = frame[24..40].try_into().ok()?;
+
+    let mut next_header = frame[6];
+    let mut offset = IPV6_HEADER_LEN;
+    loop {
+        match next_header {
+            IPPROTO_UDP => break,
+            // Hop-by-Hop, Routing and Destination Options headers
+            0 | 43 | 60 => {
+                if offset + 8 > end {
+                    return None;
+                }
+                next_header = frame[offset];
+                offset += (frame[offset + 1] as usize + 1) * 8;
+            }
+            _ => return None,
+        }
+    }
+    if offset > end {
+        return None;
+    }
+    decode_udp(
+        IpAddr::from(source),
+        IpAddr::from(destination),
+        &frame[offset..end],
+    )
+}
+
+/// Extract the UDP datagram of a raw IP frame
+pub fn decode_ip(frame: &[u8]) -> Option<UdpDatagram<'_>> {
+    match frame.first()? >> 4 {
+        4 => decode_ipv4(frame),
+        6 => decode_ipv6(frame),
+        _ => None,
+    }
+}
+
+/// Extract the UDP datagram of a frame with an `ethertype`
+fn decode_ethertype(ethertype: u16, frame: &[u8]) -> Option<UdpDatagram<'_>> {
+    match ethertype {
+        0x0800 => decode_ipv4(frame),
+        0x86DD => decode_ipv6(frame),
+        _ => None,
+    }
+}
+
+/// Extract the UDP datagram of a captured frame
+///
+/// `link_type` is the LINKTYPE_ value of the capture
+pub fn decode(link_type: u32, frame: &[u8]) -> Option<UdpDatagram<'_>> {
+    match link_type {
+        // LINKTYPE_NULL, host byte order
+        0 => {
+            let family = frame.get(0..4)?;
+            let family = u32::from_le_bytes(family.try_into().ok()?)
+                .min(u32::from_be_bytes(family.try_into().ok()?));
+            match family {
+                2 => decode_ipv4(&frame[4..]),
+                24 | 28 | 30 => decode_ipv6(&frame[4..]),
+                _ => None,
+            }
+        }
+        // LINKTYPE_ETHERNET
+        1 => {
+            let mut offset = 12;
+            let mut ethertype = u16::from_be_bytes(frame.get(offset..offset + 2)?.try_into().ok()?);
+            // 802.1Q and 802.1ad tags
+            while ethertype == 0x8100 || ethertype == 0x88A8 {
+                offset += 4;
+                ethertype = u16::from_be_bytes(frame.get(offset..offset + 2)?.try_into().ok()?);
+            }
+            decode_ethertype(ethertype, &frame[offset + 2..])
+        }
+        // LINKTYPE_RAW, and DLT_RAW of some platforms
+        101 | 12 | 14 => decode_ip(frame),
+        // LINKTYPE_LINUX_SLL
+        113 => {
+            let ethertype = u16::from_be_bytes(frame.get(14..16)?.try_into().ok()?);
+            decode_ethertype(ethertype, &frame[16..])
+        }
+        // LINKTYPE_IPV4
+        228 => decode_ipv4(frame),
+        // LINKTYPE_IPV6
+        229 => decode_ipv6(frame),
+        // LINKTYPE_LINUX_SLL2
+        276 => {
+            let ethertype = u16::from_be_bytes(frame.get(0..2)?.try_into().ok()?);
+            decode_ethertype(ethertype, frame.get(20..)?)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::udpendpoint::UDPEndpoint;
+
+    #[test]
+    pub fn test_frame_roundtrip() {
+        crate::tests::init();
+        for endpoint in [
+            UDPEndpoint::new(None, "224.0.0.1".to_owned(), 3400),
+            UDPEndpoint::new(Some("10.0.0.1".to_owned()), "232.0.0.1".to_owned(), 3400),
+            UDPEndpoint::new(Some("2001:db8::1".to_owned()), "ff3e::1".to_owned(), 3400),
+        ] {
+            let payload = vec![1u8, 2, 3, 4, 5];
+            let frame = super::encode(&endpoint, &payload).unwrap();
+            let datagram = super::decode(101, &frame).unwrap();
+            assert_eq!(datagram.destination, endpoint.destination_ip().unwrap());
+            assert_eq!(datagram.destination_port, 3400);
+            assert_eq!(datagram.payload, &payload[..]);
+
+            let mut ethernet = vec![0u8; 12];
+            ethernet.extend(match datagram.destination.is_ipv4() {
+                true => [0x08, 0x00],
+                false => [0x86, 0xDD],
+            });
+            ethernet.extend(&frame);
+            let datagram = super::decode(1, &ethernet).unwrap();
+            assert_eq!(datagram.payload, &payload[..]);
+        }
+    }
+
+    #[test]
+    pub fn test_frame_ipv4_checksum() {
+        crate::tests::init();
+        let endpoint = UDPEndpoint::new(Some("10.0.0.1".to_owned()), "232.0.0.1".to_owned(), 3400);
+        let frame = super::encode(&endpoint, &[0u8; 11]).unwrap();
+        assert_eq!(
+            super::checksum_finish(super::checksum_add(0, &frame[..20])),
+            0
+        );
+    }
+}
@@ -0,0 +1,165 @@
+//!
+//! Capture and replay of FLUTE sessions
+//!
+//! `PcapngWriter` records the packets of a `Sender`, or the packets given to a receiver,
+//! into a pcapng file. Each packet is encapsulated into a synthetic IP/UDP header built from
+//! its `UDPEndpoint`, so the capture can be analysed with Wireshark.
+//!
+//! `PcapReader` reads the UDP packets of a pcap or pcapng capture (ex: recorded with `tcpdump`)
+//! and `replay()` pushes them to a `MultiReceiver` with their original timestamps,
+//! so a reception issue can be reproduced offline. The session and object timeouts of
+//! the receiver use the local clock, see `replay()`.
+//!
+//! # Example
+//!
+//! ```
+//! use flute::capture::{replay, PcapReader, PcapngWriter, ReplayConfig};
+//! use flute::core::{Oti, UDPEndpoint};
+//! use flute::receiver::{writer, MultiReceiver};
+//! use flute::sender::{Config, ObjectDesc, Sender};
+//! use std::rc::Rc;
+//! use std::time::SystemTime;
+//!
+//! // Record a FLUTE session
+//! let endpoint = UDPEndpoint::new(None, "224.0.0.1".to_string(), 3400);
+//! let mut sender = Sender::new(endpoint, 1, &Oti::default(), &Config::default());
+//! let obj = ObjectDesc::create_from_buffer(b"hello world".to_vec(), "text/plain",
+//!     &url::Url::parse("file:///hello.txt").unwrap(), 1, None, None, None, None,
+//!     flute::core::lct::Cenc::Null, true, None, true).unwrap();
+//! sender.add_object(0, obj).unwrap();
+//! sender.publish(SystemTime::now()).unwrap();
+//!
+//! let mut capture = PcapngWriter::new(Vec::new()).unwrap();
+//! capture.write_sender(&mut sender, SystemTime::now()).unwrap();
+//! let capture = capture.into_inner();
+//!
+//! // Replay it
+//! let output = Rc::new(writer::ObjectWriterBufferBuilder::new(true));
+//! let mut receiver = MultiReceiver::new(output.clone(), None, false);
+//! let mut reader = PcapReader::new(capture.as_slice()).unwrap();
+//! let stats = replay(&mut reader, &mut receiver, &ReplayConfig::default()).unwrap();
+//! assert!(stats.nb_packets > 0);
+//! assert_eq!(output.objects.borrow().len(), 1);
+//! ```
+//!
+
+mod frame;
+mod reader;
+mod replay;
+mod writer;
+
+pub use reader::CapturedPacket;
+pub use reader::PcapReader;
+pub use replay::replay;
+pub use replay::ReplayConfig;
+pub use replay::ReplayStats;
+pub use writer::PcapngWriter;
+
+#[cfg(test)]
+mod tests {
+    use super::{PcapReader, PcapngWriter};
+    use crate::common::udpendpoint::UDPEndpoint;
+    use std::time::{Duration, SystemTime};
+
+    #[test]
+    pub fn test_capture_pcapng_roundtrip() {
+        crate::tests::init();
+        let endpoints = [
+            UDPEndpoint::new(None, "224.0.0.1".to_owned(), 3400),
+            UDPEndpoint::new(Some("2001:db8::1".to_owned()), "ff3e::1".to_owned(), 3401),
+        ];
+        let timestamp = SystemTime::UNIX_EPOCH + Duration::from_micros(1_700_000_000_123_456);
+
+        let mut writer = PcapngWriter::new(Vec::new()).unwrap();
+        for (i, endpoint) in endpoints.iter().enumerate() {
+            let data = vec![i as u8; 3 + i];
+            writer
+                .write_packet(endpoint, &data, timestamp + Duration::from_millis(i as u64))
+                .unwrap();
+        }
+        let capture = writer.into_inner();
+
+        let packets: Vec<_> = PcapReader::new(capture.as_slice())
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(packets.len(), 2);
+        for (i, pkt) in packets.iter().enumerate() {
+            assert_eq!(pkt.endpoint, endpoints[i]);
+            assert_eq!(pkt.data, vec![i as u8; 3 + i]);
+            assert_eq!(pkt.timestamp, timestamp + Duration::from_millis(i as u64));
+        }
+    }
+
+    #[test]
+    pub fn test_capture_pcap_ethernet() {
+        crate::tests::init();
+        let endpoint = UDPEndpoint::new(Some("10.0.0.1".to_owned()), "232.0.0.1".to_owned(), 3400);
+        let frame = super::frame::encode(&endpoint, &[1, 2, 3]).unwrap();
+
+        // Big endian pcap with nanosecond timestamps
+        let mut capture = Vec::new();
+        capture.extend(0xA1B23C4Du32.to_be_bytes());
+        capture.extend(2u16.to_be_bytes());
+        capture.extend(4u16.to_be_bytes());
+        capture.extend([0u8; 8]);
+        capture.extend(65535u32.to_be_bytes());
+        capture.extend(1u32.to_be_bytes());
+
+        let mut ethernet = vec![0u8; 12];
+        ethernet.extend([0x81, 0x00, 0x00, 0x01, 0x08, 0x00]);
+        ethernet.extend(&frame);
+        capture.extend(10u32.to_be_bytes());
+        capture.extend(500u32.to_be_bytes());
+        capture.extend((ethernet.len() as u32).to_be_bytes());
+        capture.extend((ethernet.len() as u32).to_be_bytes());
+        capture.extend(&ethernet);
+
+        // ARP, skipped
+        let arp = [0u8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x08, 0x06, 0, 0];
+        capture.extend(11u32.to_be_bytes());
+        capture.extend(0u32.to_be_bytes());
+        capture.extend((arp.len() as u32).to_be_bytes());
+        capture.extend((arp.len() as u32).to_be_bytes());
+        capture.extend(arp);
+
+        let mut reader = PcapReader::new(capture.as_slice()).unwrap();
+        let pkt = reader.next_packet().unwrap().unwrap();
+        assert_eq!(pkt.endpoint, endpoint);
+        assert_eq!(pkt.data, vec![1, 2, 3]);
+        assert_eq!(
+            pkt.timestamp,
+            SystemTime::UNIX_EPOCH + Duration::new(10, 500)
+        );
+        assert!(reader.next_packet().unwrap().is_none());
+    }
+
+    #[test]
+    pub fn test_capture_invalid() {
+        crate::tests::init();
+        assert!(PcapReader::new(&[][..]).is_err());
+        assert!(PcapReader::new(&[1u8, 2, 3, 4, 5, 6][..]).is_err());
+
+        // pcapng interface with a truncated if_tsresol option
+        let mut capture = Vec::new();
+        capture.extend(0x0A0D0D0Au32.to_le_bytes());
+        capture.extend(28u32.to_le_bytes());
+        capture.extend(0x1A2B3C4Du32.to_le_bytes());
+        capture.extend(1u16.to_le_bytes());
+        capture.extend(0u16.to_le_bytes());
+        capture.extend((-1i64).to_le_bytes());
+        capture.extend(28u32.to_le_bytes());
+
+        capture.extend(1u32.to_le_bytes());
+        capture.extend(24u32.to_le_bytes());
+        capture.extend(1u16.to_le_bytes());
+        capture.extend(0u16.to_le_bytes());
+        capture.extend(65535u32.to_le_bytes());
+        capture.extend(9u16.to_le_bytes());
+        capture.extend(1u16.to_le_bytes());
+        capture.extend(24u32.to_le_bytes());
+
+        let mut reader = PcapReader::new(capture.as_slice()).unwrap();
+        assert!(reader.next_packet().is_err());
+    }
+}
@@ -0,0 +1,353 @@
+use std::io::Read;
+use std::time::{Duration, SystemTime};
+
+use super::frame;
+use crate::common::udpendpoint::UDPEndpoint;
+use crate::tools::error::{FluteError, Result};
+
+const PCAPNG_SECTION_HEADER_BLOCK: u32 = 0x0A0D0D0A;
+const PCAPNG_INTERFACE_DESCRIPTION_BLOCK: u32 = 0x00000001;
+const PCAPNG_SIMPLE_PACKET_BLOCK: u32 = 0x00000003;
+const PCAPNG_ENHANCED_PACKET_BLOCK: u32 = 0x00000006;
+const PCAPNG_BYTE_ORDER_MAGIC: u32 = 0x1A2B3C4D;
+const PCAP_MAGIC_MICROS: u32 = 0xA1B2C3D4;
+const PCAP_MAGIC_NANOS: u32 = 0xA1B23C4D;
+const MAX_BLOCK_LEN: usize = 16 * 1024 * 1024;
+
+/// UDP packet read from a capture
+#[derive(Debug, Clone)]
+pub struct CapturedPacket {
+    /// Capture timestamp
+    pub timestamp: SystemTime,
+    /// Endpoint of the packet, the source address is `None` when it is unspecified
+    pub endpoint: UDPEndpoint,
+    /// UDP source port
+    pub source_port: u16,
+    /// UDP payload
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug)]
+struct PcapngInterface {
+    link_type: u32,
+    // Number of timestamp units per second
+    ts_units: u64,
+}
+
+#[derive(Debug)]
+enum Format {
+    Pcap {
+        link_type: u32,
+        nanos: bool,
+    },
+    Pcapng {
+        interfaces: Vec<PcapngInterface>,
+        last_timestamp: SystemTime,
+    },
+}
+
+/// Read the UDP packets of a pcap or pcapng capture
+///
+/// Supported link types are Ethernet, Linux cooked capture (v1 and v2), BSD loopback and raw IP.
+/// Packets that are not UDP, as well as IP fragments, are skipped.
+#[derive(Debug)]
+pub struct PcapReader<R: Read> {
+    reader: R,
+    big_endian: bool,
+    format: Format,
+}
+
+fn read_exact_or_eof<R: Read>(reader: &mut R, buf: &mut [u8]) -> Result<bool> {
+    let mut read = 0;
+    while read < buf.len() {
+        match reader.read(&mut buf[read..]) {
+            Ok(0) if read == 0 => return Ok(false),
+            Ok(0) => return Err(FluteError::new("Truncated capture")),
+            Ok(n) => read += n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Ok(true)
+}
+
+fn to_system_time(units: u64, units_per_sec: u64) -> SystemTime {
+    let secs = units / units_per_sec;
+    let nanos = (units % units_per_sec) as u128 * 1_000_000_000 / units_per_sec as u128;
+    SystemTime::UNIX_EPOCH + Duration::new(secs, nanos as u32)
+}
+
+impl<R: Read> PcapReader<R> {
+    /// Open a capture, the format (pcap or pcapng) is detected from its header
+    pub fn new(mut reader: R) -> Result<Self> {
+        let mut magic = [0u8; 4];
+        if !read_exact_or_eof(&mut reader, &mut magic)? {
+            return Err(FluteError::new("Empty capture"));
+        }
+
+        if u32::from_le_bytes(magic) == PCAPNG_SECTION_HEADER_BLOCK {
+            let mut output = Self {
+                reader,
+                big_endian: false,
+                format: Format::Pcapng {
+                    interfaces: Vec::new(),
+                    last_timestamp: SystemTime::UNIX_EPOCH,
+                },
+            };
+            output.read_section_header()?;
+            return Ok(output);
+        }
+
+        let (big_endian, nanos) = match (u32::from_le_bytes(magic), u32::from_be_bytes(magic)) {
+            (PCAP_MAGIC_MICROS, _) => (false, false),
+            (PCAP_MAGIC_NANOS, _) => (false, true),
+            (_, PCAP_MAGIC_MICROS) => (true, false),
+            (_, PCAP_MAGIC_NANOS) => (true, true),
+            _ => return Err(FluteError::new("Unknown capture format")),
+        };
+
+        let mut header = [0u8; 20];
+        if !read_exact_or_eof(&mut reader, &mut header)? {
+            return Err(FluteError::new("Truncated capture"));
+        }
+        let mut output = Self {
+            reader,
+            big_endian,
+            format: Format::Pcap {
+                link_type: 0,
+                nanos,
+            },
+        };
+        let link_type = output.u32(&header[16..20]) & 0x0FFFFFFF;
+        output.format = Format::Pcap { link_type, nanos };
+        Ok(output)
+    }
+
+    fn u16(&self, buf: &[u8]) -> u16 {
+        let buf = [buf[0], buf[1]];
+        match self.big_endian {
+            true => u16::from_be_bytes(buf),
+            false => u16::from_le_bytes(buf),
+        }
+    }
+
+    fn u32(&self, buf: &[u8]) -> u32 {
+        let buf = [buf[0], buf[1], buf[2], buf[3]];
+        match self.big_endian {
+            true => u32::from_be_bytes(buf),
+            false => u32::from_le_bytes(buf),
+        }
+    }
+
+    /// Read the body of a pcapng section header block, the block type has already been read
+    fn read_section_header(&mut self) -> Result<()> {
+        let mut header = [0u8; 8];
+        if !read_exact_or_eof(&mut self.reader, &mut header)? {
+            return Err(FluteError::new("Truncated capture"));
+        }
+        self.big_endian = match u32::from_le_bytes(header[4..8].try_into().unwrap()) {
+            PCAPNG_BYTE_ORDER_MAGIC => false,
+            _ if u32::from_be_bytes(header[4..8].try_into().unwrap())
+                == PCAPNG_BYTE_ORDER_MAGIC =>
+            {
+                true
+            }
+            _ => return Err(FluteError::new("Invalid pcapng byte order magic")),
+        };
+        let total_len = self.u32(&header[0..4]) as usize;
+        if !(28..=MAX_BLOCK_LEN).contains(&total_len) {
+            return Err(FluteError::new("Invalid pcapng section header length"));
+        }
+        let mut remaining = vec![0u8; total_len - 12];
+        if !read_exact_or_eof(&mut self.reader, &mut remaining)? {
+            return Err(FluteError::new("Truncated capture"));
+        }
+        if let Format::Pcapng { interfaces, .. } = &mut self.format {
+            interfaces.clear();
+        }
+        Ok(())
+    }
+
+    fn interface_description(&self, body: &[u8]) -> Result<PcapngInterface> {
+        if body.len() < 8 {
+            return Err(FluteError::new("Invalid pcapng interface description"));
+        }
+        let link_type = self.u16(&body[0..2]) as u32;
+        let mut ts_units = 1_000_000;
+        let mut options = &body[8..];
+        while options.len() >= 4 {
+            let code = self.u16(&options[0..2]);
+            let len = self.u16(&options[2..4]) as usize;
+            let value = options.get(4..4 + len).unwrap_or_default();
+            match code {
+                0 => break,
+                // if_tsresol
+                9 => {
+                    let resol = *value.first().ok_or_else(|| {
+                        FluteError::new("Invalid pcapng timestamp resolution option")
+                    })?;
+                    ts_units = match resol & 0x80 {
+                        0 => 10u64.checked_pow(resol as u32),
+                        _ => 2u64.checked_pow((resol & 0x7F) as u32),
+                    }
+                    .ok_or_else(|| FluteError::new("Invalid pcapng timestamp resolution"))?;
+                }
+                _ => {}
+            }
+            let padded_len = 4 + len + (4 - len % 4) % 4;
+            options = options.get(padded_len..).unwrap_or_default();
+        }
+        Ok(PcapngInterface {
+            link_type,
+            ts_units,
+        })
+    }
+
+    fn next_frame_pcap(
+        &mut self,
+        link_type: u32,
+        nanos: bool,
+    ) -> Result<Option<(u32, SystemTime, Vec<u8>)>> {
+        let mut header = [0u8; 16];
+        if !read_exact_or_eof(&mut self.reader, &mut header)? {
+            return Ok(None);
+        }
+        let secs = self.u32(&header[0..4]) as u64;
+        let fraction = self.u32(&header[4..8]) as u64;
+        let captured_len = self.u32(&header[8..12]) as usize;
+        if captured_len > MAX_BLOCK_LEN {
+            return Err(FluteError::new("Invalid pcap record length"));
+        }
+        let mut data = vec![0u8; captured_len];
+        if !read_exact_or_eof(&mut self.reader, &mut data)? && captured_len > 0 {
+            return Err(FluteError::new("Truncated capture"));
+        }
+        let timestamp = match nanos {
+            true => to_system_time(secs * 1_000_000_000 + fraction, 1_000_000_000),
+            false => to_system_time(secs * 1_000_000 + fraction, 1_000_000),
+        };
+        Ok(Some((link_type, timestamp, data)))
+    }
+
+    fn next_frame_pcapng(&mut self) -> Result<Option<(u32, SystemTime, Vec<u8>)>> {
+        loop {
+            let mut block_type = [0u8; 4];
+            if !read_exact_or_eof(&mut self.reader, &mut block_type)? {
+                return Ok(None);
+            }
+
+            // The byte order of a new section is given by its header
+            if u32::from_le_bytes(block_type) == PCAPNG_SECTION_HEADER_BLOCK {
+                self.read_section_header()?;
+                continue;
+            }
+
+            let mut len = [0u8; 4];
+            if !read_exact_or_eof(&mut self.reader, &mut len)? {
+                return Err(FluteError::new("Truncated capture"));
+            }
+            let block_type = self.u32(&block_type);
+            let total_len = self.u32(&len) as usize;
+            if !(12..=MAX_BLOCK_LEN).contains(&total_len) || total_len % 4 != 0 {
+                return Err(FluteError::new("Invalid pcapng block length"));
+            }
+            let mut body = vec![0u8; total_len - 8];
+            if !read_exact_or_eof(&mut self.reader, &mut body)? {
+                return Err(FluteError::new("Truncated capture"));
+            }
+            body.truncate(total_len - 12);
+
+            match block_type {
+                PCAPNG_INTERFACE_DESCRIPTION_BLOCK => {
+                    let interface = self.interface_description(&body)?;
+                    if let Format::Pcapng { interfaces, .. } = &mut self.format {
+                        interfaces.push(interface);
+                    }
+                }
+                PCAPNG_ENHANCED_PACKET_BLOCK => {
+                    if body.len() < 20 {
+                        return Err(FluteError::new("Invalid pcapng enhanced packet block"));
+                    }
+                    let interface_id = self.u32(&body[0..4]) as usize;
+                    let ts = ((self.u32(&body[4..8]) as u64) << 32) | self.u32(&body[8..12]) as u64;
+                    let captured_len = self.u32(&body[12..16]) as usize;
+                    let data = body
+                        .get(20..20 + captured_len)
+                        .ok_or_else(|| FluteError::new("Invalid pcapng enhanced packet block"))?
+                        .to_vec();
+                    if let Format::Pcapng {
+                        interfaces,
+                        last_timestamp,
+                    } = &mut self.format
+                    {
+                        let interface = interfaces.get(interface_id).ok_or_else(|| {
+                            FluteError::new(format!("Unknown pcapng interface {}", interface_id))
+                        })?;
+                        *last_timestamp = to_system_time(ts, interface.ts_units);
+                        return Ok(Some((interface.link_type, *last_timestamp, data)));
+                    }
+                }
+                PCAPNG_SIMPLE_PACKET_BLOCK => {
+                    // Simple packets have no timestamp, the timestamp of the previous packet is used
+                    if let Format::Pcapng {
+                        interfaces,
+                        last_timestamp,
+                    } = &self.format
+                    {
+                        let interface = interfaces
+                            .first()
+                            .ok_or_else(|| FluteError::new("Unknown pcapng interface 0"))?;
+                        let data = body.get(4..).unwrap_or_default().to_vec();
+                        return Ok(Some((interface.link_type, *last_timestamp, data)));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Read the next UDP packet of the capture
+    ///
+    /// Return `None` at the end of the capture
+    pub fn next_packet(&mut self) -> Result<Option<CapturedPacket>> {
+        loop {
+            let frame = match self.format {
+                Format::Pcap { link_type, nanos } => self.next_frame_pcap(link_type, nanos)?,
+                Format::Pcapng { .. } => self.next_frame_pcapng()?,
+            };
+
+            let (link_type, timestamp, data) = match frame {
+                Some(frame) => frame,
+                None => return Ok(None),
+            };
+
+            let datagram = match frame::decode(link_type, &data) {
+                Some(datagram) => datagram,
+                None => continue,
+            };
+
+            let source = match datagram.source.is_unspecified() {
+                true => None,
+                false => Some(datagram.source),
+            };
+            return Ok(Some(CapturedPacket {
+                timestamp,
+                endpoint: UDPEndpoint::from_addrs(
+                    source,
+                    datagram.destination,
+                    datagram.destination_port,
+                ),
+                source_port: datagram.source_port,
+                data: datagram.payload.to_vec(),
+            }));
+        }
+    }
+}
+
+impl<R: Read> Iterator for PcapReader<R> {
+    type Item = Result<CapturedPacket>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_packet().transpose()
+    }
+}
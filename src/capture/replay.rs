@@ -0,0 +1,100 @@
+use std::io::Read;
+use std::time::{Duration, Instant, SystemTime};
+
+use super::reader::PcapReader;
+use crate::receiver::MultiReceiver;
+use crate::tools::error::{FluteError, Result};
+
+/// Configuration of `replay()`
+#[derive(Debug, Clone, Copy)]
+pub struct ReplayConfig {
+    /// Pace the packets with the timing of the capture, divided by this factor
+    /// (`Some(1.0)` real time, `Some(10.0)` ten times faster).
+    /// `None` pushes the packets as fast as possible.
+    pub speed: Option<f64>,
+    /// Interval, in capture time, between two calls to `MultiReceiver::cleanup()`
+    pub cleanup_interval: Option<Duration>,
+}
+
+impl Default for ReplayConfig {
+    fn default() -> Self {
+        Self {
+            speed: None,
+            cleanup_interval: Some(Duration::from_secs(1)),
+        }
+    }
+}
+
+/// Statistics of a replay
+#[derive(Debug, Clone, Default)]
+pub struct ReplayStats {
+    /// Number of packets pushed to the receiver
+    pub nb_packets: usize,
+    /// Number of packets rejected by the receiver
+    pub nb_errors: usize,
+    /// Timestamp of the first packet
+    pub first_timestamp: Option<SystemTime>,
+    /// Timestamp of the last packet
+    pub last_timestamp: Option<SystemTime>,
+}
+
+/// Push the packets of a capture to a `MultiReceiver`
+///
+/// Each packet is pushed with its capture timestamp as `now`, so the FDT expiration
+/// and the object cache follow the capture whatever the replay speed.
+/// `Config::session_timeout` and `Config::object_timeout` of the receivers are measured
+/// with the local clock and only match the capture when it is replayed in real time.
+pub fn replay<R: Read>(
+    reader: &mut PcapReader<R>,
+    receiver: &mut MultiReceiver,
+    config: &ReplayConfig,
+) -> Result<ReplayStats> {
+    if let Some(speed) = config.speed {
+        if !(speed.is_finite() && speed > 0.0) {
+            return Err(FluteError::new(format!("Invalid replay speed {}", speed)));
+        }
+    }
+
+    let mut stats = ReplayStats::default();
+    let start = Instant::now();
+    let mut last_cleanup: Option<SystemTime> = None;
+
+    while let Some(pkt) = reader.next_packet()? {
+        let first_timestamp = *stats.first_timestamp.get_or_insert(pkt.timestamp);
+        stats.last_timestamp = Some(pkt.timestamp);
+
+        if let Some(speed) = config.speed {
+            let offset = pkt
+                .timestamp
+                .duration_since(first_timestamp)
+                .unwrap_or_default();
+            let deadline = start + offset.div_f64(speed);
+            let now = Instant::now();
+            if deadline > now {
+                std::thread::sleep(deadline - now);
+            }
+        }
+
+        match receiver.push(&pkt.endpoint, &pkt.data, pkt.timestamp) {
+            Ok(_) => stats.nb_packets += 1,
+            Err(e) => {
+                log::warn!("Fail to push packet of {:?}: {:?}", pkt.endpoint, e);
+                stats.nb_errors += 1;
+            }
+        }
+
+        if let Some(interval) = config.cleanup_interval {
+            let last = *last_cleanup.get_or_insert(pkt.timestamp);
+            if pkt.timestamp.duration_since(last).unwrap_or_default() >= interval {
+                receiver.cleanup(pkt.timestamp);
+                last_cleanup = Some(pkt.timestamp);
+            }
+        }
+    }
+
+    if let (Some(_), Some(last_timestamp)) = (config.cleanup_interval, stats.last_timestamp) {
+        receiver.cleanup(last_timestamp);
+    }
+
+    Ok(stats)
+}
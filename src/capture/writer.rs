@@ -0,0 +1,105 @@
+use std::io::Write;
+use std::time::SystemTime;
+
+use super::frame;
+use crate::common::udpendpoint::UDPEndpoint;
+use crate::sender::Sender;
+use crate::tools::error::Result;
+
+const SECTION_HEADER_BLOCK: u32 = 0x0A0D0D0A;
+const INTERFACE_DESCRIPTION_BLOCK: u32 = 0x00000001;
+const ENHANCED_PACKET_BLOCK: u32 = 0x00000006;
+const BYTE_ORDER_MAGIC: u32 = 0x1A2B3C4D;
+const LINKTYPE_RAW: u16 = 101;
+
+/// Write FLUTE packets to a pcapng capture
+///
+/// Each packet is encapsulated into a synthetic IPv4 or IPv6 + UDP header built from its
+/// `UDPEndpoint`. The capture can be opened with Wireshark or replayed with `replay()`.
+#[derive(Debug)]
+pub struct PcapngWriter<W: Write> {
+    writer: W,
+}
+
+fn write_block<W: Write>(writer: &mut W, block_type: u32, body: &[u8]) -> Result<()> {
+    let padding = (4 - body.len() % 4) % 4;
+    let total_len = (12 + body.len() + padding) as u32;
+    writer.write_all(&block_type.to_le_bytes())?;
+    writer.write_all(&total_len.to_le_bytes())?;
+    writer.write_all(body)?;
+    writer.write_all(&[0u8; 3][..padding])?;
+    writer.write_all(&total_len.to_le_bytes())?;
+    Ok(())
+}
+
+impl<W: Write> PcapngWriter<W> {
+    /// Create a new pcapng writer
+    ///
+    /// The section header and the interface description are written immediately
+    pub fn new(mut writer: W) -> Result<Self> {
+        let mut shb = Vec::new();
+        shb.extend(BYTE_ORDER_MAGIC.to_le_bytes());
+        shb.extend(1u16.to_le_bytes());
+        shb.extend(0u16.to_le_bytes());
+        // Section length is not specified
+        shb.extend((-1i64).to_le_bytes());
+        write_block(&mut writer, SECTION_HEADER_BLOCK, &shb)?;
+
+        let mut idb = Vec::new();
+        idb.extend(LINKTYPE_RAW.to_le_bytes());
+        idb.extend(0u16.to_le_bytes());
+        // No snapshot length limit
+        idb.extend(0u32.to_le_bytes());
+        write_block(&mut writer, INTERFACE_DESCRIPTION_BLOCK, &idb)?;
+
+        Ok(Self { writer })
+    }
+
+    /// Write a packet sent to or received from `endpoint`
+    pub fn write_packet(
+        &mut self,
+        endpoint: &UDPEndpoint,
+        data: &[u8],
+        timestamp: SystemTime,
+    ) -> Result<()> {
+        let frame = frame::encode(endpoint, data)?;
+        // Timestamps are in microseconds, the default resolution of pcapng
+        let ts = timestamp
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_micros() as u64;
+
+        let mut epb = Vec::with_capacity(20 + frame.len());
+        epb.extend(0u32.to_le_bytes());
+        epb.extend(((ts >> 32) as u32).to_le_bytes());
+        epb.extend((ts as u32).to_le_bytes());
+        epb.extend((frame.len() as u32).to_le_bytes());
+        epb.extend((frame.len() as u32).to_le_bytes());
+        epb.extend(&frame);
+        write_block(&mut self.writer, ENHANCED_PACKET_BLOCK, &epb)
+    }
+
+    /// Write all the packets that the sender has to transfer at `now`
+    ///
+    /// Return the number of packets written
+    pub fn write_sender(&mut self, sender: &mut Sender, now: SystemTime) -> Result<usize> {
+        let endpoint = sender.get_udp_endpoint().clone();
+        let mut nb_packets = 0;
+        while let Some(pkt) = sender.read(now) {
+            self.write_packet(&endpoint, &pkt, now)?;
+            nb_packets += 1;
+        }
+        Ok(nb_packets)
+    }
+
+    /// Flush the underlying writer
+    pub fn flush(&mut self) -> Result<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    /// Return the underlying writer
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
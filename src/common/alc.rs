@@ -9,7 +9,7 @@ pub struct AlcPkt<'a> {
     pub lct: lct::LCTHeader,
     /// OTI
     pub oti: Option<oti::Oti>,
-    /// Transfer length
+    /// Transfer length, from the FTI or the TOL extension
     pub transfer_length: Option<u64>,
     /// CENC
     pub cenc: Option<lct::Cenc>,
@@ -23,6 +23,8 @@ pub struct AlcPkt<'a> {
     pub data_payload_offset: usize,
     /// FDT info
     pub fdt_info: Option<ExtFDT>,
+    /// Number of header extensions unknown and skipped
+    pub nb_unknown_ext: usize,
 }
 
 #[derive(Debug)]
@@ -36,6 +38,7 @@ pub struct AlcPktCache {
     pub data_payload_offset: usize,
     pub data: Vec<u8>,
     pub fdt_info: Option<ExtFDT>,
+    pub nb_unknown_ext: usize,
 }
 
 /// Payload ID
@@ -68,6 +71,7 @@ impl<'a> AlcPkt<'a> {
             data_payload_offset: self.data_payload_offset,
             data: self.data.to_vec(),
             fdt_info: self.fdt_info.clone(),
+            nb_unknown_ext: self.nb_unknown_ext,
         }
     }
 }
@@ -84,6 +88,7 @@ impl<'a> AlcPktCache {
             data_payload_offset: self.data_payload_offset,
             data: self.data.as_ref(),
             fdt_info: self.fdt_info.clone(),
+            nb_unknown_ext: self.nb_unknown_ext,
         }
     }
 }
@@ -116,6 +121,7 @@ pub fn new_alc_pkt(
     cci: &u128,
     tsi: u64,
     pkt: &Pkt,
+    lct_extensions: &[lct::LctExtension],
     profile: Profile,
     now: SystemTime,
 ) -> Vec<u8> {
@@ -155,8 +161,16 @@ pub fn new_alc_pkt(
         };
     }
 
+    if pkt.inband_tol {
+        lct::push_tol(&mut data, pkt.transfer_length);
+    }
+
+    for ext in lct_extensions {
+        ext.push(&mut data);
+    }
+
     let codec = <dyn AlcCodec>::instance(oti.fec_encoding_id);
-    if pkt.toi == lct::TOI_FDT || oti.inband_fti {
+    if pkt.toi == lct::TOI_FDT || pkt.inband_fti {
         codec.add_fti(&mut data, oti, pkt.transfer_length);
     }
     codec.add_fec_payload_id(&mut data, oti, pkt);
@@ -191,15 +205,41 @@ pub fn parse_alc_pkt<'a>(data: &'a [u8]) -> Result<AlcPkt<'a>> {
     let data_alc_header_offset = lct_header.len;
     let data_payload_offset = fec_payload_id_block_length + lct_header.len;
 
-    let cenc = lct::get_ext(data, &lct_header, lct::Ext::Cenc as u8)?;
+    // Walk the header extensions once
+    let mut cenc = None;
+    let mut tol = None;
+    let mut fdt = None;
+    let mut nb_unknown_ext = 0;
+    let mut lct_ext_ext = &data[(lct_header.header_ext_offset as usize)..lct_header.len];
+    while let Some((ext, next)) = lct::split_ext(lct_ext_ext, &lct_header)? {
+        match lct::Ext::try_from(ext[0]) {
+            Ok(lct::Ext::Cenc) if cenc.is_none() => cenc = Some(ext),
+            Ok(lct::Ext::Tol24) => tol = Some(ext),
+            Ok(lct::Ext::Tol48) if tol.is_none() => tol = Some(ext),
+            Ok(lct::Ext::Fdt) if fdt.is_none() => fdt = Some(ext),
+            Ok(_) => {}
+            Err(_) => {
+                log::debug!("Skip unknown LCT extension HET={}", ext[0]);
+                nb_unknown_ext += 1;
+            }
+        }
+        lct_ext_ext = next;
+    }
+
     let cenc = match cenc {
         Some(ext) => parse_cenc(ext).ok(),
         None => None,
     };
 
+    // Without FTI, the transfer length can be signalled with EXT_TOL
+    let transfer_length = match (&fti, tol) {
+        (Some(fti), _) => Some(fti.1),
+        (None, Some(ext)) => Some(lct::parse_tol(ext)?),
+        (None, None) => None,
+    };
+
     let mut fdt_info: Option<ExtFDT> = None;
     if lct_header.toi == lct::TOI_FDT {
-        let fdt = lct::get_ext(data, &lct_header, lct::Ext::Fdt as u8)?;
         fdt_info = match fdt {
             Some(ext) => parse_ext_fdt(ext)?,
             None => None,
@@ -208,14 +248,15 @@ pub fn parse_alc_pkt<'a>(data: &'a [u8]) -> Result<AlcPkt<'a>> {
 
     Ok(AlcPkt {
         lct: lct_header,
-        oti: fti.as_ref().map(|fti| fti.0.clone()),
-        transfer_length: fti.map(|fti| fti.1),
+        oti: fti.map(|fti| fti.0),
+        transfer_length,
         cenc,
         server_time: None,
         data,
         data_alc_header_offset,
         data_payload_offset,
         fdt_info,
+        nb_unknown_ext,
     })
 }
 
@@ -397,17 +438,50 @@ mod tests {
             fdt_id: None,
             cenc: lct::Cenc::Null,
             inband_cenc: true,
+            inband_fti: oti.inband_fti,
+            inband_tol: false,
             transfer_length: transfer_length,
             close_object: false,
             source_block_length: 1,
             sender_current_time: false,
         };
 
-        let alc_pkt =
-            super::new_alc_pkt(&oti, &cci, tsi, &pkt, Profile::RFC6726, SystemTime::now());
+        let alc_pkt = super::new_alc_pkt(
+            &oti,
+            &cci,
+            tsi,
+            &pkt,
+            &[],
+            Profile::RFC6726,
+            SystemTime::now(),
+        );
         let decoded_pkt = super::parse_alc_pkt(&alc_pkt).unwrap();
         assert!(decoded_pkt.lct.toi == pkt.toi);
         assert!(decoded_pkt.lct.cci == cci);
         assert!(decoded_pkt.lct.tsi == tsi);
     }
+
+    #[test]
+    pub fn test_alc_parse_tol_without_fti() {
+        crate::tests::init();
+
+        // NoCode ALC packet with EXT_NOP, EXT_TOL (48 bits) and an unknown extension, no FTI
+        let data: [u8; 40] = [
+            0x10, 0xA0, 0x09, 0x00, // V=1 S=1 O=1 HDR_LEN=9 CP=0
+            0x00, 0x00, 0x00, 0x00, // CCI
+            0x00, 0x00, 0x00, 0x01, // TSI
+            0x00, 0x00, 0x00, 0x05, // TOI
+            0x00, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // EXT_NOP HEL=2
+            0x43, 0x02, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, // EXT_TOL 48 bits = 4294967296
+            0xFE, 0x00, 0x00, 0x00, // HET=254
+            0x00, 0x00, 0x00, 0x00, // FEC Payload ID (SBN=0, ESI=0)
+        ];
+
+        let pkt = super::parse_alc_pkt(&data).unwrap();
+        assert_eq!(pkt.lct.toi, 5);
+        assert!(pkt.oti.is_none());
+        assert_eq!(pkt.transfer_length, Some(1 << 32));
+        assert_eq!(pkt.nb_unknown_ext, 1);
+        assert_eq!(pkt.data_payload_offset, data.len());
+    }
 }
@@ -18,6 +18,7 @@ use opentelemetry::{
     KeyValue,
 };
 
+use super::objectpart::ObjectPart;
 use super::oti::{
     self, RaptorQSchemeSpecific, RaptorSchemeSpecific, ReedSolomonGF2MSchemeSpecific,
     SchemeSpecific,
@@ -406,6 +407,33 @@ pub struct File {
         skip_serializing_if = "Option::is_none"
     )]
     pub optel_propagator: Option<String>,
+
+    #[serde(rename = "@X-Part-Group", skip_serializing_if = "Option::is_none")]
+    pub part_group: Option<String>,
+
+    #[serde(rename = "@X-Part-Index", skip_serializing_if = "Option::is_none")]
+    pub part_index: Option<u32>,
+
+    #[serde(rename = "@X-Part-Count", skip_serializing_if = "Option::is_none")]
+    pub part_count: Option<u32>,
+
+    #[serde(rename = "@X-Part-Offset", skip_serializing_if = "Option::is_none")]
+    pub part_offset: Option<u64>,
+
+    #[serde(
+        rename = "@X-Part-Group-Content-Length",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub part_group_content_length: Option<u64>,
+
+    #[serde(
+        rename = "@X-Part-Group-Content-MD5",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub part_group_content_md5: Option<String>,
+
+    #[serde(rename = "@X-Live", skip_serializing_if = "Option::is_none")]
+    pub live: Option<bool>,
 }
 
 fn reed_solomon_scheme_specific(
@@ -618,6 +646,17 @@ impl File {
         })
     }
 
+    pub fn get_object_part(&self) -> Option<ObjectPart> {
+        Some(ObjectPart {
+            group: self.part_group.clone()?,
+            index: self.part_index?,
+            count: self.part_count?,
+            offset: self.part_offset?,
+            group_content_length: self.part_group_content_length?,
+            group_md5: self.part_group_content_md5.clone(),
+        })
+    }
+
     #[cfg(feature = "opentelemetry")]
     pub fn get_optel_propagator(&self) -> Option<std::collections::HashMap<String, String>> {
         use base64::Engine;
@@ -15,19 +15,67 @@ pub enum Cenc {
     Deflate = 2,
     /// Encode content with Gzip
     Gzip = 3,
+    /// Encode content with Zstandard
+    ///
+    /// Requires the `zstd` feature, otherwise encoding or decoding the content fails.
+    /// This value is not registered for EXT_CENC by RFC 6726,
+    /// the receiver must be built with this library.
+    Zstd = 4,
+    /// Encode content with Brotli
+    ///
+    /// Requires the `brotli` feature, otherwise encoding or decoding the content fails.
+    /// This value is not registered for EXT_CENC by RFC 6726,
+    /// the receiver must be built with this library.
+    Brotli = 5,
 }
 
 #[repr(u8)]
 #[derive(Clone, Copy)]
 pub enum Ext {
+    Nop = 0,
     Fdt = 192,
     Fti = 64,
     Cenc = 193,
     Time = 2,
+    Tol48 = 67,
+    Tol24 = 194,
+    /// EXT_ROUTE_PRESENTATION_TIME of ROUTE (ATSC A/331)
+    RoutePresentationTime = 66,
+}
+
+impl TryFrom<u8> for Ext {
+    type Error = ();
+
+    fn try_from(v: u8) -> std::result::Result<Self, Self::Error> {
+        match v {
+            x if x == Ext::Nop as u8 => Ok(Ext::Nop),
+            x if x == Ext::Fdt as u8 => Ok(Ext::Fdt),
+            x if x == Ext::Fti as u8 => Ok(Ext::Fti),
+            x if x == Ext::Cenc as u8 => Ok(Ext::Cenc),
+            x if x == Ext::Time as u8 => Ok(Ext::Time),
+            x if x == Ext::Tol48 as u8 => Ok(Ext::Tol48),
+            x if x == Ext::Tol24 as u8 => Ok(Ext::Tol24),
+            x if x == Ext::RoutePresentationTime as u8 => Ok(Ext::RoutePresentationTime),
+            _ => Err(()),
+        }
+    }
 }
 
 pub const TOI_FDT: u128 = 0;
 
+/// Maximum length of the LCT header, HDR_LEN is a number of 32-bit words coded on 8 bits
+pub(crate) const MAX_HEADER_LENGTH: usize = 255 * 4;
+
+/// LCT Header Extension defined by the application
+///
+/// HET >= 128 : fixed length extension, the content is 3 bytes.
+/// HET < 128 : variable length extension, the content is `HEL x 4 - 2` bytes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LctExtension {
+    het: u8,
+    content: Vec<u8>,
+}
+
 /// LCT Header
 #[derive(Clone, Debug)]
 pub struct LCTHeader {
@@ -60,6 +108,8 @@ impl TryFrom<u8> for Cenc {
             x if x == Cenc::Zlib as u8 => Ok(Cenc::Zlib),
             x if x == Cenc::Deflate as u8 => Ok(Cenc::Deflate),
             x if x == Cenc::Gzip as u8 => Ok(Cenc::Gzip),
+            x if x == Cenc::Zstd as u8 => Ok(Cenc::Zstd),
+            x if x == Cenc::Brotli as u8 => Ok(Cenc::Brotli),
             _ => Err(()),
         }
     }
@@ -74,6 +124,8 @@ impl TryFrom<&str> for Cenc {
             "zlib" => Ok(Cenc::Zlib),
             "deflate" => Ok(Cenc::Deflate),
             "gzip" => Ok(Cenc::Gzip),
+            "zstd" => Ok(Cenc::Zstd),
+            "br" => Ok(Cenc::Brotli),
             _ => Err(()),
         }
     }
@@ -87,7 +139,116 @@ impl Cenc {
             Cenc::Zlib => "zlib",
             Cenc::Deflate => "deflate",
             Cenc::Gzip => "gzip",
+            Cenc::Zstd => "zstd",
+            Cenc::Brotli => "br",
+        }
+    }
+
+    /// Return true if the content encoding is enabled by the features of this build
+    pub fn is_supported(&self) -> bool {
+        match self {
+            Cenc::Null | Cenc::Zlib | Cenc::Deflate | Cenc::Gzip => true,
+            Cenc::Zstd => cfg!(feature = "zstd"),
+            Cenc::Brotli => cfg!(feature = "brotli"),
+        }
+    }
+
+    /// Error returned when the content encoding is not enabled by the features of this build
+    #[cfg(not(all(feature = "zstd", feature = "brotli")))]
+    pub(crate) fn unsupported_error(&self) -> FluteError {
+        FluteError::new(format!(
+            "Unsupported content encoding {}, the feature is not enabled",
+            self.to_str()
+        ))
+    }
+}
+
+impl LctExtension {
+    /// Create a new header extension
+    ///
+    /// # Arguments
+    ///
+    /// * `het`: Header Extension Type, must not be an extension handled by this library.
+    /// * `content`: Content of the extension, after the HET (and the HEL for variable length extensions).
+    pub fn new(het: u8, content: Vec<u8>) -> Result<Self> {
+        Self::check_het(het)?;
+
+        if het >= 128 {
+            if content.len() != 3 {
+                return Err(FluteError::new(format!(
+                    "Content of HET {} must be 3 bytes, got {}",
+                    het,
+                    content.len()
+                )));
+            }
+        } else {
+            let len = content.len() + 2;
+            if len % 4 != 0 || len / 4 > u8::MAX as usize {
+                return Err(FluteError::new(format!(
+                    "Content of HET {} must be a multiple of 4 bytes minus 2 (up to 1018 bytes), got {}",
+                    het,
+                    content.len()
+                )));
+            }
+        }
+
+        Ok(Self { het, content })
+    }
+
+    /// Return an error if the HET is an extension handled by the library
+    pub(crate) fn check_het(het: u8) -> Result<()> {
+        if Ext::try_from(het).is_ok() {
+            return Err(FluteError::new(format!(
+                "HET {} is an extension handled by the library",
+                het
+            )));
+        }
+        Ok(())
+    }
+
+    /// Header Extension Type
+    pub fn het(&self) -> u8 {
+        self.het
+    }
+
+    /// Content of the extension
+    pub fn content(&self) -> &[u8] {
+        &self.content
+    }
+
+    /// Size of the extension inside the LCT header, in bytes
+    pub fn size(&self) -> usize {
+        match self.het {
+            het if het >= 128 => 4,
+            _ => self.content.len() + 2,
+        }
+    }
+
+    /// Parse an extension returned by `get_ext_list`
+    pub fn parse(ext: &[u8]) -> Result<Self> {
+        if ext.len() < 4 {
+            return Err(FluteError::new("Wrong size of LCT Extension"));
+        }
+
+        let content = match ext[0] {
+            het if het >= 128 => ext[1..4].to_vec(),
+            _ => ext[2..].to_vec(),
+        };
+        Ok(Self {
+            het: ext[0],
+            content,
+        })
+    }
+
+    /// Add the extension to the LCT Header
+    pub fn push(&self, data: &mut Vec<u8>) {
+        let hel = (self.size() >> 2) as u8;
+        data.push(self.het);
+        if self.het < 128 {
+            data.push(hel);
         }
+        data.extend(&self.content);
+        inc_hdr_len(data, hel);
     }
 }
 
@@ -429,30 +590,143 @@ pub fn parse_lct_header(data: &[u8]) -> Result<LCTHeader> {
 ///
 pub fn get_ext<'a>(data: &'a [u8], lct: &LCTHeader, ext: u8) -> Result<Option<&'a [u8]>> {
     let mut lct_ext_ext = &data[(lct.header_ext_offset as usize)..lct.len];
-    while lct_ext_ext.len() >= 4 {
-        let het = lct_ext_ext[0];
-        let hel = match het {
-            het if het >= 128 => 4_usize,
-            _ => (lct_ext_ext[1] << 2) as usize,
-        };
-
-        if hel == 0 || hel > lct_ext_ext.len() {
-            return Err(FluteError::new(format!(
-                "Fail, LCT EXT size is {}/{} het={} offset={}",
-                hel,
-                lct_ext_ext.len(),
-                het,
-                lct.header_ext_offset
-            )));
+    while let Some((current, next)) = split_ext(lct_ext_ext, lct)? {
+        if current[0] == ext {
+            return Ok(Some(current));
         }
+        lct_ext_ext = next;
+    }
 
-        if het == ext {
-            return Ok(Some(&lct_ext_ext[..hel]));
+    Ok(None)
+}
+
+/// Retrieves all the extensions of the LCT Packet, in the order of the header.
+///
+/// Each extension starts with its HET.
+/// Extensions unknown by the caller can be skipped, their length is always given by the HET / HEL.
+///
+/// # Returns
+///
+/// * `Vec<&[u8]>`: Bytes of each extension.
+/// * `Err`: If the packet is malformed.
+///
+pub fn get_ext_list<'a>(data: &'a [u8], lct: &LCTHeader) -> Result<Vec<&'a [u8]>> {
+    let mut list = Vec::new();
+    let mut lct_ext_ext = &data[(lct.header_ext_offset as usize)..lct.len];
+    while let Some((current, next)) = split_ext(lct_ext_ext, lct)? {
+        list.push(current);
+        lct_ext_ext = next;
+    }
+
+    Ok(list)
+}
+
+pub(crate) fn split_ext<'a>(
+    lct_ext_ext: &'a [u8],
+    lct: &LCTHeader,
+) -> Result<Option<(&'a [u8], &'a [u8])>> {
+    if lct_ext_ext.len() < 4 {
+        return Ok(None);
+    }
+
+    let het = lct_ext_ext[0];
+    let hel = match het {
+        het if het >= 128 => 4_usize,
+        _ => (lct_ext_ext[1] as usize) << 2,
+    };
+
+    if hel == 0 || hel > lct_ext_ext.len() {
+        return Err(FluteError::new(format!(
+            "Fail, LCT EXT size is {}/{} het={} offset={}",
+            hel,
+            lct_ext_ext.len(),
+            het,
+            lct.header_ext_offset
+        )));
+    }
+
+    Ok(Some(lct_ext_ext.split_at(hel)))
+}
+
+/// Return the number of extensions of the LCT Packet that are not known by this library
+///
+/// `EXT_NOP` is known, it is simply skipped
+pub fn nb_unknown_ext(data: &[u8], lct: &LCTHeader) -> Result<usize> {
+    let mut nb_unknown = 0;
+    let mut lct_ext_ext = &data[(lct.header_ext_offset as usize)..lct.len];
+    while let Some((current, next)) = split_ext(lct_ext_ext, lct)? {
+        if Ext::try_from(current[0]).is_err() {
+            log::debug!("Skip unknown LCT extension HET={}", current[0]);
+            nb_unknown += 1;
         }
-        lct_ext_ext = &lct_ext_ext[hel..];
+        lct_ext_ext = next;
     }
 
-    Ok(None)
+    Ok(nb_unknown)
+}
+
+/// Add an EXT_NOP extension of `hel` x 32-bit words to the LCT Header
+///
+/// The content of the extension is ignored by the receivers
+pub fn push_nop(data: &mut Vec<u8>, hel: u8) {
+    /*
+    +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+    |   HET = 0     |      HEL      |                               |
+    +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+                               +
+    |                   Header Extension Content                    |
+    +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+    */
+    debug_assert!(hel > 0);
+    data.extend([Ext::Nop as u8, hel, 0, 0]);
+    data.extend(std::iter::repeat(0u8).take(((hel as usize) << 2) - 4));
+    inc_hdr_len(data, hel);
+}
+
+/// Add an EXT_TOL extension (Transfer Object Length) to the LCT Header
+///
+/// The 24-bit variant (HET 194) is used when the length fits, the 48-bit variant (HET 67) otherwise.
+pub fn push_tol(data: &mut Vec<u8>, transfer_length: u64) {
+    /*
+    +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+    |   HET = 194   |           Transfer Length (24 bits)           |
+    +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+
+    +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+    |   HET = 67    |    HEL = 2    |                               |
+    +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+                               +
+    |                   Transfer Length (48 bits)                   |
+    +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+    */
+    if transfer_length < (1 << 24) {
+        let ext = (Ext::Tol24 as u32) << 24 | transfer_length as u32;
+        data.extend(ext.to_be_bytes());
+        inc_hdr_len(data, 1);
+    } else {
+        let ext = (Ext::Tol48 as u64) << 56 | 2u64 << 48 | (transfer_length & 0xFFFFFFFFFFFF);
+        data.extend(ext.to_be_bytes());
+        inc_hdr_len(data, 2);
+    }
+}
+
+/// Parse an EXT_TOL extension (24 or 48 bits)
+pub fn parse_tol(ext: &[u8]) -> Result<u64> {
+    match ext.len() {
+        4 => Ok(u32::from_be_bytes(ext.try_into().unwrap()) as u64 & 0xFFFFFF),
+        8 => Ok(u64::from_be_bytes(ext.try_into().unwrap()) & 0xFFFFFFFFFFFF),
+        _ => Err(FluteError::new("Wrong size of TOL Extension")),
+    }
+}
+
+/// Get the Transfer Object Length of the LCT Packet (EXT_TOL)
+pub fn get_tol(data: &[u8], lct: &LCTHeader) -> Result<Option<u64>> {
+    if let Some(ext) = get_ext(data, lct, Ext::Tol24 as u8)? {
+        return Ok(Some(parse_tol(ext)?));
+    }
+
+    match get_ext(data, lct, Ext::Tol48 as u8)? {
+        Some(ext) => Ok(Some(parse_tol(ext)?)),
+        None => Ok(None),
+    }
 }
 
 #[cfg(test)]
@@ -469,4 +743,84 @@ mod tests {
         let codepoint: u8 = 0;
         super::push_lct_header(&mut lct, psi, &cci, tsi, &toi, codepoint, false, false)
     }
+
+    // LCT header with EXT_NOP, EXT_TOL (24 bits) and two extensions unknown by the library
+    const CAPTURED_PKT: [u8; 41] = [
+        0x10, 0xA0, 0x09, 0x00, // V=1 S=1 O=1 HDR_LEN=9 CP=0
+        0x00, 0x00, 0x00, 0x00, // CCI
+        0x00, 0x00, 0x00, 0x01, // TSI
+        0x00, 0x00, 0x00, 0x02, // TOI
+        0x00, 0x01, 0x00, 0x00, // EXT_NOP HEL=1
+        0xC2, 0x00, 0x04, 0x00, // EXT_TOL 24 bits = 1024
+        0x46, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // HET=70 HEL=2
+        0xC8, 0x12, 0x34, 0x56, // HET=200
+        0x00, 0x00, 0x00, 0x00, // FEC Payload ID
+        0x42,
+    ];
+
+    #[test]
+    pub fn test_lct_captured_ext() {
+        crate::tests::init();
+        let lct = super::parse_lct_header(&CAPTURED_PKT).unwrap();
+        assert_eq!(lct.len, 36);
+        assert_eq!(lct.tsi, 1);
+        assert_eq!(lct.toi, 2);
+
+        let list = super::get_ext_list(&CAPTURED_PKT, &lct).unwrap();
+        let hets: Vec<u8> = list.iter().map(|ext| ext[0]).collect();
+        assert_eq!(hets, vec![0, 194, 70, 200]);
+        assert_eq!(list[2].len(), 8);
+
+        assert_eq!(super::nb_unknown_ext(&CAPTURED_PKT, &lct).unwrap(), 2);
+        assert_eq!(super::get_tol(&CAPTURED_PKT, &lct).unwrap(), Some(1024));
+        assert!(super::get_ext(&CAPTURED_PKT, &lct, 200).unwrap().is_some());
+    }
+
+    #[test]
+    pub fn test_lct_malformed_ext() {
+        crate::tests::init();
+        let mut data = CAPTURED_PKT.to_vec();
+        // HEL of the unknown extension goes beyond the LCT header
+        data[25] = 0x08;
+        let lct = super::parse_lct_header(&data).unwrap();
+        assert!(super::get_ext_list(&data, &lct).is_err());
+        assert!(super::nb_unknown_ext(&data, &lct).is_err());
+    }
+
+    #[test]
+    pub fn test_lct_extension() {
+        crate::tests::init();
+        assert!(super::LctExtension::new(super::Ext::Fdt as u8, vec![0, 0, 0]).is_err());
+        assert!(super::LctExtension::new(200, vec![0, 0]).is_err());
+        assert!(super::LctExtension::new(100, vec![0, 0, 0]).is_err());
+
+        let fixed = super::LctExtension::new(200, vec![1, 2, 3]).unwrap();
+        let variable = super::LctExtension::new(100, vec![4, 5, 6, 7, 8, 9]).unwrap();
+
+        let mut data = Vec::new();
+        super::push_lct_header(&mut data, 0, &0, 1, &2, 0, false, false);
+        fixed.push(&mut data);
+        variable.push(&mut data);
+        let lct = super::parse_lct_header(&data).unwrap();
+        assert_eq!(lct.len, data.len());
+
+        let list = super::get_ext_list(&data, &lct).unwrap();
+        assert_eq!(list.len(), 2);
+        assert_eq!(super::LctExtension::parse(list[0]).unwrap(), fixed);
+        assert_eq!(super::LctExtension::parse(list[1]).unwrap(), variable);
+    }
+
+    #[test]
+    pub fn test_lct_tol() {
+        crate::tests::init();
+        for transfer_length in [0u64, 1024, (1 << 24) - 1, 1 << 24, 0xFFFFFFFFFFFF] {
+            let mut data = Vec::new();
+            super::push_lct_header(&mut data, 0, &0, 1, &2, 0, false, false);
+            super::push_nop(&mut data, 2);
+            super::push_tol(&mut data, transfer_length);
+            let lct = super::parse_lct_header(&data).unwrap();
+            assert_eq!(super::nb_unknown_ext(&data, &lct).unwrap(), 0);
+            assert_eq!(super::get_tol(&data, &lct).unwrap(), Some(transfer_length));
+        }
+    }
 }
@@ -14,7 +14,10 @@ pub enum Profile {
 pub mod alc;
 pub mod fdtinstance;
 pub mod lct;
+pub mod objectpart;
 pub mod oti;
 pub mod partition;
+pub mod planner;
 pub mod pkt;
+pub mod segment;
 pub mod udpendpoint;
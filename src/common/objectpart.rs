@@ -0,0 +1,21 @@
+///
+/// Part of an object that has been split in several transport objects (TOI)
+///
+/// Objects larger than the maximum transfer length of an OTI can be split by the sender.
+/// Each part is announced in the FDT with the information required to re-assemble the whole object.
+///
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ObjectPart {
+    /// Identifier shared by all the parts of the object (Content-Location of the whole object)
+    pub group: String,
+    /// Index of this part, starting at 0
+    pub index: u32,
+    /// Total number of parts
+    pub count: u32,
+    /// Offset (in bytes) of this part inside the whole object
+    pub offset: u64,
+    /// Length of the whole object
+    pub group_content_length: u64,
+    /// MD5 of the whole object
+    pub group_md5: Option<String>,
+}
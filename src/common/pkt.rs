@@ -1,7 +1,8 @@
 use super::lct;
 
+/// Packet built by the sender, internal to the crate
 #[derive(Debug)]
-pub struct Pkt {
+pub(crate) struct Pkt {
     pub payload: Vec<u8>,
     pub transfer_length: u64,
     pub esi: u32,
@@ -10,6 +11,8 @@ pub struct Pkt {
     pub fdt_id: Option<u32>,
     pub cenc: lct::Cenc,
     pub inband_cenc: bool,
+    pub inband_fti: bool,
+    pub inband_tol: bool,
     pub close_object: bool,
     pub source_block_length: u32,
     pub sender_current_time: bool,
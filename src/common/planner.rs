@@ -0,0 +1,466 @@
+//!
+//! Helpers to choose the FEC parameters (OTI) of an object
+//!
+//! Finding a valid combination of `encoding_symbol_length`, `maximum_source_block_length`
+//! and `max_number_of_parity_symbols` for a given transfer length is not trivial,
+//! each FEC scheme has its own limits (number of source blocks, size of a block, transfer length...).
+//!
+
+use super::alc;
+use super::lct;
+use super::oti::{FECEncodingID, Oti};
+use super::partition;
+use super::pkt::Pkt;
+use super::udpendpoint::UDPEndpoint;
+use super::Profile;
+use crate::tools::error::{FluteError, Result};
+
+/// Size of the UDP header
+const UDP_HEADER_LENGTH: usize = 8;
+
+/// Size of the IPv4 header, without options
+const IPV4_HEADER_LENGTH: usize = 20;
+
+/// Size of the IPv6 header, without extension headers
+const IPV6_HEADER_LENGTH: usize = 40;
+
+/// Maximum size of an IP packet (16-bit total length of IPv4)
+pub(crate) const MAX_IP_PACKET_LENGTH: usize = 65535;
+
+/// Maximum transfer length of RaptorQ
+/// See <https://www.rfc-editor.org/rfc/rfc6330.html#section-4.1>
+const RAPTORQ_MAX_TRANSFER_LENGTH: u64 = 946270874880;
+
+/// Maximum number of source symbols per block for RaptorQ
+/// See <https://www.rfc-editor.org/rfc/rfc6330.html#section-5.1.2>
+const RAPTORQ_MAX_SOURCE_BLOCK_LENGTH: u32 = 56403;
+
+/// Maximum number of source symbols per block for Raptor
+/// See <https://www.rfc-editor.org/rfc/rfc5053.html#section-4.2>
+const RAPTOR_MAX_SOURCE_BLOCK_LENGTH: u32 = 8192;
+
+/// Symbol alignment used for Raptor and RaptorQ
+const SYMBOL_ALIGNMENT: u16 = 4;
+
+///
+/// A valid set of FEC parameters for a transfer
+///
+#[derive(Debug, Clone)]
+pub struct OtiCandidate {
+    /// FEC Object Transmission Information
+    pub oti: Oti,
+    /// Maximum transfer length that can be handled with this OTI
+    pub max_transfer_length: u64,
+    /// Number of source blocks of the transfer
+    pub nb_blocks: u64,
+    /// Number of source symbols of the transfer
+    pub nb_source_symbols: u64,
+    /// Number of ALC/LCT packets sent for one transfer of the object (source + repair symbols)
+    pub nb_packets: u64,
+    /// Ratio of repair symbols compared to the source symbols
+    pub overhead: f64,
+}
+
+struct SchemeLimits {
+    max_source_block_length: u32,
+    max_encoding_block_length: u64,
+    max_source_blocks_number: u64,
+    max_transfer_length: u64,
+}
+
+fn scheme_limits(fec_encoding_id: FECEncodingID) -> Result<SchemeLimits> {
+    let limits = match fec_encoding_id {
+        FECEncodingID::NoCode => SchemeLimits {
+            max_source_block_length: u16::MAX as u32,
+            max_encoding_block_length: u16::MAX as u64,
+            max_source_blocks_number: u16::MAX as u64,
+            max_transfer_length: 0xFFFFFFFFFFFF,
+        },
+        FECEncodingID::ReedSolomonGF28 => SchemeLimits {
+            max_source_block_length: u8::MAX as u32,
+            max_encoding_block_length: u8::MAX as u64,
+            max_source_blocks_number: u8::MAX as u64,
+            max_transfer_length: 0xFFFFFFFFFFFF,
+        },
+        FECEncodingID::ReedSolomonGF28UnderSpecified => SchemeLimits {
+            max_source_block_length: u16::MAX as u32,
+            max_encoding_block_length: u16::MAX as u64,
+            max_source_blocks_number: u32::MAX as u64,
+            max_transfer_length: 0xFFFFFFFFFFFF,
+        },
+        FECEncodingID::RaptorQ => SchemeLimits {
+            max_source_block_length: RAPTORQ_MAX_SOURCE_BLOCK_LENGTH,
+            max_encoding_block_length: 1 << 24,
+            max_source_blocks_number: u8::MAX as u64,
+            max_transfer_length: RAPTORQ_MAX_TRANSFER_LENGTH,
+        },
+        FECEncodingID::Raptor => SchemeLimits {
+            max_source_block_length: RAPTOR_MAX_SOURCE_BLOCK_LENGTH,
+            max_encoding_block_length: 1 << 16,
+            max_source_blocks_number: u16::MAX as u64,
+            max_transfer_length: 0xFFFFFFFFFFFF,
+        },
+        FECEncodingID::ReedSolomonGF2M => {
+            return Err(FluteError::new("ReedSolomonGF2M is not implemented"))
+        }
+    };
+    Ok(limits)
+}
+
+pub(crate) fn new_oti(
+    fec_encoding_id: FECEncodingID,
+    encoding_symbol_length: u16,
+    maximum_source_block_length: u32,
+    max_number_of_parity_symbols: u32,
+) -> Result<Oti> {
+    match fec_encoding_id {
+        FECEncodingID::NoCode => Ok(Oti::new_no_code(
+            encoding_symbol_length,
+            maximum_source_block_length as u16,
+        )),
+        FECEncodingID::ReedSolomonGF28 => Oti::new_reed_solomon_rs28(
+            encoding_symbol_length,
+            maximum_source_block_length as u8,
+            max_number_of_parity_symbols as u8,
+        ),
+        FECEncodingID::ReedSolomonGF28UnderSpecified => Oti::new_reed_solomon_rs28_under_specified(
+            encoding_symbol_length,
+            maximum_source_block_length as u16,
+            max_number_of_parity_symbols as u16,
+        ),
+        FECEncodingID::RaptorQ => {
+            if maximum_source_block_length > RAPTORQ_MAX_SOURCE_BLOCK_LENGTH {
+                return Err(FluteError::new(format!(
+                    "RaptorQ source block length {} exceeds {}",
+                    maximum_source_block_length, RAPTORQ_MAX_SOURCE_BLOCK_LENGTH
+                )));
+            }
+            let mut oti = Oti::new_raptorq(
+                encoding_symbol_length,
+                maximum_source_block_length as u16,
+                0,
+                1,
+                SYMBOL_ALIGNMENT as u8,
+            )?;
+            // new_raptorq() takes the number of repair symbols as a u16,
+            // but a RaptorQ encoding block can carry up to 2^24 symbols (24-bit ESI)
+            oti.max_number_of_parity_symbols = max_number_of_parity_symbols;
+            Ok(oti)
+        }
+        FECEncodingID::Raptor => Oti::new_raptor(
+            encoding_symbol_length,
+            maximum_source_block_length as u16,
+            max_number_of_parity_symbols as u16,
+            1,
+            SYMBOL_ALIGNMENT as u8,
+        ),
+        FECEncodingID::ReedSolomonGF2M => {
+            Err(FluteError::new("ReedSolomonGF2M is not implemented"))
+        }
+    }
+}
+
+/// Return the size of the IP + UDP headers for the address family of the endpoint
+pub fn ip_udp_header_length(endpoint: &UDPEndpoint) -> Result<usize> {
+    let ip_header_length = match endpoint.destination_ip()? {
+        std::net::IpAddr::V4(_) => IPV4_HEADER_LENGTH,
+        std::net::IpAddr::V6(_) => IPV6_HEADER_LENGTH,
+    };
+    Ok(ip_header_length + UDP_HEADER_LENGTH)
+}
+
+/// Return the maximum length of an ALC/LCT header for the FEC scheme
+/// (largest TSI & TOI, in-band FTI, TOL, CENC and sender current time)
+///
+/// Header extensions added by the application are not included
+pub fn alc_header_max_length(oti: &Oti) -> usize {
+    let pkt = Pkt {
+        payload: Vec::new(),
+        // Large enough to select the 48-bit EXT_TOL
+        transfer_length: 1 << 24,
+        esi: 0,
+        sbn: 0,
+        toi: u64::MAX as u128,
+        fdt_id: None,
+        cenc: lct::Cenc::Null,
+        inband_cenc: true,
+        inband_fti: true,
+        inband_tol: true,
+        close_object: false,
+        source_block_length: oti.maximum_source_block_length,
+        sender_current_time: true,
+    };
+    let mut oti = oti.clone();
+    oti.inband_fti = true;
+    alc::new_alc_pkt(
+        &oti,
+        &0u128,
+        u64::MAX,
+        &pkt,
+        &[],
+        Profile::RFC6726,
+        std::time::SystemTime::now(),
+    )
+    .len()
+}
+
+///
+/// Compute the FEC parameters that can be used to transfer an object
+///
+/// # Arguments
+///
+/// * `transfer_length`: Length of the object in bytes (after content encoding)
+/// * `endpoint`: Destination of the packets, its address family gives the size of the IP header
+/// * `mtu`: Maximum Transmission Unit of the network (IP packet size)
+/// * `target_overhead`: Ratio of repair symbols to add to each block (`0.1` for 10%)
+/// * `fec_encoding_id`: FEC scheme
+///
+/// # Returns
+///
+/// A list of valid candidates, ordered from the largest to the smallest source block length.
+///
+/// # Errors
+///
+/// Returns an error if the destination address is invalid, if the MTU is too small, if the overhead is not supported by the FEC scheme
+/// or if the object cannot be transferred with this FEC scheme.
+///
+/// # Example
+///
+/// ```
+/// use flute::core::FECEncodingID;
+/// use flute::core::planner;
+/// use flute::core::UDPEndpoint;
+///
+/// let endpoint = UDPEndpoint::new(None, "ff05::1".to_owned(), 3400);
+/// let candidates =
+///     planner::plan(100 * 1024 * 1024, &endpoint, 1500, 0.1, FECEncodingID::RaptorQ).unwrap();
+/// let oti = candidates[0].oti.clone();
+/// ```
+///
+pub fn plan(
+    transfer_length: u64,
+    endpoint: &UDPEndpoint,
+    mtu: usize,
+    target_overhead: f64,
+    fec_encoding_id: FECEncodingID,
+) -> Result<Vec<OtiCandidate>> {
+    if target_overhead.is_nan() || target_overhead < 0.0 {
+        return Err(FluteError::new("Target overhead must be positive"));
+    }
+
+    if fec_encoding_id == FECEncodingID::NoCode && target_overhead > 0.0 {
+        return Err(FluteError::new("NoCode scheme cannot add repair symbols"));
+    }
+
+    let limits = scheme_limits(fec_encoding_id)?;
+    if transfer_length > limits.max_transfer_length {
+        return Err(FluteError::new(format!(
+            "Transfer length {} exceeds the maximum transfer length {} of {:?}",
+            transfer_length, limits.max_transfer_length, fec_encoding_id
+        )));
+    }
+
+    let header_length = alc_header_max_length(&new_oti(fec_encoding_id, 0, 1, 0)?);
+    let encoding_symbol_length = mtu
+        .saturating_sub(ip_udp_header_length(endpoint)? + header_length)
+        .min(u16::MAX as usize) as u16;
+    let encoding_symbol_length = match fec_encoding_id {
+        FECEncodingID::RaptorQ | FECEncodingID::Raptor => {
+            encoding_symbol_length - (encoding_symbol_length % SYMBOL_ALIGNMENT)
+        }
+        _ => encoding_symbol_length,
+    };
+
+    if encoding_symbol_length == 0 {
+        return Err(FluteError::new(format!(
+            "MTU {} is too small to carry ALC/LCT packets",
+            mtu
+        )));
+    }
+
+    let nb_source_symbols =
+        num_integer::div_ceil(transfer_length, encoding_symbol_length as u64).max(1);
+
+    // Largest block that fits inside the encoding block once the repair symbols are added
+    let mut block_length = (limits.max_source_block_length as u64)
+        .min(nb_source_symbols)
+        .min((limits.max_encoding_block_length as f64 / (1.0 + target_overhead)) as u64);
+    while block_length > 0
+        && block_length + nb_parity_symbols(block_length, target_overhead)
+            > limits.max_encoding_block_length
+    {
+        block_length -= 1;
+    }
+
+    let mut candidates: Vec<OtiCandidate> = Vec::new();
+    while block_length > 0 {
+        let candidate = plan_candidate(
+            transfer_length,
+            encoding_symbol_length,
+            block_length as u32,
+            target_overhead,
+            fec_encoding_id,
+            &limits,
+        )?;
+
+        match candidate {
+            Some(candidate) => candidates.push(candidate),
+            // Smaller blocks would require even more source blocks
+            None => break,
+        }
+
+        block_length /= 2;
+    }
+
+    if candidates.is_empty() {
+        return Err(FluteError::new(format!(
+            "No valid OTI for a transfer length of {} bytes with {:?}, symbol length {} and overhead {}",
+            transfer_length, fec_encoding_id, encoding_symbol_length, target_overhead
+        )));
+    }
+
+    Ok(candidates)
+}
+
+fn nb_parity_symbols(block_length: u64, target_overhead: f64) -> u64 {
+    (block_length as f64 * target_overhead).ceil() as u64
+}
+
+fn plan_candidate(
+    transfer_length: u64,
+    encoding_symbol_length: u16,
+    block_length: u32,
+    target_overhead: f64,
+    fec_encoding_id: FECEncodingID,
+    limits: &SchemeLimits,
+) -> Result<Option<OtiCandidate>> {
+    let nb_parity_symbols = nb_parity_symbols(block_length as u64, target_overhead);
+    let (a_large, a_small, nb_a_large, nb_blocks) = partition::block_partitioning(
+        block_length as u64,
+        transfer_length,
+        encoding_symbol_length as u64,
+    );
+    if nb_blocks > limits.max_source_blocks_number {
+        return Ok(None);
+    }
+
+    let oti = new_oti(
+        fec_encoding_id,
+        encoding_symbol_length,
+        block_length,
+        nb_parity_symbols as u32,
+    )?;
+
+    let nb_source_symbols = a_large * nb_a_large + a_small * (nb_blocks - nb_a_large);
+    let nb_repair_symbols = nb_parity_symbols * nb_blocks;
+    let max_transfer_length =
+        (encoding_symbol_length as u64 * block_length as u64 * limits.max_source_blocks_number)
+            .min(limits.max_transfer_length);
+
+    Ok(Some(OtiCandidate {
+        oti,
+        max_transfer_length,
+        nb_blocks,
+        nb_source_symbols,
+        nb_packets: nb_source_symbols + nb_repair_symbols,
+        overhead: match nb_source_symbols {
+            0 => 0.0,
+            n => nb_repair_symbols as f64 / n as f64,
+        },
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::plan;
+    use crate::common::oti::FECEncodingID;
+    use crate::common::udpendpoint::UDPEndpoint;
+
+    fn endpoint() -> UDPEndpoint {
+        UDPEndpoint::new(None, "224.0.0.1".to_owned(), 3400)
+    }
+
+    #[test]
+    pub fn test_plan_raptorq() {
+        crate::tests::init();
+        let transfer_length = 100 * 1024 * 1024;
+        let candidates = plan(
+            transfer_length,
+            &endpoint(),
+            1500,
+            0.1,
+            FECEncodingID::RaptorQ,
+        )
+        .unwrap();
+        assert!(!candidates.is_empty());
+        for candidate in &candidates {
+            let oti = &candidate.oti;
+            log::info!("{:?}", candidate);
+            assert!(oti.encoding_symbol_length % 4 == 0);
+            assert!(candidate.nb_blocks <= 255);
+            assert!(candidate.max_transfer_length >= transfer_length);
+            assert!(
+                candidate.nb_source_symbols * oti.encoding_symbol_length as u64 >= transfer_length
+            );
+            assert!(candidate.nb_packets > candidate.nb_source_symbols);
+        }
+    }
+
+    #[test]
+    pub fn test_plan_reed_solomon() {
+        crate::tests::init();
+        let candidates = plan(
+            1024 * 1024,
+            &endpoint(),
+            1500,
+            0.2,
+            FECEncodingID::ReedSolomonGF28,
+        )
+        .unwrap();
+        for candidate in &candidates {
+            let oti = &candidate.oti;
+            assert!(oti.maximum_source_block_length + oti.max_number_of_parity_symbols <= 255);
+            assert!(candidate.nb_blocks <= 255);
+        }
+    }
+
+    #[test]
+    pub fn test_plan_ipv6() {
+        crate::tests::init();
+        let ipv6 = UDPEndpoint::new(None, "ff05::1".to_owned(), 3400);
+        let candidates_v4 =
+            plan(1024 * 1024, &endpoint(), 1500, 0.0, FECEncodingID::NoCode).unwrap();
+        let candidates_v6 = plan(1024 * 1024, &ipv6, 1500, 0.0, FECEncodingID::NoCode).unwrap();
+        // The IPv6 header is 20 bytes larger than the IPv4 header
+        assert_eq!(
+            candidates_v4[0].oti.encoding_symbol_length,
+            candidates_v6[0].oti.encoding_symbol_length + 20
+        );
+
+        let invalid = UDPEndpoint::new(None, "not an address".to_owned(), 3400);
+        assert!(plan(1024, &invalid, 1500, 0.0, FECEncodingID::NoCode).is_err());
+    }
+
+    #[test]
+    pub fn test_plan_limits() {
+        crate::tests::init();
+        // RaptorQ cannot transfer more than 946270874880 bytes
+        assert!(plan(1 << 40, &endpoint(), 1500, 0.1, FECEncodingID::RaptorQ).is_err());
+        // RaptorQ blocks are limited to 56403 source symbols
+        assert!(super::new_oti(FECEncodingID::RaptorQ, 1400, 56403, 100_000).is_ok());
+        assert!(super::new_oti(FECEncodingID::RaptorQ, 1400, 56404, 0).is_err());
+        // Too many blocks for RS28
+        assert!(plan(
+            1 << 30,
+            &endpoint(),
+            1500,
+            0.1,
+            FECEncodingID::ReedSolomonGF28
+        )
+        .is_err());
+        // NoCode has no repair symbols
+        assert!(plan(1024, &endpoint(), 1500, 0.1, FECEncodingID::NoCode).is_err());
+        // MTU too small
+        assert!(plan(1024, &endpoint(), 40, 0.0, FECEncodingID::NoCode).is_err());
+    }
+}
@@ -0,0 +1,9 @@
+//! FDT groups of the segmented media (DASH, HLS), shared by the `SegmentSender`
+//! and the `ObjectWriterSegmentBuilder`
+
+/// FDT group of the manifests (DASH MPD, HLS playlists)
+pub const SEGMENT_GROUP_MANIFEST: &str = "manifest";
+/// FDT group of the initialization segments
+pub const SEGMENT_GROUP_INIT: &str = "init";
+/// FDT group of the media segments
+pub const SEGMENT_GROUP_MEDIA: &str = "media";
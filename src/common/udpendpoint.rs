@@ -1,9 +1,23 @@
-use std::hash::Hash;
+use std::hash::{Hash, Hasher};
+use std::net::{IpAddr, SocketAddr};
 
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::tools::error::{FluteError, Result};
 
 /// UDP Endpoint
-#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, Eq, Hash)]
+///
+/// Addresses are stored as strings for compatibility.
+/// Use `UDPEndpoint::try_new()` or `UDPEndpoint::from_addrs()` to build a validated endpoint.
+///
+/// Two endpoints are equal when their addresses are equal once parsed (ex: `::1` and `0:0::1`).
+///
+/// An endpoint is serialized and deserialized as a struct. The addresses that can be parsed are
+/// deserialized in their canonical form, the others are kept unchanged, call `validate()` to check them.
+///
+/// An endpoint can also be parsed from a string with the format `[source@]destination:port`
+/// (ex: `10.0.0.1@232.0.0.1:3400` or `[ff3e::1]:3400`).
+#[derive(Debug, Serialize, Clone)]
 #[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct UDPEndpoint {
     /// Network source adress
@@ -14,6 +28,26 @@ pub struct UDPEndpoint {
     pub port: u16,
 }
 
+#[derive(PartialEq, Eq, Hash)]
+enum AddrKey<'a> {
+    Ip(IpAddr),
+    Str(&'a str),
+}
+
+impl<'a> AddrKey<'a> {
+    fn new(addr: &'a str) -> Self {
+        match addr.parse::<IpAddr>() {
+            Ok(ip) => AddrKey::Ip(ip),
+            Err(_) => AddrKey::Str(addr),
+        }
+    }
+}
+
+fn parse_addr(addr: &str, name: &str) -> Result<IpAddr> {
+    addr.parse::<IpAddr>()
+        .map_err(|_| FluteError::new(format!("Invalid {} address {}", name, addr)))
+}
+
 impl UDPEndpoint {
     /// Create a new UDP Endpoint
     pub fn new(src: Option<String>, dest: String, port: u16) -> Self {
@@ -23,4 +57,243 @@ impl UDPEndpoint {
             port,
         }
     }
+
+    /// Create a new UDP Endpoint from parsed addresses
+    pub fn from_addrs(src: Option<IpAddr>, dest: IpAddr, port: u16) -> Self {
+        Self {
+            source_address: src.map(|src| src.to_string()),
+            destination_group_address: dest.to_string(),
+            port,
+        }
+    }
+
+    /// Create a new UDP Endpoint
+    ///
+    /// Return an error if an address can not be parsed or if the source and the destination
+    /// are not of the same IP version.
+    /// The addresses are stored in their canonical form.
+    pub fn try_new(src: Option<&str>, dest: &str, port: u16) -> Result<Self> {
+        let endpoint = Self::new(src.map(|src| src.to_string()), dest.to_string(), port);
+        let (source, dest) = endpoint.addrs()?;
+        Ok(Self::from_addrs(source, dest, port))
+    }
+
+    /// Parsed destination group address
+    pub fn destination_ip(&self) -> Result<IpAddr> {
+        parse_addr(&self.destination_group_address, "destination")
+    }
+
+    /// Parsed source address
+    pub fn source_ip(&self) -> Result<Option<IpAddr>> {
+        self.source_address
+            .as_deref()
+            .map(|src| parse_addr(src, "source"))
+            .transpose()
+    }
+
+    /// Parsed source and destination addresses
+    pub fn addrs(&self) -> Result<(Option<IpAddr>, IpAddr)> {
+        let dest = self.destination_ip()?;
+        let source = self.source_ip()?;
+        if let Some(source) = source {
+            if source.is_ipv4() != dest.is_ipv4() {
+                return Err(FluteError::new(format!(
+                    "Source {} and destination {} are not of the same IP version",
+                    source, dest
+                )));
+            }
+        }
+        Ok((source, dest))
+    }
+
+    /// Check that the addresses of the endpoint are valid
+    pub fn validate(&self) -> Result<()> {
+        self.addrs().map(|_| ())
+    }
+
+    /// Return the endpoint with its addresses in their canonical form
+    ///
+    /// Addresses that can not be parsed are kept unchanged
+    pub fn canonical(&self) -> Self {
+        let canonical = |addr: &str| match addr.parse::<IpAddr>() {
+            Ok(ip) => ip.to_string(),
+            Err(_) => addr.to_string(),
+        };
+        Self {
+            source_address: self.source_address.as_deref().map(canonical),
+            destination_group_address: canonical(&self.destination_group_address),
+            port: self.port,
+        }
+    }
+
+    fn key(&self) -> (Option<AddrKey<'_>>, AddrKey<'_>, u16) {
+        (
+            self.source_address.as_deref().map(AddrKey::new),
+            AddrKey::new(&self.destination_group_address),
+            self.port,
+        )
+    }
+}
+
+impl PartialEq for UDPEndpoint {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for UDPEndpoint {}
+
+impl Hash for UDPEndpoint {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state)
+    }
+}
+
+impl std::fmt::Display for UDPEndpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(source) = &self.source_address {
+            write!(f, "{}@", source)?;
+        }
+        match self.destination_ip() {
+            Ok(dest) => write!(f, "{}", SocketAddr::new(dest, self.port)),
+            Err(_) => write!(f, "{}:{}", self.destination_group_address, self.port),
+        }
+    }
+}
+
+impl std::str::FromStr for UDPEndpoint {
+    type Err = FluteError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (source, dest) = match s.split_once('@') {
+            Some((source, dest)) => (Some(source), dest),
+            None => (None, s),
+        };
+        let dest = dest
+            .parse::<SocketAddr>()
+            .map_err(|_| FluteError::new(format!("Invalid endpoint {}", s)))?;
+        let source = source.map(|src| parse_addr(src, "source")).transpose()?;
+        let endpoint = Self::from_addrs(source, dest.ip(), dest.port());
+        endpoint.validate()?;
+        Ok(endpoint)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "UDPEndpoint")]
+struct UDPEndpointFields {
+    source_address: Option<String>,
+    destination_group_address: String,
+    port: u16,
+}
+
+impl<'de> Deserialize<'de> for UDPEndpoint {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let fields = UDPEndpointFields::deserialize(deserializer)?;
+        let endpoint = UDPEndpoint::new(
+            fields.source_address,
+            fields.destination_group_address,
+            fields.port,
+        );
+        Ok(endpoint.canonical())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UDPEndpoint;
+    use std::collections::HashSet;
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+    #[test]
+    pub fn test_udpendpoint_canonical() {
+        crate::tests::init();
+        let a = UDPEndpoint::new(Some("::1".to_owned()), "ff3e::1234".to_owned(), 3400);
+        let b = UDPEndpoint::new(Some("0:0::1".to_owned()), "FF3E:0::1234".to_owned(), 3400);
+        assert_eq!(a, b);
+
+        let mut set = HashSet::new();
+        set.insert(a.clone());
+        assert!(set.contains(&b));
+        assert_eq!(b.canonical().destination_group_address, "ff3e::1234");
+        assert_eq!(b.canonical().source_address.as_deref(), Some("::1"));
+
+        let c = UDPEndpoint::new(None, "ff3e::1234".to_owned(), 3400);
+        assert_ne!(a, c);
+    }
+
+    #[test]
+    pub fn test_udpendpoint_validation() {
+        crate::tests::init();
+        assert!(UDPEndpoint::try_new(None, "224.0.0.1:3400", 3400).is_err());
+        assert!(UDPEndpoint::try_new(Some("::1"), "224.0.0.1", 3400).is_err());
+        assert!(
+            UDPEndpoint::new(Some("bad".to_owned()), "224.0.0.1".to_owned(), 3400)
+                .validate()
+                .is_err()
+        );
+
+        let endpoint = UDPEndpoint::try_new(Some("10.0.0.1"), "232.0.0.1", 3400).unwrap();
+        assert_eq!(
+            endpoint.destination_ip().unwrap(),
+            IpAddr::V4(Ipv4Addr::new(232, 0, 0, 1))
+        );
+        assert_eq!(
+            endpoint.source_ip().unwrap(),
+            Some(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)))
+        );
+
+        let endpoint = UDPEndpoint::from_addrs(None, IpAddr::V6(Ipv6Addr::LOCALHOST), 3400);
+        assert_eq!(endpoint.destination_group_address, "::1");
+    }
+
+    #[test]
+    pub fn test_udpendpoint_string() {
+        crate::tests::init();
+        let endpoint: UDPEndpoint = "10.0.0.1@232.0.0.1:3400".parse().unwrap();
+        assert_eq!(
+            endpoint,
+            UDPEndpoint::new(Some("10.0.0.1".to_owned()), "232.0.0.1".to_owned(), 3400)
+        );
+        assert_eq!(endpoint.to_string(), "10.0.0.1@232.0.0.1:3400");
+
+        let endpoint: UDPEndpoint = "[ff3e::1]:3400".parse().unwrap();
+        assert_eq!(endpoint.destination_group_address, "ff3e::1");
+        assert_eq!(endpoint.to_string(), "[ff3e::1]:3400");
+
+        assert!("232.0.0.1".parse::<UDPEndpoint>().is_err());
+        assert!("::1@232.0.0.1:3400".parse::<UDPEndpoint>().is_err());
+    }
+
+    #[test]
+    pub fn test_udpendpoint_serde() {
+        crate::tests::init();
+        let endpoint = UDPEndpoint::new(None, "224.0.0.1".to_owned(), 3400);
+        let json = serde_json::to_string(&endpoint).unwrap();
+        let decoded: UDPEndpoint = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, endpoint);
+
+        let decoded: UDPEndpoint =
+            serde_json::from_str(r#"{"destination_group_address": "0:0::1", "port": 3400}"#)
+                .unwrap();
+        assert_eq!(decoded.destination_group_address, "::1");
+        assert!(decoded.source_address.is_none());
+
+        // Endpoints that are not validated are kept unchanged
+        let endpoint = UDPEndpoint::new(
+            Some("sender.local".to_owned()),
+            "flute.local".to_owned(),
+            3400,
+        );
+        let json = serde_json::to_string(&endpoint).unwrap();
+        let decoded: UDPEndpoint = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded.source_address.as_deref(), Some("sender.local"));
+        assert_eq!(decoded.destination_group_address, "flute.local");
+        assert!(decoded.validate().is_err());
+
+        assert!(serde_json::from_str::<UDPEndpoint>("\"224.0.0.1:3400\"").is_err());
+    }
 }
@@ -39,7 +39,7 @@ pub trait FecEncoder {
     fn encode(&self, data: &[u8]) -> Result<Vec<Box<dyn FecShard>>>;
 }
 
-pub trait FecDecoder {
+pub trait FecDecoder: Send {
     fn push_symbol(&mut self, encoding_symbol: &[u8], esi: u32);
     fn can_decode(&self) -> bool;
     fn decode(&mut self) -> bool;
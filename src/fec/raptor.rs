@@ -4,29 +4,37 @@ use crate::error::{FluteError, Result};
 pub struct RaptorEncoder {
     nb_parity_symbols: usize,
     nb_source_symbols: usize,
+    repair_esi_offset: u32,
 }
 
 impl RaptorEncoder {
-    pub fn new(nb_source_symbols: usize, nb_parity_symbols: usize) -> RaptorEncoder {
+    pub fn new(
+        nb_source_symbols: usize,
+        nb_parity_symbols: usize,
+        repair_esi_offset: u32,
+    ) -> RaptorEncoder {
         RaptorEncoder {
             nb_parity_symbols,
             nb_source_symbols,
+            repair_esi_offset,
         }
     }
 }
 
 impl FecEncoder for RaptorEncoder {
     fn encode(&self, data: &[u8]) -> Result<Vec<Box<dyn super::FecShard>>> {
-        let mut encoder = raptor_code::SourceBlockEncoder::new(data, self.nb_source_symbols);
-        let nb_source_symbols = encoder.nb_source_symbols() as usize;
-        let n = nb_source_symbols + self.nb_parity_symbols;
+        let mut encoder = raptor_code::SourceBlockEncoder::new(data, self.nb_source_symbols)
+            .map_err(FluteError::new)?;
+        let nb_source_symbols = encoder.nb_source_symbols();
+        let repair_start = nb_source_symbols + self.repair_esi_offset;
+        let repair_end = repair_start + self.nb_parity_symbols as u32;
 
         let mut output: Vec<Box<dyn FecShard>> = Vec::new();
 
-        for esi in 0..n {
+        for esi in (0..nb_source_symbols).chain(repair_start..repair_end) {
             let shard = DataFecShard {
-                shard: encoder.fountain(esi as u32),
-                index: esi as u32,
+                shard: encoder.fountain(esi),
+                index: esi,
             };
             log::info!("Encode shard {}", shard.shard.len());
             output.push(Box::new(shard));
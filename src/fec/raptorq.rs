@@ -6,6 +6,7 @@ use super::{FecDecoder, FecEncoder, FecShard};
 pub struct RaptorQEncoder {
     config: raptorq::ObjectTransmissionInformation,
     nb_parity_symbols: usize,
+    repair_esi_offset: u32,
 }
 
 #[derive(Debug)]
@@ -28,9 +29,11 @@ impl RaptorQEncoder {
         nb_parity_symbols: usize,
         encoding_symbol_length: usize,
         scheme: &RaptorQSchemeSpecific,
+        repair_esi_offset: u32,
     ) -> Self {
         RaptorQEncoder {
             nb_parity_symbols,
+            repair_esi_offset,
             config: raptorq::ObjectTransmissionInformation::new(
                 (nb_source_symbols * encoding_symbol_length) as u64,
                 encoding_symbol_length as u16,
@@ -58,7 +61,8 @@ impl FecEncoder for RaptorQEncoder {
         };
 
         let src_pkt = encoder.source_packets();
-        let repair_pkt = encoder.repair_packets(0, self.nb_parity_symbols as u32);
+        let repair_pkt =
+            encoder.repair_packets(self.repair_esi_offset, self.nb_parity_symbols as u32);
         let mut output: Vec<Box<dyn FecShard>> = Vec::new();
 
         for pkt in src_pkt {
@@ -137,7 +141,10 @@ impl FecDecoder for RaptorQDecoder {
 
 #[cfg(test)]
 mod tests {
-    use crate::{common::oti::RaptorQSchemeSpecific, fec::FecEncoder};
+    use crate::{
+        common::oti::RaptorQSchemeSpecific,
+        fec::{FecDecoder, FecEncoder},
+    };
 
     #[test]
     pub fn test_raptorq_encode() {
@@ -160,8 +167,49 @@ mod tests {
             nb_parity_symbols,
             symbols_length,
             &scheme,
+            0,
         );
         let encoded_data = r.encode(data.as_ref()).unwrap();
         log::info!("NB source symbols={}", encoded_data.len());
     }
+
+    #[test]
+    pub fn test_raptorq_encode_repair_offset() {
+        crate::tests::init();
+
+        let nb_source_symbols = 10usize;
+        let nb_parity_symbols = 4usize;
+        let symbols_length = 1024usize;
+
+        let data: Vec<u8> = (0..nb_source_symbols * symbols_length)
+            .map(|i| (i % 251) as u8)
+            .collect();
+
+        let scheme = RaptorQSchemeSpecific {
+            source_blocks_length: 1,
+            sub_blocks_length: 1,
+            symbol_alignment: 8,
+        };
+
+        let r = super::RaptorQEncoder::new(
+            nb_source_symbols,
+            nb_parity_symbols,
+            symbols_length,
+            &scheme,
+            100,
+        );
+        let encoded_data = r.encode(data.as_ref()).unwrap();
+        let esi: Vec<u32> = encoded_data.iter().map(|shard| shard.esi()).collect();
+        assert!(esi[..nb_source_symbols] == (0..10).collect::<Vec<u32>>());
+        assert!(esi[nb_source_symbols..] == [110, 111, 112, 113]);
+
+        // Lose the first source symbols and recover them with the shifted repair symbols
+        let mut decoder = super::RaptorQDecoder::new(0, nb_source_symbols, symbols_length, &scheme);
+        for shard in encoded_data.iter().skip(nb_parity_symbols) {
+            decoder.push_symbol(shard.data(), shard.esi());
+        }
+        assert!(decoder.can_decode());
+        assert!(decoder.decode());
+        assert!(decoder.source_block().unwrap() == data);
+    }
 }
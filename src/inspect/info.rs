@@ -0,0 +1,334 @@
+use std::fmt;
+use std::time::SystemTime;
+
+use serde::{Serialize, Serializer};
+
+use crate::common::oti::{FECEncodingID, Oti};
+use crate::common::udpendpoint::UDPEndpoint;
+
+fn serialize_time<S: Serializer>(time: &SystemTime, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&format_time(time))
+}
+
+fn serialize_opt_time<S: Serializer>(
+    time: &Option<SystemTime>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match time {
+        Some(time) => serializer.serialize_some(&format_time(time)),
+        None => serializer.serialize_none(),
+    }
+}
+
+fn format_time(time: &SystemTime) -> String {
+    let time: chrono::DateTime<chrono::Utc> = (*time).into();
+    time.to_rfc3339_opts(chrono::SecondsFormat::Micros, true)
+}
+
+/// LCT header extension
+#[derive(Debug, Clone, Serialize)]
+pub struct ExtensionInfo {
+    /// Header Extension Type
+    pub het: u8,
+    /// Name of the extension, `UNKNOWN` when the extension is not handled by this library
+    pub name: String,
+    /// Length of the extension in bytes, HET included
+    pub length: usize,
+    /// Content of the extension (hex)
+    pub content: String,
+}
+
+/// FEC Payload ID
+#[derive(Debug, Clone, Serialize)]
+pub struct PayloadIdInfo {
+    /// Source Block Number
+    pub sbn: u32,
+    /// Encoding Symbol ID
+    pub esi: u32,
+    /// Source Block Length, for the FEC schemes that carry it
+    pub source_block_length: Option<u32>,
+}
+
+/// Decoded ALC/LCT packet
+#[derive(Debug, Clone, Serialize)]
+pub struct PacketInfo {
+    /// Reception timestamp
+    #[serde(serialize_with = "serialize_time")]
+    pub timestamp: SystemTime,
+    /// Endpoint of the packet
+    pub endpoint: UDPEndpoint,
+    /// Transport Session Identifier
+    pub tsi: u64,
+    /// Transport Object Identifier, 0 for the FDT
+    pub toi: u128,
+    /// Congestion Control Information
+    pub cci: u128,
+    /// FEC Encoding ID of the codepoint
+    pub fec_encoding_id: FECEncodingID,
+    /// Close Object flag
+    pub close_object: bool,
+    /// Close Session flag
+    pub close_session: bool,
+    /// Length of the LCT header, extensions included
+    pub header_length: usize,
+    /// LCT header extensions, in the order of the header
+    pub extensions: Vec<ExtensionInfo>,
+    /// FDT Instance ID (EXT_FDT)
+    pub fdt_instance_id: Option<u32>,
+    /// FLUTE version (EXT_FDT)
+    pub fdt_version: Option<u32>,
+    /// Content Encoding (EXT_CENC)
+    pub cenc: Option<String>,
+    /// Transfer Length (EXT_FTI or EXT_TOL)
+    pub transfer_length: Option<u64>,
+    /// Sender Current Time (EXT_TIME)
+    #[serde(serialize_with = "serialize_opt_time")]
+    pub sender_current_time: Option<SystemTime>,
+    /// FEC Object Transmission Information (EXT_FTI)
+    pub fti: Option<Oti>,
+    /// FEC Payload ID, `None` when it can not be decoded without the OTI of the object
+    pub payload_id: Option<PayloadIdInfo>,
+    /// Length of the encoding symbol(s)
+    pub payload_length: usize,
+}
+
+/// Packet that is not a valid ALC/LCT packet
+#[derive(Debug, Clone, Serialize)]
+pub struct InvalidPacketInfo {
+    /// Reception timestamp
+    #[serde(serialize_with = "serialize_time")]
+    pub timestamp: SystemTime,
+    /// Endpoint of the packet
+    pub endpoint: UDPEndpoint,
+    /// Length of the packet
+    pub length: usize,
+    /// Description of the error
+    pub error: String,
+}
+
+/// File of an FDT Instance
+#[derive(Debug, Clone, Serialize)]
+pub struct FdtFileInfo {
+    /// Transport Object Identifier
+    pub toi: String,
+    /// Content-Location
+    pub content_location: String,
+    /// Content-Length
+    pub content_length: Option<u64>,
+    /// Transfer-Length
+    pub transfer_length: Option<u64>,
+    /// Content-Type
+    pub content_type: Option<String>,
+}
+
+/// Decoded FDT Instance
+#[derive(Debug, Clone, Serialize)]
+pub struct FdtInfo {
+    /// Timestamp of the last packet of the FDT Instance
+    #[serde(serialize_with = "serialize_time")]
+    pub timestamp: SystemTime,
+    /// Endpoint of the session
+    pub endpoint: UDPEndpoint,
+    /// Transport Session Identifier
+    pub tsi: u64,
+    /// FDT Instance ID
+    pub fdt_instance_id: u32,
+    /// Expiration time of the FDT Instance
+    #[serde(serialize_with = "serialize_opt_time")]
+    pub expires: Option<SystemTime>,
+    /// Files announced by the FDT Instance
+    pub files: Vec<FdtFileInfo>,
+    /// XML of the FDT Instance
+    pub xml: String,
+}
+
+/// Reception state of a source block
+#[derive(Debug, Clone, Serialize)]
+pub struct BlockInfo {
+    /// Source Block Number
+    pub sbn: u32,
+    /// Number of source symbols of the block, when the OTI and the transfer length are known
+    pub source_symbols: Option<u64>,
+    /// Number of source symbols received
+    pub received_source: usize,
+    /// Number of repair symbols received
+    pub received_repair: usize,
+    /// Map of the source symbols, `#` received and `.` missing
+    pub map: String,
+}
+
+/// Reception state of an object
+#[derive(Debug, Clone, Serialize)]
+pub struct ObjectInfo {
+    /// Endpoint of the session
+    pub endpoint: UDPEndpoint,
+    /// Transport Session Identifier
+    pub tsi: u64,
+    /// Transport Object Identifier
+    pub toi: u128,
+    /// Content-Location, when the object is announced by an FDT Instance
+    pub content_location: Option<String>,
+    /// Transfer Length
+    pub transfer_length: Option<u64>,
+    /// FEC Encoding ID
+    pub fec_encoding_id: FECEncodingID,
+    /// Number of packets received
+    pub nb_packets: usize,
+    /// Number of source blocks, when the OTI and the transfer length are known
+    pub nb_blocks: Option<u64>,
+    /// Reception state of the blocks that have received at least one symbol
+    pub blocks: Vec<BlockInfo>,
+    /// Enough symbols are received to decode all the blocks
+    pub complete: bool,
+}
+
+/// Event of the `Inspector`
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type")]
+pub enum InspectEvent {
+    /// An ALC/LCT packet is decoded
+    Packet(PacketInfo),
+    /// A packet can not be decoded
+    InvalidPacket(InvalidPacketInfo),
+    /// An FDT Instance is decoded
+    Fdt(FdtInfo),
+    /// All the blocks of an object can be decoded
+    ObjectComplete(ObjectInfo),
+}
+
+impl fmt::Display for PacketInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} tsi={} toi={} cci={} fec={:?}",
+            format_time(&self.timestamp),
+            self.endpoint,
+            self.tsi,
+            self.toi,
+            self.cci,
+            self.fec_encoding_id
+        )?;
+        if let Some(payload_id) = &self.payload_id {
+            write!(f, " sbn={} esi={}", payload_id.sbn, payload_id.esi)?;
+            if let Some(sbl) = payload_id.source_block_length {
+                write!(f, " sbl={}", sbl)?;
+            }
+        }
+        write!(f, " len={}", self.payload_length)?;
+        if self.close_object {
+            write!(f, " [A]")?;
+        }
+        if self.close_session {
+            write!(f, " [B]")?;
+        }
+        if let Some(fdt_instance_id) = self.fdt_instance_id {
+            write!(f, " fdt={}", fdt_instance_id)?;
+        }
+        if let Some(transfer_length) = self.transfer_length {
+            write!(f, " tl={}", transfer_length)?;
+        }
+        if let Some(cenc) = &self.cenc {
+            write!(f, " cenc={}", cenc)?;
+        }
+        if let Some(sct) = &self.sender_current_time {
+            write!(f, " sct={}", format_time(sct))?;
+        }
+        if !self.extensions.is_empty() {
+            let names: Vec<String> = self
+                .extensions
+                .iter()
+                .map(|ext| format!("{}({})", ext.name, ext.het))
+                .collect();
+            write!(f, " ext=[{}]", names.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for InvalidPacketInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} invalid packet of {} bytes: {}",
+            format_time(&self.timestamp),
+            self.endpoint,
+            self.length,
+            self.error
+        )
+    }
+}
+
+impl fmt::Display for FdtInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} tsi={} FDT Instance {}",
+            format_time(&self.timestamp),
+            self.endpoint,
+            self.tsi,
+            self.fdt_instance_id
+        )?;
+        if let Some(expires) = &self.expires {
+            write!(f, " expires={}", format_time(expires))?;
+        }
+        for file in &self.files {
+            write!(f, "\n  toi={} {}", file.toi, file.content_location)?;
+            if let Some(content_length) = file.content_length {
+                write!(f, " content_length={}", content_length)?;
+            }
+            if let Some(transfer_length) = file.transfer_length {
+                write!(f, " transfer_length={}", transfer_length)?;
+            }
+        }
+        write!(f, "\n{}", self.xml)
+    }
+}
+
+impl fmt::Display for ObjectInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} tsi={} toi={} {} fec={:?} packets={}",
+            self.endpoint,
+            self.tsi,
+            self.toi,
+            self.content_location.as_deref().unwrap_or("-"),
+            self.fec_encoding_id,
+            self.nb_packets
+        )?;
+        if let Some(transfer_length) = self.transfer_length {
+            write!(f, " tl={}", transfer_length)?;
+        }
+        match self.nb_blocks {
+            Some(nb_blocks) => write!(f, " blocks={}/{}", self.blocks.len(), nb_blocks)?,
+            None => write!(f, " blocks={}/?", self.blocks.len())?,
+        }
+        write!(
+            f,
+            " {}",
+            match self.complete {
+                true => "complete",
+                false => "incomplete",
+            }
+        )?;
+        for block in &self.blocks {
+            write!(f, "\n  sbn={} source={}", block.sbn, block.received_source)?;
+            if let Some(source_symbols) = block.source_symbols {
+                write!(f, "/{}", source_symbols)?;
+            }
+            write!(f, " repair={} {}", block.received_repair, block.map)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for InspectEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InspectEvent::Packet(info) => info.fmt(f),
+            InspectEvent::InvalidPacket(info) => info.fmt(f),
+            InspectEvent::Fdt(info) => info.fmt(f),
+            InspectEvent::ObjectComplete(info) => info.fmt(f),
+        }
+    }
+}
@@ -0,0 +1,653 @@
+//!
+//! Dissector of FLUTE sessions
+//!
+//! The `Inspector` decodes the ALC/LCT packets of one or several sessions:
+//! LCT header and extensions, FEC Payload ID, FDT Instances, and builds a map of the
+//! encoding symbols received for each object.
+//! Every event can be printed in a human-readable form (`Display`) or serialized to JSON.
+//!
+//! The `flute-inspect` binary runs the `Inspector` on a pcap capture or on a live UDP socket.
+//!
+//! # Example
+//!
+//! ```
+//! use flute::core::{Oti, UDPEndpoint};
+//! use flute::inspect::{InspectEvent, Inspector};
+//! use flute::sender::{Config, ObjectDesc, Sender};
+//! use std::time::SystemTime;
+//!
+//! let endpoint = UDPEndpoint::new(None, "224.0.0.1".to_string(), 3400);
+//! let mut sender = Sender::new(endpoint.clone(), 1, &Oti::default(), &Config::default());
+//! let obj = ObjectDesc::create_from_buffer(b"hello world".to_vec(), "text/plain",
+//!     &url::Url::parse("file:///hello.txt").unwrap(), 1, None, None, None, None,
+//!     flute::core::lct::Cenc::Null, true, None, true).unwrap();
+//! sender.add_object(0, obj).unwrap();
+//! sender.publish(SystemTime::now()).unwrap();
+//!
+//! let mut inspector = Inspector::new();
+//! while let Some(pkt) = sender.read(SystemTime::now()) {
+//!     for event in inspector.push(&endpoint, &pkt, SystemTime::now()) {
+//!         println!("{}", event);
+//!         println!("{}", serde_json::to_string(&event).unwrap());
+//!     }
+//! }
+//!
+//! for object in inspector.objects() {
+//!     assert!(object.complete);
+//! }
+//! ```
+//!
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::time::SystemTime;
+
+use crate::common::fdtinstance::FdtInstance;
+use crate::common::oti::{FECEncodingID, Oti};
+use crate::common::udpendpoint::UDPEndpoint;
+use crate::common::{alc, lct, partition};
+use crate::receiver::fdtreceiver::{FDTState, FdtReceiver};
+
+mod info;
+
+pub use info::BlockInfo;
+pub use info::ExtensionInfo;
+pub use info::FdtFileInfo;
+pub use info::FdtInfo;
+pub use info::InspectEvent;
+pub use info::InvalidPacketInfo;
+pub use info::ObjectInfo;
+pub use info::PacketInfo;
+pub use info::PayloadIdInfo;
+
+#[derive(Debug, Default)]
+struct Session {
+    fdt_receivers: HashMap<u32, FdtReceiver>,
+    last_fdt_instance_id: Option<u32>,
+    fdt: Option<FdtInstance>,
+}
+
+#[derive(Debug)]
+struct ObjectState {
+    fec_encoding_id: FECEncodingID,
+    oti: Option<Oti>,
+    transfer_length: Option<u64>,
+    content_location: Option<String>,
+    nb_packets: usize,
+    // Reception state, per Source Block Number
+    blocks: BTreeMap<u32, BlockState>,
+    // Number of blocks, in the partitioning of the object, with enough symbols to be decoded
+    nb_complete_blocks: u64,
+    complete: bool,
+}
+
+#[derive(Debug, Default)]
+struct BlockState {
+    // Encoding Symbol IDs received
+    esis: BTreeSet<u32>,
+    // Source Block Length signalled in the FEC Payload ID
+    source_block_length: Option<u32>,
+    // Number of source symbols used to count `received_source`
+    source_symbols: Option<u64>,
+    received_source: usize,
+    complete: bool,
+}
+
+/// Decode the packets of FLUTE sessions
+#[derive(Debug, Default)]
+pub struct Inspector {
+    sessions: HashMap<(UDPEndpoint, u64), Session>,
+    objects: HashMap<(UDPEndpoint, u64, u128), ObjectState>,
+}
+
+fn ext_name(het: u8) -> &'static str {
+    match lct::Ext::try_from(het) {
+        Ok(lct::Ext::Nop) => "EXT_NOP",
+        Ok(lct::Ext::Fdt) => "EXT_FDT",
+        Ok(lct::Ext::Fti) => "EXT_FTI",
+        Ok(lct::Ext::Cenc) => "EXT_CENC",
+        Ok(lct::Ext::Time) => "EXT_TIME",
+        Ok(lct::Ext::Tol48) | Ok(lct::Ext::Tol24) => "EXT_TOL",
+        Ok(lct::Ext::RoutePresentationTime) => "EXT_ROUTE_PRESENTATION_TIME",
+        Err(_) if het == 1 => "EXT_AUTH",
+        Err(_) => "UNKNOWN",
+    }
+}
+
+fn to_hex(data: &[u8]) -> String {
+    data.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+impl ObjectState {
+    fn update_from_fdt(&mut self, fdt: &FdtInstance, toi: u128) {
+        let file = match fdt.get_file(&toi) {
+            Some(file) => file,
+            None => return,
+        };
+        self.content_location = Some(file.content_location.clone());
+        let partitioning = self.partitioning();
+        if self.transfer_length.is_none() {
+            self.transfer_length = Some(file.get_transfer_length());
+        }
+        if self.oti.is_none() {
+            self.oti = fdt.get_oti_for_file(file);
+        }
+        if self.partitioning() != partitioning {
+            self.refresh_blocks();
+        }
+    }
+
+    /// Block partitioning `(a_large, a_small, nb_a_large, nb_blocks)`
+    fn partitioning(&self) -> Option<(u64, u64, u64, u64)> {
+        let oti = self.oti.as_ref()?;
+        let transfer_length = self.transfer_length?;
+        Some(partition::block_partitioning(
+            oti.maximum_source_block_length as u64,
+            transfer_length,
+            oti.encoding_symbol_length as u64,
+        ))
+    }
+
+    fn source_symbols(&self, sbn: u32, source_block_length: Option<u32>) -> Option<u64> {
+        match source_block_length {
+            Some(sbl) => Some(sbl as u64),
+            None => self
+                .partitioning()
+                .map(
+                    |(a_large, a_small, nb_a_large, _)| match (sbn as u64) < nb_a_large {
+                        true => a_large,
+                        false => a_small,
+                    },
+                ),
+        }
+    }
+
+    /// Update the counters of a block after new symbols or a change of its number of source symbols
+    fn refresh_block(&mut self, sbn: u32) {
+        let nb_blocks = self.partitioning().map(|(_, _, _, nb_blocks)| nb_blocks);
+        let source_block_length = match self.blocks.get(&sbn) {
+            Some(block) => block.source_block_length,
+            None => return,
+        };
+        let source_symbols = self.source_symbols(sbn, source_block_length);
+        let fec_encoding_id = self.fec_encoding_id;
+        let block = self.blocks.get_mut(&sbn).unwrap();
+
+        if block.source_symbols != source_symbols {
+            block.source_symbols = source_symbols;
+            block.received_source = match source_symbols {
+                Some(k) => block.esis.range(..k.min(u32::MAX as u64) as u32).count(),
+                None => block.esis.len(),
+            };
+        }
+
+        let complete = match source_symbols {
+            Some(k) => match fec_encoding_id {
+                FECEncodingID::NoCode => block.received_source as u64 >= k,
+                _ => block.esis.len() as u64 >= k,
+            },
+            None => false,
+        };
+        if complete == block.complete {
+            return;
+        }
+        block.complete = complete;
+        if nb_blocks.map(|nb_blocks| (sbn as u64) < nb_blocks) == Some(true) {
+            match complete {
+                true => self.nb_complete_blocks += 1,
+                false => self.nb_complete_blocks -= 1,
+            }
+        }
+    }
+
+    /// Recount all the blocks after a change of the partitioning of the object
+    fn refresh_blocks(&mut self) {
+        let sbns: Vec<u32> = self.blocks.keys().copied().collect();
+        for sbn in sbns {
+            self.refresh_block(sbn);
+        }
+        let nb_blocks = self
+            .partitioning()
+            .map(|(_, _, _, nb_blocks)| nb_blocks)
+            .unwrap_or(0);
+        self.nb_complete_blocks = self
+            .blocks
+            .range(..nb_blocks.min(u32::MAX as u64) as u32)
+            .filter(|(_, block)| block.complete)
+            .count() as u64;
+    }
+
+    fn block_info(&self, sbn: u32, block: &BlockState) -> BlockInfo {
+        let map_len = match block.source_symbols {
+            Some(k) => k,
+            None => block
+                .esis
+                .iter()
+                .next_back()
+                .map(|esi| *esi as u64 + 1)
+                .unwrap_or(0),
+        };
+        let map = (0..map_len)
+            .map(|esi| match block.esis.contains(&(esi as u32)) {
+                true => '#',
+                false => '.',
+            })
+            .collect();
+
+        BlockInfo {
+            sbn,
+            source_symbols: block.source_symbols,
+            received_source: block.received_source,
+            received_repair: block.esis.len() - block.received_source,
+            map,
+        }
+    }
+
+    fn is_complete(&self) -> bool {
+        match self.partitioning() {
+            Some((_, _, _, nb_blocks)) => self.nb_complete_blocks == nb_blocks,
+            None => false,
+        }
+    }
+
+    fn info(&self, endpoint: &UDPEndpoint, tsi: u64, toi: u128) -> ObjectInfo {
+        ObjectInfo {
+            endpoint: endpoint.clone(),
+            tsi,
+            toi,
+            content_location: self.content_location.clone(),
+            transfer_length: self.transfer_length,
+            fec_encoding_id: self.fec_encoding_id,
+            nb_packets: self.nb_packets,
+            nb_blocks: self.partitioning().map(|(_, _, _, nb_blocks)| nb_blocks),
+            blocks: self
+                .blocks
+                .iter()
+                .map(|(sbn, block)| self.block_info(*sbn, block))
+                .collect(),
+            complete: self.is_complete(),
+        }
+    }
+}
+
+impl Inspector {
+    /// Create a new inspector
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Decode a packet received on `endpoint` at `now`
+    ///
+    /// Return the decoded packet, followed by the FDT Instances and the objects that are
+    /// completed by this packet
+    pub fn push(
+        &mut self,
+        endpoint: &UDPEndpoint,
+        data: &[u8],
+        now: SystemTime,
+    ) -> Vec<InspectEvent> {
+        let pkt = match alc::parse_alc_pkt(data) {
+            Ok(pkt) => pkt,
+            Err(e) => {
+                return vec![InspectEvent::InvalidPacket(InvalidPacketInfo {
+                    timestamp: now,
+                    endpoint: endpoint.clone(),
+                    length: data.len(),
+                    error: e.0.to_string(),
+                })]
+            }
+        };
+
+        let mut events = Vec::new();
+        let info = self.packet_info(endpoint, &pkt, now);
+
+        if pkt.lct.toi == lct::TOI_FDT {
+            if let Some(fdt) = self.push_fdt(endpoint, &pkt, now) {
+                events.push(InspectEvent::Fdt(fdt));
+            }
+        } else if let Some(object) = self.push_object(endpoint, &pkt, &info) {
+            events.push(InspectEvent::ObjectComplete(object));
+        }
+
+        events.insert(0, InspectEvent::Packet(info));
+        events
+    }
+
+    /// Reception state of the objects, ordered by TSI and TOI
+    pub fn objects(&self) -> Vec<ObjectInfo> {
+        let mut objects: Vec<ObjectInfo> = self
+            .objects
+            .iter()
+            .map(|((endpoint, tsi, toi), state)| state.info(endpoint, *tsi, *toi))
+            .collect();
+        objects.sort_by_key(|a| (a.tsi, a.toi));
+        objects
+    }
+
+    fn packet_info(
+        &self,
+        endpoint: &UDPEndpoint,
+        pkt: &alc::AlcPkt,
+        now: SystemTime,
+    ) -> PacketInfo {
+        let extensions = lct::get_ext_list(pkt.data, &pkt.lct)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|ext| ExtensionInfo {
+                het: ext[0],
+                name: ext_name(ext[0]).to_string(),
+                length: ext.len(),
+                content: to_hex(&ext[1..]),
+            })
+            .collect();
+
+        let oti = pkt.oti.clone().or_else(|| {
+            self.objects
+                .get(&(endpoint.clone(), pkt.lct.tsi, pkt.lct.toi))
+                .and_then(|obj| obj.oti.clone())
+        });
+        let payload_id = match &oti {
+            Some(oti) => alc::parse_payload_id(pkt, oti),
+            None => alc::get_fec_inline_payload_id(pkt),
+        };
+
+        PacketInfo {
+            timestamp: now,
+            endpoint: endpoint.clone(),
+            tsi: pkt.lct.tsi,
+            toi: pkt.lct.toi,
+            cci: pkt.lct.cci,
+            fec_encoding_id: pkt.lct.cp.try_into().unwrap_or(FECEncodingID::NoCode),
+            close_object: pkt.lct.close_object,
+            close_session: pkt.lct.close_session,
+            header_length: pkt.lct.len,
+            extensions,
+            fdt_instance_id: pkt.fdt_info.as_ref().map(|fdt| fdt.fdt_instance_id),
+            fdt_version: pkt.fdt_info.as_ref().map(|fdt| fdt.version),
+            cenc: pkt.cenc.map(|cenc| cenc.to_str().to_string()),
+            transfer_length: pkt.transfer_length,
+            sender_current_time: alc::get_sender_current_time(pkt).ok().flatten(),
+            fti: pkt.oti.clone(),
+            payload_id: payload_id.ok().map(|payload_id| PayloadIdInfo {
+                sbn: payload_id.sbn,
+                esi: payload_id.esi,
+                source_block_length: payload_id.source_block_length,
+            }),
+            payload_length: pkt.data.len() - pkt.data_payload_offset,
+        }
+    }
+
+    fn push_fdt(
+        &mut self,
+        endpoint: &UDPEndpoint,
+        pkt: &alc::AlcPkt,
+        now: SystemTime,
+    ) -> Option<FdtInfo> {
+        let fdt_instance_id = pkt.fdt_info.as_ref()?.fdt_instance_id;
+        let session = self
+            .sessions
+            .entry((endpoint.clone(), pkt.lct.tsi))
+            .or_default();
+
+        if session.last_fdt_instance_id == Some(fdt_instance_id) {
+            return None;
+        }
+
+        let receiver = session
+            .fdt_receivers
+            .entry(fdt_instance_id)
+            .or_insert_with(|| {
+                FdtReceiver::new(endpoint, pkt.lct.tsi, fdt_instance_id, false, now)
+            });
+        receiver.push(pkt, now);
+
+        match receiver.state() {
+            FDTState::Receiving => return None,
+            FDTState::Complete => {}
+            FDTState::Error | FDTState::Expired => {
+                session.fdt_receivers.remove(&fdt_instance_id);
+                return None;
+            }
+        }
+
+        let mut receiver = session.fdt_receivers.remove(&fdt_instance_id)?;
+        let xml = receiver.fdt_xml_str().unwrap_or_default();
+        let expires = receiver.get_expiration_time();
+        let fdt = receiver.fdt_instance()?.clone();
+        session.last_fdt_instance_id = Some(fdt_instance_id);
+
+        let files = fdt
+            .file
+            .as_ref()
+            .map(|files| {
+                files
+                    .iter()
+                    .map(|file| FdtFileInfo {
+                        toi: file.toi.clone(),
+                        content_location: file.content_location.clone(),
+                        content_length: file.content_length,
+                        transfer_length: file.transfer_length,
+                        content_type: file.content_type.clone(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        for ((obj_endpoint, tsi, toi), obj) in self.objects.iter_mut() {
+            if obj_endpoint == endpoint && *tsi == pkt.lct.tsi {
+                obj.update_from_fdt(&fdt, *toi);
+            }
+        }
+        session.fdt = Some(fdt);
+
+        Some(FdtInfo {
+            timestamp: now,
+            endpoint: endpoint.clone(),
+            tsi: pkt.lct.tsi,
+            fdt_instance_id,
+            expires,
+            files,
+            xml,
+        })
+    }
+
+    fn push_object(
+        &mut self,
+        endpoint: &UDPEndpoint,
+        pkt: &alc::AlcPkt,
+        info: &PacketInfo,
+    ) -> Option<ObjectInfo> {
+        if pkt.lct.close_session {
+            return None;
+        }
+
+        let key = (endpoint.clone(), pkt.lct.tsi, pkt.lct.toi);
+        let obj = self.objects.entry(key).or_insert_with(|| {
+            let mut obj = ObjectState {
+                fec_encoding_id: info.fec_encoding_id,
+                oti: None,
+                transfer_length: None,
+                content_location: None,
+                nb_packets: 0,
+                blocks: BTreeMap::new(),
+                nb_complete_blocks: 0,
+                complete: false,
+            };
+            if let Some(fdt) = self
+                .sessions
+                .get(&(endpoint.clone(), pkt.lct.tsi))
+                .and_then(|session| session.fdt.as_ref())
+            {
+                obj.update_from_fdt(fdt, pkt.lct.toi);
+            }
+            obj
+        });
+
+        obj.nb_packets += 1;
+        let partitioning = obj.partitioning();
+        if let Some(oti) = &pkt.oti {
+            obj.oti = Some(oti.clone());
+        }
+        if let Some(transfer_length) = pkt.transfer_length {
+            obj.transfer_length = Some(transfer_length);
+        }
+        if obj.partitioning() != partitioning {
+            obj.refresh_blocks();
+        }
+
+        let payload_id = info.payload_id.as_ref()?;
+
+        // Packets can carry several consecutive encoding symbols
+        let nb_symbols = match obj.oti.as_ref() {
+            Some(oti) if oti.encoding_symbol_length > 0 => {
+                num_integer::div_ceil(info.payload_length, oti.encoding_symbol_length as usize)
+                    .max(1)
+            }
+            _ => 1,
+        };
+        let block = obj.blocks.entry(payload_id.sbn).or_default();
+        if let Some(sbl) = payload_id.source_block_length {
+            block.source_block_length = Some(sbl);
+        }
+        for i in 0..nb_symbols as u32 {
+            let esi = payload_id.esi.saturating_add(i);
+            let is_source = block.source_symbols.map(|k| (esi as u64) < k) != Some(false);
+            if block.esis.insert(esi) && is_source {
+                block.received_source += 1;
+            }
+        }
+        obj.refresh_block(payload_id.sbn);
+
+        if obj.complete || !obj.is_complete() {
+            return None;
+        }
+        obj.complete = true;
+        Some(obj.info(endpoint, pkt.lct.tsi, pkt.lct.toi))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{InspectEvent, Inspector};
+    use crate::common::{lct, oti::Oti, udpendpoint::UDPEndpoint};
+    use crate::sender::{Config, ObjectDesc, Sender};
+    use std::time::SystemTime;
+
+    fn run(oti: &Oti, lossy: bool) -> (Vec<InspectEvent>, Inspector) {
+        let endpoint = UDPEndpoint::new(None, "224.0.0.1".to_owned(), 3400);
+        let mut sender = Sender::new(endpoint.clone(), 1, oti, &Config::default());
+        let obj = ObjectDesc::create_from_buffer(
+            vec![0xAA; 50000],
+            "application/octet-stream",
+            &url::Url::parse("file:///object.bin").unwrap(),
+            1,
+            None,
+            None,
+            None,
+            None,
+            lct::Cenc::Null,
+            true,
+            None,
+            true,
+        )
+        .unwrap();
+        sender.add_object(0, obj).unwrap();
+        sender.publish(SystemTime::now()).unwrap();
+
+        let mut inspector = Inspector::new();
+        let mut events = Vec::new();
+        let mut i = 0;
+        while let Some(pkt) = sender.read(SystemTime::now()) {
+            i += 1;
+            if lossy && i % 5 == 0 {
+                continue;
+            }
+            events.extend(inspector.push(&endpoint, &pkt, SystemTime::now()));
+        }
+        (events, inspector)
+    }
+
+    #[test]
+    pub fn test_inspect_no_code() {
+        crate::tests::init();
+        let (events, inspector) = run(&Oti::new_no_code(1400, 10), false);
+
+        let fdt = events
+            .iter()
+            .find_map(|event| match event {
+                InspectEvent::Fdt(fdt) => Some(fdt),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(fdt.files.len(), 1);
+        assert_eq!(fdt.files[0].content_location, "file:///object.bin");
+        assert!(fdt.xml.contains("FDT-Instance"));
+
+        let packet = events
+            .iter()
+            .find_map(|event| match event {
+                InspectEvent::Packet(pkt) if pkt.toi != 0 => Some(pkt),
+                _ => None,
+            })
+            .unwrap();
+        assert!(packet.payload_id.is_some());
+        assert!(packet.extensions.iter().any(|ext| ext.name == "EXT_FTI"));
+        log::info!("{}", packet);
+
+        let objects = inspector.objects();
+        assert_eq!(objects.len(), 1);
+        let object = &objects[0];
+        assert!(object.complete);
+        assert_eq!(
+            object.content_location.as_deref(),
+            Some("file:///object.bin")
+        );
+        assert_eq!(object.nb_blocks, Some(4));
+        assert!(object.blocks.iter().all(|block| !block.map.contains('.')));
+        assert!(events
+            .iter()
+            .any(|event| matches!(event, InspectEvent::ObjectComplete(_))));
+
+        let json = serde_json::to_string(&events).unwrap();
+        assert!(json.contains("\"type\":\"Fdt\""));
+    }
+
+    #[test]
+    pub fn test_inspect_loss() {
+        crate::tests::init();
+        let (_, inspector) = run(&Oti::new_no_code(1400, 10), true);
+        let objects = inspector.objects();
+        assert_eq!(objects.len(), 1);
+        assert!(!objects[0].complete);
+        assert!(objects[0]
+            .blocks
+            .iter()
+            .any(|block| block.map.contains('.')));
+        log::info!("{}", objects[0]);
+    }
+
+    #[test]
+    pub fn test_inspect_raptorq() {
+        crate::tests::init();
+        let oti = Oti::new_raptorq(1400, 10, 10, 1, 4).unwrap();
+        let (events, inspector) = run(&oti, true);
+        assert!(events
+            .iter()
+            .any(|event| matches!(event, InspectEvent::Fdt(_))));
+        let objects = inspector.objects();
+        assert_eq!(objects.len(), 1);
+        assert!(objects[0].complete);
+        assert!(objects[0]
+            .blocks
+            .iter()
+            .all(|block| block.received_repair > 0));
+    }
+
+    #[test]
+    pub fn test_inspect_invalid() {
+        crate::tests::init();
+        let endpoint = UDPEndpoint::new(None, "224.0.0.1".to_owned(), 3400);
+        let mut inspector = Inspector::new();
+        let events = inspector.push(&endpoint, &[1, 2, 3], SystemTime::now());
+        assert!(matches!(events[0], InspectEvent::InvalidPacket(_)));
+    }
+}
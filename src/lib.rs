@@ -25,6 +25,7 @@
 //!| RFC 5052 | Forward Error Correction (FEC) Building Block            | <https://www.rfc-editor.org/rfc/rfc5052>      |
 //!| RFC 5510 | Reed-Solomon Forward Error Correction (FEC) Schemes      | <https://www.rfc-editor.org/rfc/rfc5510.html> |
 //!| 3GPP TS 26.346 | Extended FLUTE FDT Schema (7.2.10)      | <https://www.etsi.org/deliver/etsi_ts/126300_126399/126346/17.03.00_60/ts_126346v170300p.pdf> |
+//!| ATSC A/331 | ROUTE - Real-Time Object Delivery over Unidirectional Transport (see the `route` module) | <https://www.atsc.org/atsc-documents/3312017-signaling-delivery-synchronization-error-protection/> |
 //!
 //! # Thread Safety
 //! 
@@ -154,6 +155,65 @@
 //! let mut sender = Sender::new(endpoint, 1, &oti, &Default::default());
 //!```
 //!
+//! ## Parallel FEC Encoding / Decoding
+//!
+//! By default, blocks are FEC encoded when they are needed, inside `Sender::read()`.  
+//! With large blocks (ex: RaptorQ), encoding can be offloaded to a pool of threads that encodes the upcoming blocks
+//! ahead of the transmission, within a memory budget.
+//!
+//!```rust
+//! use flute::sender::Sender;
+//! use flute::sender::Config;
+//! use flute::core::Oti;
+//! use flute::core::UDPEndpoint;
+//!
+//! let config = Config {
+//!     // Encode blocks with 4 threads
+//!     fec_encoding_threads: 4,
+//!     // Max 32MB of encoded blocks waiting to be transmitted
+//!     fec_encoding_memory_budget: 32 * 1024 * 1024,
+//!     ..Default::default()
+//! };
+//!
+//! let endpoint = UDPEndpoint::new(None, "224.0.0.1".to_string(), 3400);
+//! let oti = Oti::new_raptorq(1400, 1000, 100, 1, 4).unwrap();
+//! let mut sender = Sender::new(endpoint, 1, &oti, &config);
+//!```
+//!
+//! On the receiver side, `receiver::Config::fec_decoding_threads` moves the decoding of the blocks
+//! to worker threads, so `push()` is not blocked while a large block is decoded.
+//! Decoded blocks are written during the following calls to `push()` or `cleanup()`.
+//!
+//! ## Choosing the FEC parameters
+//!
+//! `core::planner::plan()` computes valid OTI for a transfer length, an MTU and a target overhead.
+//! The size of the IP header is deduced from the address family of the destination endpoint.
+//! Candidates are sorted from the largest to the smallest source block length.
+//!
+//!```rust
+//! use flute::core::planner;
+//! use flute::core::FECEncodingID;
+//! use flute::core::UDPEndpoint;
+//!
+//! // 1GB file, Ethernet MTU, 5% of repair symbols
+//! let endpoint = UDPEndpoint::new(None, "224.0.0.1".to_owned(), 3400);
+//! let candidates =
+//!     planner::plan(1024 * 1024 * 1024, &endpoint, 1500, 0.05, FECEncodingID::RaptorQ).unwrap();
+//! for candidate in &candidates {
+//!     println!("{:?} blocks={} packets={}", candidate.oti, candidate.nb_blocks, candidate.nb_packets);
+//! }
+//!```
+//!
+//! ## Comparing the FEC schemes
+//!
+//! `benchmark::BenchmarkMatrix` measures the encoding and decoding throughput of the FEC schemes
+//! and simulates transfers through a lossy channel to estimate the probability to receive a file.
+//! `cargo bench` runs the criterion benchmarks, the `flute-fec-report` tool writes a CSV or markdown report:
+//!
+//!```text
+//! cargo run --release --bin flute-fec-report -- --block-lengths 64,200 --loss-rates 0,0.05,0.1 --burst 3 --csv report.csv
+//!```
+//!
 //! # Content Encoding (CENC)
 //!
 //! The following schemes are supported during the transmission/reception
@@ -465,8 +525,16 @@ mod common;
 mod fec;
 mod tools;
 
+pub mod benchmark;
+pub mod capture;
+pub mod inspect;
+
+#[cfg(all(feature = "net", unix))]
+pub mod net;
 pub mod receiver;
+pub mod route;
 pub mod sender;
+pub mod simulator;
 pub use crate::tools::error;
 
 /// Core module with low-level function
@@ -484,12 +552,28 @@ pub mod core {
     /// LCT packets
     pub mod lct {
         pub use crate::common::lct::get_ext;
+        pub use crate::common::lct::get_ext_list;
+        pub use crate::common::lct::get_tol;
         pub use crate::common::lct::inc_hdr_len;
+        pub use crate::common::lct::nb_unknown_ext;
+        pub use crate::common::lct::parse_tol;
         pub use crate::common::lct::push_lct_header;
+        pub use crate::common::lct::push_nop;
+        pub use crate::common::lct::push_tol;
         pub use crate::common::lct::Cenc;
         pub use crate::common::lct::LCTHeader;
+        pub use crate::common::lct::LctExtension;
+    }
+
+    /// FEC parameters planning
+    pub mod planner {
+        pub use crate::common::planner::alc_header_max_length;
+        pub use crate::common::planner::ip_udp_header_length;
+        pub use crate::common::planner::plan;
+        pub use crate::common::planner::OtiCandidate;
     }
 
+    pub use crate::common::objectpart::ObjectPart;
     pub use crate::common::oti::FECEncodingID;
     pub use crate::common::oti::Oti;
     pub use crate::common::udpendpoint::UDPEndpoint;
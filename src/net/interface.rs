@@ -0,0 +1,160 @@
+use std::ffi::{CStr, CString};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use crate::tools::error::{FluteError, Result};
+
+/// Network interface used to join a multicast group or to send multicast packets
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum Interface {
+    /// Let the system select the interface
+    #[default]
+    Any,
+    /// Interface selected by its name (ex: `eth0`)
+    Name(String),
+    /// Interface selected by one of its addresses
+    Address(IpAddr),
+    /// Interface selected by its index
+    Index(u32),
+}
+
+#[derive(Debug)]
+struct IfAddr {
+    name: String,
+    addr: IpAddr,
+}
+
+/// List the addresses of the network interfaces
+fn if_addrs() -> Result<Vec<IfAddr>> {
+    let mut ifap: *mut libc::ifaddrs = std::ptr::null_mut();
+    if unsafe { libc::getifaddrs(&mut ifap) } != 0 {
+        return Err(std::io::Error::last_os_error().into());
+    }
+
+    let mut output = Vec::new();
+    let mut cur = ifap;
+    while !cur.is_null() {
+        let ifa = unsafe { &*cur };
+        cur = ifa.ifa_next;
+        if ifa.ifa_addr.is_null() || ifa.ifa_name.is_null() {
+            continue;
+        }
+
+        let name = unsafe { CStr::from_ptr(ifa.ifa_name) }
+            .to_string_lossy()
+            .into_owned();
+        let addr = match unsafe { (*ifa.ifa_addr).sa_family } as libc::c_int {
+            libc::AF_INET => {
+                let sin = unsafe { &*(ifa.ifa_addr as *const libc::sockaddr_in) };
+                IpAddr::V4(Ipv4Addr::from(u32::from_be(sin.sin_addr.s_addr)))
+            }
+            libc::AF_INET6 => {
+                let sin6 = unsafe { &*(ifa.ifa_addr as *const libc::sockaddr_in6) };
+                IpAddr::V6(Ipv6Addr::from(sin6.sin6_addr.s6_addr))
+            }
+            _ => continue,
+        };
+        output.push(IfAddr { name, addr });
+    }
+
+    unsafe { libc::freeifaddrs(ifap) };
+    Ok(output)
+}
+
+fn name_to_index(name: &str) -> Result<u32> {
+    let c_name = CString::new(name).map_err(|_| FluteError::new("Invalid interface name"))?;
+    match unsafe { libc::if_nametoindex(c_name.as_ptr()) } {
+        0 => Err(FluteError::new(format!("Interface {} not found", name))),
+        index => Ok(index),
+    }
+}
+
+fn index_to_name(index: u32) -> Result<String> {
+    let mut buf = [0 as libc::c_char; libc::IF_NAMESIZE];
+    let ret = unsafe { libc::if_indextoname(index, buf.as_mut_ptr()) };
+    if ret.is_null() {
+        return Err(FluteError::new(format!("Interface {} not found", index)));
+    }
+    Ok(unsafe { CStr::from_ptr(buf.as_ptr()) }
+        .to_string_lossy()
+        .into_owned())
+}
+
+impl Interface {
+    /// Parse an interface from an address or from a name
+    pub fn parse(value: &str) -> Self {
+        match value.parse::<IpAddr>() {
+            Ok(addr) => Interface::Address(addr),
+            Err(_) => Interface::Name(value.to_string()),
+        }
+    }
+
+    fn name(&self) -> Result<Option<String>> {
+        match self {
+            Interface::Any => Ok(None),
+            Interface::Name(name) => Ok(Some(name.clone())),
+            Interface::Index(index) => index_to_name(*index).map(Some),
+            Interface::Address(addr) => if_addrs()?
+                .into_iter()
+                .find(|ifaddr| ifaddr.addr == *addr)
+                .map(|ifaddr| Some(ifaddr.name))
+                .ok_or_else(|| FluteError::new(format!("No interface with address {}", addr))),
+        }
+    }
+
+    /// Index of the interface, 0 when the system selects the interface
+    pub fn index(&self) -> Result<u32> {
+        match self {
+            Interface::Any => Ok(0),
+            Interface::Index(index) => Ok(*index),
+            _ => name_to_index(&self.name()?.unwrap_or_default()),
+        }
+    }
+
+    /// IPv4 address of the interface, `0.0.0.0` when the system selects the interface
+    pub fn ipv4(&self) -> Result<Ipv4Addr> {
+        if let Interface::Address(IpAddr::V4(addr)) = self {
+            return Ok(*addr);
+        }
+
+        let name = match self.name()? {
+            Some(name) => name,
+            None => return Ok(Ipv4Addr::UNSPECIFIED),
+        };
+
+        if_addrs()?
+            .into_iter()
+            .find_map(|ifaddr| match ifaddr.addr {
+                IpAddr::V4(addr) if ifaddr.name == name => Some(addr),
+                _ => None,
+            })
+            .ok_or_else(|| FluteError::new(format!("Interface {} has no IPv4 address", name)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Interface;
+    use std::net::{IpAddr, Ipv4Addr};
+
+    #[test]
+    pub fn test_interface_loopback() {
+        crate::tests::init();
+        let lo = Interface::Address(IpAddr::V4(Ipv4Addr::LOCALHOST));
+        let index = lo.index().unwrap();
+        assert!(index > 0);
+        assert_eq!(Interface::Index(index).ipv4().unwrap(), Ipv4Addr::LOCALHOST);
+        assert_eq!(Interface::Any.index().unwrap(), 0);
+        assert_eq!(Interface::Any.ipv4().unwrap(), Ipv4Addr::UNSPECIFIED);
+        assert_eq!(
+            Interface::parse("127.0.0.1"),
+            Interface::Address(IpAddr::V4(Ipv4Addr::LOCALHOST))
+        );
+        assert_eq!(
+            Interface::parse("eth0"),
+            Interface::Name("eth0".to_string())
+        );
+        assert!(Interface::Name("does-not-exist".to_string())
+            .index()
+            .is_err());
+    }
+}
@@ -0,0 +1,32 @@
+//!
+//! UDP sockets to send and receive FLUTE sessions over IPv4 and IPv6
+//!
+//! Any-Source (ASM) and Source-Specific (SSM) multicast groups are supported.
+//! An SSM group is joined when the `UDPEndpoint` has a source address.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use flute::core::UDPEndpoint;
+//! use flute::net::{Interface, ReceiverSocket, SocketConfig};
+//!
+//! let config = SocketConfig {
+//!     interface: Interface::Name("eth0".to_string()),
+//!     ..Default::default()
+//! };
+//! let endpoint = UDPEndpoint::new(Some("2001:db8::1".to_owned()), "ff3e::1234".to_owned(), 3400);
+//! let socket = ReceiverSocket::new(&endpoint, &config).unwrap();
+//!
+//! let mut buf = [0u8; 2048];
+//! let (size, _from) = socket.recv_from(&mut buf).unwrap();
+//! // Push &buf[..size] to the FLUTE receiver
+//! ```
+//!
+
+mod interface;
+mod socket;
+
+pub use interface::Interface;
+pub use socket::ReceiverSocket;
+pub use socket::SenderSocket;
+pub use socket::SocketConfig;
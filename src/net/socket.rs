@@ -0,0 +1,431 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, UdpSocket};
+use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+
+use super::Interface;
+use crate::common::udpendpoint::UDPEndpoint;
+use crate::tools::error::{FluteError, Result};
+
+/// Configuration of the sockets created by `ReceiverSocket` and `SenderSocket`
+#[derive(Debug, Clone)]
+pub struct SocketConfig {
+    /// Interface used to join the multicast group or to send the multicast packets
+    pub interface: Interface,
+    /// Time To Live (IPv4) or Hop Limit (IPv6) of the packets sent
+    pub ttl: u32,
+    /// Multicast packets sent are looped back to the local host
+    pub multicast_loop: bool,
+    /// Size of the receive buffer of the socket, `None` to keep the size of the system
+    pub recv_buffer_size: Option<usize>,
+    /// Set `SO_REUSEADDR` and `SO_REUSEPORT`, so several receivers can listen to the same port
+    pub reuse_address: bool,
+    /// Put the socket in non-blocking mode
+    pub nonblocking: bool,
+}
+
+impl Default for SocketConfig {
+    fn default() -> Self {
+        Self {
+            interface: Interface::Any,
+            ttl: 1,
+            multicast_loop: true,
+            recv_buffer_size: Some(1024 * 1024),
+            reuse_address: true,
+            nonblocking: false,
+        }
+    }
+}
+
+fn setsockopt<T>(fd: RawFd, level: libc::c_int, name: libc::c_int, value: &T) -> Result<()> {
+    let ret = unsafe {
+        libc::setsockopt(
+            fd,
+            level,
+            name,
+            value as *const T as *const libc::c_void,
+            std::mem::size_of::<T>() as libc::socklen_t,
+        )
+    };
+    match ret {
+        0 => Ok(()),
+        _ => Err(std::io::Error::last_os_error().into()),
+    }
+}
+
+fn to_sockaddr(addr: &SocketAddr) -> (libc::sockaddr_storage, libc::socklen_t) {
+    let mut storage: libc::sockaddr_storage = unsafe { std::mem::zeroed() };
+    let len = match addr {
+        SocketAddr::V4(addr) => {
+            let sin = unsafe { &mut *(&mut storage as *mut _ as *mut libc::sockaddr_in) };
+            sin.sin_family = libc::AF_INET as libc::sa_family_t;
+            sin.sin_port = addr.port().to_be();
+            sin.sin_addr.s_addr = u32::from(*addr.ip()).to_be();
+            std::mem::size_of::<libc::sockaddr_in>()
+        }
+        SocketAddr::V6(addr) => {
+            let sin6 = unsafe { &mut *(&mut storage as *mut _ as *mut libc::sockaddr_in6) };
+            sin6.sin6_family = libc::AF_INET6 as libc::sa_family_t;
+            sin6.sin6_port = addr.port().to_be();
+            sin6.sin6_addr.s6_addr = addr.ip().octets();
+            sin6.sin6_scope_id = addr.scope_id();
+            std::mem::size_of::<libc::sockaddr_in6>()
+        }
+    };
+    (storage, len as libc::socklen_t)
+}
+
+fn new_socket(ipv4: bool) -> Result<UdpSocket> {
+    let domain = match ipv4 {
+        true => libc::AF_INET,
+        false => libc::AF_INET6,
+    };
+    let fd = unsafe { libc::socket(domain, libc::SOCK_DGRAM, 0) };
+    if fd == -1 {
+        return Err(std::io::Error::last_os_error().into());
+    }
+    let socket = unsafe { UdpSocket::from_raw_fd(fd) };
+    if !ipv4 {
+        setsockopt(
+            fd,
+            libc::IPPROTO_IPV6,
+            libc::IPV6_V6ONLY,
+            &(1 as libc::c_int),
+        )?;
+    }
+    Ok(socket)
+}
+
+fn bind(socket: &UdpSocket, addr: &SocketAddr) -> Result<()> {
+    let (storage, len) = to_sockaddr(addr);
+    let ret = unsafe {
+        libc::bind(
+            socket.as_raw_fd(),
+            &storage as *const _ as *const libc::sockaddr,
+            len,
+        )
+    };
+    match ret {
+        0 => Ok(()),
+        _ => Err(FluteError::new(format!(
+            "Fail to bind socket to {}: {}",
+            addr,
+            std::io::Error::last_os_error()
+        ))),
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn source_group_req(source: IpAddr, group: IpAddr, interface: u32) -> libc::group_source_req {
+    let mut req: libc::group_source_req = unsafe { std::mem::zeroed() };
+    req.gsr_interface = interface;
+    req.gsr_group = to_sockaddr(&SocketAddr::new(group, 0)).0;
+    req.gsr_source = to_sockaddr(&SocketAddr::new(source, 0)).0;
+    req
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn source_membership(
+    socket: &UdpSocket,
+    source: IpAddr,
+    group: IpAddr,
+    interface: &Interface,
+    join: bool,
+) -> Result<()> {
+    let level = match group {
+        IpAddr::V4(_) => libc::IPPROTO_IP,
+        IpAddr::V6(_) => libc::IPPROTO_IPV6,
+    };
+    let name = match join {
+        true => libc::MCAST_JOIN_SOURCE_GROUP,
+        false => libc::MCAST_LEAVE_SOURCE_GROUP,
+    };
+    let req = source_group_req(source, group, interface.index()?);
+    setsockopt(socket.as_raw_fd(), level, name, &req)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn source_membership(
+    _socket: &UdpSocket,
+    _source: IpAddr,
+    _group: IpAddr,
+    _interface: &Interface,
+    _join: bool,
+) -> Result<()> {
+    Err(FluteError::new(
+        "Source-specific multicast is not supported on this platform",
+    ))
+}
+
+#[derive(Debug)]
+struct Membership {
+    source: Option<IpAddr>,
+    group: IpAddr,
+    interface: Interface,
+}
+
+impl Membership {
+    fn join(&self, socket: &UdpSocket) -> Result<()> {
+        if let Some(source) = self.source {
+            log::info!(
+                "Join SSM source={} group={} interface={:?}",
+                source,
+                self.group,
+                self.interface
+            );
+            return source_membership(socket, source, self.group, &self.interface, true);
+        }
+
+        log::info!(
+            "Join ASM group={} interface={:?}",
+            self.group,
+            self.interface
+        );
+        match self.group {
+            IpAddr::V4(group) => socket.join_multicast_v4(&group, &self.interface.ipv4()?)?,
+            IpAddr::V6(group) => socket.join_multicast_v6(&group, self.interface.index()?)?,
+        };
+        Ok(())
+    }
+
+    fn leave(&self, socket: &UdpSocket) -> Result<()> {
+        if let Some(source) = self.source {
+            return source_membership(socket, source, self.group, &self.interface, false);
+        }
+
+        match self.group {
+            IpAddr::V4(group) => socket.leave_multicast_v4(&group, &self.interface.ipv4()?)?,
+            IpAddr::V6(group) => socket.leave_multicast_v6(&group, self.interface.index()?)?,
+        };
+        Ok(())
+    }
+}
+
+///
+/// UDP socket bound to a `UDPEndpoint`
+///
+/// When the destination of the endpoint is a multicast group, the group is joined,
+/// with source-specific multicast (SSM) if the endpoint has a source address.
+/// The group is left when the socket is dropped.
+///
+#[derive(Debug)]
+pub struct ReceiverSocket {
+    socket: UdpSocket,
+    endpoint: UDPEndpoint,
+    membership: Option<Membership>,
+}
+
+impl ReceiverSocket {
+    /// Create a socket that receives the packets sent to `endpoint`
+    pub fn new(endpoint: &UDPEndpoint, config: &SocketConfig) -> Result<Self> {
+        log::info!("Create receiver socket for {:?}", endpoint);
+        let (source, dest) = endpoint.addrs()?;
+        let socket = new_socket(dest.is_ipv4())?;
+        let fd = socket.as_raw_fd();
+
+        if config.reuse_address {
+            setsockopt(
+                fd,
+                libc::SOL_SOCKET,
+                libc::SO_REUSEADDR,
+                &(1 as libc::c_int),
+            )?;
+            setsockopt(
+                fd,
+                libc::SOL_SOCKET,
+                libc::SO_REUSEPORT,
+                &(1 as libc::c_int),
+            )?;
+        }
+
+        if let Some(size) = config.recv_buffer_size {
+            let size = size.min(libc::c_int::MAX as usize) as libc::c_int;
+            setsockopt(fd, libc::SOL_SOCKET, libc::SO_RCVBUF, &size)?;
+        }
+
+        // Binding to the group address filters out the other groups on the same port (Linux)
+        let bind_addr = match dest.is_multicast() && !cfg!(target_os = "linux") {
+            true => match dest {
+                IpAddr::V4(_) => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+                IpAddr::V6(_) => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+            },
+            false => dest,
+        };
+        bind(&socket, &SocketAddr::new(bind_addr, endpoint.port))?;
+
+        let membership = match dest.is_multicast() {
+            true => {
+                let membership = Membership {
+                    source,
+                    group: dest,
+                    interface: config.interface.clone(),
+                };
+                membership.join(&socket)?;
+                Some(membership)
+            }
+            false => None,
+        };
+
+        socket.set_nonblocking(config.nonblocking)?;
+        Ok(Self {
+            socket,
+            endpoint: endpoint.clone(),
+            membership,
+        })
+    }
+
+    /// Receive a packet, return its size and the address of the sender
+    ///
+    /// When the endpoint has a source address, packets from other sources are skipped
+    pub fn recv_from(&self, buf: &mut [u8]) -> std::io::Result<(usize, SocketAddr)> {
+        let source = self.membership.as_ref().and_then(|m| m.source);
+        loop {
+            let (size, from) = self.socket.recv_from(buf)?;
+            match source {
+                Some(source) if source != from.ip() => continue,
+                _ => return Ok((size, from)),
+            }
+        }
+    }
+
+    /// Endpoint of the socket
+    pub fn endpoint(&self) -> &UDPEndpoint {
+        &self.endpoint
+    }
+
+    /// Local address of the socket
+    pub fn local_addr(&self) -> std::io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    /// The underlying `UdpSocket`
+    pub fn socket(&self) -> &UdpSocket {
+        &self.socket
+    }
+}
+
+impl Drop for ReceiverSocket {
+    fn drop(&mut self) {
+        if let Some(membership) = self.membership.as_ref() {
+            log::info!("Leave group {}", membership.group);
+            membership.leave(&self.socket).ok();
+        }
+    }
+}
+
+///
+/// UDP socket that sends packets to the destination of a `UDPEndpoint`
+///
+/// When the endpoint has a source address, the socket is bound to this address.
+///
+#[derive(Debug)]
+pub struct SenderSocket {
+    socket: UdpSocket,
+    endpoint: UDPEndpoint,
+}
+
+impl SenderSocket {
+    /// Create a socket that sends the packets to `endpoint`
+    pub fn new(endpoint: &UDPEndpoint, config: &SocketConfig) -> Result<Self> {
+        log::info!("Create sender socket for {:?}", endpoint);
+        let (source, dest) = endpoint.addrs()?;
+        let socket = new_socket(dest.is_ipv4())?;
+        let fd = socket.as_raw_fd();
+
+        let bind_addr = match (source, dest) {
+            (Some(source), _) => source,
+            (None, IpAddr::V4(_)) => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            (None, IpAddr::V6(_)) => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+        };
+        bind(&socket, &SocketAddr::new(bind_addr, 0))?;
+
+        let ttl = config.ttl as libc::c_int;
+        let multicast_loop = config.multicast_loop as libc::c_int;
+        match dest {
+            IpAddr::V4(_) if dest.is_multicast() => {
+                setsockopt(fd, libc::IPPROTO_IP, libc::IP_MULTICAST_TTL, &ttl)?;
+                setsockopt(
+                    fd,
+                    libc::IPPROTO_IP,
+                    libc::IP_MULTICAST_LOOP,
+                    &multicast_loop,
+                )?;
+                if config.interface != Interface::Any {
+                    let interface = libc::in_addr {
+                        s_addr: u32::from(config.interface.ipv4()?).to_be(),
+                    };
+                    setsockopt(fd, libc::IPPROTO_IP, libc::IP_MULTICAST_IF, &interface)?;
+                }
+            }
+            IpAddr::V4(_) => setsockopt(fd, libc::IPPROTO_IP, libc::IP_TTL, &ttl)?,
+            IpAddr::V6(_) if dest.is_multicast() => {
+                setsockopt(fd, libc::IPPROTO_IPV6, libc::IPV6_MULTICAST_HOPS, &ttl)?;
+                setsockopt(
+                    fd,
+                    libc::IPPROTO_IPV6,
+                    libc::IPV6_MULTICAST_LOOP,
+                    &multicast_loop,
+                )?;
+                if config.interface != Interface::Any {
+                    let index = config.interface.index()? as libc::c_uint;
+                    setsockopt(fd, libc::IPPROTO_IPV6, libc::IPV6_MULTICAST_IF, &index)?;
+                }
+            }
+            IpAddr::V6(_) => setsockopt(fd, libc::IPPROTO_IPV6, libc::IPV6_UNICAST_HOPS, &ttl)?,
+        };
+
+        socket.connect(SocketAddr::new(dest, endpoint.port))?;
+        socket.set_nonblocking(config.nonblocking)?;
+        Ok(Self {
+            socket,
+            endpoint: endpoint.clone(),
+        })
+    }
+
+    /// Send a packet to the destination of the endpoint
+    pub fn send(&self, pkt: &[u8]) -> std::io::Result<usize> {
+        self.socket.send(pkt)
+    }
+
+    /// Endpoint of the socket
+    pub fn endpoint(&self) -> &UDPEndpoint {
+        &self.endpoint
+    }
+
+    /// The underlying `UdpSocket`
+    pub fn socket(&self) -> &UdpSocket {
+        &self.socket
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ReceiverSocket, SenderSocket, SocketConfig};
+    use crate::common::udpendpoint::UDPEndpoint;
+
+    #[test]
+    pub fn test_socket_unicast() {
+        crate::tests::init();
+        let config = SocketConfig::default();
+        let endpoint = UDPEndpoint::new(None, "127.0.0.1".to_owned(), 0);
+        let receiver = ReceiverSocket::new(&endpoint, &config).unwrap();
+        let port = receiver.local_addr().unwrap().port();
+
+        let endpoint = UDPEndpoint::new(None, "127.0.0.1".to_owned(), port);
+        let sender = SenderSocket::new(&endpoint, &config).unwrap();
+        sender.send(&[1, 2, 3]).unwrap();
+
+        let mut buf = [0u8; 16];
+        let (size, _) = receiver.recv_from(&mut buf).unwrap();
+        assert_eq!(&buf[..size], &[1, 2, 3]);
+    }
+
+    #[test]
+    pub fn test_socket_invalid_endpoint() {
+        crate::tests::init();
+        let config = SocketConfig::default();
+        let endpoint = UDPEndpoint::new(None, "224.0.0.1:3400".to_owned(), 3400);
+        assert!(ReceiverSocket::new(&endpoint, &config).is_err());
+
+        let endpoint = UDPEndpoint::new(Some("::1".to_owned()), "232.0.0.1".to_owned(), 3400);
+        assert!(SenderSocket::new(&endpoint, &config).is_err());
+    }
+}
@@ -1,9 +1,24 @@
-use pyo3::prelude::*;
+use pyo3::{exceptions::PyValueError, prelude::*};
 use pyo3::wrap_pymodule;
+use std::time::{Duration, SystemTime};
 
 mod receiver;
 mod sender;
 
+/// Convert a `SystemTime` to seconds since UNIX epoch
+fn to_timestamp(time: SystemTime) -> f64 {
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64()
+}
+
+/// Convert seconds since UNIX epoch to a `SystemTime`
+fn from_timestamp(timestamp: f64) -> PyResult<SystemTime> {
+    let duration = Duration::try_from_secs_f64(timestamp)
+        .map_err(|_| PyValueError::new_err("Invalid timestamp"))?;
+    Ok(SystemTime::UNIX_EPOCH + duration)
+}
+
 /// A Python module implemented in Rust.
 #[pymodule]
 fn flute(m: &Bound<'_, PyModule>) -> PyResult<()> {
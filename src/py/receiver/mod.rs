@@ -1,7 +1,9 @@
 use pyo3::prelude::*;
 
 mod config;
+mod objectmetadata;
 mod objectwriterbuilder;
+mod objectwriterpy;
 mod multireceiver;
 mod receiverpy;
 mod udpendpoint;
@@ -10,7 +12,10 @@ mod lct;
 #[pymodule]
 pub fn receiver(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<config::Config>()?;
+    m.add_class::<objectmetadata::ObjectMetadata>()?;
     m.add_class::<objectwriterbuilder::ObjectWriterBuilder>()?;
+    m.add_class::<objectwriterbuilder::ObjectBuffer>()?;
+    m.add_class::<objectwriterpy::PyObjectWriterBuilderResult>()?;
     m.add_class::<receiverpy::Receiver>()?;
     m.add_class::<multireceiver::MultiReceiver>()?;
     m.add_class::<udpendpoint::UDPEndpoint>()?;
@@ -21,4 +21,9 @@ impl MultiReceiver {
             .push(&endpoint.inner, data, SystemTime::now())
             .map_err(|e| PyTypeError::new_err(e.0.to_string()))
     }
+
+    /// Remove closed or expired sessions and expired objects, shall be called from time to time
+    fn cleanup(&mut self) {
+        self.0.cleanup(SystemTime::now())
+    }
 }
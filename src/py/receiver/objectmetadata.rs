@@ -0,0 +1,81 @@
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+
+type LctExtensions<'py> = Vec<(u8, Bound<'py, PyBytes>)>;
+
+#[pyclass(unsendable)]
+#[derive(Debug)]
+pub struct ObjectMetadata {
+    pub inner: crate::receiver::writer::ObjectMetadata,
+}
+
+#[pymethods]
+impl ObjectMetadata {
+    #[getter]
+    fn content_location(&self) -> PyResult<String> {
+        Ok(self.inner.content_location.clone())
+    }
+
+    #[getter]
+    fn content_length(&self) -> PyResult<Option<usize>> {
+        Ok(self.inner.content_length)
+    }
+
+    #[getter]
+    fn transfer_length(&self) -> PyResult<Option<usize>> {
+        Ok(self.inner.transfer_length)
+    }
+
+    #[getter]
+    fn content_type(&self) -> PyResult<Option<String>> {
+        Ok(self.inner.content_type.clone())
+    }
+
+    #[getter]
+    fn groups(&self) -> PyResult<Option<Vec<String>>> {
+        Ok(self.inner.groups.clone())
+    }
+
+    #[getter]
+    fn md5(&self) -> PyResult<Option<String>> {
+        Ok(self.inner.md5.clone())
+    }
+
+    #[getter]
+    fn cenc(&self) -> PyResult<Option<String>> {
+        Ok(self
+            .inner
+            .cenc
+            .as_ref()
+            .map(|cenc| cenc.to_str().to_string()))
+    }
+
+    #[getter]
+    fn e_tag(&self) -> PyResult<Option<String>> {
+        Ok(self.inner.e_tag.clone())
+    }
+
+    /// Presentation time (seconds since UNIX epoch)
+    #[getter]
+    fn presentation_time(&self) -> PyResult<Option<f64>> {
+        Ok(self.inner.presentation_time.map(crate::py::to_timestamp))
+    }
+
+    /// Header extensions of the application, list of `(het, content)`
+    #[getter]
+    fn lct_extensions<'py>(&self, py: Python<'py>) -> PyResult<Option<LctExtensions<'py>>> {
+        Ok(self.inner.lct_extensions.as_ref().map(|extensions| {
+            extensions
+                .iter()
+                .map(|ext| (ext.het(), PyBytes::new(py, ext.content())))
+                .collect()
+        }))
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "ObjectMetadata(content_location={:?}, content_length={:?}, content_type={:?})",
+            self.inner.content_location, self.inner.content_length, self.inner.content_type
+        )
+    }
+}
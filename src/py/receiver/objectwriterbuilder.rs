@@ -1,10 +1,16 @@
+use super::objectmetadata::ObjectMetadata;
+use super::objectwriterpy::PyObjectWriterBuilder;
+use crate::receiver::writer::{ObjectWriterBuffer, ObjectWriterBufferBuilder};
+use pyo3::types::PyBytes;
 use pyo3::{exceptions::PyTypeError, prelude::*};
+use std::cell::RefCell;
 use std::rc::Rc;
 
 #[pyclass(unsendable)]
 #[derive(Debug)]
 pub struct ObjectWriterBuilder {
     pub inner: Rc<dyn crate::receiver::writer::ObjectWriterBuilder>,
+    buffer: Option<Rc<ObjectWriterBufferBuilder>>,
 }
 
 #[pymethods]
@@ -16,14 +22,72 @@ impl ObjectWriterBuilder {
                 .map_err(|e| PyTypeError::new_err(e.0.to_string()))?;
         Ok(Self {
             inner: Rc::new(writer),
+            buffer: None,
         })
     }
 
     #[staticmethod]
     fn new_buffer() -> Self {
-        let writer = crate::receiver::writer::ObjectWriterBufferBuilder::new(true);
+        let writer = Rc::new(ObjectWriterBufferBuilder::new(true));
         Self {
-            inner: Rc::new(writer),
+            inner: writer.clone(),
+            buffer: Some(writer),
+        }
+    }
+
+    /// Objects are written by a Python object implementing `new_object_writer(tsi, toi, meta)`
+    #[staticmethod]
+    fn new_python(builder: PyObject) -> Self {
+        Self {
+            inner: Rc::new(PyObjectWriterBuilder::new(builder)),
+            buffer: None,
+        }
+    }
+
+    /// Objects received by a writer created with `new_buffer()`
+    fn objects(&self) -> PyResult<Vec<ObjectBuffer>> {
+        let buffer = self
+            .buffer
+            .as_ref()
+            .ok_or_else(|| PyTypeError::new_err("Writer is not a buffer"))?;
+        Ok(buffer
+            .objects
+            .borrow()
+            .iter()
+            .map(|object| ObjectBuffer {
+                inner: object.clone(),
+            })
+            .collect())
+    }
+}
+
+#[pyclass(unsendable)]
+#[derive(Debug)]
+pub struct ObjectBuffer {
+    inner: Rc<RefCell<ObjectWriterBuffer>>,
+}
+
+#[pymethods]
+impl ObjectBuffer {
+    #[getter]
+    fn complete(&self) -> PyResult<bool> {
+        Ok(self.inner.borrow().complete)
+    }
+
+    #[getter]
+    fn error(&self) -> PyResult<bool> {
+        Ok(self.inner.borrow().error)
+    }
+
+    #[getter]
+    fn data<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, &self.inner.borrow().data)
+    }
+
+    #[getter]
+    fn meta(&self) -> ObjectMetadata {
+        ObjectMetadata {
+            inner: self.inner.borrow().meta.clone(),
         }
     }
 }
@@ -0,0 +1,265 @@
+use super::objectmetadata::ObjectMetadata;
+use crate::common::udpendpoint::UDPEndpoint;
+use crate::error::{FluteError, Result};
+use crate::receiver::writer::{
+    ObjectMetadata as Metadata, ObjectWriter, ObjectWriterBuilder, ObjectWriterBuilderResult,
+};
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use std::time::{Duration, SystemTime};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ResultKind {
+    Store,
+    Skip,
+    Abort,
+}
+
+/// Result of `new_object_writer()` implemented in Python
+#[pyclass(name = "ObjectWriterBuilderResult", unsendable)]
+#[derive(Debug)]
+pub struct PyObjectWriterBuilderResult {
+    kind: ResultKind,
+    writer: Option<PyObject>,
+}
+
+#[pymethods]
+impl PyObjectWriterBuilderResult {
+    /// Store the object with `writer`
+    #[staticmethod]
+    fn store(writer: PyObject) -> Self {
+        Self {
+            kind: ResultKind::Store,
+            writer: Some(writer),
+        }
+    }
+
+    /// The object has already been received, skip it
+    #[staticmethod]
+    fn skip() -> Self {
+        Self {
+            kind: ResultKind::Skip,
+            writer: None,
+        }
+    }
+
+    /// The object can not be stored
+    #[staticmethod]
+    fn abort() -> Self {
+        Self {
+            kind: ResultKind::Abort,
+            writer: None,
+        }
+    }
+}
+
+///
+/// `ObjectWriterBuilder` implemented by a Python object
+///
+/// The Python object implements `new_object_writer(tsi, toi, meta)` that returns an
+/// `ObjectWriterBuilderResult` or directly the writer of the object.
+/// `update_cache_control(tsi, toi, meta)` and `fdt_received(tsi, fdt_xml)` are optional.
+///
+#[derive(Debug)]
+pub struct PyObjectWriterBuilder {
+    builder: PyObject,
+}
+
+impl PyObjectWriterBuilder {
+    pub fn new(builder: PyObject) -> Self {
+        Self { builder }
+    }
+
+    fn meta(py: Python<'_>, meta: &Metadata) -> PyResult<Py<ObjectMetadata>> {
+        Py::new(
+            py,
+            ObjectMetadata {
+                inner: meta.clone(),
+            },
+        )
+    }
+
+    fn call_new_object_writer(
+        &self,
+        py: Python<'_>,
+        tsi: &u64,
+        toi: &u128,
+        meta: &Metadata,
+    ) -> PyResult<ObjectWriterBuilderResult> {
+        let meta = Self::meta(py, meta)?;
+        let result = self
+            .builder
+            .call_method1(py, "new_object_writer", (*tsi, *toi, meta))?;
+
+        let writer = match result.bind(py).downcast::<PyObjectWriterBuilderResult>() {
+            Ok(result) => {
+                let result = result.borrow();
+                match result.kind {
+                    ResultKind::Store => result.writer.as_ref().map(|w| w.clone_ref(py)),
+                    ResultKind::Skip => {
+                        return Ok(ObjectWriterBuilderResult::ObjectAlreadyReceived)
+                    }
+                    ResultKind::Abort => return Ok(ObjectWriterBuilderResult::Abort),
+                }
+            }
+            Err(_) => Some(result),
+        };
+
+        match writer {
+            Some(writer) if !writer.is_none(py) => Ok(ObjectWriterBuilderResult::StoreObject(
+                Box::new(PyObjectWriter { writer }),
+            )),
+            _ => Ok(ObjectWriterBuilderResult::Abort),
+        }
+    }
+
+    fn has_method(&self, py: Python<'_>, name: &str) -> bool {
+        self.builder.bind(py).hasattr(name).unwrap_or(false)
+    }
+}
+
+impl ObjectWriterBuilder for PyObjectWriterBuilder {
+    fn new_object_writer(
+        &self,
+        _endpoint: &UDPEndpoint,
+        tsi: &u64,
+        toi: &u128,
+        meta: &Metadata,
+        _now: SystemTime,
+    ) -> ObjectWriterBuilderResult {
+        Python::with_gil(|py| {
+            self.call_new_object_writer(py, tsi, toi, meta)
+                .unwrap_or_else(|e| {
+                    log::error!("new_object_writer failed: {}", e);
+                    ObjectWriterBuilderResult::Abort
+                })
+        })
+    }
+
+    fn update_cache_control(
+        &self,
+        _endpoint: &UDPEndpoint,
+        tsi: &u64,
+        toi: &u128,
+        meta: &Metadata,
+        _now: SystemTime,
+    ) {
+        Python::with_gil(|py| {
+            if !self.has_method(py, "update_cache_control") {
+                return;
+            }
+
+            let result = Self::meta(py, meta).and_then(|meta| {
+                self.builder
+                    .call_method1(py, "update_cache_control", (*tsi, *toi, meta))
+            });
+            if let Err(e) = result {
+                log::error!("update_cache_control failed: {}", e);
+            }
+        })
+    }
+
+    fn fdt_received(
+        &self,
+        _endpoint: &UDPEndpoint,
+        tsi: &u64,
+        fdt_xml: &str,
+        _expires: SystemTime,
+        _meta: &Metadata,
+        _transfer_duration: Duration,
+        _now: SystemTime,
+        _ext_time: Option<SystemTime>,
+    ) {
+        Python::with_gil(|py| {
+            if !self.has_method(py, "fdt_received") {
+                return;
+            }
+
+            if let Err(e) = self
+                .builder
+                .call_method1(py, "fdt_received", (*tsi, fdt_xml))
+            {
+                log::error!("fdt_received failed: {}", e);
+            }
+        })
+    }
+}
+
+///
+/// `ObjectWriter` implemented by a Python object
+///
+/// The Python object implements `open()`, `write(sbn, data)`, `complete()` and `error()`.
+/// `interrupted()` and `enable_md5_check()` are optional.
+///
+#[derive(Debug)]
+struct PyObjectWriter {
+    writer: PyObject,
+}
+
+impl PyObjectWriter {
+    fn call(&self, name: &str) -> PyResult<()> {
+        Python::with_gil(|py| {
+            self.writer.call_method0(py, name)?;
+            Ok(())
+        })
+    }
+
+    fn call_optional(&self, name: &str) -> PyResult<()> {
+        Python::with_gil(|py| {
+            if self.writer.bind(py).hasattr(name)? {
+                self.writer.call_method0(py, name)?;
+            }
+            Ok(())
+        })
+    }
+}
+
+fn to_flute_error(e: PyErr) -> FluteError {
+    FluteError::new(e.to_string())
+}
+
+impl ObjectWriter for PyObjectWriter {
+    fn open(&self, _now: SystemTime) -> Result<()> {
+        self.call("open").map_err(to_flute_error)
+    }
+
+    fn write(&self, sbn: u32, data: &[u8], _now: SystemTime) -> Result<()> {
+        Python::with_gil(|py| {
+            self.writer
+                .call_method1(py, "write", (sbn, PyBytes::new(py, data)))
+                .map(|_| ())
+                .map_err(to_flute_error)
+        })
+    }
+
+    fn complete(&self, _now: SystemTime) {
+        if let Err(e) = self.call("complete") {
+            log::error!("complete failed: {}", e);
+        }
+    }
+
+    fn error(&self, _now: SystemTime) {
+        if let Err(e) = self.call("error") {
+            log::error!("error failed: {}", e);
+        }
+    }
+
+    fn interrupted(&self, _now: SystemTime) {
+        if let Err(e) = self.call_optional("interrupted") {
+            log::error!("interrupted failed: {}", e);
+        }
+    }
+
+    fn enable_md5_check(&self) -> bool {
+        Python::with_gil(|py| {
+            let writer = self.writer.bind(py);
+            if !writer.hasattr("enable_md5_check").unwrap_or(false) {
+                return true;
+            }
+            writer
+                .call_method0("enable_md5_check")
+                .and_then(|v| v.extract::<bool>())
+                .unwrap_or(true)
+        })
+    }
+}
@@ -1,4 +1,4 @@
-use pyo3::prelude::*;
+use pyo3::{exceptions::PyValueError, prelude::*};
 
 #[pyclass(unsendable)]
 #[derive(Debug)]
@@ -8,6 +8,7 @@ pub struct UDPEndpoint {
 
 #[pymethods]
 impl UDPEndpoint {
+    /// Create an endpoint, the addresses are stored as given
     #[new]
     #[pyo3(signature = (destination_group_address, port, source_address=None))]
     fn new(
@@ -23,4 +24,38 @@ impl UDPEndpoint {
             },
         })
     }
+
+    /// Create an endpoint from valid addresses of the same IP version,
+    /// stored in their canonical form. Raise `ValueError` otherwise
+    #[staticmethod]
+    #[pyo3(signature = (destination_group_address, port, source_address=None))]
+    fn try_new(
+        destination_group_address: &str,
+        port: u16,
+        source_address: Option<&str>,
+    ) -> PyResult<Self> {
+        let inner =
+            crate::core::UDPEndpoint::try_new(source_address, destination_group_address, port)
+                .map_err(|e| PyValueError::new_err(e.0.to_string()))?;
+        Ok(Self { inner })
+    }
+
+    #[getter]
+    fn destination_group_address(&self) -> &str {
+        &self.inner.destination_group_address
+    }
+
+    #[getter]
+    fn source_address(&self) -> Option<&str> {
+        self.inner.source_address.as_deref()
+    }
+
+    #[getter]
+    fn port(&self) -> u16 {
+        self.inner.port
+    }
+
+    fn __repr__(&self) -> String {
+        format!("UDPEndpoint({})", self.inner)
+    }
 }
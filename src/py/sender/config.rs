@@ -1,4 +1,101 @@
 use pyo3::{exceptions::PyTypeError, prelude::*};
+use std::collections::BTreeMap;
+
+use super::objectdesc::CarouselRepeatMode;
+
+/// Specifies how the FDT is published
+#[pyclass(eq, eq_int)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FDTPublishMode {
+    /// FDT is published when `Sender.publish()` is called
+    FullFDT,
+    /// FDT is published before the transfer of each object
+    ObjectsBeingTransferred,
+}
+
+impl From<FDTPublishMode> for crate::sender::FDTPublishMode {
+    fn from(mode: FDTPublishMode) -> Self {
+        match mode {
+            FDTPublishMode::FullFDT => Self::FullFDT,
+            FDTPublishMode::ObjectsBeingTransferred => Self::ObjectsBeingTransferred,
+        }
+    }
+}
+
+impl From<crate::sender::FDTPublishMode> for FDTPublishMode {
+    fn from(mode: crate::sender::FDTPublishMode) -> Self {
+        match mode {
+            crate::sender::FDTPublishMode::FullFDT => Self::FullFDT,
+            crate::sender::FDTPublishMode::ObjectsBeingTransferred => Self::ObjectsBeingTransferred,
+        }
+    }
+}
+
+/// FLUTE profile
+#[pyclass(eq, eq_int)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Profile {
+    /// FLUTE Version 2
+    RFC6726,
+    /// FLUTE Version 1
+    RFC3926,
+}
+
+impl From<Profile> for crate::sender::Profile {
+    fn from(profile: Profile) -> Self {
+        match profile {
+            Profile::RFC6726 => Self::RFC6726,
+            Profile::RFC3926 => Self::RFC3926,
+        }
+    }
+}
+
+impl From<crate::sender::Profile> for Profile {
+    fn from(profile: crate::sender::Profile) -> Self {
+        match profile {
+            crate::sender::Profile::RFC6726 => Self::RFC6726,
+            crate::sender::Profile::RFC3926 => Self::RFC3926,
+        }
+    }
+}
+
+/// Maximum number of bits to encode the TOI
+#[pyclass(eq, eq_int)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TOIMaxLength {
+    ToiMax16,
+    ToiMax32,
+    ToiMax48,
+    ToiMax64,
+    ToiMax80,
+    ToiMax112,
+}
+
+impl From<TOIMaxLength> for crate::sender::TOIMaxLength {
+    fn from(length: TOIMaxLength) -> Self {
+        match length {
+            TOIMaxLength::ToiMax16 => Self::ToiMax16,
+            TOIMaxLength::ToiMax32 => Self::ToiMax32,
+            TOIMaxLength::ToiMax48 => Self::ToiMax48,
+            TOIMaxLength::ToiMax64 => Self::ToiMax64,
+            TOIMaxLength::ToiMax80 => Self::ToiMax80,
+            TOIMaxLength::ToiMax112 => Self::ToiMax112,
+        }
+    }
+}
+
+impl From<crate::sender::TOIMaxLength> for TOIMaxLength {
+    fn from(length: crate::sender::TOIMaxLength) -> Self {
+        match length {
+            crate::sender::TOIMaxLength::ToiMax16 => Self::ToiMax16,
+            crate::sender::TOIMaxLength::ToiMax32 => Self::ToiMax32,
+            crate::sender::TOIMaxLength::ToiMax48 => Self::ToiMax48,
+            crate::sender::TOIMaxLength::ToiMax64 => Self::ToiMax64,
+            crate::sender::TOIMaxLength::ToiMax80 => Self::ToiMax80,
+            crate::sender::TOIMaxLength::ToiMax112 => Self::ToiMax112,
+        }
+    }
+}
 
 #[pyclass]
 #[derive(Debug)]
@@ -64,17 +161,66 @@ impl Config {
         Ok(())
     }
 
+    #[getter]
+    pub fn get_fdt_carousel_mode(&self) -> PyResult<CarouselRepeatMode> {
+        Ok(CarouselRepeatMode(self.0.fdt_carousel_mode))
+    }
+
+    #[setter]
+    pub fn set_fdt_carousel_mode(&mut self, value: CarouselRepeatMode) -> PyResult<()> {
+        self.0.fdt_carousel_mode = value.0;
+        Ok(())
+    }
+
+    #[getter]
+    pub fn get_fdt_publish_mode(&self) -> PyResult<FDTPublishMode> {
+        Ok(self.0.fdt_publish_mode.into())
+    }
+
+    #[setter]
+    pub fn set_fdt_publish_mode(&mut self, value: FDTPublishMode) -> PyResult<()> {
+        self.0.fdt_publish_mode = value.into();
+        Ok(())
+    }
+
+    /// Max number of files multiplexed in the priority queue 0
     #[getter]
     pub fn get_multiplex_files(&self) -> PyResult<u32> {
-        Ok(self.0.priority_queues.get(&0).unwrap().multiplex_files)
+        self.0
+            .priority_queues
+            .get(&0)
+            .map(|queue| queue.multiplex_files)
+            .ok_or_else(|| PyTypeError::new_err("Priority queue 0 does not exist"))
     }
 
     #[setter]
     pub fn set_multiplex_files(&mut self, value: u32) -> PyResult<()> {
-        self.0.priority_queues.get_mut(&0).unwrap().multiplex_files = value;
+        self.0
+            .set_priority_queue(0, crate::sender::PriorityQueue::new(value));
         Ok(())
     }
 
+    /// Priority queues, `{priority: multiplex_files}`
+    #[getter]
+    pub fn get_priority_queues(&self) -> PyResult<BTreeMap<u32, u32>> {
+        Ok(self
+            .0
+            .priority_queues
+            .iter()
+            .map(|(priority, queue)| (*priority, queue.multiplex_files))
+            .collect())
+    }
+
+    /// Insert a priority queue, a lower value has a higher priority
+    pub fn set_priority_queue(&mut self, priority: u32, multiplex_files: u32) {
+        self.0
+            .set_priority_queue(priority, crate::sender::PriorityQueue::new(multiplex_files));
+    }
+
+    pub fn remove_priority_queue(&mut self, priority: u32) {
+        self.0.remove_priority_queue(priority);
+    }
+
     #[getter]
     pub fn get_interleave_blocks(&self) -> PyResult<u8> {
         Ok(self.0.interleave_blocks)
@@ -85,4 +231,82 @@ impl Config {
         self.0.interleave_blocks = value;
         Ok(())
     }
+
+    #[getter]
+    pub fn get_profile(&self) -> PyResult<Profile> {
+        Ok(self.0.profile.into())
+    }
+
+    #[setter]
+    pub fn set_profile(&mut self, value: Profile) -> PyResult<()> {
+        self.0.profile = value.into();
+        Ok(())
+    }
+
+    #[getter]
+    pub fn get_toi_max_length(&self) -> PyResult<TOIMaxLength> {
+        Ok(self.0.toi_max_length.into())
+    }
+
+    #[setter]
+    pub fn set_toi_max_length(&mut self, value: TOIMaxLength) -> PyResult<()> {
+        self.0.toi_max_length = value.into();
+        Ok(())
+    }
+
+    /// First TOI of the session, `None` to initialize it to a random value
+    #[getter]
+    pub fn get_toi_initial_value(&self) -> PyResult<Option<u128>> {
+        Ok(self.0.toi_initial_value)
+    }
+
+    #[setter]
+    pub fn set_toi_initial_value(&mut self, value: Option<u128>) -> PyResult<()> {
+        self.0.toi_initial_value = value;
+        Ok(())
+    }
+
+    #[getter]
+    pub fn get_groups(&self) -> PyResult<Option<Vec<String>>> {
+        Ok(self.0.groups.clone())
+    }
+
+    #[setter]
+    pub fn set_groups(&mut self, value: Option<Vec<String>>) -> PyResult<()> {
+        self.0.groups = value;
+        Ok(())
+    }
+
+    #[getter]
+    pub fn get_fec_encoding_threads(&self) -> PyResult<usize> {
+        Ok(self.0.fec_encoding_threads)
+    }
+
+    #[setter]
+    pub fn set_fec_encoding_threads(&mut self, value: usize) -> PyResult<()> {
+        self.0.fec_encoding_threads = value;
+        Ok(())
+    }
+
+    #[getter]
+    pub fn get_fec_encoding_memory_budget(&self) -> PyResult<usize> {
+        Ok(self.0.fec_encoding_memory_budget)
+    }
+
+    #[setter]
+    pub fn set_fec_encoding_memory_budget(&mut self, value: usize) -> PyResult<()> {
+        self.0.fec_encoding_memory_budget = value;
+        Ok(())
+    }
+
+    #[getter]
+    pub fn get_split_large_objects(&self) -> PyResult<bool> {
+        Ok(self.0.split_large_objects)
+    }
+
+    #[setter]
+    pub fn set_split_large_objects(&mut self, value: bool) -> PyResult<()> {
+        self.0.split_large_objects = value;
+        Ok(())
+    }
 }
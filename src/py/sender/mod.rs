@@ -1,12 +1,24 @@
 use pyo3::prelude::*;
 
 mod config;
+mod objectdesc;
+mod observer;
 mod oti;
 mod senderpy;
 
 #[pymodule]
 pub fn sender(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<config::Config>()?;
+    m.add_class::<config::FDTPublishMode>()?;
+    m.add_class::<config::Profile>()?;
+    m.add_class::<config::TOIMaxLength>()?;
+    m.add_class::<objectdesc::ObjectDesc>()?;
+    m.add_class::<objectdesc::CarouselRepeatMode>()?;
+    m.add_class::<objectdesc::TargetAcquisition>()?;
+    m.add_class::<objectdesc::CacheControl>()?;
+    m.add_class::<objectdesc::FecCache>()?;
+    m.add_class::<objectdesc::Toi>()?;
+    m.add_class::<observer::Event>()?;
     m.add_class::<senderpy::Sender>()?;
     m.add_class::<oti::Oti>()?;
     Ok(())
@@ -0,0 +1,434 @@
+use pyo3::{exceptions::PyTypeError, prelude::*};
+use std::collections::HashMap;
+use std::time::Duration;
+
+use super::oti;
+use crate::py::{from_timestamp, to_timestamp};
+
+/// Controls how an object (or the FDT) is repeatedly transferred in a carousel loop
+#[pyclass]
+#[derive(Debug, Clone)]
+pub struct CarouselRepeatMode(pub crate::sender::CarouselRepeatMode);
+
+#[pymethods]
+impl CarouselRepeatMode {
+    /// Wait `delay_ms` at the end of a transfer before starting the next one
+    #[staticmethod]
+    fn delay_between_transfers(delay_ms: u64) -> Self {
+        Self(crate::sender::CarouselRepeatMode::DelayBetweenTransfers(
+            Duration::from_millis(delay_ms),
+        ))
+    }
+
+    /// Start a transfer every `interval_ms`
+    #[staticmethod]
+    fn interval_between_start_times(interval_ms: u64) -> Self {
+        Self(
+            crate::sender::CarouselRepeatMode::IntervalBetweenStartTimes(Duration::from_millis(
+                interval_ms,
+            )),
+        )
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self.0)
+    }
+}
+
+/// Target acquisition of an object
+#[pyclass]
+#[derive(Debug, Clone)]
+pub struct TargetAcquisition(pub crate::sender::TargetAcquisition);
+
+#[pymethods]
+impl TargetAcquisition {
+    #[staticmethod]
+    fn as_fast_as_possible() -> Self {
+        Self(crate::sender::TargetAcquisition::AsFastAsPossible)
+    }
+
+    #[staticmethod]
+    fn within_duration(duration_ms: u64) -> Self {
+        Self(crate::sender::TargetAcquisition::WithinDuration(
+            Duration::from_millis(duration_ms),
+        ))
+    }
+
+    /// `timestamp` in seconds since UNIX epoch
+    #[staticmethod]
+    fn within_time(timestamp: f64) -> PyResult<Self> {
+        Ok(Self(crate::sender::TargetAcquisition::WithinTime(
+            from_timestamp(timestamp)?,
+        )))
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self.0)
+    }
+}
+
+/// Cache Control of an object
+#[pyclass]
+#[derive(Debug, Clone)]
+pub struct CacheControl(pub crate::sender::CacheControl);
+
+#[pymethods]
+impl CacheControl {
+    #[staticmethod]
+    fn no_cache() -> Self {
+        Self(crate::sender::CacheControl::NoCache)
+    }
+
+    #[staticmethod]
+    fn max_stale() -> Self {
+        Self(crate::sender::CacheControl::MaxStale)
+    }
+
+    #[staticmethod]
+    fn expires(duration_ms: u64) -> Self {
+        Self(crate::sender::CacheControl::Expires(Duration::from_millis(
+            duration_ms,
+        )))
+    }
+
+    /// `timestamp` in seconds since UNIX epoch
+    #[staticmethod]
+    fn expires_at(timestamp: f64) -> PyResult<Self> {
+        Ok(Self(crate::sender::CacheControl::ExpiresAt(
+            from_timestamp(timestamp)?,
+        )))
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self.0)
+    }
+}
+
+/// Storage of the FEC encoded symbols of an object between two transfers
+#[pyclass]
+#[derive(Debug, Clone)]
+pub struct FecCache(pub crate::sender::FecCache);
+
+#[pymethods]
+impl FecCache {
+    #[staticmethod]
+    fn memory() -> Self {
+        Self(crate::sender::FecCache::Memory)
+    }
+
+    /// Encoding symbols are written to a spill file created inside `dir`
+    #[staticmethod]
+    fn file(dir: &str) -> Self {
+        Self(crate::sender::FecCache::File(std::path::PathBuf::from(dir)))
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self.0)
+    }
+}
+
+/// TOI allocated with `Sender.allocate_toi()`
+#[pyclass]
+#[derive(Debug)]
+pub struct Toi(pub Option<Box<crate::sender::Toi>>);
+
+#[pymethods]
+impl Toi {
+    #[getter]
+    fn value(&self) -> PyResult<u128> {
+        self.0
+            .as_ref()
+            .map(|toi| toi.get())
+            .ok_or_else(|| PyTypeError::new_err("TOI has been assigned to an object"))
+    }
+}
+
+///
+/// Object (file) that can be sent over FLUTE
+///
+/// The object is moved to the sender by `Sender.add_object()`
+///
+#[pyclass]
+#[derive(Debug)]
+pub struct ObjectDesc(pub Option<Box<crate::sender::ObjectDesc>>);
+
+fn parse_url(url: &str) -> PyResult<url::Url> {
+    url::Url::parse(url).map_err(|e| PyTypeError::new_err(e.to_string()))
+}
+
+impl ObjectDesc {
+    fn inner(&self) -> PyResult<&crate::sender::ObjectDesc> {
+        self.0
+            .as_deref()
+            .ok_or_else(|| PyTypeError::new_err("Object has been added to a sender"))
+    }
+
+    fn inner_mut(&mut self) -> PyResult<&mut crate::sender::ObjectDesc> {
+        self.0
+            .as_deref_mut()
+            .ok_or_else(|| PyTypeError::new_err("Object has been added to a sender"))
+    }
+}
+
+#[pymethods]
+impl ObjectDesc {
+    #[staticmethod]
+    #[allow(clippy::too_many_arguments)]
+    #[pyo3(signature = (content, content_type, content_location, max_transfer_count=1,
+        carousel_mode=None, target_acquisition=None, cache_control=None, groups=None,
+        cenc=0, inband_cenc=true, oti=None, md5=true))]
+    fn create_from_buffer(
+        content: &[u8],
+        content_type: &str,
+        content_location: &str,
+        max_transfer_count: u32,
+        carousel_mode: Option<&CarouselRepeatMode>,
+        target_acquisition: Option<&TargetAcquisition>,
+        cache_control: Option<&CacheControl>,
+        groups: Option<Vec<String>>,
+        cenc: u8,
+        inband_cenc: bool,
+        oti: Option<&oti::Oti>,
+        md5: bool,
+    ) -> PyResult<Self> {
+        let cenc = cenc
+            .try_into()
+            .map_err(|_| PyTypeError::new_err("Unknown cenc"))?;
+        let object = crate::sender::ObjectDesc::create_from_buffer(
+            content.to_vec(),
+            content_type,
+            &parse_url(content_location)?,
+            max_transfer_count,
+            carousel_mode.map(|m| m.0),
+            target_acquisition.map(|t| t.0.clone()),
+            cache_control.map(|c| c.0),
+            groups,
+            cenc,
+            inband_cenc,
+            oti.map(|o| o.0.clone()),
+            md5,
+        )
+        .map_err(|e| PyTypeError::new_err(e.0.to_string()))?;
+        Ok(Self(Some(object)))
+    }
+
+    #[staticmethod]
+    #[allow(clippy::too_many_arguments)]
+    #[pyo3(signature = (filepath, content_type, content_location=None, cache_in_ram=true,
+        max_transfer_count=1, carousel_mode=None, target_acquisition=None, cache_control=None,
+        groups=None, cenc=0, inband_cenc=true, oti=None, md5=true))]
+    fn create_from_file(
+        filepath: &str,
+        content_type: &str,
+        content_location: Option<&str>,
+        cache_in_ram: bool,
+        max_transfer_count: u32,
+        carousel_mode: Option<&CarouselRepeatMode>,
+        target_acquisition: Option<&TargetAcquisition>,
+        cache_control: Option<&CacheControl>,
+        groups: Option<Vec<String>>,
+        cenc: u8,
+        inband_cenc: bool,
+        oti: Option<&oti::Oti>,
+        md5: bool,
+    ) -> PyResult<Self> {
+        let cenc = cenc
+            .try_into()
+            .map_err(|_| PyTypeError::new_err("Unknown cenc"))?;
+        let content_location = content_location.map(parse_url).transpose()?;
+        let object = crate::sender::ObjectDesc::create_from_file(
+            std::path::Path::new(filepath),
+            content_location.as_ref(),
+            content_type,
+            cache_in_ram,
+            max_transfer_count,
+            carousel_mode.map(|m| m.0),
+            target_acquisition.map(|t| t.0.clone()),
+            cache_control.map(|c| c.0),
+            groups,
+            cenc,
+            inband_cenc,
+            oti.map(|o| o.0.clone()),
+            md5,
+        )
+        .map_err(|e| PyTypeError::new_err(e.0.to_string()))?;
+        Ok(Self(Some(object)))
+    }
+
+    /// Assign a TOI allocated with `Sender.allocate_toi()`
+    fn set_toi(&mut self, toi: &mut Toi) -> PyResult<()> {
+        let value = toi
+            .0
+            .take()
+            .ok_or_else(|| PyTypeError::new_err("TOI has been assigned to an object"))?;
+        self.inner_mut()?.set_toi(value);
+        Ok(())
+    }
+
+    /// Add an LCT header extension of the application to the packets of the object
+    fn add_lct_extension(&mut self, het: u8, content: &[u8]) -> PyResult<()> {
+        let ext = crate::core::lct::LctExtension::new(het, content.to_vec())
+            .map_err(|e| PyTypeError::new_err(e.0.to_string()))?;
+        self.inner_mut()?.lct_extensions.push(ext);
+        Ok(())
+    }
+
+    #[getter]
+    fn get_toi(&self) -> PyResult<Option<u128>> {
+        Ok(self.inner()?.toi.as_ref().map(|toi| toi.get()))
+    }
+
+    #[getter]
+    fn get_content_location(&self) -> PyResult<String> {
+        Ok(self.inner()?.content_location.to_string())
+    }
+
+    #[getter]
+    fn get_content_type(&self) -> PyResult<String> {
+        Ok(self.inner()?.content_type.clone())
+    }
+
+    #[getter]
+    fn get_content_length(&self) -> PyResult<u64> {
+        Ok(self.inner()?.content_length)
+    }
+
+    #[getter]
+    fn get_transfer_length(&self) -> PyResult<u64> {
+        Ok(self.inner()?.transfer_length)
+    }
+
+    #[getter]
+    fn get_md5(&self) -> PyResult<Option<String>> {
+        Ok(self.inner()?.md5.clone())
+    }
+
+    #[getter]
+    fn get_max_transfer_count(&self) -> PyResult<u32> {
+        Ok(self.inner()?.max_transfer_count)
+    }
+
+    #[setter]
+    fn set_max_transfer_count(&mut self, value: u32) -> PyResult<()> {
+        self.inner_mut()?.max_transfer_count = value;
+        Ok(())
+    }
+
+    #[getter]
+    fn get_carousel_mode(&self) -> PyResult<Option<CarouselRepeatMode>> {
+        Ok(self.inner()?.carousel_mode.map(CarouselRepeatMode))
+    }
+
+    #[setter]
+    fn set_carousel_mode(&mut self, value: Option<CarouselRepeatMode>) -> PyResult<()> {
+        self.inner_mut()?.carousel_mode = value.map(|m| m.0);
+        Ok(())
+    }
+
+    #[getter]
+    fn get_target_acquisition(&self) -> PyResult<Option<TargetAcquisition>> {
+        Ok(self
+            .inner()?
+            .target_acquisition
+            .clone()
+            .map(TargetAcquisition))
+    }
+
+    #[setter]
+    fn set_target_acquisition(&mut self, value: Option<TargetAcquisition>) -> PyResult<()> {
+        self.inner_mut()?.target_acquisition = value.map(|t| t.0);
+        Ok(())
+    }
+
+    #[getter]
+    fn get_cache_control(&self) -> PyResult<Option<CacheControl>> {
+        Ok(self.inner()?.cache_control.map(CacheControl))
+    }
+
+    #[setter]
+    fn set_cache_control(&mut self, value: Option<CacheControl>) -> PyResult<()> {
+        self.inner_mut()?.cache_control = value.map(|c| c.0);
+        Ok(())
+    }
+
+    #[getter]
+    fn get_groups(&self) -> PyResult<Option<Vec<String>>> {
+        Ok(self.inner()?.groups.clone())
+    }
+
+    #[setter]
+    fn set_groups(&mut self, value: Option<Vec<String>>) -> PyResult<()> {
+        self.inner_mut()?.groups = value;
+        Ok(())
+    }
+
+    /// Start time of the transfer (seconds since UNIX epoch)
+    #[getter]
+    fn get_transfer_start_time(&self) -> PyResult<Option<f64>> {
+        Ok(self.inner()?.transfer_start_time.map(to_timestamp))
+    }
+
+    #[setter]
+    fn set_transfer_start_time(&mut self, value: Option<f64>) -> PyResult<()> {
+        self.inner_mut()?.transfer_start_time = value.map(from_timestamp).transpose()?;
+        Ok(())
+    }
+
+    #[getter]
+    fn get_e_tag(&self) -> PyResult<Option<String>> {
+        Ok(self.inner()?.e_tag.clone())
+    }
+
+    #[setter]
+    fn set_e_tag(&mut self, value: Option<String>) -> PyResult<()> {
+        self.inner_mut()?.e_tag = value;
+        Ok(())
+    }
+
+    #[getter]
+    fn get_inband_tol(&self) -> PyResult<bool> {
+        Ok(self.inner()?.inband_tol)
+    }
+
+    #[setter]
+    fn set_inband_tol(&mut self, value: bool) -> PyResult<()> {
+        self.inner_mut()?.inband_tol = value;
+        Ok(())
+    }
+
+    #[getter]
+    fn get_allow_immediate_stop_before_first_transfer(&self) -> PyResult<Option<bool>> {
+        Ok(self.inner()?.allow_immediate_stop_before_first_transfer)
+    }
+
+    #[setter]
+    fn set_allow_immediate_stop_before_first_transfer(
+        &mut self,
+        value: Option<bool>,
+    ) -> PyResult<()> {
+        self.inner_mut()?.allow_immediate_stop_before_first_transfer = value;
+        Ok(())
+    }
+
+    #[getter]
+    fn get_fec_cache(&self) -> PyResult<Option<FecCache>> {
+        Ok(self.inner()?.fec_cache.clone().map(FecCache))
+    }
+
+    #[setter]
+    fn set_fec_cache(&mut self, value: Option<FecCache>) -> PyResult<()> {
+        self.inner_mut()?.fec_cache = value.map(|c| c.0);
+        Ok(())
+    }
+
+    #[getter]
+    fn get_optel_propagator(&self) -> PyResult<Option<HashMap<String, String>>> {
+        Ok(self.inner()?.optel_propagator.clone())
+    }
+
+    #[setter]
+    fn set_optel_propagator(&mut self, value: Option<HashMap<String, String>>) -> PyResult<()> {
+        self.inner_mut()?.optel_propagator = value;
+        Ok(())
+    }
+}
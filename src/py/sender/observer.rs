@@ -0,0 +1,57 @@
+use pyo3::prelude::*;
+use std::time::SystemTime;
+
+use crate::py::to_timestamp;
+
+/// Event of the sender, `kind` is either `"StartTransfer"` or `"StopTransfer"`
+#[pyclass(frozen)]
+#[derive(Debug)]
+pub struct Event(pub crate::sender::Event);
+
+#[pymethods]
+impl Event {
+    #[getter]
+    fn kind(&self) -> &'static str {
+        match self.0 {
+            crate::sender::Event::StartTransfer(_) => "StartTransfer",
+            crate::sender::Event::StopTransfer(_) => "StopTransfer",
+        }
+    }
+
+    #[getter]
+    fn toi(&self) -> u128 {
+        match &self.0 {
+            crate::sender::Event::StartTransfer(info) => info.toi,
+            crate::sender::Event::StopTransfer(info) => info.toi,
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Event(kind={:?}, toi={})", self.kind(), self.toi())
+    }
+}
+
+///
+/// `Subscriber` implemented by a Python object
+///
+/// The Python object implements `on_sender_event(event, now)`,
+/// `now` is expressed in seconds since UNIX epoch.
+///
+#[derive(Debug)]
+pub struct PySubscriber {
+    pub subscriber: PyObject,
+}
+
+impl crate::sender::Subscriber for PySubscriber {
+    fn on_sender_event(&self, evt: &crate::sender::Event, now: SystemTime) {
+        Python::with_gil(|py| {
+            let result = Py::new(py, Event(evt.clone())).and_then(|evt| {
+                self.subscriber
+                    .call_method1(py, "on_sender_event", (evt, to_timestamp(now)))
+            });
+            if let Err(e) = result {
+                log::error!("on_sender_event failed: {}", e);
+            }
+        })
+    }
+}
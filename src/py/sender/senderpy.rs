@@ -1,28 +1,48 @@
 use pyo3::exceptions::PyTypeError;
 use pyo3::prelude::*;
+use std::sync::Arc;
 use std::time::SystemTime;
 
 use super::config;
+use super::objectdesc::{ObjectDesc, Toi};
+use super::observer::PySubscriber;
 use super::oti;
+use crate::py::from_timestamp;
 
 #[pyclass]
 #[derive(Debug)]
-pub struct Sender(crate::sender::Sender);
+pub struct Sender {
+    inner: crate::sender::Sender,
+    subscribers: Vec<Arc<PySubscriber>>,
+}
 
 #[pymethods]
 impl Sender {
     #[new]
     pub fn new(tsi: u64, oti: &oti::Oti, config: &config::Config) -> Self {
         Self {
-            0: crate::sender::Sender::new(
+            inner: crate::sender::Sender::new(
                 crate::core::UDPEndpoint::new(None, "224.0.0.1".to_owned(), 0), // FIXME
                 tsi,
                 &oti.0,
                 &config.0,
             ),
+            subscribers: Vec::new(),
         }
     }
 
+    /// Add an object to the priority queue `priority`
+    #[pyo3(signature = (obj, priority=0))]
+    fn add_object(&mut self, obj: &mut ObjectDesc, priority: u32) -> PyResult<u128> {
+        let object = obj
+            .0
+            .take()
+            .ok_or_else(|| PyTypeError::new_err("Object has been added to a sender"))?;
+        self.inner
+            .add_object(priority, object)
+            .map_err(|e| PyTypeError::new_err(e.0.to_string()))
+    }
+
     #[pyo3(signature = (content, content_type, content_location, oti=None))]
     fn add_object_from_buffer(
         &mut self,
@@ -51,7 +71,7 @@ impl Sender {
         )
         .map_err(|e| PyTypeError::new_err(e.0.to_string()))?;
 
-        self.0
+        self.inner
             .add_object(0, object)
             .map_err(|e| PyTypeError::new_err(e.0.to_string()))
     }
@@ -95,26 +115,82 @@ impl Sender {
         )
         .map_err(|e| PyTypeError::new_err(e.0.to_string()))?;
 
-        self.0
+        self.inner
             .add_object(0, object)
             .map_err(|e| PyTypeError::new_err(e.0.to_string()))
     }
 
     fn remove_object(&mut self, toi: u128) -> bool {
-        self.0.remove_object(toi)
+        self.inner.remove_object(toi)
     }
 
     fn nb_objects(&self) -> usize {
-        self.0.nb_objects()
+        self.inner.nb_objects()
     }
 
     fn publish(&mut self) -> PyResult<()> {
-        self.0
+        self.inner
             .publish(SystemTime::now())
             .map_err(|e| PyTypeError::new_err(e.0.to_string()))
     }
 
     fn read(&mut self) -> PyResult<Option<Vec<u8>>> {
-        Ok(self.0.read(SystemTime::now()))
+        Ok(self.inner.read(SystemTime::now()))
+    }
+
+    /// Start the transfer of an object at `timestamp` (seconds since UNIX epoch), or now
+    #[pyo3(signature = (toi, timestamp=None))]
+    fn trigger_transfer_at(&mut self, toi: u128, timestamp: Option<f64>) -> PyResult<bool> {
+        let timestamp = timestamp.map(from_timestamp).transpose()?;
+        Ok(self.inner.trigger_transfer_at(toi, timestamp))
+    }
+
+    fn is_added(&self, toi: u128) -> bool {
+        self.inner.is_added(toi)
+    }
+
+    fn nb_transfers(&mut self, toi: u128) -> Option<u64> {
+        self.inner.nb_transfers(toi)
+    }
+
+    fn set_complete(&mut self) {
+        self.inner.set_complete()
+    }
+
+    fn read_close_session(&mut self) -> Vec<u8> {
+        self.inner.read_close_session(SystemTime::now())
+    }
+
+    fn allocate_toi(&mut self) -> Toi {
+        Toi(Some(self.inner.allocate_toi()))
+    }
+
+    fn fdt_xml_data(&self) -> PyResult<Vec<u8>> {
+        self.inner
+            .fdt_xml_data(SystemTime::now())
+            .map_err(|e| PyTypeError::new_err(e.0.to_string()))
+    }
+
+    #[getter]
+    fn tsi(&self) -> u64 {
+        self.inner.get_tsi()
+    }
+
+    /// Subscribe a Python object implementing `on_sender_event(event, now)`
+    fn subscribe(&mut self, subscriber: PyObject) {
+        let subscriber = Arc::new(PySubscriber { subscriber });
+        self.inner.subscribe(subscriber.clone());
+        self.subscribers.push(subscriber);
+    }
+
+    fn unsubscribe(&mut self, subscriber: PyObject) {
+        let (removed, kept) = self
+            .subscribers
+            .drain(..)
+            .partition(|s| s.subscriber.is(&subscriber));
+        self.subscribers = kept;
+        for s in removed {
+            self.inner.unsubscribe(s);
+        }
     }
 }
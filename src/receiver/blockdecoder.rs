@@ -15,6 +15,8 @@ pub struct BlockDecoder {
     pub initialized: bool,
     pub block_size: usize,
     decoder: Option<Box<dyn FecDecoder>>,
+    /// Symbols received while the decoder is running on a worker thread
+    pending_symbols: Vec<(Vec<u8>, u32)>,
 }
 
 impl BlockDecoder {
@@ -24,6 +26,7 @@ impl BlockDecoder {
             initialized: false,
             decoder: None,
             block_size: 0,
+            pending_symbols: Vec::new(),
         }
     }
 
@@ -100,25 +103,79 @@ impl BlockDecoder {
 
     pub fn deallocate(&mut self) {
         self.decoder = None;
+        self.pending_symbols.clear();
         self.block_size = 0;
     }
 
-    pub fn push(&mut self, pkt: &alc::AlcPkt, payload_id: &alc::PayloadID) {
+    /// Push a symbol to the decoder.
+    ///
+    /// When `offload` is true and the block can be decoded, the decoder is returned
+    /// so it can be run on a worker thread, then given back with `decoded()`.
+    pub fn push(
+        &mut self,
+        pkt: &alc::AlcPkt,
+        payload_id: &alc::PayloadID,
+        offload: bool,
+    ) -> Option<Box<dyn FecDecoder>> {
         debug_assert!(self.initialized);
 
         if self.completed {
-            return;
+            return None;
         }
 
         let payload = &pkt.data[pkt.data_payload_offset..];
-        let decoder = self.decoder.as_mut().unwrap();
+        let decoder = match self.decoder.as_mut() {
+            Some(decoder) => decoder,
+            None => {
+                // Decoder is running on a worker thread
+                self.pending_symbols
+                    .push((payload.to_vec(), payload_id.esi));
+                return None;
+            }
+        };
         decoder.push_symbol(payload, payload_id.esi);
 
         if decoder.can_decode() {
+            if offload {
+                return self.decoder.take();
+            }
+
             self.completed = decoder.decode();
             if self.completed {
                 log::debug!("Block completed");
             }
         }
+
+        None
+    }
+
+    /// Give back a decoder that has been run on a worker thread.
+    ///
+    /// If the decoding failed, the symbols received in the meantime are pushed to the decoder,
+    /// and the decoder is returned again if the block can be decoded.
+    pub fn decoded(
+        &mut self,
+        mut decoder: Box<dyn FecDecoder>,
+        success: bool,
+    ) -> Option<Box<dyn FecDecoder>> {
+        let pending_symbols = std::mem::take(&mut self.pending_symbols);
+        self.completed = success;
+        if success {
+            log::debug!("Block completed");
+            self.decoder = Some(decoder);
+            return None;
+        }
+
+        let nb_pending_symbols = pending_symbols.len();
+        for (symbol, esi) in pending_symbols {
+            decoder.push_symbol(&symbol, esi);
+        }
+
+        if nb_pending_symbols > 0 && decoder.can_decode() {
+            return Some(decoder);
+        }
+
+        self.decoder = Some(decoder);
+        None
     }
 }
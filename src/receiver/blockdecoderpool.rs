@@ -0,0 +1,127 @@
+use super::receiver::Config;
+use crate::fec::FecDecoder;
+use std::collections::VecDeque;
+use std::rc::Rc;
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread::JoinHandle;
+
+struct Job {
+    sbn: u32,
+    decoder: Box<dyn FecDecoder>,
+    output: Arc<DecodedBlocks>,
+}
+
+#[derive(Debug)]
+pub struct DecodedBlock {
+    pub sbn: u32,
+    pub decoder: Box<dyn FecDecoder>,
+    pub success: bool,
+}
+
+///
+/// Completion queue of the blocks decoded by the pool
+///
+#[derive(Debug, Default)]
+pub struct DecodedBlocks {
+    blocks: Mutex<VecDeque<DecodedBlock>>,
+}
+
+impl DecodedBlocks {
+    fn push(&self, block: DecodedBlock) {
+        self.blocks.lock().unwrap().push_back(block);
+    }
+
+    /// Pop a decoded block, if any
+    pub fn pop(&self) -> Option<DecodedBlock> {
+        self.blocks.lock().unwrap().pop_front()
+    }
+}
+
+///
+/// Pool of threads running the FEC decoding outside of the reception path
+///
+#[derive(Debug)]
+pub struct BlockDecoderPool {
+    jobs: Option<mpsc::Sender<Job>>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl BlockDecoderPool {
+    pub fn new(nb_threads: usize) -> BlockDecoderPool {
+        let (tx, rx) = mpsc::channel::<Job>();
+        let rx = Arc::new(Mutex::new(rx));
+
+        let workers = (0..nb_threads)
+            .map(|_| {
+                let rx = rx.clone();
+                std::thread::spawn(move || BlockDecoderPool::worker(rx))
+            })
+            .collect();
+
+        BlockDecoderPool {
+            jobs: Some(tx),
+            workers,
+        }
+    }
+
+    /// Create the pool configured by `fec_decoding_threads`, if any
+    pub fn from_config(config: &Config) -> Option<Rc<BlockDecoderPool>> {
+        match config.fec_decoding_threads {
+            0 => None,
+            n => Some(Rc::new(BlockDecoderPool::new(n))),
+        }
+    }
+
+    fn worker(rx: Arc<Mutex<mpsc::Receiver<Job>>>) {
+        loop {
+            let mut job = match rx.lock().unwrap().recv() {
+                Ok(job) => job,
+                Err(_) => return,
+            };
+
+            let success =
+                std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| job.decoder.decode()))
+                    .unwrap_or_else(|_| {
+                        log::error!("FEC decoder of block {} panicked", job.sbn);
+                        false
+                    });
+
+            job.output.push(DecodedBlock {
+                sbn: job.sbn,
+                decoder: job.decoder,
+                success,
+            });
+        }
+    }
+
+    /// Submit a decoder that is ready to decode the block `sbn`.
+    /// The result is pushed to `output`.
+    pub fn submit(
+        &self,
+        sbn: u32,
+        decoder: Box<dyn FecDecoder>,
+        output: Arc<DecodedBlocks>,
+    ) -> Option<Box<dyn FecDecoder>> {
+        let jobs = match self.jobs.as_ref() {
+            Some(jobs) => jobs,
+            None => return Some(decoder),
+        };
+
+        jobs.send(Job {
+            sbn,
+            decoder,
+            output,
+        })
+        .err()
+        .map(|e| e.0.decoder)
+    }
+}
+
+impl Drop for BlockDecoderPool {
+    fn drop(&mut self) {
+        self.jobs.take();
+        for worker in self.workers.drain(..) {
+            worker.join().ok();
+        }
+    }
+}
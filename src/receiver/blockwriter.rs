@@ -13,8 +13,14 @@ use super::{
     writer::ObjectWriter,
 };
 
+#[cfg(feature = "brotli")]
+use super::uncompress::DecompressBrotli;
+#[cfg(feature = "zstd")]
+use super::uncompress::DecompressZstd;
+
 pub struct BlockWriter {
     sbn: u32,
+    transfer_length: usize,
     bytes_left: usize,
     content_length_left: Option<usize>,
     cenc: lct::Cenc,
@@ -47,6 +53,7 @@ impl BlockWriter {
     ) -> BlockWriter {
         BlockWriter {
             sbn: 0,
+            transfer_length,
             bytes_left: transfer_length,
             content_length_left: content_length,
             cenc,
@@ -99,29 +106,65 @@ impl BlockWriter {
         self.sbn += 1;
 
         if self.is_completed() {
-            // All blocks have been received -> flush the decoder
-            if self.decoder.is_some() {
-                self.decoder.as_mut().unwrap().finish();
-                self.decoder_read(writer, now)?;
-            }
-
-            let output = self.md5_context.take().map(|ctx| ctx.compute().0);
-            self.md5 =
-                output.map(|output| base64::engine::general_purpose::STANDARD.encode(output));
+            self.finish(writer, now)?;
         }
 
         Ok(true)
     }
 
-    fn init_decoder(&mut self, data: &[u8]) {
+    /// Set the transfer length of an object written before its length was known (live object)
+    pub fn set_transfer_length(
+        &mut self,
+        transfer_length: usize,
+        writer: &dyn ObjectWriter,
+        now: SystemTime,
+    ) -> Result<()> {
+        let written = self.transfer_length - self.bytes_left;
+        if transfer_length < written {
+            return Err(FluteError::new(format!(
+                "Transfer length {} is lower than the {} bytes already written",
+                transfer_length, written
+            )));
+        }
+
+        self.transfer_length = transfer_length;
+        self.bytes_left = transfer_length - written;
+        if self.is_completed() {
+            self.finish(writer, now)?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self, writer: &dyn ObjectWriter, now: SystemTime) -> Result<()> {
+        // All blocks have been received -> flush the decoder
+        if self.decoder.is_some() {
+            self.decoder.as_mut().unwrap().finish();
+            self.decoder_read(writer, now)?;
+        }
+
+        let output = self.md5_context.take().map(|ctx| ctx.compute().0);
+        self.md5 = output.map(|output| base64::engine::general_purpose::STANDARD.encode(output));
+        Ok(())
+    }
+
+    fn init_decoder(&mut self, data: &[u8]) -> Result<()> {
         debug_assert!(self.decoder.is_none());
         self.decoder = match self.cenc {
             lct::Cenc::Null => None,
             lct::Cenc::Zlib => Some(Box::new(DecompressZlib::new(data))),
             lct::Cenc::Deflate => Some(Box::new(DecompressDeflate::new(data))),
             lct::Cenc::Gzip => Some(Box::new(DecompressGzip::new(data))),
+            #[cfg(feature = "zstd")]
+            lct::Cenc::Zstd => Some(Box::new(DecompressZstd::new(data)?)),
+            #[cfg(feature = "brotli")]
+            lct::Cenc::Brotli => Some(Box::new(DecompressBrotli::new(data)?)),
+            #[cfg(not(feature = "zstd"))]
+            lct::Cenc::Zstd => return Err(self.cenc.unsupported_error()),
+            #[cfg(not(feature = "brotli"))]
+            lct::Cenc::Brotli => return Err(self.cenc.unsupported_error()),
         };
         self.buffer.resize(data.len(), 0);
+        Ok(())
     }
 
     fn write_pkt_cenc_null(
@@ -143,7 +186,7 @@ impl BlockWriter {
         now: SystemTime,
     ) -> Result<()> {
         if self.decoder.is_none() {
-            self.init_decoder(pkt);
+            self.init_decoder(pkt)?;
             self.decoder_read(writer, now)?;
             return Ok(());
         }
@@ -0,0 +1,76 @@
+use std::time::SystemTime;
+
+/// Event on an object of a FLUTE session
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ObjectEvent {
+    /// The object is announced by a new FDT Instance
+    Announced {
+        /// Object TOI
+        toi: u128,
+        /// Content-Location of the object
+        content_location: String,
+    },
+    /// The first packet of the object is received
+    ReceptionStarted {
+        /// Object TOI
+        toi: u128,
+    },
+    /// Encoding symbols of the object are received,
+    /// reported each time one more percent of the transfer length is received
+    /// (each time a block is decoded when the transfer length is not known yet)
+    Progress {
+        /// Object TOI
+        toi: u128,
+        /// Bytes of encoding symbols received, limited to the transfer length
+        received_bytes: u64,
+        /// Transfer length of the object, unknown until the end of a live object
+        transfer_length: Option<u64>,
+    },
+    /// The object is fully reconstructed
+    Completed {
+        /// Object TOI
+        toi: u128,
+        /// Content-Location of the object, if it has been attached to an FDT
+        content_location: Option<String>,
+    },
+    /// The object can not be reconstructed
+    Error {
+        /// Object TOI
+        toi: u128,
+        /// Description of the error
+        reason: String,
+    },
+    /// The transmission of the object is finished before the reception is complete
+    Interrupted {
+        /// Object TOI
+        toi: u128,
+    },
+}
+
+/// Event on the FDT of a FLUTE session
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FdtEvent {
+    /// A new FDT Instance is received
+    NewInstance {
+        /// FDT Instance ID
+        fdt_instance_id: u32,
+        /// Expiration time of the FDT Instance
+        expires: SystemTime,
+        /// Number of objects announced by the FDT Instance
+        nb_objects: usize,
+    },
+    /// The FDT Instance is expired
+    Expired {
+        /// FDT Instance ID
+        fdt_instance_id: u32,
+    },
+}
+
+/// Event of a FLUTE `Receiver`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReceiverEvent {
+    /// Event on an object
+    Object(ObjectEvent),
+    /// Event on the FDT
+    Fdt(FdtEvent),
+}
@@ -86,7 +86,10 @@ impl FdtReceiver {
                 &lct::TOI_FDT,
                 Some(fdt_id),
                 fdt_builder,
-                1024 * 1024,
+                objectreceiver::BlockDecodingConfig {
+                    max_size_allocated: 1024 * 1024,
+                    decoder_pool: None,
+                },
                 now,
             ))),
             inner: inner.clone(),
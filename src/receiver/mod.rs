@@ -3,8 +3,10 @@
 //!
 
 mod blockdecoder;
+mod blockdecoderpool;
 mod blockwriter;
-mod fdtreceiver;
+mod events;
+pub(crate) mod fdtreceiver;
 mod multireceiver;
 mod objectreceiver;
 mod receiver;
@@ -15,6 +17,9 @@ mod uncompress;
 mod objectreceiverlogger;
 
 pub mod writer;
+pub use events::FdtEvent;
+pub use events::ObjectEvent;
+pub use events::ReceiverEvent;
 pub use multireceiver::MultiReceiver;
 pub use multireceiver::MultiReceiverListener;
 pub use multireceiver::ReceiverEndpoint;
@@ -1,8 +1,10 @@
+use super::blockdecoderpool::BlockDecoderPool;
+use super::events::{FdtEvent, ObjectEvent, ReceiverEvent};
 use super::receiver::{Config, Receiver};
 use super::tsifilter::TSIFilter;
 use super::writer::ObjectWriterBuilder;
-use crate::common::alc;
 use crate::common::udpendpoint::UDPEndpoint;
+use crate::common::{alc, lct};
 use crate::tools::error::Result;
 use core::fmt::Debug;
 use std::collections::HashMap;
@@ -24,6 +26,10 @@ pub trait MultiReceiverListener {
     fn on_session_open(&self, endpoint: &ReceiverEndpoint);
     /// Called when a FLUTE session is being closed
     fn on_session_closed(&self, endpoint: &ReceiverEndpoint);
+    /// Called on the lifecycle events of the objects of a FLUTE session
+    fn on_object_event(&self, _endpoint: &ReceiverEndpoint, _event: &ObjectEvent) {}
+    /// Called when an FDT Instance is received or expires
+    fn on_fdt_event(&self, _endpoint: &ReceiverEndpoint, _event: &FdtEvent) {}
 }
 
 type MultiReceiverListenerBox = Box<dyn MultiReceiverListener>;
@@ -44,9 +50,12 @@ pub struct MultiReceiver {
     tsifilter: TSIFilter,
     writer: Rc<dyn ObjectWriterBuilder>,
     config: Option<Config>,
+    decoder_pool: Option<Rc<BlockDecoderPool>>,
     enable_tsi_filtering: bool,
     listeners: HashMap<u64, MultiReceiverListenerBox>,
     listeners_id: u64,
+    lct_extensions: Vec<u8>,
+    parts_spill_dir: Option<std::path::PathBuf>,
 }
 
 impl MultiReceiver {
@@ -81,14 +90,18 @@ impl MultiReceiver {
         config: Option<Config>,
         enable_tsi_filtering: bool,
     ) -> MultiReceiver {
+        let decoder_pool = BlockDecoderPool::from_config(&config.unwrap_or_default());
         MultiReceiver {
             alc_receiver: HashMap::new(),
             writer,
             config,
+            decoder_pool,
             tsifilter: TSIFilter::new(),
             enable_tsi_filtering,
             listeners: HashMap::new(),
             listeners_id: 0,
+            lct_extensions: Vec::new(),
+            parts_spill_dir: None,
         }
     }
 
@@ -136,6 +149,47 @@ impl MultiReceiver {
             .sum()
     }
 
+    ///
+    /// Number of LCT header extensions skipped because they are unknown
+    ///
+    pub fn nb_unknown_ext(&self) -> usize {
+        self.alc_receiver
+            .iter()
+            .map(|session| session.1.nb_unknown_ext())
+            .sum()
+    }
+
+    ///
+    /// Register a header extension of the application to all the FLUTE sessions
+    ///
+    /// See `Receiver::register_lct_extension()`
+    ///
+    pub fn register_lct_extension(&mut self, het: u8) -> Result<()> {
+        lct::LctExtension::check_het(het)?;
+        for receiver in self.alc_receiver.values_mut() {
+            receiver.register_lct_extension(het)?;
+        }
+
+        if !self.lct_extensions.contains(&het) {
+            self.lct_extensions.push(het);
+        }
+        Ok(())
+    }
+
+    ///
+    /// Set the directory of the object parts received out of order for all the FLUTE sessions
+    ///
+    /// See `Receiver::set_parts_spill_dir()`
+    ///
+    pub fn set_parts_spill_dir(&mut self, dir: &std::path::Path) -> Result<()> {
+        for receiver in self.alc_receiver.values_mut() {
+            receiver.set_parts_spill_dir(dir)?;
+        }
+
+        self.parts_spill_dir = Some(dir.to_path_buf());
+        Ok(())
+    }
+
     ///
     /// Enable/Disable  TSI filtering
     ///
@@ -238,7 +292,10 @@ impl MultiReceiver {
             let ret = match self.get_receiver(&key) {
                 Some(receiver) => {
                     remove_session = true;
-                    receiver.push(&alc, now)
+                    let ret = receiver.push(&alc, now);
+                    let events = receiver.take_events();
+                    self.dispatch(&key, events);
+                    ret
                 }
                 None => {
                     log::warn!(
@@ -258,7 +315,21 @@ impl MultiReceiver {
             ret
         } else {
             let receiver = self.get_receiver_or_create(&key);
-            receiver.push(&alc, now)
+            let ret = receiver.push(&alc, now);
+            let events = receiver.take_events();
+            self.dispatch(&key, events);
+            ret
+        }
+    }
+
+    fn dispatch(&self, endpoint: &ReceiverEndpoint, events: Vec<ReceiverEvent>) {
+        for event in &events {
+            for listener in self.listeners.values() {
+                match event {
+                    ReceiverEvent::Object(event) => listener.on_object_event(endpoint, event),
+                    ReceiverEvent::Fdt(event) => listener.on_fdt_event(endpoint, event),
+                }
+            }
         }
     }
 
@@ -276,8 +347,14 @@ impl MultiReceiver {
         }
 
         self.alc_receiver.retain(|_, v| !v.is_expired());
-        for receiver in &mut self.alc_receiver.values_mut() {
+        let mut events = Vec::new();
+        for (key, receiver) in &mut self.alc_receiver {
             receiver.cleanup(now);
+            events.push((key.clone(), receiver.take_events()));
+        }
+
+        for (endpoint, events) in events {
+            self.dispatch(&endpoint, events);
         }
 
         for endpoint in &output {
@@ -303,12 +380,21 @@ impl MultiReceiver {
                     listener.on_session_open(&key);
                 }
 
-                Box::new(Receiver::new(
+                let mut receiver = Receiver::new_with_decoder_pool(
                     &key.endpoint,
                     key.tsi,
                     self.writer.clone(),
-                    self.config,
-                ))
+                    self.config.unwrap_or_default(),
+                    self.decoder_pool.clone(),
+                );
+                for het in &self.lct_extensions {
+                    receiver.register_lct_extension(*het).ok();
+                }
+                if let Some(dir) = &self.parts_spill_dir {
+                    receiver.set_parts_spill_dir(dir).ok();
+                }
+                receiver.enable_events();
+                Box::new(receiver)
             })
             .as_mut()
     }
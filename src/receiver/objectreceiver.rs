@@ -1,14 +1,18 @@
 use super::blockdecoder::BlockDecoder;
+use super::blockdecoderpool::{BlockDecoderPool, DecodedBlock, DecodedBlocks};
 use super::blockwriter::BlockWriter;
 use super::writer::ObjectWriterBuilder;
+use crate::common::objectpart::ObjectPart;
 use crate::common::udpendpoint::UDPEndpoint;
 use crate::common::{alc, fdtinstance::FdtInstance, lct, oti, partition};
+use crate::fec::FecDecoder;
 use crate::receiver::writer::{
     ObjectCacheControl, ObjectMetadata, ObjectWriter, ObjectWriterBuilderResult,
 };
 use crate::tools::error::{FluteError, Result};
 use std::collections::VecDeque;
 use std::rc::Rc;
+use std::sync::Arc;
 use std::time::Instant;
 use std::time::{Duration, SystemTime};
 
@@ -39,6 +43,15 @@ struct ObjectWriterSession {
     state: ObjectWriterSessionState,
 }
 
+/// Resources used by an `ObjectReceiver` to decode its blocks
+#[derive(Debug, Clone)]
+pub struct BlockDecodingConfig {
+    /// Maximum size of the blocks allocated to receive the object
+    pub max_size_allocated: usize,
+    /// Pool decoding the blocks on worker threads, `None` to decode them during `push()`
+    pub decoder_pool: Option<Rc<BlockDecoderPool>>,
+}
+
 #[derive(Debug)]
 pub struct ObjectReceiver {
     pub state: State,
@@ -75,6 +88,17 @@ pub struct ObjectReceiver {
     groups: Vec<String>,
     last_timestamp: SystemTime,
     pub e_tag: Option<String>,
+    part: Option<ObjectPart>,
+    lct_extensions: Vec<lct::LctExtension>,
+    decoder_pool: Option<Rc<BlockDecoderPool>>,
+    decoded_blocks: Arc<DecodedBlocks>,
+    nb_blocks_decoding: usize,
+    // Close Object flag received, the object is interrupted once the decoded blocks are processed
+    close_object_received: bool,
+    live: bool,
+    pub error_reason: Option<String>,
+    nb_bytes_received: u64,
+    pub progress_notified: Option<u64>,
 }
 
 impl ObjectReceiver {
@@ -84,7 +108,7 @@ impl ObjectReceiver {
         toi: &u128,
         _fdt_instance_id: Option<u32>,
         object_writer_builder: Rc<dyn ObjectWriterBuilder>,
-        max_size_allocated: usize,
+        decoding: BlockDecodingConfig,
         now: SystemTime,
     ) -> ObjectReceiver {
         log::debug!("Create new Object Receiver with toi {}", toi);
@@ -93,7 +117,7 @@ impl ObjectReceiver {
             oti: None,
             cache: Vec::new(),
             cache_size: 0,
-            max_size_allocated,
+            max_size_allocated: decoding.max_size_allocated,
             blocks: VecDeque::new(),
             blocks_offset: 0,
             transfer_length: None,
@@ -126,6 +150,25 @@ impl ObjectReceiver {
             groups: Vec::new(),
             last_timestamp: now,
             e_tag: None,
+            part: None,
+            lct_extensions: Vec::new(),
+            decoder_pool: decoding.decoder_pool,
+            decoded_blocks: Arc::new(DecodedBlocks::default()),
+            nb_blocks_decoding: 0,
+            close_object_received: false,
+            live: false,
+            error_reason: None,
+            nb_bytes_received: 0,
+            progress_notified: None,
+        }
+    }
+
+    /// Keep the header extensions of the application, the first value received for each HET
+    pub fn add_lct_extensions(&mut self, extensions: &[lct::LctExtension]) {
+        for ext in extensions {
+            if !self.lct_extensions.iter().any(|e| e.het() == ext.het()) {
+                self.lct_extensions.push(ext.clone());
+            }
         }
     }
 
@@ -141,6 +184,29 @@ impl ObjectReceiver {
         self.blocks_offset + self.blocks.len()
     }
 
+    /// Bytes of encoding symbols received, limited to the transfer length
+    pub fn received_bytes(&self) -> u64 {
+        match self.transfer_length {
+            Some(transfer_length) => self.nb_bytes_received.min(transfer_length),
+            None => self.nb_bytes_received,
+        }
+    }
+
+    /// Step of the progress of the reception, a new step is reported to the listeners.
+    /// One percent of the transfer length, or one block when the transfer length is unknown
+    pub fn progress_step(&self) -> Option<u64> {
+        if self.nb_bytes_received == 0 {
+            return None;
+        }
+
+        match self.transfer_length {
+            Some(transfer_length) if transfer_length > 0 => {
+                Some(self.received_bytes() * 100 / transfer_length)
+            }
+            _ => Some(self.nb_block_completed() as u64),
+        }
+    }
+
     pub fn push(&mut self, pkt: &alc::AlcPkt, now: std::time::SystemTime) {
         self.last_timestamp = now;
         if self.state != State::Receiving {
@@ -151,6 +217,10 @@ impl ObjectReceiver {
         self.set_fdt_id_from_pkt(pkt);
         self.set_cenc_from_pkt(pkt);
         self.set_oti_from_pkt(pkt, now);
+        self.set_live_transfer_length_from_pkt(pkt, now);
+        if self.state != State::Receiving {
+            return;
+        }
 
         self.init_blocks_partitioning();
         self.init_object_writer(now);
@@ -162,27 +232,96 @@ impl ObjectReceiver {
             return;
         }
 
-        self.push_to_block(pkt, now)
-            .unwrap_or_else(|_| self.error("Fail to push pkt to block", now, false));
+        self.push_to_block(pkt, now).unwrap_or_else(|e| {
+            self.error(&format!("Fail to push pkt to block: {}", e.0), now, false)
+        });
+    }
+
+    /// Returns true if blocks are being decoded by the decoder pool
+    pub fn is_decoding(&self) -> bool {
+        self.nb_blocks_decoding > 0
+    }
+
+    /// Process the blocks decoded by the decoder pool
+    pub fn poll_decoded_blocks(&mut self, now: std::time::SystemTime) {
+        while let Some(block) = self.decoded_blocks.pop() {
+            self.push_decoded_block(block, now);
+        }
+        self.check_close_object(now);
+    }
+
+    /// Interrupt the object closed by the sender once no more block is being decoded
+    fn check_close_object(&mut self, now: std::time::SystemTime) {
+        if self.close_object_received
+            && self.nb_blocks_decoding == 0
+            && self.state == State::Receiving
+        {
+            self.error("No more packet for this object", now, true);
+        }
+    }
+
+    fn push_decoded_block(&mut self, block: DecodedBlock, now: std::time::SystemTime) {
+        debug_assert!(self.nb_blocks_decoding > 0);
+        self.nb_blocks_decoding -= 1;
+        if self.state != State::Receiving || (block.sbn as usize) < self.blocks_offset {
+            return;
+        }
+
+        let block_offset = block.sbn as usize - self.blocks_offset;
+        let decoder = match self.blocks.get_mut(block_offset) {
+            Some(block_decoder) => block_decoder.decoded(block.decoder, block.success),
+            None => return,
+        };
+
+        if let Some(decoder) = decoder {
+            self.decode_block(block.sbn, decoder, now);
+            return;
+        }
+
+        if self.blocks[block_offset].completed {
+            log::debug!("block {} is completed", block.sbn);
+            self.write_blocks(block.sbn, now)
+                .unwrap_or_else(|_| self.error("Fail to write blocks to storage", now, false));
+        }
+    }
+
+    fn decode_block(&mut self, sbn: u32, decoder: Box<dyn FecDecoder>, now: std::time::SystemTime) {
+        debug_assert!(self.decoder_pool.is_some());
+        let pool = self.decoder_pool.as_ref().unwrap();
+        match pool.submit(sbn, decoder, self.decoded_blocks.clone()) {
+            None => self.nb_blocks_decoding += 1,
+            Some(mut decoder) => {
+                // Pool is not available, decode the block synchronously
+                let success = decoder.decode();
+                self.nb_blocks_decoding += 1;
+                self.push_decoded_block(
+                    DecodedBlock {
+                        sbn,
+                        decoder,
+                        success,
+                    },
+                    now,
+                );
+            }
+        }
     }
 
     fn push_to_block(&mut self, pkt: &alc::AlcPkt, now: std::time::SystemTime) -> Result<()> {
         self.push_to_block2(pkt, now)?;
         if pkt.lct.close_object {
-            if self.state == State::Receiving {
-                self.error("No more packet for this object", now, true);
-            }
+            self.close_object_received = true;
+            self.check_close_object(now);
         }
         Ok(())
     }
 
     fn push_to_block2(&mut self, pkt: &alc::AlcPkt, now: std::time::SystemTime) -> Result<()> {
         debug_assert!(self.oti.is_some());
-        debug_assert!(self.transfer_length.is_some());
+        debug_assert!(self.transfer_length.is_some() || self.live);
         let payload_id = alc::parse_payload_id(pkt, self.oti.as_ref().unwrap())?;
         let nb_blocks = self.blocks.len();
 
-        if self.transfer_length.unwrap() == 0 {
+        if self.transfer_length == Some(0) {
             debug_assert!(self.block_writer.is_none());
             self.complete(now);
             return Ok(());
@@ -226,6 +365,11 @@ impl ObjectReceiver {
 
             let block_length: usize = match payload_id.source_block_length {
                 Some(_) => source_block_length as usize * oti.encoding_symbol_length as usize,
+                None if self.live => Self::live_block_length(
+                    self.a_large * oti.encoding_symbol_length as u64,
+                    self.transfer_length,
+                    payload_id.sbn,
+                ) as usize,
                 None => partition::block_length(
                     self.a_large,
                     self.a_small,
@@ -236,6 +380,15 @@ impl ObjectReceiver {
                 ) as usize,
             };
 
+            // The last block of a live object can be shorter than the others
+            let source_block_length = match payload_id.source_block_length {
+                None if self.live => {
+                    num_integer::div_ceil(block_length as u64, oti.encoding_symbol_length as u64)
+                        as u32
+                }
+                _ => source_block_length,
+            };
+
             if self.nb_allocated_blocks >= 2
                 && self.total_allocated_blocks_size + block_length > self.max_size_allocated
             {
@@ -266,7 +419,14 @@ impl ObjectReceiver {
             self.total_allocated_blocks_size += block_length;
         }
 
-        block.push(pkt, &payload_id);
+        self.nb_bytes_received += (pkt.data.len() - pkt.data_payload_offset) as u64;
+        let offload = self.decoder_pool.is_some()
+            && self.oti.as_ref().unwrap().fec_encoding_id != oti::FECEncodingID::NoCode;
+        if let Some(decoder) = block.push(pkt, &payload_id, offload) {
+            self.decode_block(payload_id.sbn, decoder, now);
+            return Ok(());
+        }
+
         if block.completed {
             log::debug!("block {} is completed", payload_id.sbn);
             self.write_blocks(payload_id.sbn, now)?;
@@ -289,6 +449,16 @@ impl ObjectReceiver {
         ))
     }
 
+    /// Replace the `ObjectWriterBuilder`, only before the creation of the object writer
+    pub fn set_object_writer_builder(
+        &mut self,
+        object_writer_builder: Rc<dyn ObjectWriterBuilder>,
+    ) {
+        if self.object_writer.is_none() {
+            self.object_writer_builder = object_writer_builder;
+        }
+    }
+
     pub fn attach_fdt(
         &mut self,
         fdt_instance_id: u32,
@@ -298,7 +468,7 @@ impl ObjectReceiver {
         debug_assert!(self.toi != lct::TOI_FDT);
         self.last_timestamp = now;
         if self.fdt_instance_id.is_some() {
-            return false;
+            return self.update_live_transfer_length_from_fdt(fdt, now);
         }
 
         let file = match fdt.get_file(&self.toi) {
@@ -306,6 +476,31 @@ impl ObjectReceiver {
             None => return false,
         };
 
+        if file.live == Some(true) {
+            if self.nb_block() > 0 {
+                self.error(
+                    "Blocks of the live object have been received before the FDT",
+                    now,
+                    false,
+                );
+                return false;
+            }
+            self.live = true;
+        }
+
+        // The transfer length of a live object is unknown until the end of the stream
+        let fdt_transfer_length = match self.live {
+            true => file.transfer_length,
+            // The transfer length may have been received with EXT_TOL only
+            false if file.transfer_length.is_none()
+                && file.content_length.is_none()
+                && self.transfer_length.is_some() =>
+            {
+                None
+            }
+            false => Some(file.get_transfer_length()),
+        };
+
         #[cfg(feature = "opentelemetry")]
         if self.logger.is_none() {
             let propagator = file.get_optel_propagator();
@@ -322,16 +517,11 @@ impl ObjectReceiver {
 
         if self.oti.is_none() {
             self.oti = fdt.get_oti_for_file(file);
-            if self.oti.is_some() {
-                debug_assert!(self.transfer_length.is_none());
-                self.transfer_length = Some(file.get_transfer_length());
-            }
         }
 
         if self.transfer_length.is_none() {
-            self.transfer_length = Some(file.get_transfer_length());
-        } else {
-            let fdt_transfer_length = file.get_transfer_length();
+            self.transfer_length = fdt_transfer_length;
+        } else if let Some(fdt_transfer_length) = fdt_transfer_length {
             if self.transfer_length.unwrap() != fdt_transfer_length {
                 log::warn!(
                     "Transfer length mismatch {} != {}",
@@ -363,6 +553,7 @@ impl ObjectReceiver {
         self.content_type = file.content_type.clone();
         self.groups = groups;
         self.e_tag = file.file_etag.clone();
+        self.part = file.get_object_part();
 
         self.init_blocks_partitioning();
         self.init_object_writer(now);
@@ -395,6 +586,12 @@ impl ObjectReceiver {
             transfer_length: self.transfer_length.map(|s| s as usize),
             cenc: self.cenc.clone(),
             e_tag: self.e_tag.clone(),
+            part: self.part.clone(),
+            presentation_time: None,
+            lct_extensions: match self.lct_extensions.is_empty() {
+                true => None,
+                false => Some(self.lct_extensions.clone()),
+            },
         }
     }
 
@@ -412,7 +609,7 @@ impl ObjectReceiver {
 
         if self.fdt_instance_id.is_none()
             || self.cenc.is_none()
-            || self.transfer_length.is_none()
+            || (self.transfer_length.is_none() && !self.live)
             || self.oti.is_none()
         {
             return;
@@ -432,6 +629,7 @@ impl ObjectReceiver {
             }
             ObjectWriterBuilderResult::Abort => {
                 self.state = State::Error;
+                self.error_reason = Some("Object writer aborted the reception".to_string());
                 return;
             }
         };
@@ -453,7 +651,8 @@ impl ObjectReceiver {
             return;
         };
 
-        let transfer_length = self.transfer_length.unwrap();
+        // The length of a live object is set when the end of the stream is received
+        let transfer_length = self.transfer_length.unwrap_or(u64::MAX);
         if transfer_length != 0 {
             self.block_writer = Some(BlockWriter::new(
                 transfer_length as usize,
@@ -571,6 +770,9 @@ impl ObjectReceiver {
         });
 
         log::debug!("{}", description);
+        if self.error_reason.is_none() {
+            self.error_reason = Some(description.to_string());
+        }
         self.state = match interrupted {
             true => State::Interrupted,
             false => State::Error,
@@ -631,6 +833,7 @@ impl ObjectReceiver {
         }
 
         if pkt.oti.is_none() {
+            self.set_transfer_length_from_tol(pkt);
             return;
         }
 
@@ -661,6 +864,80 @@ impl ObjectReceiver {
         }
     }
 
+    /// Without FTI, the transfer length can be signalled with EXT_TOL
+    fn set_transfer_length_from_tol(&mut self, pkt: &alc::AlcPkt) {
+        if self.live || self.transfer_length.is_some() {
+            return;
+        }
+
+        if let Some(transfer_length) = pkt.transfer_length {
+            log::debug!("Set transfer length from TOL {}", transfer_length);
+            self.transfer_length = Some(transfer_length);
+        }
+    }
+
+    fn set_live_transfer_length_from_pkt(&mut self, pkt: &alc::AlcPkt, now: SystemTime) {
+        if !self.live || self.transfer_length.is_some() {
+            return;
+        }
+
+        if let Some(transfer_length) = pkt.transfer_length {
+            self.set_live_transfer_length(transfer_length, now);
+        }
+    }
+
+    fn update_live_transfer_length_from_fdt(&mut self, fdt: &FdtInstance, now: SystemTime) -> bool {
+        if !self.live || self.transfer_length.is_some() || self.state != State::Receiving {
+            return false;
+        }
+
+        let transfer_length = match fdt.get_file(&self.toi).and_then(|f| f.transfer_length) {
+            Some(transfer_length) => transfer_length,
+            None => return false,
+        };
+
+        self.set_live_transfer_length(transfer_length, now);
+        true
+    }
+
+    /// Set the transfer length of a live object once the end of the stream is announced
+    fn set_live_transfer_length(&mut self, transfer_length: u64, now: SystemTime) {
+        log::debug!(
+            "TOI={} Transfer length of the live object is {}",
+            self.toi,
+            transfer_length
+        );
+        self.transfer_length = Some(transfer_length);
+        self.content_length = Some(transfer_length as usize);
+
+        let writer = match self.block_writer.as_mut() {
+            Some(writer) => writer,
+            None => return,
+        };
+
+        let object_writer = self.object_writer.as_ref().unwrap().writer.as_ref();
+        if let Err(e) = writer.set_transfer_length(transfer_length as usize, object_writer, now) {
+            log::error!("{:?}", e);
+            self.error("Invalid transfer length of live object", now, false);
+            return;
+        }
+
+        if writer.is_completed() {
+            self.complete(now);
+        }
+    }
+
+    /// Length of a block of a live object
+    /// All the blocks have the maximum length, except the last one
+    fn live_block_length(max_block_length: u64, transfer_length: Option<u64>, sbn: u32) -> u64 {
+        match transfer_length {
+            Some(transfer_length) => {
+                max_block_length.min(transfer_length.saturating_sub(sbn as u64 * max_block_length))
+            }
+            None => max_block_length,
+        }
+    }
+
     fn cache(&mut self, pkt: &alc::AlcPkt) -> Result<()> {
         if self.cache_size == 0 {
             log::warn!(
@@ -689,18 +966,32 @@ impl ObjectReceiver {
             return;
         }
 
-        if self.oti.is_none() || self.transfer_length.is_none() {
+        if self.oti.is_none() || (self.transfer_length.is_none() && !self.live) {
             return;
         }
 
         debug_assert!(self.blocks.is_empty());
         let oti = self.oti.as_ref().unwrap();
 
-        let (a_large, a_small, nb_a_large, nb_blocks) = partition::block_partitioning(
-            oti.maximum_source_block_length as u64,
-            self.transfer_length.unwrap_or_default(),
-            oti.encoding_symbol_length as u64,
-        );
+        let (a_large, a_small, nb_a_large, nb_blocks) = match self.live {
+            // Blocks of a live object have the maximum length, except the last one
+            true => {
+                let max_block_length = oti.maximum_source_block_length as u64;
+                let nb_blocks = match self.transfer_length {
+                    Some(transfer_length) => num_integer::div_ceil(
+                        transfer_length,
+                        max_block_length * oti.encoding_symbol_length as u64,
+                    ),
+                    None => 1,
+                };
+                (max_block_length, max_block_length, u64::MAX, nb_blocks)
+            }
+            false => partition::block_partitioning(
+                oti.maximum_source_block_length as u64,
+                self.transfer_length.unwrap_or_default(),
+                oti.encoding_symbol_length as u64,
+            ),
+        };
 
         log::debug!(
             "Block partitioning
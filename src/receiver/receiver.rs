@@ -1,14 +1,19 @@
+use super::blockdecoderpool::BlockDecoderPool;
+use super::events::{FdtEvent, ObjectEvent, ReceiverEvent};
 use super::fdtreceiver;
 use super::fdtreceiver::FdtReceiver;
 use super::objectreceiver;
+use super::objectreceiver::BlockDecodingConfig;
 use super::objectreceiver::ObjectReceiver;
-use super::writer::{ObjectMetadata, ObjectWriterBuilder};
+use super::writer::{ObjectMetadata, ObjectWriterBuilder, ObjectWriterPartsBuilder};
+use crate::common::fdtinstance::FdtInstance;
 use crate::common::udpendpoint::UDPEndpoint;
 use crate::common::{alc, lct};
 use crate::receiver::writer::ObjectCacheControl;
 use crate::tools::error::FluteError;
 use crate::tools::error::Result;
-use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use std::time::Duration;
 use std::time::Instant;
@@ -36,6 +41,12 @@ pub struct Config {
     pub object_receive_once: bool,
     /// When set to `true`, the receiver will check the expiration date of the FDT.
     pub enable_fdt_expiration_check: bool,
+    /// Number of threads used to FEC decode the blocks outside of the reception path.
+    /// Decoded blocks are processed during the next calls to `push()` or `cleanup()`.
+    /// 0 : blocks are decoded synchronously during the call to `push()`
+    ///
+    /// The threads of a `MultiReceiver` are shared by all its sessions.
+    pub fec_decoding_threads: usize,
 }
 
 impl Default for Config {
@@ -47,6 +58,7 @@ impl Default for Config {
             object_max_cache_size: None,
             object_receive_once: true,
             enable_fdt_expiration_check: true,
+            fec_decoding_threads: 0,
         }
     }
 }
@@ -68,11 +80,18 @@ pub struct Receiver {
     fdt_receivers: BTreeMap<u32, Box<FdtReceiver>>,
     fdt_current: VecDeque<Box<FdtReceiver>>,
     writer: Rc<dyn ObjectWriterBuilder>,
+    parts_writer: Option<Rc<ObjectWriterPartsBuilder>>,
+    parts_spill_dir: PathBuf,
     config: Config,
     last_activity: Instant,
     closed_is_imminent: bool,
     endpoint: UDPEndpoint,
     last_timestamp: Option<SystemTime>,
+    decoder_pool: Option<Rc<BlockDecoderPool>>,
+    nb_unknown_ext: usize,
+    lct_extensions: BTreeSet<u8>,
+    events: Option<Vec<ReceiverEvent>>,
+    announced_objects: HashSet<u128>,
 }
 
 impl Receiver {
@@ -94,6 +113,19 @@ impl Receiver {
         tsi: u64,
         writer: Rc<dyn ObjectWriterBuilder>,
         config: Option<Config>,
+    ) -> Self {
+        let config = config.unwrap_or_default();
+        let decoder_pool = BlockDecoderPool::from_config(&config);
+        Self::new_with_decoder_pool(endpoint, tsi, writer, config, decoder_pool)
+    }
+
+    /// Create a receiver using the FEC decoding threads of its `MultiReceiver`
+    pub(crate) fn new_with_decoder_pool(
+        endpoint: &UDPEndpoint,
+        tsi: u64,
+        writer: Rc<dyn ObjectWriterBuilder>,
+        config: Config,
+        decoder_pool: Option<Rc<BlockDecoderPool>>,
     ) -> Self {
         Self {
             tsi,
@@ -101,13 +133,20 @@ impl Receiver {
             fdt_receivers: BTreeMap::new(),
             fdt_current: VecDeque::new(),
             writer,
+            parts_writer: None,
+            parts_spill_dir: std::env::temp_dir(),
             objects_completed: BTreeMap::new(),
             objects_error: BTreeSet::new(),
-            config: config.unwrap_or_default(),
+            config,
             last_activity: Instant::now(),
             closed_is_imminent: false,
             endpoint: endpoint.clone(),
             last_timestamp: None,
+            decoder_pool,
+            nb_unknown_ext: 0,
+            lct_extensions: BTreeSet::new(),
+            events: None,
+            announced_objects: HashSet::new(),
         }
     }
 
@@ -156,6 +195,86 @@ impl Receiver {
         self.objects_error.len()
     }
 
+    /// Get the number of LCT header extensions skipped because they are unknown.
+    ///
+    /// Unknown extensions do not prevent the reception of the objects,
+    /// this counter helps to diagnose senders using extensions not supported by this library.
+    ///
+    /// # Returns
+    ///
+    /// The number of unknown header extensions received.
+    ///
+    pub fn nb_unknown_ext(&self) -> usize {
+        self.nb_unknown_ext
+    }
+
+    /// Register a header extension of the application.
+    ///
+    /// Registered extensions are not counted as unknown, they are reported to the `ObjectWriterBuilder`
+    /// with `lct_extensions_received()` for each packet and inside the `ObjectMetadata` of the objects.
+    ///
+    /// # Arguments
+    ///
+    /// * `het` - Header Extension Type, must not be an extension handled by the library.
+    ///
+    pub fn register_lct_extension(&mut self, het: u8) -> Result<()> {
+        lct::LctExtension::check_het(het)?;
+        self.lct_extensions.insert(het);
+        Ok(())
+    }
+
+    /// Set the directory where the parts of an object received out of order are stored
+    /// until the previous parts are complete.
+    ///
+    /// The default is the temporary directory of the system.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - An existing directory, used by the objects announced after this call.
+    ///
+    pub fn set_parts_spill_dir(&mut self, dir: &Path) -> Result<()> {
+        if !dir.is_dir() {
+            return Err(FluteError::new(format!("{:?} is not a directory", dir)));
+        }
+
+        self.parts_spill_dir = dir.to_path_buf();
+        if let Some(parts_writer) = &self.parts_writer {
+            parts_writer.set_spill_dir(dir);
+        }
+        Ok(())
+    }
+
+    /// Queue the lifecycle events of the objects and of the FDT, they are retrieved with `take_events()`.
+    ///
+    /// The events are disabled by default, they are dispatched to the listeners by `MultiReceiver`.
+    pub fn enable_events(&mut self) {
+        self.events.get_or_insert_with(Vec::new);
+    }
+
+    /// Return the events queued since the previous call, empty if the events are not enabled
+    pub fn take_events(&mut self) -> Vec<ReceiverEvent> {
+        self.events.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
+    fn notify(events: &mut Option<Vec<ReceiverEvent>>, event: ReceiverEvent) {
+        if let Some(events) = events.as_mut() {
+            events.push(event);
+        }
+    }
+
+    fn get_lct_extensions(&self, alc_pkt: &alc::AlcPkt) -> Result<Vec<lct::LctExtension>> {
+        // Registered extensions are unknown to the library
+        if self.lct_extensions.is_empty() || alc_pkt.nb_unknown_ext == 0 {
+            return Ok(Vec::new());
+        }
+
+        lct::get_ext_list(alc_pkt.data, &alc_pkt.lct)?
+            .into_iter()
+            .filter(|ext| self.lct_extensions.contains(&ext[0]))
+            .map(lct::LctExtension::parse)
+            .collect()
+    }
+
     /// Free objects that timed out.
     ///
     /// This method performs cleanup operations on the `Receiver`, freeing objects that
@@ -167,21 +286,66 @@ impl Receiver {
     ///
     pub fn cleanup(&mut self, now: std::time::SystemTime) {
         self.last_timestamp = Some(now);
+        self.poll_decoded_blocks(now);
         self.cleanup_objects();
         self.cleanup_fdt(now);
     }
 
+    fn poll_decoded_blocks(&mut self, now: std::time::SystemTime) {
+        if self.decoder_pool.is_none() {
+            return;
+        }
+
+        let tois: Vec<u128> = self
+            .objects
+            .iter()
+            .filter(|(_, obj)| obj.is_decoding())
+            .map(|(toi, _)| *toi)
+            .collect();
+
+        for toi in tois {
+            if let Some(obj) = self.objects.get_mut(&toi) {
+                obj.poll_decoded_blocks(now);
+            }
+            self.check_object_state(toi);
+        }
+    }
+
     fn cleanup_fdt(&mut self, now: std::time::SystemTime) {
         self.fdt_receivers.iter_mut().for_each(|fdt| {
             fdt.1.update_expired_state(now);
         });
 
+        for fdt in &self.fdt_current {
+            Self::update_fdt_expired_state(&mut self.events, fdt, now);
+        }
+
         self.fdt_receivers.retain(|_, fdt| {
             let state = fdt.state();
             state == fdtreceiver::FDTState::Complete || state == fdtreceiver::FDTState::Receiving
         });
     }
 
+    fn update_fdt_expired_state(
+        events: &mut Option<Vec<ReceiverEvent>>,
+        fdt: &FdtReceiver,
+        now: SystemTime,
+    ) {
+        if fdt.state() != fdtreceiver::FDTState::Complete {
+            return;
+        }
+
+        fdt.update_expired_state(now);
+        if fdt.state() == fdtreceiver::FDTState::Expired {
+            Self::notify(
+                events,
+                ReceiverEvent::Fdt(FdtEvent::Expired {
+                    fdt_instance_id: fdt.fdt_id,
+                }),
+            );
+        }
+    }
+
     fn cleanup_objects(&mut self) {
         if self.config.object_timeout.is_none() {
             return;
@@ -220,7 +384,15 @@ impl Receiver {
 
         for toi in expired_objects_toi {
             self.objects_error.remove(&toi);
-            self.objects.remove(&toi);
+            if self.objects.remove(&toi).is_some() {
+                Self::notify(
+                    &mut self.events,
+                    ReceiverEvent::Object(ObjectEvent::Error {
+                        toi,
+                        reason: "Object expired".to_string(),
+                    }),
+                );
+            }
         }
     }
 
@@ -269,14 +441,28 @@ impl Receiver {
         self.last_activity = Instant::now();
         self.last_timestamp = Some(now);
 
+        let lct_extensions = self.get_lct_extensions(alc_pkt)?;
+        self.nb_unknown_ext += alc_pkt.nb_unknown_ext - lct_extensions.len();
+        if !lct_extensions.is_empty() {
+            self.writer.lct_extensions_received(
+                &self.endpoint,
+                &self.tsi,
+                &alc_pkt.lct.toi,
+                &lct_extensions,
+                now,
+            );
+        }
+
         if alc_pkt.lct.close_session {
             log::info!("Close session");
             self.closed_is_imminent = true;
         }
 
+        self.poll_decoded_blocks(now);
+
         match alc_pkt.lct.toi {
             toi if toi == lct::TOI_FDT => self.push_fdt_obj(alc_pkt, now),
-            _ => self.push_obj(alc_pkt, now),
+            _ => self.push_obj(alc_pkt, &lct_extensions, now),
         }
     }
 
@@ -340,6 +526,10 @@ impl Receiver {
                 fdtreceiver::FDTState::Complete => {}
                 fdtreceiver::FDTState::Error => return Err(FluteError::new("Fail to decode FDT")),
                 fdtreceiver::FDTState::Expired => {
+                    Self::notify(
+                        &mut self.events,
+                        ReceiverEvent::Fdt(FdtEvent::Expired { fdt_instance_id }),
+                    );
                     let expiration = fdt_receiver.get_expiration_time().unwrap_or(now);
                     let server_time = fdt_receiver.get_server_time(now);
 
@@ -393,6 +583,7 @@ impl Receiver {
                     fdt_current.ext_time,
                 );
             }
+            self.notify_fdt_instance(&mut fdt_current, now);
             self.fdt_current.push_front(fdt_current);
             self.attach_latest_fdt_to_objects(now);
             self.gc_object_completed();
@@ -406,6 +597,47 @@ impl Receiver {
         Ok(())
     }
 
+    fn notify_fdt_instance(&mut self, fdt: &mut FdtReceiver, now: SystemTime) {
+        if self.events.is_none() {
+            return;
+        }
+
+        let fdt_instance_id = fdt.fdt_id;
+        let instance = match fdt.fdt_instance() {
+            Some(instance) => instance,
+            None => return,
+        };
+
+        let files = instance.file.as_deref().unwrap_or_default();
+        Self::notify(
+            &mut self.events,
+            ReceiverEvent::Fdt(FdtEvent::NewInstance {
+                fdt_instance_id,
+                expires: instance.get_expiration_date().unwrap_or(now),
+                nb_objects: files.len(),
+            }),
+        );
+
+        let mut announced_objects = HashSet::new();
+        for file in files {
+            let toi: u128 = match file.toi.parse() {
+                Ok(toi) => toi,
+                Err(_) => continue,
+            };
+            announced_objects.insert(toi);
+            if !self.announced_objects.contains(&toi) {
+                Self::notify(
+                    &mut self.events,
+                    ReceiverEvent::Object(ObjectEvent::Announced {
+                        toi,
+                        content_location: file.content_location.clone(),
+                    }),
+                );
+            }
+        }
+        self.announced_objects = announced_objects;
+    }
+
     fn attach_latest_fdt_to_objects(&mut self, now: std::time::SystemTime) -> Option<()> {
         let fdt = self.fdt_current.front_mut()?;
         let fdt_id = fdt.fdt_id;
@@ -413,6 +645,13 @@ impl Receiver {
         log::debug!("TSI={} Attach FDT id {}", self.tsi, fdt_id);
         let mut check_state = Vec::new();
         for obj in &mut self.objects {
+            Self::select_object_writer(
+                obj.1,
+                fdt_instance,
+                &self.writer,
+                &mut self.parts_writer,
+                &self.parts_spill_dir,
+            );
             let success = obj.1.attach_fdt(fdt_id, fdt_instance, now);
             if success {
                 check_state.push(*obj.0);
@@ -441,7 +680,12 @@ impl Receiver {
             if let Some(obj) = self.objects_completed.get_mut(&toi) {
                 if obj.metadata.cache_control.should_update(cache_control) {
                     obj.metadata.cache_control = cache_control;
-                    self.writer.update_cache_control(
+                    let writer: &dyn ObjectWriterBuilder =
+                        match (&obj.metadata.part, &self.parts_writer) {
+                            (Some(_), Some(parts_writer)) => parts_writer.as_ref(),
+                            _ => self.writer.as_ref(),
+                        };
+                    writer.update_cache_control(
                         &self.endpoint,
                         &self.tsi,
                         &toi,
@@ -455,7 +699,12 @@ impl Receiver {
         Some(())
     }
 
-    fn push_obj(&mut self, pkt: &alc::AlcPkt, now: SystemTime) -> Result<()> {
+    fn push_obj(
+        &mut self,
+        pkt: &alc::AlcPkt,
+        lct_extensions: &[lct::LctExtension],
+        now: SystemTime,
+    ) -> Result<()> {
         if self.objects_completed.contains_key(&pkt.lct.toi) {
             if self.config.object_receive_once {
                 return Ok(());
@@ -480,7 +729,7 @@ impl Receiver {
 
         let mut obj = self.objects.get_mut(&pkt.lct.toi);
         if obj.is_none() {
-            self.create_obj(&pkt.lct.toi, now);
+            self.create_obj(&pkt.lct.toi, lct_extensions, now);
             obj = self.objects.get_mut(&pkt.lct.toi);
         }
 
@@ -489,6 +738,7 @@ impl Receiver {
             None => return Err(FluteError::new("Bug ? Object not found")),
         };
 
+        obj.add_lct_extensions(lct_extensions);
         obj.push(pkt, now);
         self.check_object_state(pkt.lct.toi);
 
@@ -506,9 +756,29 @@ impl Receiver {
             let obj = obj.unwrap();
 
             match obj.state {
-                objectreceiver::State::Receiving => {}
+                objectreceiver::State::Receiving => {
+                    let step = obj.progress_step();
+                    if step.is_some() && step != obj.progress_notified {
+                        obj.progress_notified = step;
+                        Self::notify(
+                            &mut self.events,
+                            ReceiverEvent::Object(ObjectEvent::Progress {
+                                toi,
+                                received_bytes: obj.received_bytes(),
+                                transfer_length: obj.transfer_length,
+                            }),
+                        );
+                    }
+                }
                 objectreceiver::State::Completed => {
                     remove_object = true;
+                    Self::notify(
+                        &mut self.events,
+                        ReceiverEvent::Object(ObjectEvent::Completed {
+                            toi,
+                            content_location: obj.content_location.clone(),
+                        }),
+                    );
                     log::debug!(
                         "Object state is completed {:?} tsi={} toi={}",
                         self.endpoint,
@@ -536,12 +806,26 @@ impl Receiver {
                         obj.toi
                     );
                     remove_object = true;
+                    Self::notify(
+                        &mut self.events,
+                        ReceiverEvent::Object(ObjectEvent::Interrupted { toi }),
+                    );
                     self.objects_error.insert(toi);
                     self.gc_object_error();
                 }
                 objectreceiver::State::Error => {
                     log::error!("Object in error state tsi={} toi={}", self.tsi, obj.toi);
                     remove_object = true;
+                    Self::notify(
+                        &mut self.events,
+                        ReceiverEvent::Object(ObjectEvent::Error {
+                            toi,
+                            reason: obj
+                                .error_reason
+                                .clone()
+                                .unwrap_or_else(|| "Unknown error".to_string()),
+                        }),
+                    );
                     self.objects_error.insert(toi);
                     self.gc_object_error();
                 }
@@ -592,25 +876,67 @@ impl Receiver {
         }
     }
 
-    fn create_obj(&mut self, toi: &u128, now: SystemTime) {
+    /// The parts of an object split by the sender are re-assembled by an `ObjectWriterPartsBuilder`,
+    /// created when the first part is announced
+    fn select_object_writer(
+        obj: &mut ObjectReceiver,
+        fdt_instance: &FdtInstance,
+        writer: &Rc<dyn ObjectWriterBuilder>,
+        parts_writer: &mut Option<Rc<ObjectWriterPartsBuilder>>,
+        parts_spill_dir: &Path,
+    ) {
+        let is_part = fdt_instance
+            .get_file(&obj.toi)
+            .map(|file| file.get_object_part().is_some())
+            .unwrap_or(false);
+        if !is_part {
+            return;
+        }
+
+        let parts_writer = parts_writer.get_or_insert_with(|| {
+            Rc::new(ObjectWriterPartsBuilder::new(
+                writer.clone(),
+                parts_spill_dir,
+            ))
+        });
+        obj.set_object_writer_builder(parts_writer.clone());
+    }
+
+    fn create_obj(&mut self, toi: &u128, lct_extensions: &[lct::LctExtension], now: SystemTime) {
         let mut obj = Box::new(ObjectReceiver::new(
             &self.endpoint,
             self.tsi,
             toi,
             None,
             self.writer.clone(),
-            self.config
-                .object_max_cache_size
-                .unwrap_or(10 * 1024 * 1024),
+            BlockDecodingConfig {
+                max_size_allocated: self
+                    .config
+                    .object_max_cache_size
+                    .unwrap_or(10 * 1024 * 1024),
+                decoder_pool: self.decoder_pool.clone(),
+            },
             now,
         ));
+        obj.add_lct_extensions(lct_extensions);
+        Self::notify(
+            &mut self.events,
+            ReceiverEvent::Object(ObjectEvent::ReceptionStarted { toi: *toi }),
+        );
 
         let mut is_attached = false;
         for (fdt_index, fdt) in (&mut self.fdt_current.iter_mut()).enumerate() {
             let fdt_id = fdt.fdt_id;
-            fdt.update_expired_state(now);
+            Self::update_fdt_expired_state(&mut self.events, fdt, now);
             if fdt.state() == fdtreceiver::FDTState::Complete {
                 if let Some(fdt_instance) = fdt.fdt_instance() {
+                    Self::select_object_writer(
+                        &mut obj,
+                        fdt_instance,
+                        &self.writer,
+                        &mut self.parts_writer,
+                        &self.parts_spill_dir,
+                    );
                     let success = obj.attach_fdt(fdt_id, fdt_instance, now);
                     if success {
                         is_attached = true;
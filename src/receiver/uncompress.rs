@@ -99,6 +99,121 @@ impl Decompress for DecompressZlib {
     }
 }
 
+/// Read the data uncompressed by a decoder that writes to a `Vec<u8>`
+#[cfg(any(feature = "zstd", feature = "brotli"))]
+fn read_output(
+    output: &mut Vec<u8>,
+    offset: &mut usize,
+    finished: bool,
+    data: &mut [u8],
+) -> std::io::Result<usize> {
+    let available = &output[*offset..];
+    if available.is_empty() {
+        output.clear();
+        *offset = 0;
+        if finished {
+            return Ok(0);
+        }
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::WouldBlock,
+            "Waiting for more data",
+        ));
+    }
+
+    let size = available.len().min(data.len());
+    data[..size].copy_from_slice(&available[..size]);
+    *offset += size;
+    Ok(size)
+}
+
+#[cfg(feature = "zstd")]
+pub struct DecompressZstd {
+    decoder: zstd::stream::write::Decoder<'static, Vec<u8>>,
+    offset: usize,
+    finished: bool,
+}
+
+#[cfg(feature = "zstd")]
+impl DecompressZstd {
+    pub fn new(pkt: &[u8]) -> std::io::Result<DecompressZstd> {
+        let mut decoder = zstd::stream::write::Decoder::new(Vec::new())?;
+        decoder.write_all(pkt)?;
+        Ok(DecompressZstd {
+            decoder,
+            offset: 0,
+            finished: false,
+        })
+    }
+}
+
+#[cfg(feature = "zstd")]
+impl Decompress for DecompressZstd {
+    fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+        self.decoder.write_all(data)?;
+        Ok(data.len())
+    }
+
+    fn read(&mut self, data: &mut [u8]) -> std::io::Result<usize> {
+        read_output(
+            self.decoder.get_mut(),
+            &mut self.offset,
+            self.finished,
+            data,
+        )
+    }
+
+    fn finish(&mut self) {
+        if let Err(e) = self.decoder.flush() {
+            log::error!("Fail to flush ZSTD decoder {:?}", e);
+        }
+        self.finished = true;
+    }
+}
+
+#[cfg(feature = "brotli")]
+pub struct DecompressBrotli {
+    decoder: brotli::DecompressorWriter<Vec<u8>>,
+    offset: usize,
+    finished: bool,
+}
+
+#[cfg(feature = "brotli")]
+impl DecompressBrotli {
+    pub fn new(pkt: &[u8]) -> std::io::Result<DecompressBrotli> {
+        let mut decoder = brotli::DecompressorWriter::new(Vec::new(), 4096);
+        decoder.write_all(pkt)?;
+        Ok(DecompressBrotli {
+            decoder,
+            offset: 0,
+            finished: false,
+        })
+    }
+}
+
+#[cfg(feature = "brotli")]
+impl Decompress for DecompressBrotli {
+    fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+        self.decoder.write_all(data)?;
+        Ok(data.len())
+    }
+
+    fn read(&mut self, data: &mut [u8]) -> std::io::Result<usize> {
+        read_output(
+            self.decoder.get_mut(),
+            &mut self.offset,
+            self.finished,
+            data,
+        )
+    }
+
+    fn finish(&mut self) {
+        if let Err(e) = self.decoder.close() {
+            log::error!("Fail to close Brotli decoder {:?}", e);
+        }
+        self.finished = true;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     #[test]
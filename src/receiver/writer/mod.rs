@@ -15,8 +15,10 @@ use std::collections::HashMap;
 use std::time::Duration;
 use std::time::SystemTime;
 
+use crate::common::objectpart::ObjectPart;
 use crate::common::udpendpoint::UDPEndpoint;
 use crate::core::lct::Cenc;
+use crate::core::lct::LctExtension;
 use crate::core::Oti;
 use crate::tools::error::Result;
 
@@ -71,6 +73,7 @@ impl ObjectCacheControl {
 /// Struct representing metadata for an object.
 ///
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub struct ObjectMetadata {
     /// URI that can be used as an identifier for this object
     pub content_location: String,
@@ -96,6 +99,13 @@ pub struct ObjectMetadata {
     pub cenc: Option<Cenc>,
     /// ETag
     pub e_tag: Option<String>,
+    /// Set when this object is a part of a larger object split by the sender
+    pub part: Option<ObjectPart>,
+    /// Presentation time of the object, signalled by ROUTE with `EXT_ROUTE_PRESENTATION_TIME`
+    pub presentation_time: Option<SystemTime>,
+    /// Header extensions of the application received with the object,
+    /// only the extensions registered to the receiver are reported
+    pub lct_extensions: Option<Vec<LctExtension>>,
 }
 
 ///
@@ -145,6 +155,16 @@ pub trait ObjectWriterBuilder {
         now: std::time::SystemTime,
         ext_time: Option<std::time::SystemTime>,
     );
+    /// Called for each ALC/LCT packet containing header extensions registered to the receiver
+    fn lct_extensions_received(
+        &self,
+        _endpoint: &UDPEndpoint,
+        _tsi: &u64,
+        _toi: &u128,
+        _extensions: &[LctExtension],
+        _now: std::time::SystemTime,
+    ) {
+    }
 }
 
 ///
@@ -195,9 +215,23 @@ impl std::fmt::Debug for dyn ObjectWriter {
 
 mod objectwriterbuffer;
 mod objectwriterfs;
+mod objectwritermirror;
+mod objectwriterparts;
+mod objectwritersegment;
 
 pub use objectwriterbuffer::ObjectWriterBuffer;
 pub use objectwriterbuffer::ObjectWriterBufferBuilder;
 
 pub use objectwriterfs::ObjectWriterFS;
 pub use objectwriterfs::ObjectWriterFSBuilder;
+
+pub use objectwritermirror::MirrorRemovePolicy;
+pub use objectwritermirror::ObjectWriterMirror;
+pub use objectwritermirror::ObjectWriterMirrorBuilder;
+
+pub use objectwritersegment::ObjectWriterSegmentBuilder;
+pub use crate::common::segment::SEGMENT_GROUP_INIT;
+pub use crate::common::segment::SEGMENT_GROUP_MANIFEST;
+pub use crate::common::segment::SEGMENT_GROUP_MEDIA;
+
+pub(crate) use objectwriterparts::ObjectWriterPartsBuilder;
@@ -0,0 +1,365 @@
+use super::{ObjectMetadata, ObjectWriter, ObjectWriterBuilder, ObjectWriterBuilderResult};
+use crate::common::fdtinstance::FdtInstance;
+use crate::common::udpendpoint::UDPEndpoint;
+use crate::error::{FluteError, Result};
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Prefix of the files being received
+const TEMP_FILE_PREFIX: &str = ".flute-mirror-";
+
+///
+/// Action applied to the local files that are not announced anymore by the sender
+///
+#[derive(Debug, Clone, PartialEq)]
+pub enum MirrorRemovePolicy {
+    /// Files are deleted
+    Delete,
+    /// Files are moved to this directory, keeping their relative path
+    Archive(PathBuf),
+    /// Files are kept
+    Keep,
+}
+
+///
+/// Maintain a local mirror of the files announced by the sender
+///
+/// Objects are written to a temporary file and moved to their final destination once complete,
+/// so a new version of a file replaces the old version atomically.
+///
+/// When a complete FDT (`FullFDT="true"`) is received,
+/// the files of the destination directory not listed in this FDT are removed according to the `MirrorRemovePolicy`.
+/// The destination directory must be dedicated to a single FLUTE session.
+///
+#[derive(Debug)]
+pub struct ObjectWriterMirrorBuilder {
+    dest: PathBuf,
+    enable_md5_check: bool,
+    remove_policy: MirrorRemovePolicy,
+}
+
+impl ObjectWriterMirrorBuilder {
+    /// Return a new `ObjectWriterMirrorBuilder`
+    pub fn new(
+        dest: &Path,
+        enable_md5_check: bool,
+        remove_policy: MirrorRemovePolicy,
+    ) -> Result<ObjectWriterMirrorBuilder> {
+        if !dest.is_dir() {
+            return Err(FluteError::new(format!("{:?} is not a directory", dest)));
+        }
+
+        if let MirrorRemovePolicy::Archive(archive) = &remove_policy {
+            if !archive.is_dir() {
+                return Err(FluteError::new(format!("{:?} is not a directory", archive)));
+            }
+        }
+
+        Ok(ObjectWriterMirrorBuilder {
+            dest: dest.to_path_buf(),
+            enable_md5_check,
+            remove_policy,
+        })
+    }
+
+    /// Convert a Content-Location to a path relative to the destination directory
+    ///
+    /// Each segment of the path is percent-decoded,
+    /// segments that could escape the destination directory are rejected.
+    pub(crate) fn relative_path(content_location: &str) -> Option<PathBuf> {
+        let path = match url::Url::parse(content_location) {
+            Ok(url) => url.path().to_string(),
+            Err(url::ParseError::RelativeUrlWithoutBase) => content_location.to_string(),
+            Err(_) => return None,
+        };
+
+        let mut relative_path = PathBuf::new();
+        for segment in path.split('/').filter(|segment| !segment.is_empty()) {
+            let segment = percent_encoding::percent_decode_str(segment)
+                .decode_utf8()
+                .ok()?;
+            if segment.contains(['/', '\\', '\0']) {
+                return None;
+            }
+
+            let segment = Path::new(segment.as_ref());
+            let valid = segment
+                .components()
+                .all(|component| matches!(component, Component::Normal(_)));
+            if !valid {
+                return None;
+            }
+            relative_path.push(segment);
+        }
+
+        if relative_path.as_os_str().is_empty() {
+            return None;
+        }
+
+        Some(relative_path)
+    }
+
+    fn list_files(&self, dir: &Path, output: &mut Vec<PathBuf>) -> Result<()> {
+        for entry in std::fs::read_dir(dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            if let MirrorRemovePolicy::Archive(archive) = &self.remove_policy {
+                if &path == archive {
+                    continue;
+                }
+            }
+
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                self.list_files(&path, output)?;
+            } else if !entry
+                .file_name()
+                .to_string_lossy()
+                .starts_with(TEMP_FILE_PREFIX)
+            {
+                output.push(path);
+            }
+        }
+        Ok(())
+    }
+
+    fn remove_file(&self, path: &Path) -> Result<()> {
+        match &self.remove_policy {
+            MirrorRemovePolicy::Delete => std::fs::remove_file(path)?,
+            MirrorRemovePolicy::Archive(archive) => {
+                let relative_path = path.strip_prefix(&self.dest).unwrap_or(path);
+                let destination = archive.join(relative_path);
+                if let Some(parent) = destination.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                std::fs::rename(path, destination)?;
+            }
+            MirrorRemovePolicy::Keep => return Ok(()),
+        }
+
+        // Remove the directories that are now empty
+        let mut parent = path.parent();
+        while let Some(dir) = parent {
+            if dir == self.dest || std::fs::remove_dir(dir).is_err() {
+                break;
+            }
+            parent = dir.parent();
+        }
+
+        Ok(())
+    }
+
+    fn sync(&self, fdt: &FdtInstance) -> Result<()> {
+        let announced: HashSet<PathBuf> = fdt
+            .file
+            .iter()
+            .flatten()
+            .filter_map(|file| {
+                let content_location = match file.get_object_part() {
+                    Some(part) => part.group,
+                    None => file.content_location.clone(),
+                };
+                Self::relative_path(&content_location)
+            })
+            .map(|relative_path| self.dest.join(relative_path))
+            .collect();
+
+        let mut files = Vec::new();
+        self.list_files(&self.dest, &mut files)?;
+
+        for file in files.iter().filter(|file| !announced.contains(*file)) {
+            log::info!("{:?} is not announced anymore, remove it", file);
+            if let Err(e) = self.remove_file(file) {
+                log::error!("Fail to remove {:?} {:?}", file, e);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl ObjectWriterBuilder for ObjectWriterMirrorBuilder {
+    fn new_object_writer(
+        &self,
+        _endpoint: &UDPEndpoint,
+        _tsi: &u64,
+        toi: &u128,
+        meta: &ObjectMetadata,
+        _now: std::time::SystemTime,
+    ) -> ObjectWriterBuilderResult {
+        let relative_path = match Self::relative_path(&meta.content_location) {
+            Some(relative_path) => relative_path,
+            None => {
+                log::error!("Invalid content location {:?}", meta.content_location);
+                return ObjectWriterBuilderResult::Abort;
+            }
+        };
+
+        ObjectWriterBuilderResult::StoreObject(Box::new(ObjectWriterMirror::new(
+            self.dest.join(relative_path),
+            toi,
+            self.enable_md5_check,
+        )))
+    }
+
+    fn update_cache_control(
+        &self,
+        _endpoint: &UDPEndpoint,
+        _tsi: &u64,
+        _toi: &u128,
+        _meta: &ObjectMetadata,
+        _now: std::time::SystemTime,
+    ) {
+    }
+
+    fn fdt_received(
+        &self,
+        _endpoint: &UDPEndpoint,
+        _tsi: &u64,
+        fdt_xml: &str,
+        _expires: std::time::SystemTime,
+        _meta: &ObjectMetadata,
+        _transfer_duration: Duration,
+        _now: std::time::SystemTime,
+        _ext_time: Option<std::time::SystemTime>,
+    ) {
+        let fdt = match FdtInstance::parse(fdt_xml.as_bytes()) {
+            Ok(fdt) => fdt,
+            Err(e) => {
+                log::error!("Fail to parse FDT {:?}", e);
+                return;
+            }
+        };
+
+        if fdt.full_fdt != Some(true) {
+            return;
+        }
+
+        if let Err(e) = self.sync(&fdt) {
+            log::error!("Fail to synchronize {:?} {:?}", self.dest, e);
+        }
+    }
+}
+
+///
+/// Write an object to a temporary file, moved to its destination when the object is complete
+///
+#[derive(Debug)]
+pub struct ObjectWriterMirror {
+    destination: PathBuf,
+    temp_destination: PathBuf,
+    writer: RefCell<Option<std::io::BufWriter<std::fs::File>>>,
+    enable_md5_check: bool,
+}
+
+impl ObjectWriterMirror {
+    pub(crate) fn new(destination: PathBuf, toi: &u128, enable_md5_check: bool) -> Self {
+        let file_name = destination
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let temp_destination =
+            destination.with_file_name(format!("{}{}-{}", TEMP_FILE_PREFIX, toi, file_name));
+
+        ObjectWriterMirror {
+            destination,
+            temp_destination,
+            writer: RefCell::new(None),
+            enable_md5_check,
+        }
+    }
+}
+
+impl ObjectWriter for ObjectWriterMirror {
+    fn open(&self, _now: SystemTime) -> Result<()> {
+        if let Some(parent) = self.temp_destination.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let file = std::fs::File::create(&self.temp_destination)?;
+        self.writer.replace(Some(std::io::BufWriter::new(file)));
+        Ok(())
+    }
+
+    fn write(&self, _sbn: u32, data: &[u8], _now: SystemTime) -> Result<()> {
+        let mut writer = self.writer.borrow_mut();
+        match writer.as_mut() {
+            Some(writer) => writer.write_all(data).map_err(|e| {
+                FluteError::new(format!(
+                    "Fail to write data to file {:?} {:?}",
+                    self.temp_destination, e
+                ))
+            }),
+            None => Ok(()),
+        }
+    }
+
+    fn complete(&self, _now: SystemTime) {
+        let writer = match self.writer.take() {
+            Some(writer) => writer,
+            None => return,
+        };
+
+        if let Err(e) = writer.into_inner() {
+            log::error!("Fail to flush {:?} {:?}", self.temp_destination, e);
+            std::fs::remove_file(&self.temp_destination).ok();
+            return;
+        }
+
+        log::info!("{:?} received", self.destination);
+        if let Err(e) = std::fs::rename(&self.temp_destination, &self.destination) {
+            log::error!("Fail to move file to {:?} {:?}", self.destination, e);
+            std::fs::remove_file(&self.temp_destination).ok();
+        }
+    }
+
+    fn error(&self, _now: SystemTime) {
+        if self.writer.take().is_some() {
+            std::fs::remove_file(&self.temp_destination).ok();
+        }
+    }
+
+    fn interrupted(&self, now: SystemTime) {
+        self.error(now);
+    }
+
+    fn enable_md5_check(&self) -> bool {
+        self.enable_md5_check
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ObjectWriterMirrorBuilder;
+    use std::path::PathBuf;
+
+    #[test]
+    pub fn test_mirror_relative_path() {
+        crate::tests::init();
+        assert!(
+            ObjectWriterMirrorBuilder::relative_path("file:///dir/file.txt")
+                == Some(PathBuf::from("dir/file.txt"))
+        );
+        assert!(
+            ObjectWriterMirrorBuilder::relative_path("dir/file.txt")
+                == Some(PathBuf::from("dir/file.txt"))
+        );
+        assert!(
+            ObjectWriterMirrorBuilder::relative_path("file:///dir/../../etc/passwd")
+                == Some(PathBuf::from("etc/passwd"))
+        );
+        assert!(
+            ObjectWriterMirrorBuilder::relative_path("file:///dir/my%20file.txt")
+                == Some(PathBuf::from("dir/my file.txt"))
+        );
+        assert!(ObjectWriterMirrorBuilder::relative_path("../file.txt").is_none());
+        assert!(ObjectWriterMirrorBuilder::relative_path("dir/%2e%2e/%2E%2E/file.txt").is_none());
+        assert!(
+            ObjectWriterMirrorBuilder::relative_path("file:///dir/..%2Fetc%2Fpasswd").is_none()
+        );
+        assert!(ObjectWriterMirrorBuilder::relative_path("file:///").is_none());
+    }
+}
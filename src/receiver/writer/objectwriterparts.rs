@@ -0,0 +1,534 @@
+use super::{ObjectMetadata, ObjectWriter, ObjectWriterBuilder, ObjectWriterBuilderResult};
+use crate::common::lct::LctExtension;
+use crate::common::udpendpoint::UDPEndpoint;
+use crate::tools::error::Result;
+use base64::Engine;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::io::{Read, Seek, Write};
+use std::rc::Rc;
+use std::time::{Duration, SystemTime};
+
+///
+/// Re-assemble the parts of an object split by the sender.
+///
+/// Objects that are not a part are forwarded to the inner `ObjectWriterBuilder`.
+/// The parts are written in order to a single `ObjectWriter` created for the whole object,
+/// parts received out of order are spilled to a temporary file until the previous parts are complete.
+///
+/// When a part fails, the whole object is discarded and its re-assembly starts again
+/// with the parts of the next transfer.
+///
+#[derive(Debug)]
+pub(crate) struct ObjectWriterPartsBuilder {
+    inner: Rc<dyn ObjectWriterBuilder>,
+    spill_dir: RefCell<std::path::PathBuf>,
+    groups: RefCell<HashMap<String, Rc<RefCell<PartGroup>>>>,
+}
+
+/// Temporary file holding a part received out of order, removed when dropped
+#[derive(Debug)]
+struct SpillFile {
+    path: std::path::PathBuf,
+    file: std::fs::File,
+}
+
+impl SpillFile {
+    fn new(dir: &std::path::Path) -> Result<SpillFile> {
+        let (path, file) = crate::tools::create_temp_file(dir, "flute_part")?;
+        Ok(SpillFile { path, file })
+    }
+}
+
+impl Drop for SpillFile {
+    fn drop(&mut self) {
+        if let Err(e) = std::fs::remove_file(&self.path) {
+            log::error!("Fail to remove {:?}: {}", self.path, e);
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct PendingPart {
+    spill: Option<SpillFile>,
+    complete: bool,
+}
+
+impl PendingPart {
+    fn append(&mut self, dir: &std::path::Path, data: &[u8]) -> Result<()> {
+        let spill = match self.spill.as_mut() {
+            Some(spill) => spill,
+            None => self.spill.insert(SpillFile::new(dir)?),
+        };
+        spill.file.write_all(data)?;
+        Ok(())
+    }
+
+    /// Copy the content of the part to the writer of the whole object
+    fn flush(
+        &mut self,
+        md5: &mut md5::Context,
+        writer: &dyn ObjectWriter,
+        now: SystemTime,
+    ) -> Result<()> {
+        let spill = match self.spill.as_mut() {
+            Some(spill) => spill,
+            None => return Ok(()),
+        };
+
+        spill.file.rewind()?;
+        let mut buffer = vec![0; 64 * 1024];
+        loop {
+            let size = spill.file.read(&mut buffer)?;
+            if size == 0 {
+                break;
+            }
+            md5.consume(&buffer[..size]);
+            writer.write(0, &buffer[..size], now)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PartGroupState {
+    Receiving,
+    Completed,
+    Error,
+}
+
+struct PartGroup {
+    writer: Box<dyn ObjectWriter>,
+    meta: ObjectMetadata,
+    nb_parts: u32,
+    opened: bool,
+    next_part: u32,
+    pending: BTreeMap<u32, PendingPart>,
+    spill_dir: std::path::PathBuf,
+    md5: md5::Context,
+    state: PartGroupState,
+}
+
+impl std::fmt::Debug for PartGroup {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PartGroup")
+            .field("meta", &self.meta)
+            .field("nb_parts", &self.nb_parts)
+            .field("next_part", &self.next_part)
+            .field("state", &self.state)
+            .finish()
+    }
+}
+
+impl PartGroup {
+    fn open(&mut self, now: SystemTime) -> Result<()> {
+        if !self.opened {
+            self.writer.open(now)?;
+            self.opened = true;
+        }
+        Ok(())
+    }
+
+    /// The part has already been received by a previous transfer
+    fn is_received(&self, index: u32) -> bool {
+        index < self.next_part
+            || self
+                .pending
+                .get(&index)
+                .map(|part| part.complete)
+                .unwrap_or(false)
+    }
+
+    fn write(&mut self, index: u32, sbn: u32, data: &[u8], now: SystemTime) -> Result<()> {
+        if self.state != PartGroupState::Receiving {
+            return Ok(());
+        }
+
+        if index != self.next_part {
+            let part = self.pending.entry(index).or_default();
+            if let Err(e) = part.append(&self.spill_dir, data) {
+                self.error(now);
+                return Err(e);
+            }
+            return Ok(());
+        }
+
+        self.md5.consume(data);
+        self.writer.write(sbn, data, now)
+    }
+
+    fn part_complete(&mut self, index: u32, now: SystemTime) {
+        if self.state != PartGroupState::Receiving {
+            return;
+        }
+
+        if index != self.next_part {
+            self.pending.entry(index).or_default().complete = true;
+            return;
+        }
+
+        self.next_part += 1;
+
+        // Write the parts received in advance
+        while let Some(mut part) = self.pending.remove(&self.next_part) {
+            if let Err(e) = part.flush(&mut self.md5, self.writer.as_ref(), now) {
+                log::error!("Fail to write part {}: {:?}", self.next_part, e);
+                self.error(now);
+                return;
+            }
+
+            if !part.complete {
+                break;
+            }
+            self.next_part += 1;
+        }
+
+        if self.next_part == self.nb_parts {
+            self.complete(now);
+        }
+    }
+
+    fn complete(&mut self, now: SystemTime) {
+        if self.writer.enable_md5_check() {
+            if let Some(expected_md5) = self.meta.md5.as_ref() {
+                let md5 = std::mem::replace(&mut self.md5, md5::Context::new()).compute();
+                let md5 = base64::engine::general_purpose::STANDARD.encode(md5.0);
+                if &md5 != expected_md5 {
+                    log::error!(
+                        "MD5 mismatch for object {} expected={} received={}",
+                        self.meta.content_location,
+                        expected_md5,
+                        md5
+                    );
+                    self.error(now);
+                    return;
+                }
+            }
+        }
+
+        self.state = PartGroupState::Completed;
+        self.writer.complete(now);
+    }
+
+    fn error(&mut self, now: SystemTime) {
+        if self.state != PartGroupState::Receiving {
+            return;
+        }
+
+        self.state = PartGroupState::Error;
+        self.pending.clear();
+        self.writer.error(now);
+    }
+
+    fn interrupted(&mut self, now: SystemTime) {
+        if self.state != PartGroupState::Receiving {
+            return;
+        }
+
+        self.state = PartGroupState::Error;
+        self.pending.clear();
+        self.writer.interrupted(now);
+    }
+}
+
+///
+/// Write a part to the writer of the whole object
+///
+#[derive(Debug)]
+struct ObjectWriterPart {
+    group: Rc<RefCell<PartGroup>>,
+    index: u32,
+}
+
+impl ObjectWriter for ObjectWriterPart {
+    fn open(&self, now: SystemTime) -> Result<()> {
+        self.group.borrow_mut().open(now)
+    }
+
+    fn write(&self, sbn: u32, data: &[u8], now: SystemTime) -> Result<()> {
+        self.group.borrow_mut().write(self.index, sbn, data, now)
+    }
+
+    fn complete(&self, now: SystemTime) {
+        self.group.borrow_mut().part_complete(self.index, now)
+    }
+
+    fn error(&self, now: SystemTime) {
+        self.group.borrow_mut().error(now)
+    }
+
+    fn interrupted(&self, now: SystemTime) {
+        self.group.borrow_mut().interrupted(now)
+    }
+
+    fn enable_md5_check(&self) -> bool {
+        self.group.borrow().writer.enable_md5_check()
+    }
+}
+
+impl ObjectWriterPartsBuilder {
+    pub fn new(
+        inner: Rc<dyn ObjectWriterBuilder>,
+        spill_dir: &std::path::Path,
+    ) -> ObjectWriterPartsBuilder {
+        ObjectWriterPartsBuilder {
+            inner,
+            spill_dir: RefCell::new(spill_dir.to_path_buf()),
+            groups: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Directory of the parts received out of order, used by the objects announced after this call
+    pub fn set_spill_dir(&self, spill_dir: &std::path::Path) {
+        *self.spill_dir.borrow_mut() = spill_dir.to_path_buf();
+    }
+
+    /// Metadata of the whole object
+    fn group_meta(meta: &ObjectMetadata) -> ObjectMetadata {
+        let mut group_meta = meta.clone();
+        if let Some(part) = meta.part.as_ref() {
+            group_meta.content_location = part.group.clone();
+            group_meta.content_length = Some(part.group_content_length as usize);
+            group_meta.transfer_length = Some(part.group_content_length as usize);
+            group_meta.md5 = part.group_md5.clone();
+            group_meta.part = None;
+        }
+        group_meta
+    }
+}
+
+impl ObjectWriterBuilder for ObjectWriterPartsBuilder {
+    fn new_object_writer(
+        &self,
+        endpoint: &UDPEndpoint,
+        tsi: &u64,
+        toi: &u128,
+        meta: &ObjectMetadata,
+        now: SystemTime,
+    ) -> ObjectWriterBuilderResult {
+        let part = match meta.part.as_ref() {
+            Some(part) => part,
+            None => return self.inner.new_object_writer(endpoint, tsi, toi, meta, now),
+        };
+
+        // A group in error is re-assembled again from the parts of the next transfer
+        let mut groups = self.groups.borrow_mut();
+        groups.retain(|_, group| group.borrow().state == PartGroupState::Receiving);
+
+        let group = match groups.get(&part.group) {
+            Some(group) if group.borrow().is_received(part.index) => {
+                log::debug!("Part {} of {} is already received", part.index, part.group);
+                return ObjectWriterBuilderResult::ObjectAlreadyReceived;
+            }
+            Some(group) => group.clone(),
+            None => {
+                let group_meta = Self::group_meta(meta);
+                let writer =
+                    match self
+                        .inner
+                        .new_object_writer(endpoint, tsi, toi, &group_meta, now)
+                    {
+                        ObjectWriterBuilderResult::StoreObject(writer) => writer,
+                        result => return result,
+                    };
+
+                let group = Rc::new(RefCell::new(PartGroup {
+                    writer,
+                    meta: group_meta,
+                    nb_parts: part.count,
+                    opened: false,
+                    next_part: 0,
+                    pending: BTreeMap::new(),
+                    spill_dir: self.spill_dir.borrow().clone(),
+                    md5: md5::Context::new(),
+                    state: PartGroupState::Receiving,
+                }));
+                groups.insert(part.group.clone(), group.clone());
+                group
+            }
+        };
+
+        ObjectWriterBuilderResult::StoreObject(Box::new(ObjectWriterPart {
+            group,
+            index: part.index,
+        }))
+    }
+
+    fn update_cache_control(
+        &self,
+        endpoint: &UDPEndpoint,
+        tsi: &u64,
+        toi: &u128,
+        meta: &ObjectMetadata,
+        now: SystemTime,
+    ) {
+        self.inner
+            .update_cache_control(endpoint, tsi, toi, &Self::group_meta(meta), now)
+    }
+
+    fn fdt_received(
+        &self,
+        endpoint: &UDPEndpoint,
+        tsi: &u64,
+        fdt_xml: &str,
+        expires: SystemTime,
+        meta: &ObjectMetadata,
+        transfer_duration: Duration,
+        now: SystemTime,
+        ext_time: Option<SystemTime>,
+    ) {
+        self.inner.fdt_received(
+            endpoint,
+            tsi,
+            fdt_xml,
+            expires,
+            meta,
+            transfer_duration,
+            now,
+            ext_time,
+        )
+    }
+
+    fn lct_extensions_received(
+        &self,
+        endpoint: &UDPEndpoint,
+        tsi: &u64,
+        toi: &u128,
+        extensions: &[LctExtension],
+        now: SystemTime,
+    ) {
+        self.inner
+            .lct_extensions_received(endpoint, tsi, toi, extensions, now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ObjectWriterPartsBuilder;
+    use crate::common::objectpart::ObjectPart;
+    use crate::common::udpendpoint::UDPEndpoint;
+    use crate::receiver::writer::{
+        ObjectCacheControl, ObjectMetadata, ObjectWriterBufferBuilder, ObjectWriterBuilder,
+        ObjectWriterBuilderResult,
+    };
+    use std::rc::Rc;
+    use std::time::SystemTime;
+
+    fn part_meta(index: u32, count: u32) -> ObjectMetadata {
+        ObjectMetadata {
+            content_location: format!("file:///object.part{}", index),
+            content_length: Some(4),
+            transfer_length: Some(4),
+            content_type: None,
+            cache_control: ObjectCacheControl::NoCache,
+            groups: None,
+            md5: None,
+            optel_propagator: None,
+            oti: None,
+            cenc: None,
+            e_tag: None,
+            part: Some(ObjectPart {
+                group: "file:///object".to_owned(),
+                index,
+                count,
+                offset: index as u64 * 4,
+                group_content_length: count as u64 * 4,
+                group_md5: None,
+            }),
+            presentation_time: None,
+            lct_extensions: None,
+        }
+    }
+
+    fn new_part(
+        builder: &ObjectWriterPartsBuilder,
+        index: u32,
+        count: u32,
+    ) -> ObjectWriterBuilderResult {
+        let endpoint = UDPEndpoint::new(None, "224.0.0.1".to_owned(), 3400);
+        builder.new_object_writer(
+            &endpoint,
+            &1,
+            &(index as u128 + 1),
+            &part_meta(index, count),
+            SystemTime::now(),
+        )
+    }
+
+    #[test]
+    pub fn test_parts_out_of_order() {
+        crate::tests::init();
+        let output = Rc::new(ObjectWriterBufferBuilder::new(false));
+        let builder = ObjectWriterPartsBuilder::new(output.clone(), &std::env::temp_dir());
+        let now = SystemTime::now();
+
+        let mut writers = Vec::new();
+        for index in 0..3 {
+            match new_part(&builder, index, 3) {
+                ObjectWriterBuilderResult::StoreObject(writer) => writers.push(writer),
+                _ => panic!("Part {} is not stored", index),
+            }
+        }
+
+        // Parts received in reverse order are spilled until the first part is complete
+        for (index, writer) in writers.iter().enumerate().rev() {
+            writer.open(now).unwrap();
+            writer.write(0, &[index as u8; 4], now).unwrap();
+            writer.complete(now);
+        }
+
+        let objects = output.objects.borrow();
+        assert_eq!(objects.len(), 1);
+        let object = objects[0].borrow();
+        assert!(object.complete);
+        assert_eq!(object.meta.content_location, "file:///object");
+        assert_eq!(object.data, [[0u8; 4], [1u8; 4], [2u8; 4]].concat());
+    }
+
+    #[test]
+    pub fn test_parts_group_error() {
+        crate::tests::init();
+        let output = Rc::new(ObjectWriterBufferBuilder::new(false));
+        let spill_dir = tempfile::tempdir().unwrap();
+        let builder = ObjectWriterPartsBuilder::new(output.clone(), spill_dir.path());
+        let now = SystemTime::now();
+
+        let store = |index: u32| match new_part(&builder, index, 3) {
+            ObjectWriterBuilderResult::StoreObject(writer) => writer,
+            _ => panic!("Part {} is not stored", index),
+        };
+
+        // First transfer, part 1 is interrupted and part 2 is spilled
+        let part_1 = store(1);
+        part_1.open(now).unwrap();
+        part_1.write(0, &[1u8; 4], now).unwrap();
+        part_1.interrupted(now);
+
+        let part_2 = store(2);
+        part_2.open(now).unwrap();
+        part_2.write(0, &[2u8; 4], now).unwrap();
+        part_2.complete(now);
+        assert_eq!(std::fs::read_dir(spill_dir.path()).unwrap().count(), 1);
+
+        // Second transfer, part 2 is already received
+        let part_0 = store(0);
+        assert!(matches!(
+            new_part(&builder, 2, 3),
+            ObjectWriterBuilderResult::ObjectAlreadyReceived
+        ));
+        let part_1 = store(1);
+        for (index, writer) in [part_0, part_1].iter().enumerate() {
+            writer.open(now).unwrap();
+            writer.write(0, &[index as u8; 4], now).unwrap();
+            writer.complete(now);
+        }
+        assert_eq!(std::fs::read_dir(spill_dir.path()).unwrap().count(), 0);
+
+        let objects = output.objects.borrow();
+        assert_eq!(objects.len(), 2);
+        assert!(!objects[0].borrow().complete);
+        let object = objects[1].borrow();
+        assert!(object.complete);
+        assert_eq!(object.data, [[0u8; 4], [1u8; 4], [2u8; 4]].concat());
+    }
+}
@@ -0,0 +1,190 @@
+use super::objectwritermirror::{ObjectWriterMirror, ObjectWriterMirrorBuilder};
+use super::{ObjectMetadata, ObjectWriter, ObjectWriterBuilder, ObjectWriterBuilderResult};
+use crate::common::segment::SEGMENT_GROUP_MEDIA;
+use crate::common::udpendpoint::UDPEndpoint;
+use crate::error::{FluteError, Result};
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::{Duration, SystemTime};
+
+///
+/// Expose the segments of a DASH / HLS stream sent by a `SegmentSender` to a local player
+///
+/// Manifests and segments are written to the player directory at the path of their Content-Location,
+/// relative to `base_url`, so the relative URIs of the manifest resolve to the received segments.
+/// Files are written to a temporary file and moved to their destination once complete,
+/// the player never reads a partial segment.
+///
+/// Only the last `max_media_segments` media segments are kept in the directory,
+/// the oldest segment is removed when a new media segment is complete.
+///
+#[derive(Debug)]
+pub struct ObjectWriterSegmentBuilder {
+    dest: PathBuf,
+    base_url: Option<url::Url>,
+    enable_md5_check: bool,
+    media_segments: Rc<RefCell<MediaSegments>>,
+}
+
+/// Media segments present in the player directory, from the oldest to the newest
+#[derive(Debug)]
+struct MediaSegments {
+    paths: VecDeque<PathBuf>,
+    max_media_segments: usize,
+}
+
+impl MediaSegments {
+    fn add(&mut self, destination: &Path) {
+        if self.paths.iter().any(|path| path == destination) {
+            return;
+        }
+
+        self.paths.push_back(destination.to_path_buf());
+        while self.paths.len() > self.max_media_segments {
+            let oldest = self.paths.pop_front().unwrap();
+            log::debug!("Remove old segment {:?}", oldest);
+            std::fs::remove_file(&oldest).ok();
+        }
+    }
+}
+
+/// Write a segment to the player directory, register the media segments once complete
+struct ObjectWriterSegment {
+    mirror: ObjectWriterMirror,
+    destination: PathBuf,
+    media_segments: Option<Rc<RefCell<MediaSegments>>>,
+}
+
+impl ObjectWriterSegmentBuilder {
+    /// Return a new `ObjectWriterSegmentBuilder`
+    ///
+    /// # Arguments
+    ///
+    /// * `dest` - Player directory
+    /// * `base_url` - Base URL of the stream, the prefix removed from the Content-Location of the objects
+    /// * `max_media_segments` - Number of media segments kept in the directory
+    /// * `enable_md5_check` - Check the MD5 of the objects
+    pub fn new(
+        dest: &Path,
+        base_url: Option<&url::Url>,
+        max_media_segments: usize,
+        enable_md5_check: bool,
+    ) -> Result<ObjectWriterSegmentBuilder> {
+        if !dest.is_dir() {
+            return Err(FluteError::new(format!("{:?} is not a directory", dest)));
+        }
+
+        Ok(ObjectWriterSegmentBuilder {
+            dest: dest.to_path_buf(),
+            base_url: base_url.cloned(),
+            enable_md5_check,
+            media_segments: Rc::new(RefCell::new(MediaSegments {
+                paths: VecDeque::new(),
+                max_media_segments: max_media_segments.max(1),
+            })),
+        })
+    }
+
+    fn relative_path(&self, content_location: &str) -> Option<PathBuf> {
+        let relative = self
+            .base_url
+            .as_ref()
+            .and_then(|base_url| content_location.strip_prefix(base_url.as_str()));
+        match relative {
+            Some(relative) => ObjectWriterMirrorBuilder::relative_path(relative),
+            None => ObjectWriterMirrorBuilder::relative_path(content_location),
+        }
+    }
+}
+
+impl ObjectWriterBuilder for ObjectWriterSegmentBuilder {
+    fn new_object_writer(
+        &self,
+        _endpoint: &UDPEndpoint,
+        _tsi: &u64,
+        toi: &u128,
+        meta: &ObjectMetadata,
+        _now: std::time::SystemTime,
+    ) -> ObjectWriterBuilderResult {
+        let relative_path = match self.relative_path(&meta.content_location) {
+            Some(relative_path) => relative_path,
+            None => {
+                log::error!("Invalid content location {:?}", meta.content_location);
+                return ObjectWriterBuilderResult::Abort;
+            }
+        };
+
+        let destination = self.dest.join(relative_path);
+        let is_media = meta
+            .groups
+            .as_ref()
+            .map(|groups| groups.iter().any(|group| group == SEGMENT_GROUP_MEDIA))
+            .unwrap_or(false);
+
+        ObjectWriterBuilderResult::StoreObject(Box::new(ObjectWriterSegment {
+            mirror: ObjectWriterMirror::new(destination.clone(), toi, self.enable_md5_check),
+            destination,
+            media_segments: match is_media {
+                true => Some(self.media_segments.clone()),
+                false => None,
+            },
+        }))
+    }
+
+    fn update_cache_control(
+        &self,
+        _endpoint: &UDPEndpoint,
+        _tsi: &u64,
+        _toi: &u128,
+        _meta: &ObjectMetadata,
+        _now: std::time::SystemTime,
+    ) {
+    }
+
+    fn fdt_received(
+        &self,
+        _endpoint: &UDPEndpoint,
+        _tsi: &u64,
+        _fdt_xml: &str,
+        _expires: std::time::SystemTime,
+        _meta: &ObjectMetadata,
+        _transfer_duration: Duration,
+        _now: std::time::SystemTime,
+        _ext_time: Option<std::time::SystemTime>,
+    ) {
+    }
+}
+
+impl ObjectWriter for ObjectWriterSegment {
+    fn open(&self, now: SystemTime) -> Result<()> {
+        self.mirror.open(now)
+    }
+
+    fn write(&self, sbn: u32, data: &[u8], now: SystemTime) -> Result<()> {
+        self.mirror.write(sbn, data, now)
+    }
+
+    fn complete(&self, now: SystemTime) {
+        self.mirror.complete(now);
+        // Segments are evicted only once a newer segment is available to the player
+        if let Some(media_segments) = self.media_segments.as_ref() {
+            if self.destination.exists() {
+                media_segments.borrow_mut().add(&self.destination);
+            }
+        }
+    }
+
+    fn error(&self, now: SystemTime) {
+        self.mirror.error(now)
+    }
+
+    fn interrupted(&self, now: SystemTime) {
+        self.mirror.interrupted(now)
+    }
+
+    fn enable_md5_check(&self) -> bool {
+        self.mirror.enable_md5_check()
+    }
+}
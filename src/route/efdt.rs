@@ -0,0 +1,207 @@
+use quick_xml::de::from_reader;
+use serde::{Deserialize, Serialize};
+
+use crate::tools::error::{FluteError, Result};
+
+const ROUTE_XMLNS: &str = "tag:atsc.org,2016:XMLSchemas/ATSC3/Delivery/ROUTE/1.0/";
+
+///
+/// File entry of an EFDT
+///
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+pub struct EfdtFile {
+    /// URI of the object
+    #[serde(rename = "@Content-Location")]
+    pub content_location: String,
+    /// TOI of the object
+    #[serde(rename = "@TOI")]
+    pub toi: String,
+    /// Size of the object
+    #[serde(rename = "@Content-Length", skip_serializing_if = "Option::is_none")]
+    pub content_length: Option<u64>,
+    /// Transfer length of the object
+    #[serde(rename = "@Transfer-Length", skip_serializing_if = "Option::is_none")]
+    pub transfer_length: Option<u64>,
+    /// MIME type of the object
+    #[serde(rename = "@Content-Type", skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+}
+
+///
+/// FDT parameters of an EFDT
+///
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Default)]
+pub struct FdtParameters {
+    /// Files described by the EFDT
+    #[serde(rename = "File", default)]
+    pub file: Vec<EfdtFile>,
+}
+
+///
+/// Extended File Delivery Table (ATSC A/331 A.3.3.2.3)
+///
+/// The EFDT replaces the FLUTE FDT in ROUTE sessions.
+/// It is either embedded inside the S-TSID or delivered in-band with the TOI 0.
+///
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+#[serde(rename = "EFDT")]
+pub struct Efdt {
+    /// XML Namespace
+    #[serde(rename = "@xmlns", skip_serializing_if = "Option::is_none")]
+    pub xmlns: Option<String>,
+    /// Identifier of the EFDT
+    #[serde(rename = "@idRef", skip_serializing_if = "Option::is_none")]
+    pub id_ref: Option<String>,
+    /// Version of the EFDT, incremented each time its content changes
+    #[serde(rename = "@version", default)]
+    pub version: u32,
+    /// Maximum validity, in seconds, of the objects after the first packet is received
+    #[serde(rename = "@maxExpiresDelta", skip_serializing_if = "Option::is_none")]
+    pub max_expires_delta: Option<u32>,
+    /// Maximum transport size of the objects of the session
+    #[serde(rename = "@maxTransportSize", skip_serializing_if = "Option::is_none")]
+    pub max_transport_size: Option<u64>,
+    /// Template used to derive the Content-Location of an object from its TOI (`$TOI$`)
+    #[serde(rename = "FileTemplate", skip_serializing_if = "Option::is_none")]
+    pub file_template: Option<String>,
+    /// Objects explicitly described by the EFDT
+    #[serde(rename = "FDTParameters", skip_serializing_if = "Option::is_none")]
+    pub fdt_parameters: Option<FdtParameters>,
+}
+
+impl Efdt {
+    /// Create a new empty EFDT
+    pub fn new(file_template: Option<String>) -> Self {
+        Self {
+            xmlns: None,
+            id_ref: None,
+            version: 0,
+            max_expires_delta: None,
+            max_transport_size: None,
+            file_template,
+            fdt_parameters: None,
+        }
+    }
+
+    /// Parse an EFDT from its XML representation
+    pub fn parse(buffer: &[u8]) -> Result<Efdt> {
+        from_reader(buffer).map_err(|err| FluteError::new(err.to_string()))
+    }
+
+    /// Convert the EFDT to XML
+    pub fn to_xml(&self) -> Result<Vec<u8>> {
+        let mut efdt = self.clone();
+        if efdt.xmlns.is_none() {
+            efdt.xmlns = Some(ROUTE_XMLNS.to_string());
+        }
+        super::to_xml("EFDT", &efdt)
+    }
+
+    /// Return the file entry of an object
+    pub fn get_file(&self, toi: &u128) -> Option<&EfdtFile> {
+        let toi = toi.to_string();
+        self.fdt_parameters
+            .as_ref()
+            .and_then(|params| params.file.iter().find(|file| file.toi == toi))
+    }
+
+    /// Add or replace the file entry of an object
+    pub fn add_file(&mut self, file: EfdtFile) {
+        let params = self.fdt_parameters.get_or_insert_with(Default::default);
+        params.file.retain(|f| f.toi != file.toi);
+        params.file.push(file);
+    }
+
+    /// Remove the file entry of an object, return `true` if the entry was present
+    pub fn remove_file(&mut self, toi: &u128) -> bool {
+        let toi = toi.to_string();
+        let params = match self.fdt_parameters.as_mut() {
+            Some(params) => params,
+            None => return false,
+        };
+
+        let len = params.file.len();
+        params.file.retain(|f| f.toi != toi);
+        if params.file.is_empty() {
+            self.fdt_parameters = None;
+        }
+        len != self.fdt_parameters.as_ref().map_or(0, |p| p.file.len())
+    }
+
+    /// Resolve the Content-Location of an object, either from its file entry or from the file template
+    pub fn content_location(&self, toi: &u128) -> Option<String> {
+        if let Some(file) = self.get_file(toi) {
+            return Some(file.content_location.clone());
+        }
+
+        self.file_template
+            .as_ref()
+            .and_then(|template| expand_template(template, toi))
+    }
+}
+
+/// Replace the `$TOI$` (or `$TOI%0<width>d$`) identifier of a file template
+fn expand_template(template: &str, toi: &u128) -> Option<String> {
+    let start = template.find("$TOI")?;
+    let end = start + 4 + template[start + 4..].find('$')?;
+    let format = &template[start + 4..end];
+    let value = match format {
+        "" => toi.to_string(),
+        _ => {
+            let width: usize = format
+                .strip_prefix("%0")
+                .and_then(|f| f.strip_suffix('d'))
+                .and_then(|w| w.parse().ok())?;
+            format!("{:0width$}", toi, width = width)
+        }
+    };
+
+    Some(format!(
+        "{}{}{}",
+        &template[..start],
+        value,
+        &template[end + 1..]
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Efdt, EfdtFile};
+
+    #[test]
+    pub fn test_efdt_template() {
+        crate::tests::init();
+        let mut efdt = Efdt::new(Some("video/segment-$TOI%05d$.m4s".to_string()));
+        assert!(efdt.content_location(&12).as_deref() == Some("video/segment-00012.m4s"));
+
+        efdt.file_template = Some("audio/$TOI$.mp4".to_string());
+        assert!(efdt.content_location(&3).as_deref() == Some("audio/3.mp4"));
+
+        efdt.file_template = Some("audio/$TOI%5d$.mp4".to_string());
+        assert!(efdt.content_location(&3).is_none());
+    }
+
+    #[test]
+    pub fn test_efdt_xml() {
+        crate::tests::init();
+        let mut efdt = Efdt::new(Some("segment-$TOI$.m4s".to_string()));
+        efdt.version = 3;
+        efdt.add_file(EfdtFile {
+            content_location: "init.mp4".to_string(),
+            toi: "1".to_string(),
+            content_length: Some(512),
+            transfer_length: Some(512),
+            content_type: Some("video/mp4".to_string()),
+        });
+
+        let xml = efdt.to_xml().unwrap();
+        log::info!("{}", String::from_utf8_lossy(&xml));
+        let parsed = Efdt::parse(&xml).unwrap();
+        assert!(parsed.version == 3);
+        assert!(parsed.content_location(&1).as_deref() == Some("init.mp4"));
+        assert!(parsed.content_location(&2).as_deref() == Some("segment-2.m4s"));
+
+        efdt.remove_file(&1);
+        assert!(efdt.fdt_parameters.is_none());
+    }
+}
@@ -0,0 +1,68 @@
+//!
+//! ROUTE - Real-Time Object Delivery over Unidirectional Transport (ATSC A/331)
+//!
+//! ROUTE reuses the LCT/ALC packet layer of FLUTE. The FDT is replaced by the S-TSID, which
+//! describes the LCT channels of the service, and by the EFDT, which maps the TOI of the objects
+//! (DASH segments, NRT files) to their Content-Location.
+//!
+//! # Example
+//!
+//! ```
+//! use flute::route::{CodePoint, RouteObject, RouteSender, SenderConfig};
+//! use std::time::SystemTime;
+//!
+//! let config = SenderConfig {
+//!     file_template: Some("video/segment-$TOI$.m4s".to_string()),
+//!     ..Default::default()
+//! };
+//! let mut sender = RouteSender::new(1, &config);
+//! let segment = RouteObject::new(vec![0u8; 4000], "video/segment-1.m4s", Some("video/mp4"),
+//!                                CodePoint::MediaSegmentFileMode, Some(SystemTime::now()));
+//! sender.add_object(segment).unwrap();
+//!
+//! while let Some(pkt) = sender.read(SystemTime::now()) {
+//!     // Send pkt over UDP/IP
+//! }
+//! ```
+//!
+
+use serde::Serialize;
+
+use crate::tools::error::{FluteError, Result};
+
+mod efdt;
+mod pkt;
+mod receiver;
+mod sender;
+mod stsid;
+
+pub use efdt::Efdt;
+pub use efdt::EfdtFile;
+pub use efdt::FdtParameters;
+pub use pkt::new_route_pkt;
+pub use pkt::parse_route_pkt;
+pub use pkt::CodePoint;
+pub use pkt::RoutePkt;
+pub use pkt::EXT_ROUTE_PRESENTATION_TIME;
+pub use pkt::EXT_TOL_24;
+pub use pkt::EXT_TOL_48;
+pub use receiver::Config as ReceiverConfig;
+pub use receiver::RouteReceiver;
+pub use sender::Config as SenderConfig;
+pub use sender::RouteObject;
+pub use sender::RouteSender;
+pub use stsid::ContentInfo;
+pub use stsid::Ls;
+pub use stsid::MediaInfo;
+pub use stsid::Payload;
+pub use stsid::Rs;
+pub use stsid::SrcFlow;
+pub use stsid::Stsid;
+
+fn to_xml<T: Serialize>(root: &str, value: &T) -> Result<Vec<u8>> {
+    let xml = quick_xml::se::to_string_with_root(root, value)
+        .map_err(|e| FluteError::new(e.to_string()))?;
+    let mut buffer = br#"<?xml version="1.0" encoding="UTF-8"?>"#.to_vec();
+    buffer.extend(xml.into_bytes());
+    Ok(buffer)
+}
@@ -0,0 +1,176 @@
+use std::time::SystemTime;
+
+use crate::common::lct;
+use crate::tools::{
+    self,
+    error::{FluteError, Result},
+};
+
+/// HET of `EXT_ROUTE_PRESENTATION_TIME`
+pub const EXT_ROUTE_PRESENTATION_TIME: u8 = lct::Ext::RoutePresentationTime as u8;
+/// HET of `EXT_TOL` when the transfer length is coded on 48 bits
+pub const EXT_TOL_48: u8 = lct::Ext::Tol48 as u8;
+/// HET of `EXT_TOL` when the transfer length is coded on 24 bits
+pub const EXT_TOL_24: u8 = lct::Ext::Tol24 as u8;
+
+///
+/// Codepoint of a ROUTE packet (ATSC A/331 Table A.3.6)
+///
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CodePoint {
+    /// Non Real Time object, file mode
+    NrtFileMode = 1,
+    /// Non Real Time object, entity mode
+    NrtEntityMode = 2,
+    /// Non Real Time object, unsigned package mode
+    NrtUnsignedPackageMode = 3,
+    /// Non Real Time object, signed package mode
+    NrtSignedPackageMode = 4,
+    /// New Initialization Segment, timeline changed
+    InitSegmentTimelineChanged = 5,
+    /// New Initialization Segment, timeline continued
+    InitSegmentTimelineContinued = 6,
+    /// Redundant Initialization Segment
+    InitSegmentRedundant = 7,
+    /// Media Segment, file mode
+    MediaSegmentFileMode = 8,
+    /// Media Segment, entity mode
+    MediaSegmentEntityMode = 9,
+}
+
+/// ROUTE packet
+#[derive(Debug)]
+pub struct RoutePkt<'a> {
+    /// LCT Header
+    pub lct: lct::LCTHeader,
+    /// Offset of the payload inside the object
+    pub start_offset: u32,
+    /// Transfer length of the object, when signalled with `EXT_TOL`
+    pub transfer_length: Option<u64>,
+    /// Presentation time of the object, when signalled with `EXT_ROUTE_PRESENTATION_TIME`
+    pub presentation_time: Option<SystemTime>,
+    /// Payload of the packet
+    pub payload: &'a [u8],
+}
+
+fn push_presentation_time(data: &mut Vec<u8>, time: SystemTime) -> Result<()> {
+    /*
+    +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+    |   HET = 66    |    HEL = 3    |           Reserved            |
+    +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+    |                 NTP Presentation Time (64 bits)               |
+    +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+    */
+    let ntp = tools::system_time_to_ntp(time)?;
+    let header: u32 = (EXT_ROUTE_PRESENTATION_TIME as u32) << 24 | 3u32 << 16;
+    data.extend(header.to_be_bytes());
+    data.extend(ntp.to_be_bytes());
+    lct::inc_hdr_len(data, 3);
+    Ok(())
+}
+
+fn parse_presentation_time(ext: &[u8]) -> Result<SystemTime> {
+    if ext.len() != 12 {
+        return Err(FluteError::new("Wrong size of Presentation Time Extension"));
+    }
+    let ntp = u64::from_be_bytes(ext[4..12].try_into().unwrap());
+    tools::ntp_to_system_time(ntp)
+}
+
+/// Create a ROUTE packet (source flow, file mode with a 32 bits start offset)
+#[allow(clippy::too_many_arguments)]
+pub fn new_route_pkt(
+    tsi: u64,
+    toi: &u128,
+    codepoint: u8,
+    start_offset: u32,
+    transfer_length: u64,
+    presentation_time: Option<SystemTime>,
+    close_object: bool,
+    payload: &[u8],
+) -> Result<Vec<u8>> {
+    let mut data = Vec::new();
+    lct::push_lct_header(
+        &mut data,
+        0,
+        &0u128,
+        tsi,
+        toi,
+        codepoint,
+        close_object,
+        false,
+    );
+    lct::push_tol(&mut data, transfer_length);
+    if let Some(time) = presentation_time {
+        push_presentation_time(&mut data, time)?;
+    }
+    data.extend(start_offset.to_be_bytes());
+    data.extend(payload);
+    Ok(data)
+}
+
+/// Parse a buffer to a ROUTE packet
+pub fn parse_route_pkt(data: &[u8]) -> Result<RoutePkt<'_>> {
+    let lct = lct::parse_lct_header(data)?;
+    if data.len() < lct.len + 4 {
+        return Err(FluteError::new("ROUTE packet is too short"));
+    }
+
+    let transfer_length = lct::get_tol(data, &lct)?;
+
+    let presentation_time = match lct::get_ext(data, &lct, EXT_ROUTE_PRESENTATION_TIME)? {
+        Some(ext) => Some(parse_presentation_time(ext)?),
+        None => None,
+    };
+
+    let start_offset = u32::from_be_bytes(data[lct.len..lct.len + 4].try_into().unwrap());
+    let payload = &data[lct.len + 4..];
+    Ok(RoutePkt {
+        lct,
+        start_offset,
+        transfer_length,
+        presentation_time,
+        payload,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    #[test]
+    pub fn test_route_pkt() {
+        crate::tests::init();
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1700000000);
+        let data = super::new_route_pkt(
+            1,
+            &10u128,
+            super::CodePoint::MediaSegmentFileMode as u8,
+            1000,
+            5000,
+            Some(now),
+            false,
+            &[1, 2, 3],
+        )
+        .unwrap();
+
+        let pkt = super::parse_route_pkt(&data).unwrap();
+        assert!(pkt.lct.tsi == 1);
+        assert!(pkt.lct.toi == 10);
+        assert!(pkt.lct.cp == super::CodePoint::MediaSegmentFileMode as u8);
+        assert!(pkt.start_offset == 1000);
+        assert!(pkt.transfer_length == Some(5000));
+        assert!(pkt.presentation_time == Some(now));
+        assert!(pkt.payload == [1, 2, 3]);
+        // EXT_ROUTE_PRESENTATION_TIME is known by the library
+        assert!(crate::common::lct::nb_unknown_ext(&data, &pkt.lct).unwrap() == 0);
+
+        let large_length = 1u64 << 40;
+        let data = super::new_route_pkt(1, &10u128, 8, 0, large_length, None, true, &[]).unwrap();
+        let pkt = super::parse_route_pkt(&data).unwrap();
+        assert!(pkt.transfer_length == Some(large_length));
+        assert!(pkt.presentation_time.is_none());
+        assert!(pkt.lct.close_object);
+    }
+}
@@ -0,0 +1,293 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::time::{Duration, SystemTime};
+
+use super::efdt::Efdt;
+use super::pkt;
+use super::stsid::Stsid;
+use crate::common::lct;
+use crate::common::udpendpoint::UDPEndpoint;
+use crate::receiver::writer::{
+    ObjectCacheControl, ObjectMetadata, ObjectWriterBuilder, ObjectWriterBuilderResult,
+};
+use crate::tools::error::{FluteError, Result};
+
+///
+/// Configuration of the ROUTE Receiver
+///
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// Objects that do not receive any packet during this duration are discarded
+    pub object_timeout: Duration,
+    /// Maximum size of an object, larger objects are discarded
+    pub max_object_size: u64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            object_timeout: Duration::from_secs(10),
+            max_object_size: 256 * 1024 * 1024,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct ObjectReceiver {
+    data: Vec<u8>,
+    ranges: Vec<(u64, u64)>,
+    transfer_length: Option<u64>,
+    presentation_time: Option<SystemTime>,
+    last_activity: SystemTime,
+}
+
+impl ObjectReceiver {
+    fn new(now: SystemTime) -> Self {
+        Self {
+            data: Vec::new(),
+            ranges: Vec::new(),
+            transfer_length: None,
+            presentation_time: None,
+            last_activity: now,
+        }
+    }
+
+    fn push(&mut self, start: u64, payload: &[u8]) {
+        let end = start + payload.len() as u64;
+        if self.data.len() < end as usize {
+            self.data.resize(end as usize, 0);
+        }
+        self.data[start as usize..end as usize].copy_from_slice(payload);
+
+        self.ranges.push((start, end));
+        self.ranges.sort_unstable();
+        let mut merged: Vec<(u64, u64)> = Vec::with_capacity(self.ranges.len());
+        for &(start, end) in &self.ranges {
+            match merged.last_mut() {
+                Some(last) if start <= last.1 => last.1 = last.1.max(end),
+                _ => merged.push((start, end)),
+            }
+        }
+        self.ranges = merged;
+    }
+
+    fn is_complete(&self) -> bool {
+        match self.transfer_length {
+            Some(0) => true,
+            Some(length) => self.ranges.first() == Some(&(0, length)),
+            None => false,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Session {
+    efdt: Option<Efdt>,
+    objects: HashMap<u128, ObjectReceiver>,
+}
+
+///
+/// ROUTE Receiver
+///
+/// Reassemble the objects delivered on the LCT channels of ROUTE sessions.
+/// The Content-Location of an object is resolved with the EFDT of its channel, received either
+/// out-of-band inside the S-TSID or in-band with the TOI 0.
+/// Complete objects are delivered to an `ObjectWriterBuilder`.
+///
+#[derive(Debug)]
+pub struct RouteReceiver {
+    writer: Rc<dyn ObjectWriterBuilder>,
+    stsid: Option<Stsid>,
+    config: Config,
+    sessions: HashMap<(UDPEndpoint, u64), Session>,
+}
+
+impl RouteReceiver {
+    /// Create a new ROUTE Receiver
+    ///
+    /// When an S-TSID is provided, only the LCT channels it describes are received
+    pub fn new(writer: Rc<dyn ObjectWriterBuilder>, stsid: Option<Stsid>, config: &Config) -> Self {
+        Self {
+            writer,
+            stsid,
+            config: config.clone(),
+            sessions: HashMap::new(),
+        }
+    }
+
+    /// Replace the S-TSID
+    pub fn set_stsid(&mut self, stsid: Option<Stsid>) {
+        self.stsid = stsid;
+        for ((endpoint, tsi), session) in self.sessions.iter_mut() {
+            if let Some(efdt) = Self::stsid_efdt(self.stsid.as_ref(), endpoint, *tsi) {
+                session.efdt = Some(efdt);
+            }
+        }
+    }
+
+    /// EFDT currently used to resolve the objects of a channel
+    pub fn efdt(&self, endpoint: &UDPEndpoint, tsi: u64) -> Option<&Efdt> {
+        self.sessions
+            .get(&(endpoint.clone(), tsi))
+            .and_then(|session| session.efdt.as_ref())
+    }
+
+    fn stsid_efdt(stsid: Option<&Stsid>, endpoint: &UDPEndpoint, tsi: u64) -> Option<Efdt> {
+        stsid?
+            .find_ls(endpoint, tsi)?
+            .src_flow
+            .as_ref()?
+            .efdt
+            .clone()
+    }
+
+    /// Push a ROUTE packet to the receiver
+    pub fn push(&mut self, endpoint: &UDPEndpoint, data: &[u8], now: SystemTime) -> Result<()> {
+        let pkt = pkt::parse_route_pkt(data)?;
+        let tsi = pkt.lct.tsi;
+        let toi = pkt.lct.toi;
+
+        if let Some(stsid) = self.stsid.as_ref() {
+            if stsid.find_ls(endpoint, tsi).is_none() {
+                log::debug!("Ignore packet of tsi={} not described by the S-TSID", tsi);
+                return Ok(());
+            }
+        }
+
+        let end = pkt.start_offset as u64 + pkt.payload.len() as u64;
+        let transfer_length = pkt.transfer_length.or(match pkt.lct.close_object {
+            true => Some(end),
+            false => None,
+        });
+
+        if end.max(transfer_length.unwrap_or_default()) > self.config.max_object_size {
+            return Err(FluteError::new(format!(
+                "Object tsi={} toi={} is larger than the maximum object size",
+                tsi, toi
+            )));
+        }
+
+        if transfer_length.map_or(false, |length| end > length) {
+            return Err(FluteError::new(format!(
+                "Packet of toi={} ends at offset {} after the transfer length",
+                toi, end
+            )));
+        }
+
+        let stsid = self.stsid.as_ref();
+        let session = self
+            .sessions
+            .entry((endpoint.clone(), tsi))
+            .or_insert_with(|| Session {
+                efdt: Self::stsid_efdt(stsid, endpoint, tsi),
+                objects: HashMap::new(),
+            });
+
+        let obj = session
+            .objects
+            .entry(toi)
+            .or_insert_with(|| ObjectReceiver::new(now));
+        obj.last_activity = now;
+        if obj.transfer_length.is_none() {
+            obj.transfer_length = transfer_length;
+        }
+        if pkt.presentation_time.is_some() {
+            obj.presentation_time = pkt.presentation_time;
+        }
+        obj.push(pkt.start_offset as u64, pkt.payload);
+
+        if !obj.is_complete() {
+            return Ok(());
+        }
+
+        if toi == lct::TOI_FDT {
+            let obj = session.objects.remove(&toi).unwrap();
+            let efdt = Efdt::parse(&obj.data)?;
+            log::debug!("Received EFDT version {} tsi={}", efdt.version, tsi);
+            session.efdt = Some(efdt);
+        }
+
+        self.deliver(endpoint, tsi, now);
+        Ok(())
+    }
+
+    /// Discard the objects that did not receive any packet during `object_timeout`
+    pub fn cleanup(&mut self, now: SystemTime) {
+        let timeout = self.config.object_timeout;
+        for ((_, tsi), session) in self.sessions.iter_mut() {
+            session.objects.retain(|toi, obj| {
+                let expired = now
+                    .duration_since(obj.last_activity)
+                    .map_or(false, |d| d > timeout);
+                if expired {
+                    log::warn!("Discard incomplete object tsi={} toi={}", tsi, toi);
+                }
+                !expired
+            });
+        }
+        self.sessions
+            .retain(|_, session| !session.objects.is_empty() || session.efdt.is_some());
+    }
+
+    /// Deliver complete objects whose Content-Location is known
+    fn deliver(&mut self, endpoint: &UDPEndpoint, tsi: u64, now: SystemTime) {
+        let session = match self.sessions.get_mut(&(endpoint.clone(), tsi)) {
+            Some(session) => session,
+            None => return,
+        };
+
+        let efdt = match session.efdt.as_ref() {
+            Some(efdt) => efdt,
+            None => return,
+        };
+
+        let ready: Vec<u128> = session
+            .objects
+            .iter()
+            .filter(|(toi, obj)| {
+                **toi != lct::TOI_FDT && obj.is_complete() && efdt.content_location(toi).is_some()
+            })
+            .map(|(toi, _)| *toi)
+            .collect();
+
+        for toi in ready {
+            let obj = session.objects.remove(&toi).unwrap();
+            let file = efdt.get_file(&toi);
+            let meta = ObjectMetadata {
+                content_location: efdt.content_location(&toi).unwrap(),
+                content_length: Some(obj.data.len()),
+                transfer_length: Some(obj.data.len()),
+                content_type: file.and_then(|f| f.content_type.clone()),
+                cache_control: ObjectCacheControl::NoCache,
+                groups: None,
+                md5: None,
+                optel_propagator: None,
+                oti: None,
+                cenc: None,
+                e_tag: None,
+                part: None,
+                presentation_time: obj.presentation_time,
+                lct_extensions: None,
+            };
+
+            let writer = match self
+                .writer
+                .new_object_writer(endpoint, &tsi, &toi, &meta, now)
+            {
+                ObjectWriterBuilderResult::StoreObject(writer) => writer,
+                _ => continue,
+            };
+
+            let result = writer
+                .open(now)
+                .and_then(|_| writer.write(0, &obj.data, now));
+            match result {
+                Ok(_) => writer.complete(now),
+                Err(e) => {
+                    log::error!("Fail to write object toi={} {:?}", toi, e);
+                    writer.error(now)
+                }
+            }
+        }
+    }
+}
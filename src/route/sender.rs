@@ -0,0 +1,215 @@
+use std::collections::VecDeque;
+use std::time::SystemTime;
+
+use super::efdt::{Efdt, EfdtFile};
+use super::pkt::{self, CodePoint};
+use crate::common::lct;
+use crate::tools::error::{FluteError, Result};
+
+///
+/// Configuration of the ROUTE Sender
+///
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// Maximum number of bytes of an object carried by a packet
+    pub max_payload_size: usize,
+    /// Deliver the EFDT in-band with the TOI 0 each time it is updated.
+    /// When disabled, the EFDT must be signalled out-of-band inside the S-TSID.
+    pub inband_efdt: bool,
+    /// Template used by the receivers to derive the Content-Location of the objects from their TOI.
+    /// Objects that do not match the template are described by an entry of the EFDT.
+    pub file_template: Option<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            max_payload_size: 1400,
+            inband_efdt: true,
+            file_template: None,
+        }
+    }
+}
+
+///
+/// Object (DASH segment, NRT file...) transferred over ROUTE
+///
+#[derive(Debug, Clone)]
+pub struct RouteObject {
+    /// Content of the object
+    pub content: Vec<u8>,
+    /// URI of the object
+    pub content_location: String,
+    /// MIME type of the object
+    pub content_type: Option<String>,
+    /// Codepoint of the packets
+    pub codepoint: CodePoint,
+    /// Presentation time, transferred with `EXT_ROUTE_PRESENTATION_TIME`
+    pub presentation_time: Option<SystemTime>,
+}
+
+impl RouteObject {
+    /// Create a new object
+    pub fn new(
+        content: Vec<u8>,
+        content_location: &str,
+        content_type: Option<&str>,
+        codepoint: CodePoint,
+        presentation_time: Option<SystemTime>,
+    ) -> Self {
+        Self {
+            content,
+            content_location: content_location.to_string(),
+            content_type: content_type.map(|s| s.to_string()),
+            codepoint,
+            presentation_time,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Transfer {
+    toi: u128,
+    content: Vec<u8>,
+    codepoint: u8,
+    presentation_time: Option<SystemTime>,
+    offset: usize,
+}
+
+///
+/// ROUTE Sender
+///
+/// Transfer objects on an LCT channel of a ROUTE session.
+/// Objects are cut in byte ranges, each packet carries the offset of its range inside the object.
+///
+#[derive(Debug)]
+pub struct RouteSender {
+    tsi: u64,
+    config: Config,
+    efdt: Efdt,
+    efdt_updated: bool,
+    next_toi: u128,
+    transfers: VecDeque<Transfer>,
+}
+
+impl RouteSender {
+    /// Create a new ROUTE Sender for the LCT channel `tsi`
+    pub fn new(tsi: u64, config: &Config) -> Self {
+        Self {
+            tsi,
+            config: config.clone(),
+            efdt: Efdt::new(config.file_template.clone()),
+            efdt_updated: false,
+            next_toi: 1,
+            transfers: VecDeque::new(),
+        }
+    }
+
+    /// EFDT describing the objects of the channel, to be inserted inside the S-TSID
+    pub fn efdt(&self) -> &Efdt {
+        &self.efdt
+    }
+
+    /// Number of objects waiting to be transferred
+    pub fn nb_objects(&self) -> usize {
+        self.transfers
+            .iter()
+            .filter(|t| t.toi != lct::TOI_FDT)
+            .count()
+    }
+
+    /// Add an object to the transfer queue, return its TOI
+    pub fn add_object(&mut self, obj: RouteObject) -> Result<u128> {
+        if obj.content.len() > u32::MAX as usize {
+            return Err(FluteError::new(
+                "ROUTE object is larger than the 32 bits start offset",
+            ));
+        }
+
+        let toi = self.next_toi;
+        self.next_toi += 1;
+
+        if self.efdt.content_location(&toi).as_ref() != Some(&obj.content_location) {
+            self.efdt.add_file(EfdtFile {
+                content_location: obj.content_location.clone(),
+                toi: toi.to_string(),
+                content_length: Some(obj.content.len() as u64),
+                transfer_length: Some(obj.content.len() as u64),
+                content_type: obj.content_type.clone(),
+            });
+            self.efdt.version += 1;
+            self.efdt_updated = true;
+        }
+
+        self.transfers.push_back(Transfer {
+            toi,
+            content: obj.content,
+            codepoint: obj.codepoint as u8,
+            presentation_time: obj.presentation_time,
+            offset: 0,
+        });
+        Ok(toi)
+    }
+
+    /// Read the next packet to send, return `None` when there is no more object to transfer
+    pub fn read(&mut self, _now: SystemTime) -> Option<Vec<u8>> {
+        if self.efdt_updated && self.config.inband_efdt {
+            self.efdt_updated = false;
+            match self.efdt.to_xml() {
+                Ok(content) => self.transfers.push_front(Transfer {
+                    toi: lct::TOI_FDT,
+                    content,
+                    codepoint: CodePoint::NrtFileMode as u8,
+                    presentation_time: None,
+                    offset: 0,
+                }),
+                Err(e) => log::error!("Fail to generate EFDT {:?}", e),
+            }
+        }
+
+        loop {
+            let transfer = self.transfers.front_mut()?;
+            let end = (transfer.offset + self.config.max_payload_size).min(transfer.content.len());
+            let close_object = end == transfer.content.len();
+            let data = pkt::new_route_pkt(
+                self.tsi,
+                &transfer.toi,
+                transfer.codepoint,
+                transfer.offset as u32,
+                transfer.content.len() as u64,
+                transfer.presentation_time,
+                close_object,
+                &transfer.content[transfer.offset..end],
+            );
+            transfer.offset = end;
+
+            let data = match data {
+                Ok(data) => data,
+                Err(e) => {
+                    // Skip the transfer, the next ones are not blocked
+                    log::error!(
+                        "Fail to create ROUTE packet of TOI {} {:?}",
+                        transfer.toi,
+                        e
+                    );
+                    self.end_transfer();
+                    continue;
+                }
+            };
+
+            if close_object {
+                self.end_transfer();
+            }
+            return Some(data);
+        }
+    }
+
+    fn end_transfer(&mut self) {
+        if let Some(transfer) = self.transfers.pop_front() {
+            if transfer.toi != lct::TOI_FDT {
+                // The receivers have been notified of the object in a previous EFDT
+                self.efdt.remove_file(&transfer.toi);
+            }
+        }
+    }
+}
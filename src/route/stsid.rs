@@ -0,0 +1,244 @@
+use quick_xml::de::from_reader;
+use serde::{Deserialize, Serialize};
+
+use super::efdt::Efdt;
+use crate::common::udpendpoint::UDPEndpoint;
+use crate::tools::error::{FluteError, Result};
+
+const STSID_XMLNS: &str = "tag:atsc.org,2016:XMLSchemas/ATSC3/Delivery/S-TSID/1.0/";
+
+///
+/// Payload format of a source flow
+///
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+pub struct Payload {
+    /// Codepoint of the LCT packets carrying this format
+    #[serde(rename = "@codePoint", skip_serializing_if = "Option::is_none")]
+    pub code_point: Option<u8>,
+    /// Payload format (1: file mode, 2: entity mode, 3: unsigned package, 4: signed package)
+    #[serde(rename = "@formatId")]
+    pub format_id: u8,
+    /// Fragmentation (0: arbitrary, 1: application specific sample, 2: application specific collection)
+    #[serde(rename = "@frag", skip_serializing_if = "Option::is_none")]
+    pub frag: Option<u8>,
+    /// True when the packets are delivered in order
+    #[serde(rename = "@order", skip_serializing_if = "Option::is_none")]
+    pub order: Option<bool>,
+    /// Format of the source FEC Payload ID (0: none, 1: 32 bits start offset, 2: FEC scheme)
+    #[serde(rename = "@srcFecPayloadId", skip_serializing_if = "Option::is_none")]
+    pub src_fec_payload_id: Option<u8>,
+}
+
+///
+/// Media information of a source flow
+///
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+pub struct MediaInfo {
+    /// Identifier of the DASH Representation carried by the source flow
+    #[serde(rename = "@repId")]
+    pub rep_id: String,
+    /// Type of the media (audio, video...)
+    #[serde(rename = "@contentType", skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+}
+
+///
+/// Content information of a source flow
+///
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+pub struct ContentInfo {
+    /// Media carried by the source flow
+    #[serde(rename = "MediaInfo", skip_serializing_if = "Option::is_none")]
+    pub media_info: Option<MediaInfo>,
+}
+
+///
+/// Source flow of an LCT channel
+///
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+pub struct SrcFlow {
+    /// True when the source flow carries real-time media
+    #[serde(rename = "@rt", skip_serializing_if = "Option::is_none")]
+    pub rt: Option<bool>,
+    /// Minimum buffer size, in kilobytes, required by the receiver
+    #[serde(rename = "@minBuffSize", skip_serializing_if = "Option::is_none")]
+    pub min_buff_size: Option<u32>,
+    /// EFDT delivered out-of-band
+    #[serde(rename = "EFDT", skip_serializing_if = "Option::is_none")]
+    pub efdt: Option<Efdt>,
+    /// Content information
+    #[serde(rename = "ContentInfo", skip_serializing_if = "Option::is_none")]
+    pub content_info: Option<ContentInfo>,
+    /// Payload formats
+    #[serde(rename = "Payload", default)]
+    pub payload: Vec<Payload>,
+}
+
+///
+/// LCT channel, identified by its TSI
+///
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+pub struct Ls {
+    /// Transport Session Identifier
+    #[serde(rename = "@tsi")]
+    pub tsi: u64,
+    /// Maximum bitrate of the channel, in kbps
+    #[serde(rename = "@bw", skip_serializing_if = "Option::is_none")]
+    pub bw: Option<u32>,
+    /// Start time of the channel
+    #[serde(rename = "@startTime", skip_serializing_if = "Option::is_none")]
+    pub start_time: Option<String>,
+    /// End time of the channel
+    #[serde(rename = "@endTime", skip_serializing_if = "Option::is_none")]
+    pub end_time: Option<String>,
+    /// Source flow
+    #[serde(rename = "SrcFlow", skip_serializing_if = "Option::is_none")]
+    pub src_flow: Option<SrcFlow>,
+}
+
+///
+/// ROUTE session, identified by its IP/UDP destination
+///
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+pub struct Rs {
+    /// Source IP address
+    #[serde(rename = "@sIpAddr", skip_serializing_if = "Option::is_none")]
+    pub s_ip_addr: Option<String>,
+    /// Destination IP address
+    #[serde(rename = "@dIpAddr", skip_serializing_if = "Option::is_none")]
+    pub d_ip_addr: Option<String>,
+    /// Destination port
+    #[serde(rename = "@dPort", skip_serializing_if = "Option::is_none")]
+    pub d_port: Option<u16>,
+    /// LCT channels of the session
+    #[serde(rename = "LS", default)]
+    pub ls: Vec<Ls>,
+}
+
+///
+/// Service-based Transport Session Instance Description (ATSC A/331 7.1.4)
+///
+/// Describes the ROUTE sessions and the LCT channels carrying the components of a service.
+///
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+#[serde(rename = "S-TSID")]
+pub struct Stsid {
+    /// XML Namespace
+    #[serde(rename = "@xmlns", skip_serializing_if = "Option::is_none")]
+    pub xmlns: Option<String>,
+    /// ROUTE sessions
+    #[serde(rename = "RS", default)]
+    pub rs: Vec<Rs>,
+}
+
+impl Rs {
+    /// Return `true` if the ROUTE session is delivered on this endpoint
+    ///
+    /// Attributes that are not present in the session match any endpoint
+    pub fn match_endpoint(&self, endpoint: &UDPEndpoint) -> bool {
+        if let Some(d_ip_addr) = self.d_ip_addr.as_ref() {
+            if d_ip_addr != &endpoint.destination_group_address {
+                return false;
+            }
+        }
+
+        if let Some(d_port) = self.d_port {
+            if d_port != endpoint.port {
+                return false;
+            }
+        }
+
+        if let (Some(s_ip_addr), Some(source_address)) =
+            (self.s_ip_addr.as_ref(), endpoint.source_address.as_ref())
+        {
+            if s_ip_addr != source_address {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+impl Stsid {
+    /// Create a new S-TSID
+    pub fn new(rs: Vec<Rs>) -> Self {
+        Self { xmlns: None, rs }
+    }
+
+    /// Parse an S-TSID from its XML representation
+    pub fn parse(buffer: &[u8]) -> Result<Stsid> {
+        from_reader(buffer).map_err(|err| FluteError::new(err.to_string()))
+    }
+
+    /// Convert the S-TSID to XML
+    pub fn to_xml(&self) -> Result<Vec<u8>> {
+        let mut stsid = self.clone();
+        if stsid.xmlns.is_none() {
+            stsid.xmlns = Some(STSID_XMLNS.to_string());
+        }
+        super::to_xml("S-TSID", &stsid)
+    }
+
+    /// Return the LCT channel delivered on this endpoint and TSI
+    pub fn find_ls(&self, endpoint: &UDPEndpoint, tsi: u64) -> Option<&Ls> {
+        self.rs
+            .iter()
+            .filter(|rs| rs.match_endpoint(endpoint))
+            .flat_map(|rs| rs.ls.iter())
+            .find(|ls| ls.tsi == tsi)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::udpendpoint::UDPEndpoint;
+
+    #[test]
+    pub fn test_stsid_parse() {
+        crate::tests::init();
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<S-TSID xmlns="tag:atsc.org,2016:XMLSchemas/ATSC3/Delivery/S-TSID/1.0/">
+  <RS dIpAddr="239.255.1.1" dPort="5000">
+    <LS tsi="10" bw="4000">
+      <SrcFlow rt="true">
+        <EFDT version="1">
+          <FileTemplate>video-$TOI$.m4s</FileTemplate>
+          <FDTParameters>
+            <File Content-Location="video-init.mp4" TOI="4294967295" Content-Type="video/mp4"/>
+          </FDTParameters>
+        </EFDT>
+        <ContentInfo>
+          <MediaInfo repId="video" contentType="video"/>
+        </ContentInfo>
+        <Payload codePoint="8" formatId="1" frag="0" order="true"/>
+      </SrcFlow>
+    </LS>
+    <LS tsi="20">
+      <SrcFlow rt="true">
+        <Payload codePoint="8" formatId="1"/>
+      </SrcFlow>
+    </LS>
+  </RS>
+</S-TSID>"#;
+
+        let stsid = super::Stsid::parse(xml.as_bytes()).unwrap();
+        assert!(stsid.rs.len() == 1);
+        assert!(stsid.rs[0].ls.len() == 2);
+
+        let endpoint = UDPEndpoint::new(None, "239.255.1.1".to_owned(), 5000);
+        let ls = stsid.find_ls(&endpoint, 10).unwrap();
+        let src_flow = ls.src_flow.as_ref().unwrap();
+        assert!(src_flow.payload[0].code_point == Some(8));
+        let efdt = src_flow.efdt.as_ref().unwrap();
+        assert!(efdt.content_location(&4294967295).as_deref() == Some("video-init.mp4"));
+        assert!(efdt.content_location(&5).as_deref() == Some("video-5.m4s"));
+
+        let other_endpoint = UDPEndpoint::new(None, "239.255.1.2".to_owned(), 5000);
+        assert!(stsid.find_ls(&other_endpoint, 10).is_none());
+
+        let xml = stsid.to_xml().unwrap();
+        let parsed = super::Stsid::parse(&xml).unwrap();
+        assert!(parsed.rs == stsid.rs);
+    }
+}
@@ -24,6 +24,7 @@ impl Block {
         buffer: &[u8],
         block_length: u64,
         oti: &Oti,
+        repair_esi_offset: u32,
     ) -> Result<Box<Block>> {
         let nb_source_symbols: usize =
             num_integer::div_ceil(buffer.len(), oti.encoding_symbol_length as usize);
@@ -49,12 +50,20 @@ impl Block {
                 )?
             }
             oti::FECEncodingID::ReedSolomonGF2M => return Err(FluteError::new("Not implemented")),
-            oti::FECEncodingID::RaptorQ => {
-                Block::create_shards_raptorq(oti, nb_source_symbols, block_length as usize, buffer)?
-            }
-            oti::FECEncodingID::Raptor => {
-                Block::create_shards_raptor(oti, nb_source_symbols, block_length as usize, buffer)?
-            }
+            oti::FECEncodingID::RaptorQ => Block::create_shards_raptorq(
+                oti,
+                nb_source_symbols,
+                block_length as usize,
+                buffer,
+                repair_esi_offset,
+            )?,
+            oti::FECEncodingID::Raptor => Block::create_shards_raptor(
+                oti,
+                nb_source_symbols,
+                block_length as usize,
+                buffer,
+                repair_esi_offset,
+            )?,
         };
 
         Ok(Box::new(Block {
@@ -65,6 +74,41 @@ impl Block {
         }))
     }
 
+    pub fn new_from_shards(
+        sbn: u32,
+        shards: Vec<Box<dyn FecShard>>,
+        nb_source_symbols: usize,
+    ) -> Box<Block> {
+        Box::new(Block {
+            sbn,
+            read_index: 0,
+            shards,
+            nb_source_symbols,
+        })
+    }
+
+    pub fn shards(&self) -> &[Box<dyn FecShard>] {
+        &self.shards
+    }
+
+    /// Keep only the repair symbols matching the predicate.
+    /// The predicate is called with the index of the repair symbol (ESI - number of source symbols)
+    pub fn retain_repair_symbols<F>(&mut self, f: F)
+    where
+        F: Fn(u32) -> bool,
+    {
+        debug_assert!(self.read_index == 0);
+        let nb_source_symbols = self.nb_source_symbols as u32;
+        self.shards.retain(|shard| {
+            let esi = shard.esi();
+            esi < nb_source_symbols || f(esi - nb_source_symbols)
+        });
+    }
+
+    pub fn sbn(&self) -> u32 {
+        self.sbn
+    }
+
     pub fn is_empty(&self) -> bool {
         self.read_index as usize == self.shards.len()
     }
@@ -118,6 +162,7 @@ impl Block {
         nb_source_symbols: usize,
         block_length: usize,
         buffer: &[u8],
+        repair_esi_offset: u32,
     ) -> Result<Vec<Box<dyn FecShard>>> {
         debug_assert!(nb_source_symbols <= oti.maximum_source_block_length as usize);
         debug_assert!(nb_source_symbols <= block_length);
@@ -129,6 +174,7 @@ impl Block {
                 oti.max_number_of_parity_symbols as usize,
                 oti.encoding_symbol_length as usize,
                 scheme,
+                repair_esi_offset,
             );
 
             let shards = encoder.encode(buffer)?;
@@ -143,6 +189,7 @@ impl Block {
         nb_source_symbols: usize,
         block_length: usize,
         buffer: &[u8],
+        repair_esi_offset: u32,
     ) -> Result<Vec<Box<dyn FecShard>>> {
         debug_assert!(nb_source_symbols <= oti.maximum_source_block_length as usize);
         debug_assert!(nb_source_symbols <= block_length);
@@ -151,6 +198,7 @@ impl Block {
         let encoder = fec::raptor::RaptorEncoder::new(
             nb_source_symbols,
             oti.max_number_of_parity_symbols as usize,
+            repair_esi_offset,
         );
         let shards = encoder.encode(buffer)?;
         Ok(shards)
@@ -0,0 +1,310 @@
+use super::block::Block;
+use super::objectdesc::FecCache;
+use crate::common::oti::{FECEncodingID, Oti};
+use crate::common::partition;
+use crate::fec::{DataFecShard, FecShard};
+use crate::tools::error::{FluteError, Result};
+use std::collections::BTreeMap;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::sync::Mutex;
+
+/// Max number of transfers with different repair symbols stored in the cache (fountain codes only)
+const MAX_REPAIR_ROUNDS: u32 = 4;
+
+#[derive(Debug)]
+enum SymbolStorage {
+    Memory(Vec<u8>),
+    File {
+        path: std::path::PathBuf,
+        file: std::fs::File,
+        size: u64,
+    },
+}
+
+impl SymbolStorage {
+    fn write(&mut self, data: &[u8]) -> Result<u64> {
+        match self {
+            SymbolStorage::Memory(buffer) => {
+                let offset = buffer.len() as u64;
+                buffer.extend_from_slice(data);
+                Ok(offset)
+            }
+            SymbolStorage::File { file, size, .. } => {
+                let offset = *size;
+                file.seek(SeekFrom::Start(offset))?;
+                file.write_all(data)?;
+                *size += data.len() as u64;
+                Ok(offset)
+            }
+        }
+    }
+
+    fn read(&mut self, offset: u64, length: usize) -> Result<Vec<u8>> {
+        match self {
+            SymbolStorage::Memory(buffer) => {
+                let start = offset as usize;
+                buffer
+                    .get(start..start + length)
+                    .map(|data| data.to_vec())
+                    .ok_or_else(|| FluteError::new("Symbol not found in cache"))
+            }
+            SymbolStorage::File { file, .. } => {
+                let mut data = vec![0u8; length];
+                file.seek(SeekFrom::Start(offset))?;
+                file.read_exact(&mut data)?;
+                Ok(data)
+            }
+        }
+    }
+}
+
+impl Drop for SymbolStorage {
+    fn drop(&mut self) {
+        if let SymbolStorage::File { path, .. } = self {
+            std::fs::remove_file(path).ok();
+        }
+    }
+}
+
+#[derive(Debug)]
+struct CachedSymbol {
+    esi: u32,
+    offset: u64,
+    length: usize,
+}
+
+#[derive(Debug)]
+struct CachedBlock {
+    nb_source_symbols: usize,
+    symbols: Vec<CachedSymbol>,
+}
+
+#[derive(Debug)]
+struct BlockCacheInner {
+    storage: SymbolStorage,
+    blocks: BTreeMap<u32, CachedBlock>,
+}
+
+///
+/// Encoding symbols of an object, kept between two transfers
+///
+#[derive(Debug)]
+pub struct BlockCache {
+    toi: u128,
+    oti: Oti,
+    encoding_oti: Oti,
+    nb_blocks: u64,
+    nb_repair_rounds: u32,
+    inner: Mutex<BlockCacheInner>,
+}
+
+impl BlockCache {
+    pub fn new(
+        toi: u128,
+        oti: &Oti,
+        transfer_length: u64,
+        max_nb_transfers: Option<u32>,
+        mode: &FecCache,
+    ) -> Result<BlockCache> {
+        let storage = match mode {
+            FecCache::Memory => SymbolStorage::Memory(Vec::new()),
+            FecCache::File(dir) => {
+                if !dir.is_dir() {
+                    return Err(FluteError::new(format!("{:?} is not a directory", dir)));
+                }
+
+                let (path, file) = crate::tools::create_temp_file(dir, "flute_fec_cache")?;
+                SymbolStorage::File {
+                    path,
+                    file,
+                    size: 0,
+                }
+            }
+        };
+
+        let nb_repair_rounds = match oti.fec_encoding_id {
+            FECEncodingID::RaptorQ | FECEncodingID::Raptor => max_nb_transfers
+                .unwrap_or(MAX_REPAIR_ROUNDS)
+                .clamp(1, MAX_REPAIR_ROUNDS),
+            _ => 1,
+        };
+
+        let mut encoding_oti = oti.clone();
+        encoding_oti.max_number_of_parity_symbols *= nb_repair_rounds;
+
+        let (_, _, _, nb_blocks) = partition::block_partitioning(
+            oti.maximum_source_block_length as u64,
+            transfer_length,
+            oti.encoding_symbol_length as u64,
+        );
+
+        Ok(BlockCache {
+            toi,
+            oti: oti.clone(),
+            encoding_oti,
+            nb_blocks,
+            nb_repair_rounds,
+            inner: Mutex::new(BlockCacheInner {
+                storage,
+                blocks: BTreeMap::new(),
+            }),
+        })
+    }
+
+    /// OTI used to encode the blocks stored in this cache.
+    /// For fountain codes, repair symbols of multiple transfers are generated at once.
+    pub fn encoding_oti(&self) -> &Oti {
+        &self.encoding_oti
+    }
+
+    /// Return true if all the blocks of the object are inside the cache
+    pub fn is_complete(&self) -> bool {
+        let inner = self.inner.lock().unwrap();
+        inner.blocks.len() as u64 == self.nb_blocks
+    }
+
+    /// Return true if the repair symbol must be transmitted during the transfer `round`
+    pub fn is_repair_symbol_in_round(&self, repair_index: u32, round: u64) -> bool {
+        let nb_repair_symbols = self.oti.max_number_of_parity_symbols;
+        let first = (round % self.nb_repair_rounds as u64) as u32 * nb_repair_symbols;
+        repair_index >= first && repair_index < first + nb_repair_symbols
+    }
+
+    /// Store the encoding symbols of a block
+    pub fn store(&self, block: &Block) -> Result<()> {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.blocks.contains_key(&block.sbn()) {
+            return Ok(());
+        }
+
+        let mut symbols = Vec::new();
+        for shard in block.shards() {
+            let offset = inner.storage.write(shard.data())?;
+            symbols.push(CachedSymbol {
+                esi: shard.esi(),
+                offset,
+                length: shard.data().len(),
+            });
+        }
+
+        log::debug!(
+            "TOI={} store block {} with {} symbols in cache",
+            self.toi,
+            block.sbn(),
+            symbols.len()
+        );
+
+        inner.blocks.insert(
+            block.sbn(),
+            CachedBlock {
+                nb_source_symbols: block.nb_source_symbols,
+                symbols,
+            },
+        );
+        Ok(())
+    }
+
+    /// Load the block `sbn` with the repair symbols of the transfer `round`
+    pub fn load(&self, sbn: u32, round: u64) -> Result<Box<Block>> {
+        let mut inner = self.inner.lock().unwrap();
+        let inner = &mut *inner;
+        let block = inner
+            .blocks
+            .get(&sbn)
+            .ok_or_else(|| FluteError::new(format!("Block {} not found in cache", sbn)))?;
+
+        let mut shards: Vec<Box<dyn FecShard>> = Vec::new();
+        for symbol in &block.symbols {
+            let is_source_symbol = (symbol.esi as usize) < block.nb_source_symbols;
+            if !is_source_symbol
+                && !self
+                    .is_repair_symbol_in_round(symbol.esi - block.nb_source_symbols as u32, round)
+            {
+                continue;
+            }
+
+            let data = inner.storage.read(symbol.offset, symbol.length)?;
+            shards.push(Box::new(DataFecShard::new(&data, symbol.esi)));
+        }
+
+        Ok(Block::new_from_shards(sbn, shards, block.nb_source_symbols))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BlockCache;
+    use crate::sender::block::Block;
+    use crate::sender::FecCache;
+
+    fn test_cache(mode: &FecCache) {
+        let oti = crate::core::Oti::new_raptorq(1024, 10, 4, 1, 4).unwrap();
+        let buffer: Vec<u8> = (0..10 * 1024).map(|i| (i % 255) as u8).collect();
+        let cache = BlockCache::new(1, &oti, buffer.len() as u64, None, mode).unwrap();
+        assert!(!cache.is_complete());
+
+        let block = Block::new_from_buffer(0, &buffer, 10, cache.encoding_oti(), 0).unwrap();
+        cache.store(&block).unwrap();
+        assert!(cache.is_complete());
+
+        let mut round_0 = cache.load(0, 0).unwrap();
+        let mut round_1 = cache.load(0, 1).unwrap();
+
+        let mut esi_0 = Vec::new();
+        while let Some((symbol, _)) = round_0.read() {
+            esi_0.push(symbol.esi);
+        }
+
+        let mut esi_1 = Vec::new();
+        while let Some((symbol, _)) = round_1.read() {
+            if symbol.is_source_symbol {
+                assert!(symbol.symbols == &buffer[symbol.esi as usize * 1024..][..1024]);
+            }
+            esi_1.push(symbol.esi);
+        }
+
+        assert!(esi_0.len() == 14);
+        assert!(esi_1.len() == 14);
+        assert!(esi_0[..10] == esi_1[..10]);
+        assert!(esi_0[10..] == [10, 11, 12, 13]);
+        assert!(esi_1[10..] == [14, 15, 16, 17]);
+    }
+
+    #[test]
+    pub fn test_block_cache_memory() {
+        crate::tests::init();
+        test_cache(&FecCache::Memory);
+    }
+
+    #[test]
+    pub fn test_block_cache_file() {
+        crate::tests::init();
+        test_cache(&FecCache::File(std::env::temp_dir()));
+    }
+
+    #[test]
+    pub fn test_block_cache_file_same_toi() {
+        crate::tests::init();
+        let dir = tempfile::tempdir().unwrap();
+        let mode = FecCache::File(dir.path().to_path_buf());
+        let oti = crate::core::Oti::new_raptorq(1024, 10, 4, 1, 4).unwrap();
+        let buffer: Vec<u8> = (0..10 * 1024).map(|i| (i % 255) as u8).collect();
+
+        // Two senders in the same process use the same TOI
+        let cache = BlockCache::new(1, &oti, buffer.len() as u64, None, &mode).unwrap();
+        let block = Block::new_from_buffer(0, &buffer, 10, cache.encoding_oti(), 0).unwrap();
+        cache.store(&block).unwrap();
+
+        let other = BlockCache::new(1, &oti, buffer.len() as u64, None, &mode).unwrap();
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 2);
+        drop(other);
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+
+        let mut block = cache.load(0, 0).unwrap();
+        while let Some((symbol, _)) = block.read() {
+            if symbol.is_source_symbol {
+                assert!(symbol.symbols == &buffer[symbol.esi as usize * 1024..][..1024]);
+            }
+        }
+    }
+}
@@ -1,10 +1,16 @@
+use std::borrow::Cow;
+use std::collections::VecDeque;
 use std::sync::Arc;
 
+use super::blockencoderpool::{BlockEncoderPool, EncodedBlocks};
 use super::filedesc;
+use super::livestream::{LiveRead, LiveStream};
 use super::objectdesc::ObjectDataSource;
-use crate::common::{partition, pkt};
-use crate::error::FluteError;
-use crate::tools::error::Result;
+use crate::common::{
+    oti::{FECEncodingID, Oti},
+    partition, pkt,
+};
+use crate::tools::error::{FluteError, Result};
 
 #[derive(Debug)]
 pub struct BlockEncoder {
@@ -22,7 +28,18 @@ pub struct BlockEncoder {
     source_size_transferred: usize,
     nb_pkt_sent: usize,
     stopped: bool,
+    failed: bool,
     closabled_object: bool,
+    pool: Option<Arc<BlockEncoderPool>>,
+    encoded_blocks: Arc<EncodedBlocks>,
+    pending_blocks: VecDeque<u32>,
+    memory_reserved: usize,
+    encoding_oti: Oti,
+    round: u64,
+    read_from_cache: bool,
+    live: Option<LiveStream>,
+    live_transfer_length: Option<u64>,
+    transfer_length_update: bool,
 }
 
 use super::block::Block;
@@ -32,13 +49,21 @@ impl BlockEncoder {
         file: Arc<filedesc::FileDesc>,
         block_multiplex_windows: usize,
         closabled_object: bool,
+        pool: Option<Arc<BlockEncoderPool>>,
     ) -> Result<BlockEncoder> {
         match &file.object.source {
-            ObjectDataSource::Buffer(_) => {}
+            ObjectDataSource::Buffer(_) | ObjectDataSource::Live(_) => {}
             ObjectDataSource::Stream(stream) => {
                 stream.lock().unwrap().seek(std::io::SeekFrom::Start(0))?;
             }
         }
+        let live = file.object.live_stream().cloned();
+
+        let round = file.total_nb_transfer();
+        let (encoding_oti, read_from_cache) = match file.fec_cache.as_ref() {
+            Some(cache) => (cache.encoding_oti().clone(), cache.is_complete()),
+            None => (file.oti.clone(), false),
+        };
 
         let mut block = BlockEncoder {
             file,
@@ -55,7 +80,18 @@ impl BlockEncoder {
             source_size_transferred: 0,
             nb_pkt_sent: 0,
             stopped: false,
+            failed: false,
             closabled_object,
+            pool,
+            encoded_blocks: Arc::new(EncodedBlocks::default()),
+            pending_blocks: VecDeque::new(),
+            memory_reserved: 0,
+            encoding_oti,
+            round,
+            read_from_cache,
+            live,
+            live_transfer_length: None,
+            transfer_length_update: false,
         };
         block.block_partitioning();
         Ok(block)
@@ -72,9 +108,19 @@ impl BlockEncoder {
 
         loop {
             self.read_window();
+            if self.failed {
+                return None;
+            }
 
             if self.blocks.is_empty() {
-                if self.nb_pkt_sent == 0 {
+                if self.is_waiting_data() {
+                    return None;
+                }
+
+                // A live object interrupted before its end is not an empty file
+                let transfer_length_known =
+                    self.live.is_none() || self.live_transfer_length.is_some();
+                if self.nb_pkt_sent == 0 && transfer_length_known {
                     log::debug!("Empty file ? Send a pkt containing close object flag");
                     self.nb_pkt_sent += 1;
 
@@ -88,6 +134,8 @@ impl BlockEncoder {
                         fdt_id: self.file.fdt_id,
                         cenc: self.file.object.cenc,
                         inband_cenc: self.file.object.inband_cenc,
+                        inband_fti: self.live.is_some() || self.file.oti.inband_fti,
+                        inband_tol: self.live.is_some() || self.file.object.inband_tol,
                         close_object: true,
                         source_block_length: 0,
                         sender_current_time: self.file.sender_current_time,
@@ -104,7 +152,8 @@ impl BlockEncoder {
             let block = &mut self.blocks[self.block_multiplex_index];
             let symbol = block.read();
             if symbol.is_none() {
-                self.blocks.remove(self.block_multiplex_index);
+                let block = self.blocks.remove(self.block_multiplex_index);
+                self.release_block_memory(block.sbn());
                 continue;
             }
 
@@ -117,19 +166,35 @@ impl BlockEncoder {
 
             self.nb_pkt_sent += 1;
 
-            let is_last_packet = (self.source_size_transferred
-                >= self.file.object.transfer_length as usize)
+            let transfer_length = match self.live {
+                Some(_) => self.live_transfer_length,
+                None => Some(self.file.object.transfer_length),
+            };
+
+            let is_last_packet = transfer_length
+                .map(|transfer_length| self.source_size_transferred >= transfer_length as usize)
+                .unwrap_or(false)
                 && *is_last_symbol;
 
             return Some(pkt::Pkt {
                 payload: symbol.symbols.to_vec(),
-                transfer_length: self.file.object.transfer_length,
+                transfer_length: transfer_length.unwrap_or_default(),
                 esi: symbol.esi,
                 sbn: symbol.sbn,
                 toi: self.file.toi,
                 fdt_id: self.file.fdt_id,
                 cenc: self.file.object.cenc,
                 inband_cenc: self.file.object.inband_cenc,
+                // The transfer length of a live object is sent in-band once known
+                inband_fti: match self.live {
+                    Some(_) => transfer_length.is_some(),
+                    None => self.file.oti.inband_fti,
+                },
+                // and with EXT_TOL when the live source is closed
+                inband_tol: match self.live {
+                    Some(_) => transfer_length.is_some(),
+                    None => self.file.object.inband_tol,
+                },
                 close_object: force_close_object || (self.closabled_object && is_last_packet),
                 source_block_length: block.nb_source_symbols as u32,
                 sender_current_time: self.file.sender_current_time,
@@ -137,8 +202,57 @@ impl BlockEncoder {
         }
     }
 
+    /// Return true if the transfer waits for the producer of a live object
+    /// or for the encoder pool
+    pub fn is_waiting_data(&self) -> bool {
+        if self.stopped {
+            return false;
+        }
+
+        (self.live.is_some() && !self.read_end) || !self.pending_blocks.is_empty()
+    }
+
+    /// Return true if the transfer has been interrupted by an error
+    pub fn is_failed(&self) -> bool {
+        self.failed
+    }
+
+    /// Stop the transfer, the object is not complete and must not be announced as such
+    fn fail(&mut self, e: &FluteError) {
+        log::error!(
+            "Fail to transfer {} toi={} at block {}: {:?}",
+            self.file.object.content_location.as_str(),
+            self.file.toi,
+            self.curr_sbn,
+            e
+        );
+        self.failed = true;
+        self.stopped = true;
+        self.read_end = true;
+        while let Some(sbn) = self.pending_blocks.pop_front() {
+            self.release_block_memory(sbn);
+        }
+        while let Some(block) = self.blocks.pop() {
+            self.release_block_memory(block.sbn());
+        }
+    }
+
+    /// Return true once, when the transfer length of a live object becomes known
+    pub fn take_transfer_length_update(&mut self) -> bool {
+        std::mem::take(&mut self.transfer_length_update)
+    }
+
     fn block_partitioning(&mut self) {
         let oti = &self.file.oti;
+        if self.live.is_some() {
+            // The transfer length is unknown, all the blocks have the maximum length except the last one
+            self.a_large = oti.maximum_source_block_length as u64;
+            self.a_small = self.a_large;
+            self.nb_a_large = u64::MAX;
+            self.nb_blocks = u64::MAX;
+            return;
+        }
+
         (self.a_large, self.a_small, self.nb_a_large, self.nb_blocks) =
             partition::block_partitioning(
                 oti.maximum_source_block_length as u64,
@@ -147,28 +261,104 @@ impl BlockEncoder {
             );
     }
 
+    fn block_length(&self, sbn: u32) -> u64 {
+        match sbn as u64 {
+            value if value < self.nb_a_large => self.a_large,
+            _ => self.a_small,
+        }
+    }
+
+    /// Upper bound of the memory used by a block once encoded
+    fn block_memory_size(&self, sbn: u32) -> usize {
+        let oti = &self.encoding_oti;
+        (self.block_length(sbn) as usize + oti.max_number_of_parity_symbols as usize)
+            * oti.encoding_symbol_length as usize
+    }
+
+    /// Offset of the first repair symbol of the block.
+    ///
+    /// With fountain codes, the offset advances at each transfer of the object
+    /// so a receiver listening to several carousel rounds gets fresh repair symbols.
+    fn repair_esi_offset(&self, sbn: u32) -> u32 {
+        if self.file.fec_cache.is_some() {
+            // Repair symbols are already rotated by the cache
+            return 0;
+        }
+
+        let oti = &self.file.oti;
+        let max_esi: u64 = match oti.fec_encoding_id {
+            FECEncodingID::RaptorQ => 0xFFFFFF,
+            FECEncodingID::Raptor => 0xFFFF,
+            _ => return 0,
+        };
+
+        let nb_parity_symbols = oti.max_number_of_parity_symbols as u64;
+        let nb_offsets =
+            (max_esi + 1).saturating_sub(self.block_length(sbn) + nb_parity_symbols) + 1;
+        ((self.round * nb_parity_symbols) % nb_offsets) as u32
+    }
+
+    fn release_block_memory(&mut self, sbn: u32) {
+        if let Some(pool) = &self.pool {
+            let size = self.block_memory_size(sbn).min(self.memory_reserved);
+            pool.release(size);
+            self.memory_reserved -= size;
+        }
+    }
+
     fn read_block(&mut self) -> Result<()> {
         debug_assert!(!self.read_end);
-        let source = &self.file.object.source;
-        match source {
-            ObjectDataSource::Buffer(_) => self.read_block_buffer(),
-            ObjectDataSource::Stream(_) => self.read_block_stream(),
+        let file = self.file.clone();
+        let buffer = match &file.object.source {
+            ObjectDataSource::Buffer(buffer) => Cow::Borrowed(self.read_source_buffer(buffer)),
+            ObjectDataSource::Stream(stream) => {
+                let mut stream = stream.lock().unwrap();
+                match self.read_source_stream(&mut stream)? {
+                    Some(buffer) => Cow::Owned(buffer),
+                    None => return Ok(()),
+                }
+            }
+            ObjectDataSource::Live(stream) => match self.read_source_live(stream)? {
+                Some(buffer) => Cow::Owned(buffer),
+                None => return Ok(()),
+            },
+        };
+
+        let block_length = self.block_length(self.curr_sbn);
+        let repair_esi_offset = self.repair_esi_offset(self.curr_sbn);
+        match &self.pool {
+            Some(pool) => {
+                pool.submit(
+                    self.curr_sbn,
+                    buffer.into_owned(),
+                    block_length,
+                    &self.encoding_oti,
+                    repair_esi_offset,
+                    self.encoded_blocks.clone(),
+                )?;
+                self.pending_blocks.push_back(self.curr_sbn);
+            }
+            None => {
+                let block = Block::new_from_buffer(
+                    self.curr_sbn,
+                    &buffer,
+                    block_length,
+                    &self.encoding_oti,
+                    repair_esi_offset,
+                )?;
+                self.push_block(block)?;
+            }
         }
+
+        self.curr_sbn += 1;
+        Ok(())
     }
 
-    fn read_block_buffer(&mut self) -> Result<()> {
+    fn read_source_buffer<'a>(&mut self, content: &'a [u8]) -> &'a [u8] {
         log::debug!("Read block nb {}", self.curr_sbn);
 
-        let content = match &self.file.object.source {
-            ObjectDataSource::Buffer(buffer) => Ok(buffer),
-            _ => Err(FluteError::new("Not a data source buffer")),
-        }?;
-
         let oti = &self.file.oti;
-        let block_length = match self.curr_sbn as u64 {
-            value if value < self.nb_a_large => self.a_large,
-            _ => self.a_small,
-        };
+        let block_length = self.block_length(self.curr_sbn);
 
         let offset_start = self.curr_content_offset as usize;
         let mut offset_end =
@@ -177,10 +367,6 @@ impl BlockEncoder {
             offset_end = content.len();
         }
 
-        let buffer = &content.as_slice()[offset_start..offset_end];
-        let block = Block::new_from_buffer(self.curr_sbn, buffer, block_length, oti)?;
-        self.blocks.push(block);
-        self.curr_sbn += 1;
         self.read_end = offset_end == content.len();
         self.curr_content_offset = offset_end as u64;
         log::debug!(
@@ -189,53 +375,184 @@ impl BlockEncoder {
             content.len(),
             self.read_end
         );
-        Ok(())
+        &content[offset_start..offset_end]
     }
 
-    fn read_block_stream(&mut self) -> Result<()> {
+    fn read_source_stream(
+        &mut self,
+        stream: &mut Box<dyn super::objectdesc::ObjectDataStreamTrait>,
+    ) -> Result<Option<Vec<u8>>> {
         log::info!("Read block nb {}", self.curr_sbn);
 
-        let mut stream = match &self.file.object.source {
-            ObjectDataSource::Stream(stream) => Ok(stream.lock().unwrap()),
-            _ => Err(FluteError::new("Not a data source stream")),
-        }?;
-
         let oti = &self.file.oti;
-        let block_length = match self.curr_sbn as u64 {
-            value if value < self.nb_a_large => self.a_large,
-            _ => self.a_small,
-        };
+        let block_length = self.block_length(self.curr_sbn);
         let mut buffer: Vec<u8> =
             vec![0; block_length as usize * oti.encoding_symbol_length as usize];
-        let result = match stream.read(&mut buffer) {
-            Ok(s) => s,
-            Err(e) => {
-                log::error!("Fail to read file {:?}", e.to_string());
-                self.read_end = true;
-                return Ok(());
-            }
-        };
+        let result = stream.read(&mut buffer)?;
 
         if result == 0 {
             self.read_end = true;
-            return Ok(());
+            if self.curr_content_offset != self.file.object.transfer_length {
+                return Err(FluteError::new(format!(
+                    "Stream ends at offset {} but the object length is {}",
+                    self.curr_content_offset, self.file.object.transfer_length
+                )));
+            }
+            return Ok(None);
         }
 
         buffer.truncate(result);
+        self.curr_content_offset += buffer.len() as u64;
+        Ok(Some(buffer))
+    }
 
-        let block = Block::new_from_buffer(self.curr_sbn, &buffer, block_length, oti)?;
-        self.blocks.push(block);
-        self.curr_sbn += 1;
+    fn read_source_live(&mut self, stream: &LiveStream) -> Result<Option<Vec<u8>>> {
+        let oti = &self.file.oti;
+        let block_size =
+            self.block_length(self.curr_sbn) as usize * oti.encoding_symbol_length as usize;
+        let (buffer, is_last) = match stream.read(block_size) {
+            LiveRead::Data(buffer, is_last) => (buffer, is_last),
+            LiveRead::Pending => return Ok(None),
+            LiveRead::End => {
+                self.end_of_live_stream();
+                return Ok(None);
+            }
+        };
+
+        log::debug!("Read live block nb {}", self.curr_sbn);
         self.curr_content_offset += buffer.len() as u64;
+        let max_transfer_length = oti.max_transfer_length() as u64;
+        if self.curr_content_offset > max_transfer_length {
+            return Err(FluteError::new(format!(
+                "Live object is bigger than {}, so is incompatible with the parameters of your OTI",
+                max_transfer_length
+            )));
+        }
+
+        if is_last {
+            self.end_of_live_stream();
+        }
+        Ok(Some(buffer))
+    }
+
+    fn end_of_live_stream(&mut self) {
+        self.read_end = true;
+        self.live_transfer_length = Some(self.curr_content_offset);
+        self.transfer_length_update = true;
+    }
+
+    /// Add an encoded block to the interleave window
+    fn push_block(&mut self, mut block: Box<Block>) -> Result<()> {
+        if let Some(cache) = self.file.fec_cache.as_ref() {
+            cache.store(&block)?;
+            let round = self.round;
+            block.retain_repair_symbols(|repair_index| {
+                cache.is_repair_symbol_in_round(repair_index, round)
+            });
+        }
+
+        self.blocks.push(block);
         Ok(())
     }
 
+    fn read_window_cache(&mut self) {
+        let cache = self.file.fec_cache.as_ref().unwrap();
+        while !self.read_end && (self.blocks.len() < self.block_multiplex_windows) {
+            match cache.load(self.curr_sbn, self.round) {
+                Ok(block) => self.blocks.push(block),
+                Err(e) => {
+                    self.fail(&e);
+                    return;
+                }
+            }
+
+            self.curr_sbn += 1;
+            if self.curr_sbn as u64 >= self.nb_blocks {
+                self.read_end = true;
+            }
+        }
+    }
+
     fn read_window(&mut self) {
+        if self.read_from_cache {
+            self.read_window_cache();
+            return;
+        }
+
+        if self.pool.is_some() {
+            self.read_window_pool();
+            return;
+        }
+
         while !self.read_end && (self.blocks.len() < self.block_multiplex_windows) {
-            match self.read_block() {
-                Ok(_) => {}
-                Err(_) => self.read_end = true, // TODO handle error
+            let sbn = self.curr_sbn;
+            if let Err(e) = self.read_block() {
+                self.fail(&e);
+                return;
+            }
+
+            if self.curr_sbn == sbn {
+                // No block available yet (live object)
+                break;
+            }
+        }
+    }
+
+    fn read_window_pool(&mut self) {
+        let pool = self.pool.clone().unwrap();
+
+        // Submit blocks ahead of the transmission while the memory budget allows it
+        // The interleave window is always filled, even when the budget is exceeded
+        while !self.read_end {
+            let size = self.block_memory_size(self.curr_sbn);
+            let force =
+                self.blocks.len() + self.pending_blocks.len() < self.block_multiplex_windows;
+            if !pool.try_reserve(size, force) {
+                break;
+            }
+            self.memory_reserved += size;
+
+            let sbn = self.curr_sbn;
+            let nb_pending = self.pending_blocks.len();
+            if let Err(e) = self.read_block() {
+                self.release_block_memory(sbn);
+                self.fail(&e);
+                return;
+            }
+
+            if self.pending_blocks.len() == nb_pending {
+                // Nothing has been submitted
+                self.release_block_memory(sbn);
+                break;
+            }
+        }
+
+        while self.blocks.len() < self.block_multiplex_windows {
+            let sbn = match self.pending_blocks.front() {
+                Some(sbn) => *sbn,
+                None => break,
+            };
+
+            // Never wait for the encoder, the sender loop must not be blocked
+            let block = match self.encoded_blocks.try_take(sbn) {
+                Some(block) => block,
+                None => break,
             };
+
+            self.pending_blocks.pop_front();
+            if let Err(e) = block.and_then(|block| self.push_block(block)) {
+                self.release_block_memory(sbn);
+                self.fail(&e);
+                return;
+            }
+        }
+    }
+}
+
+impl Drop for BlockEncoder {
+    fn drop(&mut self) {
+        if let Some(pool) = &self.pool {
+            pool.release(self.memory_reserved);
         }
     }
 }
@@ -0,0 +1,188 @@
+use super::block::Block;
+use crate::common::oti::Oti;
+use crate::tools::error::{FluteError, Result};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread::JoinHandle;
+
+struct Job {
+    sbn: u32,
+    buffer: Vec<u8>,
+    block_length: u64,
+    oti: Oti,
+    repair_esi_offset: u32,
+    output: Arc<EncodedBlocks>,
+}
+
+///
+/// Blocks encoded by the pool, waiting to be transmitted by a `BlockEncoder`
+///
+#[derive(Debug, Default)]
+pub struct EncodedBlocks {
+    blocks: Mutex<BTreeMap<u32, Result<Box<Block>>>>,
+}
+
+impl EncodedBlocks {
+    fn push(&self, sbn: u32, block: Result<Box<Block>>) {
+        let mut blocks = self.blocks.lock().unwrap();
+        blocks.insert(sbn, block);
+    }
+
+    /// Take the block `sbn` if its encoding is finished
+    pub fn try_take(&self, sbn: u32) -> Option<Result<Box<Block>>> {
+        self.blocks.lock().unwrap().remove(&sbn)
+    }
+}
+
+///
+/// Pool of threads encoding source blocks ahead of the transmission
+///
+#[derive(Debug)]
+pub struct BlockEncoderPool {
+    jobs: Mutex<Option<mpsc::Sender<Job>>>,
+    workers: Vec<JoinHandle<()>>,
+    memory_budget: usize,
+    memory_used: AtomicUsize,
+}
+
+impl BlockEncoderPool {
+    pub fn new(nb_threads: usize, memory_budget: usize) -> BlockEncoderPool {
+        let (tx, rx) = mpsc::channel::<Job>();
+        let rx = Arc::new(Mutex::new(rx));
+
+        let workers = (0..nb_threads)
+            .map(|_| {
+                let rx = rx.clone();
+                std::thread::spawn(move || BlockEncoderPool::worker(rx))
+            })
+            .collect();
+
+        BlockEncoderPool {
+            jobs: Mutex::new(Some(tx)),
+            workers,
+            memory_budget,
+            memory_used: AtomicUsize::new(0),
+        }
+    }
+
+    fn worker(rx: Arc<Mutex<mpsc::Receiver<Job>>>) {
+        loop {
+            let job = match rx.lock().unwrap().recv() {
+                Ok(job) => job,
+                Err(_) => return,
+            };
+
+            let block = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                Block::new_from_buffer(
+                    job.sbn,
+                    &job.buffer,
+                    job.block_length,
+                    &job.oti,
+                    job.repair_esi_offset,
+                )
+            }))
+            .unwrap_or_else(|_| Err(FluteError::new("FEC encoder panicked")));
+            job.output.push(job.sbn, block);
+        }
+    }
+
+    /// Reserve memory for a block that is going to be encoded.
+    /// When `force` is true, the budget can be exceeded.
+    pub fn try_reserve(&self, size: usize, force: bool) -> bool {
+        let mut used = self.memory_used.load(Ordering::Relaxed);
+        loop {
+            if !force && used + size > self.memory_budget {
+                return false;
+            }
+
+            match self.memory_used.compare_exchange_weak(
+                used,
+                used + size,
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return true,
+                Err(current) => used = current,
+            }
+        }
+    }
+
+    pub fn release(&self, size: usize) {
+        self.memory_used.fetch_sub(size, Ordering::Relaxed);
+    }
+
+    pub fn submit(
+        &self,
+        sbn: u32,
+        buffer: Vec<u8>,
+        block_length: u64,
+        oti: &Oti,
+        repair_esi_offset: u32,
+        output: Arc<EncodedBlocks>,
+    ) -> Result<()> {
+        let jobs = self.jobs.lock().unwrap();
+        let jobs = jobs
+            .as_ref()
+            .ok_or_else(|| FluteError::new("Encoder pool is closed"))?;
+        jobs.send(Job {
+            sbn,
+            buffer,
+            block_length,
+            oti: oti.clone(),
+            repair_esi_offset,
+            output,
+        })
+        .map_err(|_| FluteError::new("Fail to submit block to the encoder pool"))
+    }
+}
+
+impl Drop for BlockEncoderPool {
+    fn drop(&mut self) {
+        self.jobs.lock().unwrap().take();
+        for worker in self.workers.drain(..) {
+            worker.join().ok();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BlockEncoderPool, EncodedBlocks};
+    use std::sync::Arc;
+
+    #[test]
+    pub fn test_pool_memory_budget() {
+        crate::tests::init();
+        let pool = BlockEncoderPool::new(1, 1000);
+        assert!(pool.try_reserve(600, false));
+        assert!(!pool.try_reserve(600, false));
+        assert!(pool.try_reserve(600, true));
+        pool.release(1200);
+        assert!(pool.try_reserve(1000, false));
+    }
+
+    #[test]
+    pub fn test_pool_encode() {
+        crate::tests::init();
+        let oti = crate::core::Oti::new_raptorq(1400, 64, 20, 1, 4).unwrap();
+        let pool = BlockEncoderPool::new(2, 1024 * 1024);
+        let output = Arc::new(EncodedBlocks::default());
+        for sbn in 0..4 {
+            let buffer = vec![sbn as u8; 64 * 1400];
+            pool.submit(sbn, buffer, 64, &oti, 0, output.clone())
+                .unwrap();
+        }
+
+        for sbn in (0..4).rev() {
+            let block = loop {
+                match output.try_take(sbn) {
+                    Some(block) => break block.unwrap(),
+                    None => std::thread::sleep(std::time::Duration::from_millis(1)),
+                }
+            };
+            assert!(block.sbn() == sbn);
+            assert!(block.nb_source_symbols == 64);
+        }
+    }
+}
@@ -4,11 +4,16 @@
 //! - Zlib
 //! - Deflate
 //! - Gzip
+//! - Zstd (only available with the `zstd` feature)
+//! - Brotli (only available with the `brotli` feature)
 //!
 //! The module offers two main functions for compression:
 //! - `compress_buffer`: Compresses a byte slice and returns the compressed data as a vector of bytes.
 //! - `compress_stream`: Compresses data from an input stream and writes the compressed data to an output stream.
 //!
+//! Large streams can be compressed to a temporary file with `CompressedTempFile`,
+//! so the compressed content does not have to be kept in memory.
+//!
 //! # Examples
 //!
 //! ## Compressing a Byte Slice
@@ -37,7 +42,76 @@
 use crate::common::lct;
 use crate::tools::error::{FluteError, Result};
 use flate2::write::{DeflateEncoder, GzEncoder, ZlibEncoder};
-use std::io::Write;
+use std::io::{Seek, Write};
+
+///
+/// Temporary file containing the compressed content of a stream
+///
+/// The file is created by `CompressedTempFile::new` and removed when the `CompressedTempFile` is dropped.
+///
+#[derive(Debug)]
+pub struct CompressedTempFile {
+    path: std::path::PathBuf,
+    file: std::fs::File,
+}
+
+impl CompressedTempFile {
+    /// Compress `input` into a new temporary file created inside `dir`
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - Stream to compress, read until the end.
+    /// * `cenc` - The compression encoding to use (`lct::Cenc::Null` returns an error).
+    /// * `dir` - Directory where the temporary file is created.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the temporary file, positioned at the beginning of the compressed content.
+    pub fn new(
+        input: &mut dyn std::io::Read,
+        cenc: lct::Cenc,
+        dir: &std::path::Path,
+    ) -> Result<CompressedTempFile> {
+        if !dir.is_dir() {
+            return Err(FluteError::new(format!("{:?} is not a directory", dir)));
+        }
+
+        let (path, file) = crate::tools::create_temp_file(dir, "flute_compressed")?;
+
+        // The file is removed on drop, including when the compression fails
+        let mut temp_file = CompressedTempFile { path, file };
+        {
+            let mut output = std::io::BufWriter::new(&mut temp_file.file);
+            compress_stream(input, cenc, &mut output)?;
+            output.flush()?;
+        }
+        temp_file.file.rewind()?;
+        Ok(temp_file)
+    }
+
+    /// Path of the temporary file
+    pub fn path(&self) -> &std::path::Path {
+        &self.path
+    }
+}
+
+impl std::io::Read for CompressedTempFile {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.file.read(buf)
+    }
+}
+
+impl std::io::Seek for CompressedTempFile {
+    fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+        self.file.seek(pos)
+    }
+}
+
+impl Drop for CompressedTempFile {
+    fn drop(&mut self) {
+        std::fs::remove_file(&self.path).ok();
+    }
+}
 
 /// Compresses the given data using the specified compression encoding.
 ///
@@ -49,6 +123,8 @@ use std::io::Write;
 ///     - `lct::Cenc::Zlib`: Compress using the Zlib algorithm.
 ///     - `lct::Cenc::Deflate`: Compress using the Deflate algorithm.
 ///     - `lct::Cenc::Gzip`: Compress using the Gzip algorithm.
+///     - `lct::Cenc::Zstd`: Compress using the Zstandard algorithm (`zstd` feature).
+///     - `lct::Cenc::Brotli`: Compress using the Brotli algorithm (`brotli` feature).
 ///
 /// # Returns
 ///
@@ -74,6 +150,14 @@ pub fn compress_buffer(data: &[u8], cenc: lct::Cenc) -> Result<Vec<u8>> {
         lct::Cenc::Zlib => compress_zlib(data),
         lct::Cenc::Deflate => compress_deflate(data),
         lct::Cenc::Gzip => compress_gzip(data),
+        #[cfg(feature = "zstd")]
+        lct::Cenc::Zstd => compress_zstd(data),
+        #[cfg(feature = "brotli")]
+        lct::Cenc::Brotli => compress_brotli(data),
+        #[cfg(not(feature = "zstd"))]
+        lct::Cenc::Zstd => Err(cenc.unsupported_error()),
+        #[cfg(not(feature = "brotli"))]
+        lct::Cenc::Brotli => Err(cenc.unsupported_error()),
     }
 }
 
@@ -88,6 +172,8 @@ pub fn compress_buffer(data: &[u8], cenc: lct::Cenc) -> Result<Vec<u8>> {
 ///     - `lct::Cenc::Zlib`: Compress using the Zlib algorithm.
 ///     - `lct::Cenc::Deflate`: Compress using the Deflate algorithm.
 ///     - `lct::Cenc::Gzip`: Compress using the Gzip algorithm.
+///     - `lct::Cenc::Zstd`: Compress using the Zstandard algorithm (`zstd` feature).
+///     - `lct::Cenc::Brotli`: Compress using the Brotli algorithm (`brotli` feature).
 /// * `output` - A mutable reference to a type that implements the `std::io::Write` trait, representing the output stream.
 ///
 /// # Returns
@@ -109,6 +195,14 @@ pub fn compress_stream(
         lct::Cenc::Zlib => stream_compress_zlib(input, output),
         lct::Cenc::Deflate => stream_compress_deflate(input, output),
         lct::Cenc::Gzip => stream_compress_gzip(input, output),
+        #[cfg(feature = "zstd")]
+        lct::Cenc::Zstd => stream_compress_zstd(input, output),
+        #[cfg(feature = "brotli")]
+        lct::Cenc::Brotli => stream_compress_brotli(input, output),
+        #[cfg(not(feature = "zstd"))]
+        lct::Cenc::Zstd => Err(cenc.unsupported_error()),
+        #[cfg(not(feature = "brotli"))]
+        lct::Cenc::Brotli => Err(cenc.unsupported_error()),
     }
 }
 
@@ -134,6 +228,31 @@ fn compress_zlib(data: &[u8]) -> Result<Vec<u8>> {
     Ok(output)
 }
 
+#[cfg(feature = "zstd")]
+fn compress_zstd(data: &[u8]) -> Result<Vec<u8>> {
+    let output = zstd::stream::encode_all(data, zstd::DEFAULT_COMPRESSION_LEVEL)?;
+    Ok(output)
+}
+
+#[cfg(feature = "brotli")]
+const BROTLI_QUALITY: i32 = 6;
+
+#[cfg(feature = "brotli")]
+fn brotli_params() -> brotli::enc::BrotliEncoderParams {
+    brotli::enc::BrotliEncoderParams {
+        quality: BROTLI_QUALITY,
+        ..Default::default()
+    }
+}
+
+#[cfg(feature = "brotli")]
+fn compress_brotli(data: &[u8]) -> Result<Vec<u8>> {
+    let mut input = data;
+    let mut output = Vec::new();
+    brotli::BrotliCompress(&mut input, &mut output, &brotli_params())?;
+    Ok(output)
+}
+
 fn stream_compress_gzip(
     input: &mut dyn std::io::Read,
     output: &mut dyn std::io::Write,
@@ -190,3 +309,25 @@ fn stream_compress_deflate(
     encoder.finish()?;
     Ok(())
 }
+
+#[cfg(feature = "zstd")]
+fn stream_compress_zstd(
+    input: &mut dyn std::io::Read,
+    output: &mut dyn std::io::Write,
+) -> Result<()> {
+    log::debug!("Create ZSTD encoder");
+    zstd::stream::copy_encode(input, output, zstd::DEFAULT_COMPRESSION_LEVEL)?;
+    Ok(())
+}
+
+#[cfg(feature = "brotli")]
+fn stream_compress_brotli(
+    input: &mut dyn std::io::Read,
+    output: &mut dyn std::io::Write,
+) -> Result<()> {
+    log::debug!("Create Brotli encoder");
+    let mut input = input;
+    let mut output = output;
+    brotli::BrotliCompress(&mut input, &mut output, &brotli_params())?;
+    Ok(())
+}
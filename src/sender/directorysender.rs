@@ -0,0 +1,504 @@
+use super::objectdesc::{CarouselRepeatMode, ObjectDesc};
+use super::sender::{Config, FDTPublishMode, Sender};
+use crate::common::{lct, oti};
+use crate::core::UDPEndpoint;
+use crate::error::FluteError;
+use crate::tools::error::Result;
+use base64::Engine;
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+///
+/// Configuration of a `DirectorySender`
+///
+#[derive(Debug, Clone)]
+pub struct DirectoryConfig {
+    /// Directory to watch, sub-directories are included
+    pub path: PathBuf,
+    /// Base URL of the objects.
+    /// The Content-Location of a file is its path relative to `path` joined to this URL.
+    /// The URL must end with a `/`
+    pub base_url: url::Url,
+    /// Media type of the objects
+    pub content_type: String,
+    /// Priority queue used to transfer the objects
+    pub priority: u32,
+    /// Interval between two scans of the directory
+    pub scan_interval: Duration,
+    /// Files modified less than `min_file_age` ago are ignored until the next scan.
+    /// Prevents sending a file that is still being written.
+    pub min_file_age: Duration,
+    /// If set, the objects are kept in a carousel until the file is deleted or modified
+    pub carousel_mode: Option<CarouselRepeatMode>,
+    /// Number of times an object is transferred
+    pub max_transfer_count: u32,
+    /// Content Encoding (compression) of the objects
+    pub cenc: lct::Cenc,
+    /// If `true`, Cenc extension are added to ALC/LCT packet
+    pub inband_cenc: bool,
+    /// If `true`, the content of the files is loaded in memory
+    pub cache_in_ram: bool,
+    /// Add the MD5 of the files to the FDT
+    pub md5: bool,
+    /// If `true`, files and directories starting with a `.` are sent
+    pub include_hidden: bool,
+}
+
+impl Default for DirectoryConfig {
+    fn default() -> Self {
+        Self {
+            path: PathBuf::from("."),
+            base_url: url::Url::parse("file:///").unwrap(),
+            content_type: "application/octet-stream".to_owned(),
+            priority: 0,
+            scan_interval: Duration::from_secs(1),
+            min_file_age: Duration::ZERO,
+            carousel_mode: None,
+            max_transfer_count: 1,
+            cenc: lct::Cenc::Null,
+            inband_cenc: true,
+            cache_in_ram: false,
+            md5: true,
+            include_hidden: false,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct WatchedFile {
+    toi: u128,
+    content_location: url::Url,
+    modified: Option<SystemTime>,
+    len: u64,
+    e_tag: String,
+}
+
+///
+/// FLUTE `Sender` broadcasting the content of a directory (hot-folder)
+///
+/// The directory is scanned periodically.
+/// New files are added to the FDT, modified files are replaced by a new object with a new ETag
+/// and deleted files are removed from the FDT.
+///
+#[derive(Debug)]
+pub struct DirectorySender {
+    sender: Sender,
+    config: DirectoryConfig,
+    fdt_publish_mode: FDTPublishMode,
+    files: BTreeMap<PathBuf, WatchedFile>,
+    next_scan: Option<SystemTime>,
+}
+
+impl DirectorySender {
+    ///
+    /// Creation of a `DirectorySender`
+    ///
+    pub fn new(
+        endpoint: UDPEndpoint,
+        tsi: u64,
+        oti: &oti::Oti,
+        sender_config: &Config,
+        config: DirectoryConfig,
+    ) -> Result<DirectorySender> {
+        if !config.path.is_dir() {
+            return Err(FluteError::new(format!(
+                "{:?} is not a directory",
+                config.path
+            )));
+        }
+
+        if config.base_url.cannot_be_a_base() {
+            return Err(FluteError::new(format!(
+                "{} cannot be used as a base URL",
+                config.base_url
+            )));
+        }
+
+        Ok(DirectorySender {
+            sender: Sender::new(endpoint, tsi, oti, sender_config),
+            config,
+            fdt_publish_mode: sender_config.fdt_publish_mode,
+            files: BTreeMap::new(),
+            next_scan: None,
+        })
+    }
+
+    /// Get the FLUTE `Sender`
+    pub fn sender(&self) -> &Sender {
+        &self.sender
+    }
+
+    /// Get the FLUTE `Sender`
+    pub fn sender_mut(&mut self) -> &mut Sender {
+        &mut self.sender
+    }
+
+    /// Number of files currently broadcasted
+    pub fn nb_files(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Return the TOI of a file
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the file relative to the watched directory
+    pub fn get_toi(&self, path: &Path) -> Option<u128> {
+        self.files.get(path).map(|file| file.toi)
+    }
+
+    /// Scan the directory and update the FDT
+    ///
+    /// # Returns
+    ///
+    /// `true` if the FDT has been modified
+    pub fn scan(&mut self, now: SystemTime) -> Result<bool> {
+        let mut found = Vec::new();
+        Self::list_files(
+            &self.config.path,
+            Path::new(""),
+            self.config.include_hidden,
+            &mut found,
+        )?;
+
+        let mut changed = false;
+        let mut present = std::collections::HashSet::new();
+        for relative_path in found {
+            present.insert(relative_path.clone());
+            match self.update_file(&relative_path, now) {
+                Ok(true) => changed = true,
+                Ok(false) => {}
+                Err(e) => log::warn!("Fail to add {:?} {:?}", relative_path, e),
+            }
+        }
+
+        let deleted: Vec<PathBuf> = self
+            .files
+            .keys()
+            .filter(|path| !present.contains(*path))
+            .cloned()
+            .collect();
+        for relative_path in deleted {
+            log::info!("{:?} has been deleted", relative_path);
+            let file = self.files.remove(&relative_path).unwrap();
+            self.remove_file(&file);
+            changed = true;
+        }
+
+        if changed && self.fdt_publish_mode == FDTPublishMode::FullFDT {
+            self.sender.publish(now)?;
+        }
+
+        Ok(changed)
+    }
+
+    /// Read the next ALC/LCT packet
+    /// The directory is scanned when `scan_interval` is elapsed
+    /// return None if there is no new packet to be transferred
+    pub fn read(&mut self, now: SystemTime) -> Option<Vec<u8>> {
+        let scan_required = match self.next_scan {
+            Some(next_scan) => now >= next_scan,
+            None => true,
+        };
+
+        if scan_required {
+            if let Err(e) = self.scan(now) {
+                log::error!("Fail to scan {:?} {:?}", self.config.path, e);
+            }
+            self.next_scan = Some(now + self.config.scan_interval);
+        }
+
+        self.sender.read(now)
+    }
+
+    fn list_files(
+        root: &Path,
+        relative_dir: &Path,
+        include_hidden: bool,
+        output: &mut Vec<PathBuf>,
+    ) -> Result<()> {
+        for entry in std::fs::read_dir(root.join(relative_dir))? {
+            let entry = entry?;
+            let name = entry.file_name();
+            if !include_hidden && name.to_string_lossy().starts_with('.') {
+                continue;
+            }
+
+            let relative_path = relative_dir.join(&name);
+            let metadata = match std::fs::metadata(entry.path()) {
+                Ok(metadata) => metadata,
+                Err(_) => continue,
+            };
+
+            if metadata.is_dir() {
+                Self::list_files(root, &relative_path, include_hidden, output)?;
+            } else if metadata.is_file() {
+                output.push(relative_path);
+            }
+        }
+        Ok(())
+    }
+
+    /// Each component of the path is percent-encoded as a segment of the URL
+    fn content_location(&self, relative_path: &Path) -> Result<url::Url> {
+        let mut url = self.config.base_url.clone();
+        url.set_query(None);
+        url.set_fragment(None);
+        url.path_segments_mut()
+            .map_err(|_| {
+                FluteError::new(format!(
+                    "{} cannot be used as a base URL",
+                    self.config.base_url
+                ))
+            })?
+            .pop_if_empty()
+            .extend(
+                relative_path
+                    .components()
+                    .map(|component| component.as_os_str().to_string_lossy()),
+            );
+        Ok(url)
+    }
+
+    fn update_file(&mut self, relative_path: &Path, now: SystemTime) -> Result<bool> {
+        let path = self.config.path.join(relative_path);
+        let metadata = std::fs::metadata(&path)?;
+        let modified = metadata.modified().ok();
+        let len = metadata.len();
+
+        if let Some(modified) = modified {
+            if now.duration_since(modified).unwrap_or_default() < self.config.min_file_age {
+                return Ok(false);
+            }
+        }
+
+        if let Some(file) = self.files.get_mut(relative_path) {
+            if file.modified == modified && file.len == len {
+                return Ok(false);
+            }
+        }
+
+        let md5 = match self.config.md5 {
+            true => Some(Self::file_md5(&path)?),
+            false => None,
+        };
+
+        let e_tag = match md5.as_ref() {
+            Some(md5) => md5.clone(),
+            None => format!(
+                "{:x}-{:x}",
+                len,
+                modified
+                    .and_then(|modified| modified.duration_since(SystemTime::UNIX_EPOCH).ok())
+                    .unwrap_or_default()
+                    .as_nanos()
+            ),
+        };
+
+        if let Some(file) = self.files.get_mut(relative_path) {
+            if file.e_tag == e_tag {
+                // Modification time has changed, not the content
+                file.modified = modified;
+                file.len = len;
+                return Ok(false);
+            }
+        }
+
+        let content_location = self.content_location(relative_path)?;
+        let mut obj = ObjectDesc::create_from_file(
+            &path,
+            Some(&content_location),
+            &self.config.content_type,
+            self.config.cache_in_ram,
+            self.config.max_transfer_count,
+            self.config.carousel_mode,
+            None,
+            None,
+            None,
+            self.config.cenc,
+            self.config.inband_cenc,
+            None,
+            false,
+        )?;
+        obj.md5 = md5;
+        obj.e_tag = Some(e_tag.clone());
+
+        // The new version and the old version share the same Content-Location
+        if let Some(old_file) = self.files.remove(relative_path) {
+            self.remove_file(&old_file);
+        }
+
+        let toi = self.sender.add_object(self.config.priority, obj)?;
+        log::info!("Add {:?} toi={} etag={}", relative_path, toi, e_tag);
+
+        self.files.insert(
+            relative_path.to_path_buf(),
+            WatchedFile {
+                toi,
+                content_location,
+                modified,
+                len,
+                e_tag,
+            },
+        );
+
+        Ok(true)
+    }
+
+    fn remove_file(&mut self, file: &WatchedFile) {
+        // Parts of an object split by the sender share the Content-Location of the file as group
+        let group = file.content_location.to_string();
+        let mut tois: Vec<u128> = self
+            .sender
+            .get_objects_in_fdt()
+            .into_iter()
+            .filter(|(_, obj)| {
+                obj.part
+                    .as_ref()
+                    .map(|part| part.group == group)
+                    .unwrap_or(false)
+            })
+            .map(|(toi, _)| toi)
+            .collect();
+
+        if !tois.contains(&file.toi) {
+            tois.push(file.toi);
+        }
+
+        for toi in tois {
+            self.sender.remove_object(toi);
+        }
+    }
+
+    fn file_md5(path: &Path) -> Result<String> {
+        let mut file = std::fs::File::open(path)?;
+        let mut context = md5::Context::new();
+        let mut buffer = vec![0; 102400];
+        loop {
+            let count = file.read(&mut buffer)?;
+            if count == 0 {
+                break;
+            }
+            context.consume(&buffer[..count]);
+        }
+        // https://www.rfc-editor.org/rfc/rfc2616#section-14.15
+        Ok(base64::engine::general_purpose::STANDARD.encode(context.compute().0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DirectoryConfig, DirectorySender};
+    use crate::core::UDPEndpoint;
+    use std::path::Path;
+
+    fn create_sender(dir: &Path) -> DirectorySender {
+        let endpoint = UDPEndpoint::new(None, "224.0.0.1".to_owned(), 1234);
+        let config = DirectoryConfig {
+            path: dir.to_path_buf(),
+            base_url: url::Url::parse("http://localhost/data/").unwrap(),
+            ..Default::default()
+        };
+        DirectorySender::new(
+            endpoint,
+            1,
+            &Default::default(),
+            &Default::default(),
+            config,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    pub fn test_directory_sender() {
+        crate::tests::init();
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("sub")).unwrap();
+        std::fs::write(dir.path().join("file1.txt"), b"file1").unwrap();
+        std::fs::write(dir.path().join("sub").join("file2.txt"), b"file2").unwrap();
+        std::fs::write(dir.path().join(".hidden"), b"hidden").unwrap();
+
+        let now = std::time::SystemTime::now();
+        let mut sender = create_sender(dir.path());
+        assert!(sender.scan(now).unwrap());
+        assert!(sender.nb_files() == 2);
+        assert!(sender.sender().nb_objects() == 2);
+
+        let toi = sender.get_toi(Path::new("sub/file2.txt")).unwrap();
+        let objects = sender.sender().get_objects_in_fdt();
+        let obj = objects.get(&toi).unwrap();
+        assert!(obj.content_location.as_str() == "http://localhost/data/sub/file2.txt");
+        assert!(obj.e_tag.is_some());
+        assert!(obj.e_tag == obj.md5);
+
+        // Nothing changed
+        assert!(!sender.scan(now).unwrap());
+
+        // Modify a file
+        std::fs::write(dir.path().join("sub").join("file2.txt"), b"file2 modified").unwrap();
+        assert!(sender.scan(now).unwrap());
+        assert!(sender.sender().nb_objects() == 2);
+        let new_toi = sender.get_toi(Path::new("sub/file2.txt")).unwrap();
+        assert!(new_toi != toi);
+        assert!(!sender.sender().is_added(toi));
+
+        // Delete a file
+        std::fs::remove_file(dir.path().join("file1.txt")).unwrap();
+        assert!(sender.scan(now).unwrap());
+        assert!(sender.nb_files() == 1);
+        assert!(sender.sender().nb_objects() == 1);
+    }
+
+    #[test]
+    pub fn test_directory_sender_special_characters() {
+        crate::tests::init();
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("a#b")).unwrap();
+        std::fs::write(dir.path().join("a#b").join("50% off?.txt"), b"file").unwrap();
+
+        let now = std::time::SystemTime::now();
+        let mut sender = create_sender(dir.path());
+        assert!(sender.scan(now).unwrap());
+
+        let toi = sender.get_toi(Path::new("a#b/50% off?.txt")).unwrap();
+        let objects = sender.sender().get_objects_in_fdt();
+        let obj = objects.get(&toi).unwrap();
+        assert_eq!(
+            obj.content_location.as_str(),
+            "http://localhost/data/a%23b/50%25%20off%3F.txt"
+        );
+
+        let segments: Vec<String> = obj
+            .content_location
+            .path_segments()
+            .unwrap()
+            .map(|segment| {
+                percent_encoding::percent_decode_str(segment)
+                    .decode_utf8_lossy()
+                    .into_owned()
+            })
+            .collect();
+        assert_eq!(segments, ["data", "a#b", "50% off?.txt"]);
+    }
+
+    #[test]
+    pub fn test_directory_sender_not_a_directory() {
+        crate::tests::init();
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let endpoint = UDPEndpoint::new(None, "224.0.0.1".to_owned(), 1234);
+        let config = DirectoryConfig {
+            path: file.path().to_path_buf(),
+            ..Default::default()
+        };
+        let res = DirectorySender::new(
+            endpoint,
+            1,
+            &Default::default(),
+            &Default::default(),
+            config,
+        );
+        assert!(res.is_err());
+    }
+}
@@ -173,6 +173,43 @@ impl Fdt {
         Ok(ret)
     }
 
+    /// Default OTI of the objects
+    pub fn oti(&self) -> &oti::Oti {
+        &self.oti
+    }
+
+    /// Add an object, split in several parts if it is larger than the maximum transfer length of its OTI.
+    /// Return the TOI of the first part
+    pub fn add_object_split(
+        &mut self,
+        priority: u32,
+        obj: Box<objectdesc::ObjectDesc>,
+    ) -> Result<u128> {
+        let max_transfer_length =
+            obj.oti.as_ref().unwrap_or(&self.oti).max_transfer_length() as u64;
+        if obj.transfer_length <= max_transfer_length {
+            return self.add_object(priority, obj);
+        }
+
+        let parts = obj.split(max_transfer_length)?;
+        log::info!("Object split in {} parts", parts.len());
+
+        let mut tois = Vec::new();
+        for part in parts {
+            match self.add_object(priority, Box::new(part)) {
+                Ok(toi) => tois.push(toi),
+                Err(e) => {
+                    for toi in tois {
+                        self.remove_object(toi);
+                    }
+                    return Err(e);
+                }
+            }
+        }
+
+        Ok(tois[0])
+    }
+
     pub fn trigger_transfer_at(&mut self, toi: u128, timestamp: Option<SystemTime>) -> bool {
         if let Some(file) = self.files.get(&toi) {
             if file.is_transferring() {
@@ -372,6 +409,27 @@ impl Fdt {
         }
     }
 
+    /// The object can not be read, remove it from the FDT so it is never announced as complete
+    pub fn transfer_failed(&mut self, file: Arc<FileDesc>, now: SystemTime) {
+        file.transfer_done(now);
+
+        if file.toi == lct::TOI_FDT {
+            return;
+        }
+
+        let evt = observer::Event::StopTransfer(observer::FileInfo { toi: file.toi });
+        self.observers.dispatch(&evt, now);
+
+        log::error!(
+            "Transfer of {} toi={} failed, remove it from the FDT",
+            file.object.content_location.as_str(),
+            file.toi
+        );
+        if self.remove_object(file.toi) {
+            self.publish(now).ok();
+        }
+    }
+
     pub fn set_complete(&mut self) {
         self.complete = Some(true)
     }
@@ -530,4 +588,25 @@ mod tests {
         );
         // log::info!("content={}", content);
     }
+
+    #[test]
+    pub fn test_fdt_file_group() {
+        crate::tests::init();
+        let fdt = create_fdt();
+        let buffer = fdt.to_xml(SystemTime::now()).unwrap();
+        let instance = crate::common::fdtinstance::FdtInstance::parse(&buffer).unwrap();
+        assert!(instance.group == Some(vec!["Group1".to_owned(), "Group2".to_owned()]));
+
+        let files = instance.file.unwrap();
+        let group = |content_location: &str| {
+            files
+                .iter()
+                .find(|file| file.content_location == content_location)
+                .unwrap()
+                .group
+                .clone()
+        };
+        assert!(group("file:///object1") == Some(vec!["Test1".to_owned()]));
+        assert!(group("file:///object2").is_none());
+    }
 }
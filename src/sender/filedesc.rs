@@ -1,5 +1,6 @@
 use base64::Engine;
 
+use super::blockcache::BlockCache;
 use super::objectdesc::{create_fdt_cache_control, ObjectDesc};
 use super::FDTPublishMode;
 use crate::common::oti::SchemeSpecific;
@@ -92,6 +93,7 @@ pub struct FileDesc {
     pub sender_current_time: bool,
     pub published: AtomicBool,
     pub toi: u128,
+    pub fec_cache: Option<BlockCache>,
     transfer_info: RwLock<TransferInfo>,
 }
 
@@ -109,6 +111,10 @@ impl FileDesc {
             None => default_oti.clone(),
         };
 
+        if object.live_stream().is_some() {
+            Self::check_live_object(&object, &oti)?;
+        }
+
         let max_transfer_length = oti.max_transfer_length();
         if object.transfer_length as usize > max_transfer_length {
             return Err(FluteError::new(format!(
@@ -169,6 +175,20 @@ impl FileDesc {
 
         let toi = object.toi.as_ref().unwrap().get();
         let transfer_start_time = object.transfer_start_time.clone();
+        let fec_cache = match object.fec_cache.as_ref() {
+            Some(mode) => Some(BlockCache::new(
+                toi,
+                &oti,
+                object.transfer_length,
+                match object.carousel_mode {
+                    Some(_) => None,
+                    None => Some(object.max_transfer_count),
+                },
+                mode,
+            )?),
+            None => None,
+        };
+
         Ok(FileDesc {
             priority,
             object,
@@ -187,9 +207,37 @@ impl FileDesc {
             }),
             published: AtomicBool::new(false),
             toi,
+            fec_cache,
         })
     }
 
+    fn check_live_object(object: &ObjectDesc, oti: &oti::Oti) -> Result<()> {
+        match oti.fec_encoding_id {
+            oti::FECEncodingID::NoCode
+            | oti::FECEncodingID::ReedSolomonGF28
+            | oti::FECEncodingID::ReedSolomonGF28UnderSpecified => {}
+            _ => {
+                return Err(FluteError::new(format!(
+                    "FEC {:?} requires the transfer length before the transfer, it cannot be used with a live object",
+                    oti.fec_encoding_id
+                )))
+            }
+        }
+
+        if object.cenc != crate::core::lct::Cenc::Null {
+            return Err(FluteError::new("Live object cannot be compressed"));
+        }
+
+        if object.carousel_mode.is_some()
+            || object.max_transfer_count != 1
+            || object.fec_cache.is_some()
+        {
+            return Err(FluteError::new("Live object can only be transferred once"));
+        }
+
+        Ok(())
+    }
+
     pub fn total_nb_transfer(&self) -> u64 {
         let info = self.transfer_info.read().unwrap();
         info.total_nb_transfer
@@ -325,11 +373,21 @@ impl FileDesc {
             base64::engine::general_purpose::STANDARD.encode(s)
         });
 
+        let part = self.object.part.as_ref();
+        let live_stream = self.object.live_stream();
+        let (content_length, transfer_length) = match live_stream {
+            Some(stream) => (stream.transfer_length(), stream.transfer_length()),
+            None => (
+                Some(self.object.content_length),
+                Some(self.object.transfer_length),
+            ),
+        };
+
         fdtinstance::File {
             content_location: self.object.content_location.to_string(),
             toi: self.toi.to_string(),
-            content_length: Some(self.object.content_length),
-            transfer_length: Some(self.object.transfer_length),
+            content_length,
+            transfer_length,
             content_type: Some(self.object.content_type.clone()),
             content_encoding: match &self.object.cenc {
                 crate::core::lct::Cenc::Null => None,
@@ -368,8 +426,67 @@ impl FileDesc {
             independent_unit_positions: None,
             delimiter: Some(0),
             delimiter2: Some(0),
-            group: None,
+            // An empty list would be read back as a file without group
+            group: self
+                .object
+                .groups
+                .clone()
+                .filter(|groups| !groups.is_empty()),
             optel_propagator,
+            part_group: part.map(|part| part.group.clone()),
+            part_index: part.map(|part| part.index),
+            part_count: part.map(|part| part.count),
+            part_offset: part.map(|part| part.offset),
+            part_group_content_length: part.map(|part| part.group_content_length),
+            part_group_content_md5: part.and_then(|part| part.group_md5.clone()),
+            live: live_stream.map(|_| true),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::FileDesc;
+    use crate::common::{lct, oti};
+    use crate::sender::objectdesc::ObjectDesc;
+    use crate::sender::toiallocator::ToiAllocator;
+    use crate::sender::TOIMaxLength;
+    use std::time::SystemTime;
+
+    fn create_file(groups: Option<Vec<String>>) -> FileDesc {
+        let mut object = ObjectDesc::create_from_buffer(
+            vec![0u8; 16],
+            "plain/txt",
+            &url::Url::parse("file:///object").unwrap(),
+            1,
+            None,
+            None,
+            None,
+            groups,
+            lct::Cenc::Null,
+            true,
+            None,
+            true,
+        )
+        .unwrap();
+        let allocator = ToiAllocator::new(TOIMaxLength::ToiMax112, Some(1));
+        object.toi = Some(ToiAllocator::allocate(&allocator));
+        FileDesc::new(0, object, &oti::Oti::default(), None, false).unwrap()
+    }
+
+    #[test]
+    pub fn test_file_xml_group() {
+        crate::tests::init();
+        let now = SystemTime::now();
+
+        let file = create_file(Some(vec!["media".to_owned(), "video".to_owned()]));
+        let xml = file.to_file_xml(now);
+        assert!(xml.group == Some(vec!["media".to_owned(), "video".to_owned()]));
+
+        let file = create_file(Some(Vec::new()));
+        assert!(file.to_file_xml(now).group.is_none());
+
+        let file = create_file(None);
+        assert!(file.to_file_xml(now).group.is_none());
+    }
+}
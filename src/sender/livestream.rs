@@ -0,0 +1,134 @@
+use crate::error::{FluteError, Result};
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Default)]
+struct LiveStreamState {
+    buffer: Vec<u8>,
+    bytes_written: u64,
+    finished: bool,
+}
+
+/// Result of a read of a `LiveStream` by the block encoder
+#[derive(Debug, PartialEq)]
+pub(crate) enum LiveRead {
+    /// Next block of data, `true` if this is the last block of the stream
+    Data(Vec<u8>, bool),
+    /// Not enough data has been produced yet
+    Pending,
+    /// The stream is finished and all its data have been read
+    End,
+}
+
+///
+/// Content of an object produced while it is being transferred (live log, recording in progress...)
+///
+/// The producer appends data with `write()` and calls `finish()` once the object is complete.
+/// The sender transfers a source block as soon as enough data is available,
+/// the transfer length is announced to the receivers when the stream is finished.
+///
+/// `LiveStream` can be cloned and shared between the producer thread and the sender.
+///
+#[derive(Debug, Clone, Default)]
+pub struct LiveStream {
+    state: Arc<Mutex<LiveStreamState>>,
+}
+
+impl LiveStream {
+    /// Return a new empty `LiveStream`
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append data to the stream
+    pub fn write(&self, data: &[u8]) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        if state.finished {
+            return Err(FluteError::new("Live stream is already finished"));
+        }
+
+        state.buffer.extend_from_slice(data);
+        state.bytes_written += data.len() as u64;
+        Ok(())
+    }
+
+    /// Mark the end of the stream, no more data can be written
+    pub fn finish(&self) {
+        self.state.lock().unwrap().finished = true;
+    }
+
+    /// Return `true` if `finish()` has been called
+    pub fn is_finished(&self) -> bool {
+        self.state.lock().unwrap().finished
+    }
+
+    /// Number of bytes written to the stream so far
+    pub fn bytes_written(&self) -> u64 {
+        self.state.lock().unwrap().bytes_written
+    }
+
+    /// Transfer length of the stream, known once the stream is finished
+    pub fn transfer_length(&self) -> Option<u64> {
+        let state = self.state.lock().unwrap();
+        match state.finished {
+            true => Some(state.bytes_written),
+            false => None,
+        }
+    }
+
+    /// Read the next block of `block_size` bytes
+    ///
+    /// A shorter block is returned only at the end of the stream.
+    /// Data are removed from the stream once read.
+    pub(crate) fn read(&self, block_size: usize) -> LiveRead {
+        let mut state = self.state.lock().unwrap();
+        if state.buffer.is_empty() && state.finished {
+            return LiveRead::End;
+        }
+
+        if state.buffer.len() < block_size && !state.finished {
+            return LiveRead::Pending;
+        }
+
+        let size = block_size.min(state.buffer.len());
+        let data: Vec<u8> = state.buffer.drain(..size).collect();
+        let is_last = state.finished && state.buffer.is_empty();
+        LiveRead::Data(data, is_last)
+    }
+}
+
+impl std::io::Write for LiveStream {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        LiveStream::write(self, buf).map_err(|e| e.0)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LiveRead, LiveStream};
+
+    #[test]
+    pub fn test_live_stream_read() {
+        crate::tests::init();
+        let stream = LiveStream::new();
+        assert!(stream.read(4) == LiveRead::Pending);
+
+        stream.write(&[1, 2, 3]).unwrap();
+        assert!(stream.read(4) == LiveRead::Pending);
+
+        stream.write(&[4, 5, 6]).unwrap();
+        assert!(stream.read(4) == LiveRead::Data(vec![1, 2, 3, 4], false));
+        assert!(stream.read(4) == LiveRead::Pending);
+        assert!(stream.transfer_length().is_none());
+
+        stream.finish();
+        assert!(stream.write(&[7]).is_err());
+        assert!(stream.transfer_length() == Some(6));
+        assert!(stream.read(4) == LiveRead::Data(vec![5, 6], true));
+        assert!(stream.read(4) == LiveRead::End);
+    }
+}
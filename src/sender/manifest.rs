@@ -0,0 +1,592 @@
+use crate::error::{FluteError, Result};
+use quick_xml::events::{BytesStart, Event};
+use std::collections::HashSet;
+use std::time::Duration;
+
+/// Type of a resource referenced by a manifest
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SegmentKind {
+    /// Initialization segment
+    Init,
+    /// Media segment
+    Media,
+    /// HLS media playlist referenced by a master playlist
+    Playlist,
+}
+
+/// Resource referenced by a manifest
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ManifestEntry {
+    pub uri: String,
+    pub kind: SegmentKind,
+    pub duration: Option<Duration>,
+}
+
+/// Parse an HLS playlist or a DASH MPD and list the resources it references
+pub(crate) fn parse(content: &str) -> Result<Vec<ManifestEntry>> {
+    match content.trim_start().starts_with("#EXTM3U") {
+        true => Ok(parse_hls(content)),
+        false => parse_dash(content),
+    }
+}
+
+fn hls_attribute(line: &str, name: &str) -> Option<String> {
+    let pattern = format!("{}=\"", name);
+    let start = line.find(&pattern)? + pattern.len();
+    let end = start + line[start..].find('"')?;
+    Some(line[start..end].to_string())
+}
+
+fn parse_hls(content: &str) -> Vec<ManifestEntry> {
+    let mut entries = Vec::new();
+    let mut duration = None;
+    let mut variant = false;
+
+    for line in content.lines().map(|line| line.trim()) {
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(tag) = line.strip_prefix('#') {
+            if let Some(value) = tag.strip_prefix("EXTINF:") {
+                duration = value
+                    .split(',')
+                    .next()
+                    .and_then(|d| d.trim().parse::<f64>().ok())
+                    .filter(|d| d.is_finite() && *d >= 0.0)
+                    .map(Duration::from_secs_f64);
+            } else if tag.starts_with("EXT-X-MAP:") {
+                if let Some(uri) = hls_attribute(tag, "URI") {
+                    entries.push(ManifestEntry {
+                        uri,
+                        kind: SegmentKind::Init,
+                        duration: None,
+                    });
+                }
+            } else if tag.starts_with("EXT-X-MEDIA:") {
+                if let Some(uri) = hls_attribute(tag, "URI") {
+                    entries.push(ManifestEntry {
+                        uri,
+                        kind: SegmentKind::Playlist,
+                        duration: None,
+                    });
+                }
+            } else if tag.starts_with("EXT-X-STREAM-INF:") {
+                variant = true;
+            }
+            continue;
+        }
+
+        let kind = match variant || line.ends_with(".m3u8") {
+            true => SegmentKind::Playlist,
+            false => SegmentKind::Media,
+        };
+        entries.push(ManifestEntry {
+            uri: line.to_string(),
+            kind,
+            duration: duration.take(),
+        });
+        variant = false;
+    }
+
+    entries
+}
+
+/// Maximum number of media segments listed for a SegmentTimeline of a representation
+const MAX_TIMELINE_SEGMENTS: u64 = 100_000;
+
+#[derive(Debug, Clone, Default)]
+struct SegmentTemplate {
+    media: Option<String>,
+    initialization: Option<String>,
+    start_number: Option<u64>,
+    timescale: Option<u64>,
+    presentation_time_offset: Option<u64>,
+    // (t, d, r)
+    timeline: Vec<(Option<u64>, u64, i64)>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Representation {
+    id: String,
+    bandwidth: String,
+    template: Option<SegmentTemplate>,
+    list: Vec<ManifestEntry>,
+}
+
+fn dash_attributes(e: &BytesStart) -> Result<Vec<(String, String)>> {
+    e.attributes()
+        .map(|attr| {
+            let attr = attr.map_err(|e| FluteError::new(e.to_string()))?;
+            let key = String::from_utf8_lossy(attr.key.local_name().as_ref()).into_owned();
+            let value = attr
+                .unescape_value()
+                .map_err(|e| FluteError::new(e.to_string()))?
+                .into_owned();
+            Ok((key, value))
+        })
+        .collect()
+}
+
+fn dash_attribute<'a>(attributes: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    attributes
+        .iter()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.as_str())
+}
+
+/// Expand the identifiers of a DASH SegmentTemplate (ISO/IEC 23009-1 5.3.9.4.4)
+fn expand_template(template: &str, rep: &Representation, number: u64, time: u64) -> String {
+    let mut output = String::new();
+    let mut parts = template.split('$');
+    output.push_str(parts.next().unwrap_or_default());
+    while let Some(identifier) = parts.next() {
+        let (name, format) = match identifier.find('%') {
+            Some(pos) => (&identifier[..pos], Some(&identifier[pos..])),
+            None => (identifier, None),
+        };
+        let value = match name {
+            "" => Some("$".to_string()),
+            "RepresentationID" => Some(rep.id.clone()),
+            "Bandwidth" => Some(rep.bandwidth.clone()),
+            "Number" => Some(number.to_string()),
+            "Time" => Some(time.to_string()),
+            _ => None,
+        };
+
+        match value {
+            Some(value) => {
+                let width = format
+                    .and_then(|f| f.strip_prefix("%0"))
+                    .and_then(|f| f.strip_suffix('d'))
+                    .and_then(|w| w.parse::<usize>().ok())
+                    .unwrap_or(0);
+                output.push_str(&format!("{:0>width$}", value, width = width));
+            }
+            None => {
+                output.push('$');
+                output.push_str(identifier);
+                output.push('$');
+            }
+        }
+        output.push_str(parts.next().unwrap_or_default());
+    }
+    output
+}
+
+/// Parse an `xs:duration` of the MPD (e.g. `PT1H30M2.5S`), years and months are not supported
+fn parse_xs_duration(value: &str) -> Option<Duration> {
+    let value = value.trim().strip_prefix('P')?;
+    let (date, time) = match value.split_once('T') {
+        Some((date, time)) => (date, Some(time)),
+        None => (value, None),
+    };
+
+    let mut seconds = 0.0;
+    for (part, units) in [
+        (date, &[('D', 86400.0)][..]),
+        (
+            time.unwrap_or_default(),
+            &[('H', 3600.0), ('M', 60.0), ('S', 1.0)][..],
+        ),
+    ] {
+        let mut rest = part;
+        for &(unit, factor) in units {
+            if let Some(pos) = rest.find(unit) {
+                let number: f64 = rest[..pos].parse().ok()?;
+                seconds += number * factor;
+                rest = &rest[pos + 1..];
+            }
+        }
+        if !rest.is_empty() {
+            return None;
+        }
+    }
+
+    match seconds.is_finite() && seconds >= 0.0 {
+        true => Some(Duration::from_secs_f64(seconds)),
+        false => None,
+    }
+}
+
+/// Number of segments of an `S` element, `r="-1"` repeats the segment until `end`
+fn timeline_repeat(time: u64, d: u64, r: i64, end: Option<u64>) -> Option<u64> {
+    if r >= 0 {
+        return Some(r as u64 + 1);
+    }
+    let end = end?;
+    match d {
+        0 => None,
+        _ => Some(num_integer::div_ceil(end.saturating_sub(time), d).max(1)),
+    }
+}
+
+fn expand_representation(
+    rep: &Representation,
+    period_duration: Option<Duration>,
+    output: &mut Vec<ManifestEntry>,
+) {
+    output.extend(rep.list.iter().cloned());
+    let template = match rep.template.as_ref() {
+        Some(template) => template,
+        None => return,
+    };
+
+    if let Some(initialization) = template.initialization.as_ref() {
+        output.push(ManifestEntry {
+            uri: expand_template(initialization, rep, 0, 0),
+            kind: SegmentKind::Init,
+            duration: None,
+        });
+    }
+
+    let media = match template.media.as_ref() {
+        Some(media) => media,
+        None => return,
+    };
+
+    // Without SegmentTimeline, the list of segments depends on the wall clock and is not listed
+    let timescale = template.timescale.unwrap_or(1).max(1);
+    let period_end = period_duration.map(|duration| {
+        template.presentation_time_offset.unwrap_or(0)
+            + (duration.as_secs_f64() * timescale as f64) as u64
+    });
+    let mut number = template.start_number.unwrap_or(1);
+    let mut time = 0;
+    let mut nb_segments = 0;
+    for (index, &(t, d, r)) in template.timeline.iter().enumerate() {
+        if let Some(t) = t {
+            time = t;
+        }
+
+        // A negative repeat count lasts until the next S element or the end of the Period
+        let end = match template.timeline.get(index + 1) {
+            Some(&(next_t, _, _)) => next_t,
+            None => period_end,
+        };
+        let repeat = match timeline_repeat(time, d, r, end) {
+            Some(repeat) => repeat,
+            None => {
+                log::warn!("End of the SegmentTimeline is unknown, r={} is ignored", r);
+                1
+            }
+        };
+
+        for _ in 0..repeat {
+            if nb_segments >= MAX_TIMELINE_SEGMENTS {
+                log::warn!(
+                    "SegmentTimeline of {} is truncated to {} segments",
+                    rep.id,
+                    MAX_TIMELINE_SEGMENTS
+                );
+                return;
+            }
+            output.push(ManifestEntry {
+                uri: expand_template(media, rep, number, time),
+                kind: SegmentKind::Media,
+                duration: Some(Duration::from_secs_f64(d as f64 / timescale as f64)),
+            });
+            nb_segments += 1;
+            number += 1;
+            time += d;
+        }
+    }
+}
+
+fn parse_dash(content: &str) -> Result<Vec<ManifestEntry>> {
+    let mut reader = quick_xml::Reader::from_str(content);
+    let mut entries = Vec::new();
+
+    let mut adaptation_template: Option<SegmentTemplate> = None;
+    let mut representations: Vec<Representation> = Vec::new();
+    let mut current_rep: Option<Representation> = None;
+    let mut list_duration: Option<Duration> = None;
+    let mut presentation_duration: Option<Duration> = None;
+    let mut period_duration: Option<Duration> = None;
+
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|e| FluteError::new(format!("Fail to parse MPD {:?}", e)))?;
+
+        let (e, empty) = match &event {
+            Event::Start(e) => (e, false),
+            Event::Empty(e) => (e, true),
+            Event::End(e) => {
+                match e.local_name().as_ref() {
+                    b"Representation" => {
+                        if let Some(rep) = current_rep.take() {
+                            representations.push(rep);
+                        }
+                    }
+                    b"AdaptationSet" => {
+                        for mut rep in representations.drain(..) {
+                            if rep.template.is_none() {
+                                rep.template = adaptation_template.clone();
+                            }
+                            expand_representation(&rep, period_duration, &mut entries);
+                        }
+                        adaptation_template = None;
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+            Event::Eof => break,
+            _ => continue,
+        };
+
+        let attributes = dash_attributes(e)?;
+        match e.local_name().as_ref() {
+            b"MPD" => {
+                presentation_duration = dash_attribute(&attributes, "mediaPresentationDuration")
+                    .and_then(parse_xs_duration);
+            }
+            b"Period" => {
+                // Without duration, the Period ends with the presentation
+                let start = dash_attribute(&attributes, "start").and_then(parse_xs_duration);
+                period_duration = dash_attribute(&attributes, "duration")
+                    .and_then(parse_xs_duration)
+                    .or_else(|| {
+                        presentation_duration
+                            .map(|duration| duration.saturating_sub(start.unwrap_or_default()))
+                    });
+            }
+            b"AdaptationSet" => {
+                adaptation_template = None;
+                representations.clear();
+            }
+            b"Representation" => {
+                let rep = Representation {
+                    id: dash_attribute(&attributes, "id")
+                        .unwrap_or_default()
+                        .to_string(),
+                    bandwidth: dash_attribute(&attributes, "bandwidth")
+                        .unwrap_or_default()
+                        .to_string(),
+                    template: None,
+                    list: Vec::new(),
+                };
+                match empty {
+                    true => representations.push(rep),
+                    false => current_rep = Some(rep),
+                }
+            }
+            b"SegmentTemplate" => {
+                let parse = |name| dash_attribute(&attributes, name).and_then(|v| v.parse().ok());
+                let template = SegmentTemplate {
+                    media: dash_attribute(&attributes, "media").map(|s| s.to_string()),
+                    initialization: dash_attribute(&attributes, "initialization")
+                        .map(|s| s.to_string()),
+                    start_number: parse("startNumber"),
+                    timescale: parse("timescale"),
+                    presentation_time_offset: parse("presentationTimeOffset"),
+                    timeline: Vec::new(),
+                };
+                match current_rep.as_mut() {
+                    Some(rep) => rep.template = Some(template),
+                    None => adaptation_template = Some(template),
+                }
+            }
+            b"S" => {
+                let parse = |name| dash_attribute(&attributes, name).and_then(|v| v.parse().ok());
+                let d: u64 = match parse("d") {
+                    Some(d) => d,
+                    None => continue,
+                };
+                let r = dash_attribute(&attributes, "r")
+                    .and_then(|v| v.parse::<i64>().ok())
+                    .unwrap_or(0);
+                let template = match current_rep.as_mut() {
+                    Some(rep) => rep.template.as_mut(),
+                    None => adaptation_template.as_mut(),
+                };
+                if let Some(template) = template {
+                    template.timeline.push((parse("t"), d, r));
+                }
+            }
+            b"SegmentList" => {
+                let duration: Option<u64> =
+                    dash_attribute(&attributes, "duration").and_then(|v| v.parse().ok());
+                let timescale: u64 = dash_attribute(&attributes, "timescale")
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(1);
+                list_duration =
+                    duration.map(|d| Duration::from_secs_f64(d as f64 / timescale.max(1) as f64));
+            }
+            b"Initialization" | b"SegmentURL" => {
+                let (name, kind, duration) = match e.local_name().as_ref() {
+                    b"Initialization" => ("sourceURL", SegmentKind::Init, None),
+                    _ => ("media", SegmentKind::Media, list_duration),
+                };
+                let uri = match dash_attribute(&attributes, name) {
+                    Some(uri) => uri.to_string(),
+                    None => continue,
+                };
+                let entry = ManifestEntry {
+                    uri,
+                    kind,
+                    duration,
+                };
+                match current_rep.as_mut() {
+                    Some(rep) => rep.list.push(entry),
+                    None => entries.push(entry),
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Remove duplicates (init segment shared by several representations)
+    let mut uris = HashSet::new();
+    entries.retain(|entry| uris.insert(entry.uri.clone()));
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SegmentKind;
+    use std::time::Duration;
+
+    #[test]
+    pub fn test_manifest_hls() {
+        crate::tests::init();
+        let master = "#EXTM3U\n#EXT-X-MEDIA:TYPE=AUDIO,GROUP-ID=\"aud\",URI=\"audio/index.m3u8\"\n#EXT-X-STREAM-INF:BANDWIDTH=800000\nvideo/index\n";
+        let entries = super::parse(master).unwrap();
+        assert!(entries.len() == 2);
+        assert!(entries.iter().all(|e| e.kind == SegmentKind::Playlist));
+
+        let media = "#EXTM3U\n#EXT-X-TARGETDURATION:4\n#EXT-X-MAP:URI=\"init.mp4\"\n#EXTINF:4.000,\nseg-1.m4s\n#EXTINF:2.5,\nseg-2.m4s\n";
+        let entries = super::parse(media).unwrap();
+        assert!(entries.len() == 3);
+        assert!(entries[0].uri == "init.mp4" && entries[0].kind == SegmentKind::Init);
+        assert!(entries[1].uri == "seg-1.m4s" && entries[1].kind == SegmentKind::Media);
+        assert!(entries[1].duration == Some(Duration::from_secs(4)));
+        assert!(entries[2].duration == Some(Duration::from_millis(2500)));
+    }
+
+    #[test]
+    pub fn test_manifest_dash() {
+        crate::tests::init();
+        let mpd = r#"<?xml version="1.0"?>
+<MPD xmlns="urn:mpeg:dash:schema:mpd:2011" type="dynamic">
+  <Period id="0">
+    <AdaptationSet mimeType="video/mp4">
+      <SegmentTemplate timescale="1000" initialization="$RepresentationID$/init.mp4" media="$RepresentationID$/seg-$Number%03d$.m4s" startNumber="10">
+        <SegmentTimeline>
+          <S t="0" d="2000" r="1"/>
+          <S d="1000"/>
+        </SegmentTimeline>
+      </SegmentTemplate>
+      <Representation id="v1" bandwidth="1000000"/>
+      <Representation id="v2" bandwidth="2000000"/>
+    </AdaptationSet>
+    <AdaptationSet mimeType="audio/mp4">
+      <Representation id="a1" bandwidth="64000">
+        <SegmentList timescale="10" duration="40">
+          <Initialization sourceURL="audio/init.mp4"/>
+          <SegmentURL media="audio/1.m4s"/>
+        </SegmentList>
+      </Representation>
+    </AdaptationSet>
+  </Period>
+</MPD>"#;
+        let entries = super::parse(mpd).unwrap();
+        let uris: Vec<&str> = entries.iter().map(|e| e.uri.as_str()).collect();
+        assert!(
+            uris == vec![
+                "v1/init.mp4",
+                "v1/seg-010.m4s",
+                "v1/seg-011.m4s",
+                "v1/seg-012.m4s",
+                "v2/init.mp4",
+                "v2/seg-010.m4s",
+                "v2/seg-011.m4s",
+                "v2/seg-012.m4s",
+                "audio/init.mp4",
+                "audio/1.m4s",
+            ]
+        );
+        assert!(entries[1].duration == Some(Duration::from_secs(2)));
+        assert!(entries[3].duration == Some(Duration::from_secs(1)));
+        assert!(entries[9].duration == Some(Duration::from_secs(4)));
+    }
+
+    #[test]
+    pub fn test_manifest_dash_repeat_until_end() {
+        crate::tests::init();
+        let mpd = r#"<?xml version="1.0"?>
+<MPD xmlns="urn:mpeg:dash:schema:mpd:2011" type="static" mediaPresentationDuration="PT1M">
+  <Period id="0" duration="PT10S">
+    <AdaptationSet mimeType="video/mp4">
+      <SegmentTemplate timescale="1000" media="v/$Time$.m4s" presentationTimeOffset="1000">
+        <SegmentTimeline>
+          <S t="1000" d="2000" r="-1"/>
+          <S t="6000" d="1500" r="-1"/>
+        </SegmentTimeline>
+      </SegmentTemplate>
+      <Representation id="v" bandwidth="1000000"/>
+    </AdaptationSet>
+  </Period>
+  <Period id="1" start="PT50S">
+    <AdaptationSet mimeType="audio/mp4">
+      <SegmentTemplate timescale="1" media="a/$Number$.m4s">
+        <SegmentTimeline>
+          <S d="4" r="-1"/>
+        </SegmentTimeline>
+      </SegmentTemplate>
+      <Representation id="a" bandwidth="64000"/>
+    </AdaptationSet>
+  </Period>
+</MPD>"#;
+        let entries = super::parse(mpd).unwrap();
+        let uris: Vec<&str> = entries.iter().map(|e| e.uri.as_str()).collect();
+        assert!(
+            uris == vec![
+                "v/1000.m4s",
+                "v/3000.m4s",
+                "v/5000.m4s",
+                "v/6000.m4s",
+                "v/7500.m4s",
+                "v/9000.m4s",
+                "v/10500.m4s",
+                "a/1.m4s",
+                "a/2.m4s",
+                "a/3.m4s",
+            ]
+        );
+    }
+
+    #[test]
+    pub fn test_manifest_dash_repeat_limit() {
+        crate::tests::init();
+        let mpd = r#"<?xml version="1.0"?>
+<MPD xmlns="urn:mpeg:dash:schema:mpd:2011" type="dynamic">
+  <Period id="0">
+    <AdaptationSet mimeType="video/mp4">
+      <SegmentTemplate media="$Number$.m4s">
+        <SegmentTimeline>
+          <S d="1" r="-1"/>
+          <S d="1" r="4000000000"/>
+        </SegmentTimeline>
+      </SegmentTemplate>
+      <Representation id="v" bandwidth="1000000"/>
+    </AdaptationSet>
+  </Period>
+</MPD>"#;
+        let entries = super::parse(mpd).unwrap();
+        // The end of the first S is unknown, it is listed once
+        assert!(entries[0].uri == "1.m4s" && entries[1].uri == "2.m4s");
+        assert!(entries.len() as u64 == super::MAX_TIMELINE_SEGMENTS);
+    }
+
+    #[test]
+    pub fn test_manifest_xs_duration() {
+        crate::tests::init();
+        assert!(super::parse_xs_duration("PT10S") == Some(Duration::from_secs(10)));
+        assert!(super::parse_xs_duration("PT1H2M3.5S") == Some(Duration::from_millis(3_723_500)));
+        assert!(super::parse_xs_duration("P1DT1S") == Some(Duration::from_secs(86_401)));
+        assert!(super::parse_xs_duration("P1Y").is_none());
+        assert!(super::parse_xs_duration("10S").is_none());
+    }
+}
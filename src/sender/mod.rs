@@ -3,11 +3,17 @@
 //!
 
 mod block;
+mod blockcache;
 mod blockencoder;
+mod blockencoderpool;
+mod directorysender;
 mod fdt;
 mod filedesc;
+mod livestream;
+mod manifest;
 mod objectdesc;
 mod observer;
+mod segmentsender;
 mod sender;
 mod sendersession;
 mod toiallocator;
@@ -17,7 +23,12 @@ mod objectsenderlogger;
 
 pub mod compress;
 pub use crate::common::Profile;
+pub use directorysender::DirectoryConfig;
+pub use directorysender::DirectorySender;
+pub use livestream::LiveStream;
 pub use objectdesc::CacheControl;
+pub use objectdesc::CompressedStreamOptions;
+pub use objectdesc::FecCache;
 pub use objectdesc::ObjectDesc;
 pub use objectdesc::ObjectDataSource;
 pub use objectdesc::ObjectDataStream;
@@ -27,6 +38,11 @@ pub use objectdesc::CarouselRepeatMode;
 pub use observer::Event;
 pub use observer::FileInfo;
 pub use observer::Subscriber;
+pub use segmentsender::SegmentConfig;
+pub use segmentsender::SegmentSender;
+pub use crate::common::segment::SEGMENT_GROUP_INIT;
+pub use crate::common::segment::SEGMENT_GROUP_MANIFEST;
+pub use crate::common::segment::SEGMENT_GROUP_MEDIA;
 pub use sender::Config;
 pub use sender::PriorityQueue;
 pub use sender::Sender;
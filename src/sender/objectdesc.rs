@@ -1,7 +1,9 @@
 use base64::Engine;
 
 use super::compress;
+use super::livestream::LiveStream;
 use super::toiallocator::Toi;
+use crate::common::objectpart::ObjectPart;
 use crate::common::{fdtinstance, lct, oti};
 use crate::error::FluteError;
 use crate::tools;
@@ -10,7 +12,7 @@ use std::collections::HashMap;
 use std::ffi::OsStr;
 use std::io::BufReader;
 use std::io::{Read, Seek};
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 use std::time::SystemTime;
 
 /// Cache Control
@@ -111,13 +113,58 @@ impl dyn ObjectDataStreamTrait + '_ {
 /// Boxed Object Data Stream
 pub type ObjectDataStream = Box<dyn ObjectDataStreamTrait>;
 
+///
+/// Range of bytes of a stream shared between several objects
+///
+#[derive(Debug)]
+struct ObjectDataStreamPart {
+    stream: Arc<Mutex<ObjectDataStream>>,
+    offset: u64,
+    length: u64,
+    position: u64,
+}
+
+impl std::io::Read for ObjectDataStreamPart {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let left = self.length.saturating_sub(self.position);
+        let size = (buf.len() as u64).min(left) as usize;
+        if size == 0 {
+            return Ok(0);
+        }
+
+        let mut stream = self.stream.lock().unwrap();
+        stream.seek(std::io::SeekFrom::Start(self.offset + self.position))?;
+        let size = stream.read(&mut buf[..size])?;
+        self.position += size as u64;
+        Ok(size)
+    }
+}
+
+impl std::io::Seek for ObjectDataStreamPart {
+    fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+        let position = match pos {
+            std::io::SeekFrom::Start(pos) => Some(pos),
+            std::io::SeekFrom::End(delta) => self.length.checked_add_signed(delta),
+            std::io::SeekFrom::Current(delta) => self.position.checked_add_signed(delta),
+        };
+
+        self.position = position.ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::InvalidInput, "Invalid seek position")
+        })?;
+        Ok(self.position)
+    }
+}
+
 /// Object Data Source
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum ObjectDataSource {
     /// Source from a stream
     Stream(Mutex<ObjectDataStream>),
     /// Source from a buffer
     Buffer(Vec<u8>),
+    /// Source from a live stream, its length is known at the end of the transfer
+    Live(LiveStream),
 }
 
 impl ObjectDataSource {
@@ -146,16 +193,87 @@ impl ObjectDataSource {
         ObjectDataSource::Stream(Mutex::new(stream))
     }
 
+    /// Create an Object Data Source from a stream compressed with `cenc`
+    ///
+    /// The compressed content is written to a temporary file created inside `temp_dir`,
+    /// the file is removed when the source is dropped.
+    pub fn from_stream_compressed(
+        stream: &mut dyn std::io::Read,
+        cenc: lct::Cenc,
+        temp_dir: &std::path::Path,
+    ) -> Result<Self> {
+        let file = compress::CompressedTempFile::new(stream, cenc, temp_dir)?;
+        Ok(Self::from_stream(Box::new(file)))
+    }
+
     fn len(&mut self) -> Result<u64> {
         match self {
             ObjectDataSource::Buffer(buffer) => Ok(buffer.len() as u64),
             ObjectDataSource::Stream(stream) => {
-                let mut stream = stream.lock().unwrap();
+                let stream = stream.get_mut().unwrap();
                 let current_pos = stream.stream_position()?;
                 let end_pos = stream.seek(std::io::SeekFrom::End(0))?;
                 stream.seek(std::io::SeekFrom::Start(current_pos))?;
                 Ok(end_pos)
             }
+            ObjectDataSource::Live(stream) => Ok(stream.bytes_written()),
+        }
+    }
+}
+
+///
+/// Storage of the FEC encoded symbols of an object between two transfers
+///
+#[derive(Debug, Clone, PartialEq)]
+pub enum FecCache {
+    /// Encoding symbols are kept in memory
+    Memory,
+    /// Encoding symbols are written to a spill file created inside this directory.
+    /// The file is removed when the object is released by the sender.
+    File(std::path::PathBuf),
+}
+
+///
+/// Options of `ObjectDesc::create_from_compressed_stream()`
+///
+#[derive(Debug, Clone)]
+pub struct CompressedStreamOptions {
+    /// Maximum number of transfers of the object
+    pub max_transfer_count: u32,
+    /// Carousel mode, `None` to transfer the object `max_transfer_count` times
+    pub carousel_mode: Option<CarouselRepeatMode>,
+    /// Target acquisition time of the object
+    pub target_acquisition: Option<TargetAcquisition>,
+    /// Cache control of the object
+    pub cache_control: Option<CacheControl>,
+    /// Groups of the object
+    pub groups: Option<Vec<String>>,
+    /// Content encoding, `lct::Cenc::Null` to send the stream as is
+    pub cenc: lct::Cenc,
+    /// Send the content encoding in-band with `EXT_CENC`
+    pub inband_cenc: bool,
+    /// OTI of the object, `None` to use the OTI of the sender
+    pub oti: Option<oti::Oti>,
+    /// Compute the MD5 of the stream
+    pub md5: bool,
+    /// Directory of the temporary file containing the compressed stream.
+    /// `None` : `std::env::temp_dir()`
+    pub temp_dir: Option<std::path::PathBuf>,
+}
+
+impl Default for CompressedStreamOptions {
+    fn default() -> Self {
+        Self {
+            max_transfer_count: 1,
+            carousel_mode: None,
+            target_acquisition: None,
+            cache_control: None,
+            groups: None,
+            cenc: lct::Cenc::Null,
+            inband_cenc: true,
+            oti: None,
+            md5: false,
+            temp_dir: None,
         }
     }
 }
@@ -185,15 +303,22 @@ pub struct ObjectDesc {
     pub content_type: String,
     /// Size of the object (uncompressed)
     /// as defined in [rfc2616 14.13](https://www.rfc-editor.org/rfc/rfc2616#section-14.13)
+    /// Set to 0 for a live object
     pub content_length: u64,
     /// Size of the object after transfer-coding (`Cenc`) has been applied
     /// as defined in [rfc2616 4.4](https://www.rfc-editor.org/rfc/rfc2616#section-4.4)
+    /// Set to 0 for a live object, the length is given by `LiveStream::transfer_length()` at the end of the stream
     pub transfer_length: u64,
     /// Content Encoding (compression)
     pub cenc: lct::Cenc,
     /// If `true`, Cenc extension are added to ALC/LCT packet
     /// Else Cenc is defined only inside the FDT
     pub inband_cenc: bool,
+    /// If `true`, the Transfer Object Length (EXT_TOL) is added to ALC/LCT packets,
+    /// so the receiver knows the transfer length without the FTI or the FDT
+    pub inband_tol: bool,
+    /// Header extensions of the application added to every ALC/LCT packet of the object
+    pub(crate) lct_extensions: Vec<lct::LctExtension>,
     /// the MD5 sum of this object. Can be used by the FLUTE `receiver`to validate the integrity of the reception
     pub md5: Option<String>,
     /// If defined, FEC Object Transmission Information (OTI) overload the default OTI defined in the FDT
@@ -212,7 +337,7 @@ pub struct ObjectDesc {
     pub transfer_start_time: Option<SystemTime>,
     /// Define object cache control
     pub cache_control: Option<CacheControl>,
-    /// Add file to a list of groups
+    /// Add file to a list of groups, sent in the `Group` elements of the file in the FDT
     pub groups: Option<Vec<String>>,
     /// Assign an optional TOI to this object
     pub toi: Option<Box<Toi>>,
@@ -223,6 +348,12 @@ pub struct ObjectDesc {
     /// If `true`, the object can be stopped immediately before the first transfer
     /// if `false` (default) then transfer is stopped only after being transferred at least once
     pub allow_immediate_stop_before_first_transfer: Option<bool>,
+    /// If defined, the FEC encoding symbols of the first transfer are kept and reused by the next transfers
+    /// (`carousel_mode` or `max_transfer_count` > 1) instead of encoding the object again.
+    /// With RaptorQ and Raptor, each transfer sends different repair symbols.
+    pub fec_cache: Option<FecCache>,
+    /// Set when the object is a part of a larger object split by the sender
+    pub part: Option<ObjectPart>,
 }
 
 impl ObjectDesc {
@@ -237,6 +368,144 @@ impl ObjectDesc {
         self.toi = Some(toi);
     }
 
+    /// Add the Transfer Object Length (EXT_TOL) to the ALC/LCT packets of this object.
+    ///
+    /// The `create_*` functions disable it by default.
+    pub fn set_inband_tol(&mut self, inband_tol: bool) {
+        self.inband_tol = inband_tol;
+    }
+
+    /// Add header extensions of the application to every ALC/LCT packet of this object.
+    ///
+    /// The size of the extensions must be taken into account in the encoding symbol length of the OTI,
+    /// `Sender::add_object()` fails if the packets do not fit in an IP packet.
+    pub fn set_lct_extensions(&mut self, lct_extensions: Vec<lct::LctExtension>) {
+        self.lct_extensions = lct_extensions;
+    }
+
+    /// Header extensions of the application added to every ALC/LCT packet of this object
+    pub fn lct_extensions(&self) -> &[lct::LctExtension] {
+        &self.lct_extensions
+    }
+
+    /// Return the stream of a live object
+    pub(crate) fn live_stream(&self) -> Option<&LiveStream> {
+        match &self.source {
+            ObjectDataSource::Live(stream) => Some(stream),
+            _ => None,
+        }
+    }
+
+    /// Split the object in several parts of maximum `max_part_length` bytes.
+    ///
+    /// Each part is announced in the FDT as a separate object,
+    /// with the information required by the receiver to re-assemble the whole object.
+    pub(crate) fn split(self: Box<Self>, max_part_length: u64) -> Result<Vec<ObjectDesc>> {
+        if self.cenc != lct::Cenc::Null {
+            return Err(FluteError::new(
+                "Compressed object cannot be split in several parts",
+            ));
+        }
+
+        if max_part_length == 0 {
+            return Err(FluteError::new("Maximum length of a part is 0"));
+        }
+
+        let object = *self;
+        let nb_parts = num_integer::div_ceil(object.transfer_length, max_part_length).max(1);
+        let nb_parts: u32 = nb_parts
+            .try_into()
+            .map_err(|_| FluteError::new("Too many parts"))?;
+
+        let sources: Vec<ObjectDataSource> = match object.source {
+            ObjectDataSource::Buffer(mut buffer) => {
+                let mut buffers = Vec::new();
+                for index in (1..nb_parts as u64).rev() {
+                    buffers.push(buffer.split_off((index * max_part_length) as usize));
+                }
+                buffer.shrink_to_fit();
+                buffers.push(buffer);
+                buffers.reverse();
+                buffers.into_iter().map(ObjectDataSource::Buffer).collect()
+            }
+            ObjectDataSource::Stream(stream) => {
+                let stream = Arc::new(stream);
+                (0..nb_parts as u64)
+                    .map(|index| {
+                        let offset = index * max_part_length;
+                        ObjectDataSource::from_stream(Box::new(ObjectDataStreamPart {
+                            stream: stream.clone(),
+                            offset,
+                            length: max_part_length.min(object.transfer_length - offset),
+                            position: 0,
+                        }))
+                    })
+                    .collect()
+            }
+            ObjectDataSource::Live(_) => {
+                return Err(FluteError::new(
+                    "Live object cannot be split in several parts",
+                ))
+            }
+        };
+
+        let group = object.content_location.to_string();
+        let mut toi = object.toi;
+        let mut parts = Vec::new();
+        for (index, mut source) in sources.into_iter().enumerate() {
+            let length = source.len()?;
+            let md5 = match object.md5.is_some() {
+                true => Some(match &mut source {
+                    ObjectDataSource::Buffer(buffer) => {
+                        base64::engine::general_purpose::STANDARD.encode(md5::compute(buffer).0)
+                    }
+                    ObjectDataSource::Stream(stream) => stream.get_mut().unwrap().md5_base64()?,
+                    ObjectDataSource::Live(_) => unreachable!(),
+                }),
+                false => None,
+            };
+
+            let mut content_location = object.content_location.clone();
+            content_location.set_path(&format!("{}.part{}", object.content_location.path(), index));
+
+            parts.push(ObjectDesc {
+                content_location,
+                source,
+                content_type: object.content_type.clone(),
+                content_length: length,
+                transfer_length: length,
+                cenc: object.cenc,
+                inband_cenc: object.inband_cenc,
+                inband_tol: object.inband_tol,
+                lct_extensions: object.lct_extensions.clone(),
+                md5,
+                oti: object.oti.clone(),
+                max_transfer_count: object.max_transfer_count,
+                target_acquisition: object.target_acquisition.clone(),
+                carousel_mode: object.carousel_mode,
+                transfer_start_time: object.transfer_start_time,
+                cache_control: object.cache_control,
+                groups: object.groups.clone(),
+                toi: toi.take(),
+                optel_propagator: object.optel_propagator.clone(),
+                e_tag: object.e_tag.clone(),
+                allow_immediate_stop_before_first_transfer: object
+                    .allow_immediate_stop_before_first_transfer,
+                fec_cache: object.fec_cache.clone(),
+                part: Some(ObjectPart {
+                    group: group.clone(),
+                    index: index as u32,
+                    count: nb_parts,
+                    offset: index as u64 * max_part_length,
+                    group_content_length: object.transfer_length,
+                    group_md5: object.md5.clone(),
+                }),
+            });
+        }
+
+        Ok(parts)
+    }
+
     /// Return an `ObjectDesc` from a file
     pub fn create_from_file(
         path: &std::path::Path,
@@ -282,31 +551,30 @@ impl ObjectDesc {
                 md5,
             )
         } else {
-            if cenc != lct::Cenc::Null {
-                return Err(FluteError::new(
-                    "Compressed object is not compatible with file path",
-                ));
-            }
             let file = std::fs::File::open(path)?;
-            Self::create_from_stream(
+            Self::create_from_compressed_stream(
                 Box::new(file),
                 content_type,
                 &content_location,
-                max_transfer_count,
-                carousel_mode,
-                target_acquisition,
-                cache_control,
-                groups,
-                inband_cenc,
-                oti,
-                md5,
+                CompressedStreamOptions {
+                    max_transfer_count,
+                    carousel_mode,
+                    target_acquisition,
+                    cache_control,
+                    groups,
+                    cenc,
+                    inband_cenc,
+                    oti,
+                    md5,
+                    temp_dir: None,
+                },
             )
         }
     }
 
     /// Create an Object Description from a stream
     pub fn create_from_stream(
-        mut stream: ObjectDataStream,
+        stream: ObjectDataStream,
         content_type: &str,
         content_location: &url::Url,
         max_transfer_count: u32,
@@ -318,22 +586,81 @@ impl ObjectDesc {
         oti: Option<oti::Oti>,
         md5: bool,
     ) -> Result<Box<ObjectDesc>> {
+        Self::create_from_compressed_stream(
+            stream,
+            content_type,
+            content_location,
+            CompressedStreamOptions {
+                max_transfer_count,
+                carousel_mode,
+                target_acquisition,
+                cache_control,
+                groups,
+                cenc: lct::Cenc::Null,
+                inband_cenc,
+                oti,
+                md5,
+                temp_dir: None,
+            },
+        )
+    }
+
+    /// Create an Object Description from a stream compressed with `cenc`
+    ///
+    /// The stream is compressed to a temporary file created in `options.temp_dir`,
+    /// so the object is never loaded entirely in memory.
+    /// The temporary file is removed when the object is released by the sender.
+    /// With `lct::Cenc::Null`, the stream is sent as is.
+    pub fn create_from_compressed_stream(
+        mut stream: ObjectDataStream,
+        content_type: &str,
+        content_location: &url::Url,
+        options: CompressedStreamOptions,
+    ) -> Result<Box<ObjectDesc>> {
+        let CompressedStreamOptions {
+            max_transfer_count,
+            carousel_mode,
+            target_acquisition,
+            cache_control,
+            groups,
+            cenc,
+            inband_cenc,
+            oti,
+            md5,
+            temp_dir,
+        } = options;
+
         let md5 = match md5 {
             true => Some(stream.md5_base64()?),
             false => None,
         };
 
         let mut source = ObjectDataSource::from_stream(stream);
+        let content_length = source.len()?;
+
+        if cenc != lct::Cenc::Null {
+            let stream = match source {
+                ObjectDataSource::Stream(stream) => stream.into_inner().unwrap(),
+                ObjectDataSource::Buffer(_) | ObjectDataSource::Live(_) => unreachable!(),
+            };
+            let mut reader = BufReader::new(stream);
+            reader.rewind()?;
+            let temp_dir = temp_dir.unwrap_or_else(std::env::temp_dir);
+            source = ObjectDataSource::from_stream_compressed(&mut reader, cenc, &temp_dir)?;
+        }
+
         let transfer_length = source.len()?;
 
         Ok(Box::new(ObjectDesc {
             content_location: content_location.clone(),
             source,
             content_type: content_type.to_string(),
-            content_length: transfer_length,
+            content_length,
             transfer_length,
-            cenc: lct::Cenc::Null,
+            cenc,
             inband_cenc,
+            inband_tol: false,
+            lct_extensions: Vec::new(),
             md5,
             oti,
             max_transfer_count,
@@ -346,6 +673,50 @@ impl ObjectDesc {
             optel_propagator: None,
             e_tag: None,
             allow_immediate_stop_before_first_transfer: None,
+            fec_cache: None,
+            part: None,
+        }))
+    }
+
+    /// Create an Object Description from a live stream
+    ///
+    /// The object is transferred while its content is being produced,
+    /// a source block is sent each time enough data has been written to the stream.
+    /// The object is transferred once and cannot be compressed.
+    ///
+    /// Live objects are supported with the `NoCode` and `ReedSolomon GF(2^8)` FEC schemes.
+    pub fn create_from_live_stream(
+        stream: LiveStream,
+        content_type: &str,
+        content_location: &url::Url,
+        cache_control: Option<CacheControl>,
+        groups: Option<Vec<String>>,
+        oti: Option<oti::Oti>,
+    ) -> Result<Box<ObjectDesc>> {
+        Ok(Box::new(ObjectDesc {
+            content_location: content_location.clone(),
+            source: ObjectDataSource::Live(stream),
+            content_type: content_type.to_string(),
+            content_length: 0,
+            transfer_length: 0,
+            cenc: lct::Cenc::Null,
+            inband_cenc: false,
+            inband_tol: false,
+            lct_extensions: Vec::new(),
+            md5: None,
+            oti,
+            max_transfer_count: 1,
+            carousel_mode: None,
+            target_acquisition: None,
+            transfer_start_time: None,
+            cache_control,
+            groups,
+            toi: None,
+            optel_propagator: None,
+            e_tag: None,
+            allow_immediate_stop_before_first_transfer: None,
+            fec_cache: None,
+            part: None,
         }))
     }
 
@@ -415,6 +786,8 @@ impl ObjectDesc {
             transfer_length,
             cenc,
             inband_cenc,
+            inband_tol: false,
+            lct_extensions: Vec::new(),
             md5,
             oti,
             max_transfer_count,
@@ -427,6 +800,8 @@ impl ObjectDesc {
             optel_propagator: None,
             e_tag: None,
             allow_immediate_stop_before_first_transfer: None,
+            fec_cache: None,
+            part: None,
         }))
     }
 }
@@ -0,0 +1,455 @@
+use super::manifest::{self, SegmentKind};
+use super::objectdesc::{CarouselRepeatMode, ObjectDesc, TargetAcquisition};
+use super::sender::{Config, FDTPublishMode, Sender};
+use crate::common::segment::{SEGMENT_GROUP_INIT, SEGMENT_GROUP_MANIFEST, SEGMENT_GROUP_MEDIA};
+use crate::common::{lct, oti};
+use crate::core::UDPEndpoint;
+use crate::error::FluteError;
+use crate::tools::error::Result;
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+///
+/// Configuration of a `SegmentSender`
+///
+#[derive(Debug, Clone)]
+pub struct SegmentConfig {
+    /// Path of the DASH MPD or of the HLS playlist (master or media) written by the packager.
+    /// The segments are read relatively to the directory of the manifest.
+    pub manifest: PathBuf,
+    /// Base URL of the objects.
+    /// The Content-Location of a segment is its URI inside the manifest joined to this URL.
+    /// The URL must end with a `/`
+    pub base_url: url::Url,
+    /// Priority queue used to transfer the objects
+    pub priority: u32,
+    /// Interval between two reads of the manifest
+    pub scan_interval: Duration,
+    /// Carousel of the manifests
+    pub manifest_carousel_mode: CarouselRepeatMode,
+    /// Carousel of the initialization segments
+    pub init_carousel_mode: CarouselRepeatMode,
+    /// Content Encoding (compression) of the manifests
+    pub manifest_cenc: lct::Cenc,
+    /// Add the MD5 of the objects to the FDT
+    pub md5: bool,
+}
+
+impl Default for SegmentConfig {
+    fn default() -> Self {
+        Self {
+            manifest: PathBuf::from("manifest.mpd"),
+            base_url: url::Url::parse("file:///").unwrap(),
+            priority: 0,
+            scan_interval: Duration::from_millis(500),
+            manifest_carousel_mode: CarouselRepeatMode::DelayBetweenTransfers(Duration::from_secs(
+                1,
+            )),
+            init_carousel_mode: CarouselRepeatMode::DelayBetweenTransfers(Duration::from_secs(1)),
+            manifest_cenc: lct::Cenc::Null,
+            md5: true,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct SentSegment {
+    toi: u128,
+}
+
+#[derive(Debug)]
+struct SentManifest {
+    toi: u128,
+    content: Vec<u8>,
+}
+
+///
+/// FLUTE `Sender` broadcasting a live MPEG-DASH or HLS stream
+///
+/// The manifest written by the packager is read periodically.
+/// - Initialization segments are transferred in carousel.
+/// - Media segments are transferred once, within their duration, so a segment is received
+///   before the next one becomes available at the live edge.
+/// - Manifests are transferred in carousel and replaced each time their content changes.
+/// - Segments that are not referenced anymore by the manifest are removed from the FDT.
+///
+/// Objects are tagged with the FDT groups `SEGMENT_GROUP_MANIFEST`, `SEGMENT_GROUP_INIT`
+/// and `SEGMENT_GROUP_MEDIA`.
+///
+#[derive(Debug)]
+pub struct SegmentSender {
+    sender: Sender,
+    config: SegmentConfig,
+    fdt_publish_mode: FDTPublishMode,
+    segments: BTreeMap<String, SentSegment>,
+    manifests: BTreeMap<String, SentManifest>,
+    next_scan: Option<SystemTime>,
+}
+
+impl SegmentSender {
+    ///
+    /// Creation of a `SegmentSender`
+    ///
+    pub fn new(
+        endpoint: UDPEndpoint,
+        tsi: u64,
+        oti: &oti::Oti,
+        sender_config: &Config,
+        config: SegmentConfig,
+    ) -> Result<SegmentSender> {
+        if config.manifest.file_name().is_none() {
+            return Err(FluteError::new(format!(
+                "{:?} is not a valid manifest path",
+                config.manifest
+            )));
+        }
+
+        if config.base_url.cannot_be_a_base() {
+            return Err(FluteError::new(format!(
+                "{} cannot be used as a base URL",
+                config.base_url
+            )));
+        }
+
+        Ok(SegmentSender {
+            sender: Sender::new(endpoint, tsi, oti, sender_config),
+            config,
+            fdt_publish_mode: sender_config.fdt_publish_mode,
+            segments: BTreeMap::new(),
+            manifests: BTreeMap::new(),
+            next_scan: None,
+        })
+    }
+
+    /// Get the FLUTE `Sender`
+    pub fn sender(&self) -> &Sender {
+        &self.sender
+    }
+
+    /// Get the FLUTE `Sender`
+    pub fn sender_mut(&mut self) -> &mut Sender {
+        &mut self.sender
+    }
+
+    /// Return the TOI of a segment or of a manifest
+    ///
+    /// # Arguments
+    ///
+    /// * `uri` - URI of the segment relative to the directory of the manifest
+    pub fn get_toi(&self, uri: &str) -> Option<u128> {
+        self.segments
+            .get(uri)
+            .map(|segment| segment.toi)
+            .or_else(|| self.manifests.get(uri).map(|manifest| manifest.toi))
+    }
+
+    /// Read the manifest, add the new segments and update the FDT
+    ///
+    /// # Returns
+    ///
+    /// `true` if the FDT has been modified
+    pub fn ingest(&mut self, now: SystemTime) -> Result<bool> {
+        let manifest_name = self
+            .config
+            .manifest
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .into_owned();
+        let root = self
+            .config
+            .manifest
+            .parent()
+            .map(|p| p.to_path_buf())
+            .unwrap_or_default();
+
+        let mut changed = false;
+        let mut referenced = HashSet::new();
+        let mut manifests = Vec::new();
+        let mut pending = vec![manifest_name];
+
+        while let Some(manifest_uri) = pending.pop() {
+            let content = std::fs::read(root.join(&manifest_uri))?;
+            let entries = manifest::parse(&String::from_utf8_lossy(&content))?;
+            let manifest_dir = Self::parent_uri(&manifest_uri);
+
+            for entry in entries {
+                let uri = format!("{}{}", manifest_dir, entry.uri);
+                if Self::relative_path(&uri).is_none() {
+                    log::warn!("Ignore {}, not a local relative URI", uri);
+                    continue;
+                }
+
+                if entry.kind == SegmentKind::Playlist {
+                    if !manifests.iter().any(|(m, _)| m == &uri) && !pending.contains(&uri) {
+                        pending.push(uri);
+                    }
+                    continue;
+                }
+
+                referenced.insert(uri.clone());
+                match self.add_segment(&root, &uri, entry.kind, entry.duration, now) {
+                    Ok(true) => changed = true,
+                    Ok(false) => {}
+                    Err(e) => log::warn!("Fail to add segment {} {:?}", uri, e),
+                }
+            }
+            manifests.push((manifest_uri, content));
+        }
+
+        let removed: Vec<String> = self
+            .segments
+            .keys()
+            .filter(|uri| !referenced.contains(*uri))
+            .cloned()
+            .collect();
+        for uri in removed {
+            let segment = self.segments.remove(&uri).unwrap();
+            log::info!("{} has left the manifest", uri);
+            self.sender.remove_object(segment.toi);
+            changed = true;
+        }
+
+        let removed: Vec<String> = self
+            .manifests
+            .keys()
+            .filter(|uri| !manifests.iter().any(|(m, _)| m == *uri))
+            .cloned()
+            .collect();
+        for uri in removed {
+            let manifest = self.manifests.remove(&uri).unwrap();
+            self.sender.remove_object(manifest.toi);
+            changed = true;
+        }
+
+        // Manifests are added after the segments they reference
+        for (uri, content) in manifests.into_iter().rev() {
+            if self.add_manifest(&uri, content)? {
+                changed = true;
+            }
+        }
+
+        if changed && self.fdt_publish_mode == FDTPublishMode::FullFDT {
+            self.sender.publish(now)?;
+        }
+
+        Ok(changed)
+    }
+
+    /// Read the next ALC/LCT packet
+    /// The manifest is read when `scan_interval` is elapsed
+    /// return None if there is no new packet to be transferred
+    pub fn read(&mut self, now: SystemTime) -> Option<Vec<u8>> {
+        let scan_required = match self.next_scan {
+            Some(next_scan) => now >= next_scan,
+            None => true,
+        };
+
+        if scan_required {
+            if let Err(e) = self.ingest(now) {
+                log::error!("Fail to ingest {:?} {:?}", self.config.manifest, e);
+            }
+            self.next_scan = Some(now + self.config.scan_interval);
+        }
+
+        self.sender.read(now)
+    }
+
+    fn parent_uri(uri: &str) -> &str {
+        match uri.rfind('/') {
+            Some(pos) => &uri[..pos + 1],
+            None => "",
+        }
+    }
+
+    fn relative_path(uri: &str) -> Option<PathBuf> {
+        if uri.contains("://") || uri.starts_with('/') {
+            return None;
+        }
+
+        let path = PathBuf::from(uri.split(['?', '#']).next().unwrap_or_default());
+        let valid = path
+            .components()
+            .all(|component| matches!(component, std::path::Component::Normal(_)));
+        match valid && !path.as_os_str().is_empty() {
+            true => Some(path),
+            false => None,
+        }
+    }
+
+    fn content_location(&self, uri: &str) -> Result<url::Url> {
+        self.config
+            .base_url
+            .join(uri)
+            .map_err(|e| FluteError::new(format!("{} {:?}", uri, e)))
+    }
+
+    fn add_segment(
+        &mut self,
+        root: &Path,
+        uri: &str,
+        kind: SegmentKind,
+        duration: Option<Duration>,
+        now: SystemTime,
+    ) -> Result<bool> {
+        if self.segments.contains_key(uri) {
+            return Ok(false);
+        }
+
+        let path = root.join(Self::relative_path(uri).unwrap());
+        if !path.is_file() {
+            // Not yet written by the packager
+            return Ok(false);
+        }
+
+        let content_location = self.content_location(uri)?;
+        let (carousel_mode, target_acquisition, group) = match kind {
+            SegmentKind::Init => (
+                Some(self.config.init_carousel_mode),
+                None,
+                SEGMENT_GROUP_INIT,
+            ),
+            _ => (
+                None,
+                duration.map(|duration| TargetAcquisition::WithinTime(now + duration)),
+                SEGMENT_GROUP_MEDIA,
+            ),
+        };
+
+        let obj = ObjectDesc::create_from_file(
+            &path,
+            Some(&content_location),
+            Self::content_type(uri),
+            true,
+            1,
+            carousel_mode,
+            target_acquisition,
+            None,
+            Some(vec![group.to_owned()]),
+            lct::Cenc::Null,
+            true,
+            None,
+            self.config.md5,
+        )?;
+
+        let toi = self.sender.add_object(self.config.priority, obj)?;
+        log::info!("Add segment {} toi={}", uri, toi);
+        self.segments.insert(uri.to_owned(), SentSegment { toi });
+        Ok(true)
+    }
+
+    fn add_manifest(&mut self, uri: &str, content: Vec<u8>) -> Result<bool> {
+        if let Some(manifest) = self.manifests.get(uri) {
+            if manifest.content == content {
+                return Ok(false);
+            }
+        }
+
+        let content_location = self.content_location(uri)?;
+        let obj = ObjectDesc::create_from_buffer(
+            content.clone(),
+            Self::content_type(uri),
+            &content_location,
+            1,
+            Some(self.config.manifest_carousel_mode),
+            None,
+            None,
+            Some(vec![SEGMENT_GROUP_MANIFEST.to_owned()]),
+            self.config.manifest_cenc,
+            true,
+            None,
+            self.config.md5,
+        )?;
+
+        // The new version replaces the old one
+        if let Some(old) = self.manifests.remove(uri) {
+            self.sender.remove_object(old.toi);
+        }
+
+        let toi = self.sender.add_object(self.config.priority, obj)?;
+        log::info!("Add manifest {} toi={}", uri, toi);
+        self.manifests
+            .insert(uri.to_owned(), SentManifest { toi, content });
+        Ok(true)
+    }
+
+    fn content_type(uri: &str) -> &'static str {
+        let path = uri.split(['?', '#']).next().unwrap_or_default();
+        match path.rsplit('.').next().unwrap_or_default() {
+            "mpd" => "application/dash+xml",
+            "m3u8" => "application/vnd.apple.mpegurl",
+            "m4s" | "mp4" | "cmfv" => "video/mp4",
+            "m4a" | "cmfa" => "audio/mp4",
+            "ts" => "video/mp2t",
+            "aac" => "audio/aac",
+            "vtt" => "text/vtt",
+            _ => "application/octet-stream",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SegmentConfig, SegmentSender};
+    use crate::core::UDPEndpoint;
+    use std::path::Path;
+
+    fn write_playlist(dir: &Path, segments: &[u32]) {
+        let mut playlist =
+            "#EXTM3U\n#EXT-X-TARGETDURATION:2\n#EXT-X-MAP:URI=\"init.mp4\"\n".to_string();
+        for segment in segments {
+            playlist.push_str(&format!("#EXTINF:2.0,\nseg-{}.m4s\n", segment));
+        }
+        std::fs::write(dir.join("index.m3u8"), playlist).unwrap();
+    }
+
+    #[test]
+    pub fn test_segment_sender_hls() {
+        crate::tests::init();
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("init.mp4"), b"init").unwrap();
+        for segment in 1..=4 {
+            std::fs::write(dir.path().join(format!("seg-{}.m4s", segment)), b"data").unwrap();
+        }
+
+        let endpoint = UDPEndpoint::new(None, "224.0.0.1".to_owned(), 1234);
+        let config = SegmentConfig {
+            manifest: dir.path().join("index.m3u8"),
+            base_url: url::Url::parse("http://localhost/live/").unwrap(),
+            ..Default::default()
+        };
+        let mut sender = SegmentSender::new(
+            endpoint,
+            1,
+            &Default::default(),
+            &Default::default(),
+            config,
+        )
+        .unwrap();
+
+        let now = std::time::SystemTime::now();
+        write_playlist(dir.path(), &[1, 2]);
+        assert!(sender.ingest(now).unwrap());
+        // init, 2 segments, playlist
+        assert!(sender.sender().nb_objects() == 4);
+        let playlist_toi = sender.get_toi("index.m3u8").unwrap();
+        let toi = sender.get_toi("seg-1.m4s").unwrap();
+        let objects = sender.sender().get_objects_in_fdt();
+        let obj = objects.get(&toi).unwrap();
+        assert!(obj.content_location.as_str() == "http://localhost/live/seg-1.m4s");
+        assert!(obj.groups.as_ref().unwrap()[0] == super::SEGMENT_GROUP_MEDIA);
+
+        // Nothing changed
+        assert!(!sender.ingest(now).unwrap());
+
+        // Live edge advances
+        write_playlist(dir.path(), &[2, 3, 4]);
+        assert!(sender.ingest(now).unwrap());
+        assert!(sender.get_toi("seg-1.m4s").is_none());
+        assert!(!sender.sender().is_added(toi));
+        assert!(sender.get_toi("seg-4.m4s").is_some());
+        assert!(sender.get_toi("index.m3u8").unwrap() != playlist_toi);
+        assert!(!sender.sender().is_added(playlist_toi));
+    }
+}
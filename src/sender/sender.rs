@@ -1,8 +1,9 @@
+use super::blockencoderpool::BlockEncoderPool;
 use super::fdt::Fdt;
 use super::observer::ObserverList;
 use super::sendersession::SenderSession;
 use super::{objectdesc, ObjectDesc, Subscriber, Toi};
-use crate::common::{alc, lct, oti, Profile};
+use crate::common::{alc, lct, oti, planner, Profile};
 use crate::core::UDPEndpoint;
 use crate::error::FluteError;
 use crate::sender::objectdesc::CarouselRepeatMode;
@@ -121,6 +122,20 @@ pub struct Config {
     pub toi_initial_value: Option<u128>,
     /// List of groups added to the FDT-Instance
     pub groups: Option<Vec<String>>,
+    /// Number of threads used to FEC encode the blocks ahead of the transmission.
+    /// 0 : blocks are encoded synchronously during the call to `Sender::read()`
+    ///
+    /// `Sender::read()` never waits for the encoder, it returns `None`
+    /// while the next blocks of the transfer are being encoded
+    pub fec_encoding_threads: usize,
+    /// Max amount of memory (in bytes) used by the blocks encoded ahead of the transmission.
+    /// The blocks of the interleave window are always encoded, even if this budget is exceeded.
+    /// Only used when `fec_encoding_threads` > 0
+    pub fec_encoding_memory_budget: usize,
+    /// If `true`, objects larger than the maximum transfer length of their OTI
+    /// are split in several parts (TOI) by `Sender::add_object()`.
+    /// The parts are announced in the FDT so the receiver can re-assemble the whole object.
+    pub split_large_objects: bool,
 }
 
 impl Config {
@@ -166,6 +181,9 @@ impl Default for Config {
             toi_initial_value: Some(1),
             groups: None,
             fdt_publish_mode: FDTPublishMode::FullFDT,
+            fec_encoding_threads: 0,
+            fec_encoding_memory_budget: 64 * 1024 * 1024,
+            split_large_objects: false,
         }
     }
 }
@@ -188,6 +206,7 @@ pub struct Sender {
     observers: ObserverList,
     tsi: u64,
     endpoint: UDPEndpoint,
+    split_large_objects: bool,
 }
 
 impl Sender {
@@ -212,6 +231,14 @@ impl Sender {
             config.fdt_publish_mode,
         );
 
+        let encoder_pool = match config.fec_encoding_threads {
+            0 => None,
+            n => Some(Arc::new(BlockEncoderPool::new(
+                n,
+                config.fec_encoding_memory_budget,
+            ))),
+        };
+
         let fdt_session = SenderSession::new(
             0,
             tsi,
@@ -219,6 +246,7 @@ impl Sender {
             true,
             config.profile,
             endpoint.clone(),
+            None,
         );
 
         let mut sessions = std::collections::BTreeMap::new();
@@ -238,6 +266,7 @@ impl Sender {
                         false,
                         config.profile,
                         endpoint.clone(),
+                        encoder_pool.clone(),
                     )
                 })
                 .collect();
@@ -257,6 +286,7 @@ impl Sender {
             observers,
             tsi,
             endpoint,
+            split_large_objects: config.split_large_objects,
         }
     }
 
@@ -295,6 +325,9 @@ impl Sender {
     /// # Returns
     ///
     /// A `Result` containing an `u128` representing the unique identifier of the added object (TOI), if the operation was successful.
+    ///
+    /// When `Config::split_large_objects` is enabled and the object is split in several parts,
+    /// the TOI of the first part is returned.
     pub fn add_object(&mut self, priority: u32, obj: Box<objectdesc::ObjectDesc>) -> Result<u128> {
         if !self.sessions.contains_key(&priority) {
             return Err(FluteError::new(
@@ -302,9 +335,44 @@ impl Sender {
             ));
         }
 
+        self.check_lct_extensions(&obj)?;
+
+        if self.split_large_objects {
+            return self.fdt.add_object_split(priority, obj);
+        }
+
         self.fdt.add_object(priority, obj)
     }
 
+    /// Check that the packets of the object still fit in an IP packet with its header extensions
+    fn check_lct_extensions(&self, obj: &objectdesc::ObjectDesc) -> Result<()> {
+        if obj.lct_extensions().is_empty() {
+            return Ok(());
+        }
+
+        let oti = obj.oti.as_ref().unwrap_or(self.fdt.oti());
+        let extensions_length: usize = obj.lct_extensions().iter().map(|ext| ext.size()).sum();
+        let header_length = planner::alc_header_max_length(oti) + extensions_length;
+        if header_length > lct::MAX_HEADER_LENGTH {
+            return Err(FluteError::new(format!(
+                "Header extensions of {} bytes exceed the maximum length of the LCT header",
+                extensions_length
+            )));
+        }
+
+        let packet_length = planner::ip_udp_header_length(&self.endpoint)?
+            + header_length
+            + oti.encoding_symbol_length as usize;
+        if packet_length > planner::MAX_IP_PACKET_LENGTH {
+            return Err(FluteError::new(format!(
+                "Header extensions of {} bytes and encoding symbols of {} bytes exceed the maximum size of an IP packet",
+                extensions_length, oti.encoding_symbol_length
+            )));
+        }
+
+        Ok(())
+    }
+
     /// Initiates the transfer of an object that is broadcasted in a carousel.
     ///
     /// - The object must be listed in the File Delivery Table (FDT).
@@ -512,6 +580,81 @@ mod tests {
         assert!(res.is_err());
     }
 
+    #[test]
+    pub fn test_sender_split_large_object() {
+        crate::tests::init();
+        let oti = oti::Oti::new_no_code(4, 2);
+        let endpoint = UDPEndpoint::new(None, "224.0.0.1".to_owned(), 1234);
+        let object = create_obj(oti.max_transfer_length() + 1);
+        let config = super::Config {
+            split_large_objects: true,
+            ..Default::default()
+        };
+        let mut sender = super::Sender::new(endpoint, 1, &oti, &config);
+        sender.add_object(0, object).unwrap();
+        assert!(sender.nb_objects() == 2);
+    }
+
+    #[derive(Debug)]
+    struct FailingStream {
+        inner: std::io::Cursor<Vec<u8>>,
+        fail_offset: u64,
+    }
+
+    impl std::io::Read for FailingStream {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            if self.inner.position() >= self.fail_offset {
+                return Err(std::io::Error::new(std::io::ErrorKind::Other, "disk error"));
+            }
+            self.inner.read(buf)
+        }
+    }
+
+    impl std::io::Seek for FailingStream {
+        fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+            self.inner.seek(pos)
+        }
+    }
+
+    #[test]
+    pub fn test_sender_stream_read_error() {
+        crate::tests::init();
+        let oti = oti::Oti::new_no_code(1000, 2);
+        let endpoint = UDPEndpoint::new(None, "224.0.0.1".to_owned(), 1234);
+        let mut sender = super::Sender::new(endpoint, 1, &oti, &Default::default());
+
+        let stream = FailingStream {
+            inner: std::io::Cursor::new(vec![0u8; 10000]),
+            fail_offset: 4000,
+        };
+        let object = objectdesc::ObjectDesc::create_from_stream(
+            Box::new(stream),
+            "text",
+            &url::Url::parse("file:///hello").unwrap(),
+            1,
+            Some(objectdesc::CarouselRepeatMode::DelayBetweenTransfers(
+                std::time::Duration::ZERO,
+            )),
+            None,
+            None,
+            None,
+            true,
+            None,
+            false,
+        )
+        .unwrap();
+        sender.add_object(0, object).unwrap();
+        sender.publish(std::time::SystemTime::now()).unwrap();
+
+        // Only the blocks read before the error are sent, the object is removed from the FDT
+        let mut nb_pkt = 0;
+        while sender.read(std::time::SystemTime::now()).is_some() {
+            nb_pkt += 1;
+            assert!(nb_pkt < 100);
+        }
+        assert!(sender.nb_objects() == 0);
+    }
+
     #[test]
     pub fn test_sender_remove_object() {
         crate::tests::init();
@@ -1,4 +1,5 @@
 use super::blockencoder::BlockEncoder;
+use super::blockencoderpool::BlockEncoderPool;
 use super::fdt::Fdt;
 use super::filedesc::FileDesc;
 #[cfg(feature = "opentelemetry")]
@@ -20,6 +21,7 @@ pub struct SenderSession {
     interleave_blocks: usize,
     transfer_fdt_only: bool,
     profile: Profile,
+    encoder_pool: Option<Arc<BlockEncoderPool>>,
     #[cfg(feature = "opentelemetry")]
     logger: Option<ObjectSenderLogger>,
 }
@@ -32,6 +34,7 @@ impl SenderSession {
         transfer_fdt_only: bool,
         profile: Profile,
         endpoint: UDPEndpoint,
+        encoder_pool: Option<Arc<BlockEncoderPool>>,
     ) -> SenderSession {
         SenderSession {
             priority,
@@ -42,6 +45,7 @@ impl SenderSession {
             interleave_blocks,
             transfer_fdt_only,
             profile,
+            encoder_pool,
             #[cfg(feature = "opentelemetry")]
             logger: None,
         }
@@ -79,8 +83,22 @@ impl SenderSession {
             }
 
             let pkt = encoder.read(must_stop_transfer);
+            if encoder.take_transfer_length_update() {
+                // Announce the transfer length of the live object
+                fdt.publish(now).ok();
+            }
+
             if pkt.is_none() {
-                self.release_file(fdt, now);
+                if encoder.is_waiting_data() {
+                    // Wait for the producer of the live object or for the encoder pool
+                    return None;
+                }
+
+                if encoder.is_failed() {
+                    self.fail_file(fdt, now);
+                } else {
+                    self.release_file(fdt, now);
+                }
                 continue;
             }
 
@@ -91,6 +109,7 @@ impl SenderSession {
                 &0u128,
                 self.tsi,
                 pkt,
+                &file.object.lct_extensions,
                 self.profile,
                 now,
             ));
@@ -123,7 +142,12 @@ impl SenderSession {
 
         let file = self.file.as_ref().unwrap().clone();
         let is_last_transfer = file.is_last_transfer();
-        let block_encoder = BlockEncoder::new(file, self.interleave_blocks, is_last_transfer);
+        let block_encoder = BlockEncoder::new(
+            file,
+            self.interleave_blocks,
+            is_last_transfer,
+            self.encoder_pool.clone(),
+        );
         if block_encoder.is_err() {
             log::error!("Fail to open Block Encoder");
             self.release_file(fdt, now);
@@ -133,6 +157,13 @@ impl SenderSession {
         self.encoder = block_encoder.ok();
     }
 
+    fn fail_file(&mut self, fdt: &mut Fdt, now: SystemTime) {
+        if let Some(file) = self.file.take() {
+            fdt.transfer_failed(file, now);
+        }
+        self.release_file(fdt, now);
+    }
+
     fn release_file(&mut self, fdt: &mut Fdt, now: SystemTime) {
         if let Some(file) = &self.file {
             fdt.transfer_done(file.clone(), now)
@@ -0,0 +1,395 @@
+use std::collections::BTreeMap;
+use std::time::{Duration, SystemTime};
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::tools::error::{FluteError, Result};
+
+/// Packet loss model of a `Channel`
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum LossModel {
+    /// No packet is lost
+    #[default]
+    None,
+    /// Each packet is lost independently with the probability `p`
+    Bernoulli {
+        /// Loss probability
+        p: f64,
+    },
+    /// Two-state Markov chain that models burst losses
+    ///
+    /// The chain moves between a Good and a Bad state after each packet,
+    /// the packets are lost with the loss probability of the current state.
+    GilbertElliott {
+        /// Probability to move from the Good to the Bad state
+        p_good_to_bad: f64,
+        /// Probability to move from the Bad to the Good state
+        p_bad_to_good: f64,
+        /// Loss probability in the Good state
+        loss_good: f64,
+        /// Loss probability in the Bad state
+        loss_bad: f64,
+    },
+}
+
+impl LossModel {
+    /// Gilbert model, all the packets of the Bad state are lost and none of the Good state
+    ///
+    /// `p_good_to_bad` is `average_loss / (mean_burst_length * (1 - average_loss))`
+    /// and `p_bad_to_good` is `1 / mean_burst_length`.
+    pub fn gilbert(average_loss: f64, mean_burst_length: f64) -> Self {
+        let p_bad_to_good = 1.0 / mean_burst_length;
+        LossModel::GilbertElliott {
+            p_good_to_bad: average_loss * p_bad_to_good / (1.0 - average_loss),
+            p_bad_to_good,
+            loss_good: 0.0,
+            loss_bad: 1.0,
+        }
+    }
+
+    /// Long-term average loss probability of the model
+    pub fn average_loss(&self) -> f64 {
+        match self {
+            LossModel::None => 0.0,
+            LossModel::Bernoulli { p } => *p,
+            LossModel::GilbertElliott {
+                p_good_to_bad,
+                p_bad_to_good,
+                loss_good,
+                loss_bad,
+            } => {
+                let transitions = p_good_to_bad + p_bad_to_good;
+                if transitions == 0.0 {
+                    return *loss_good;
+                }
+                let bad = p_good_to_bad / transitions;
+                (1.0 - bad) * loss_good + bad * loss_bad
+            }
+        }
+    }
+
+    fn probabilities(&self) -> Vec<f64> {
+        match self {
+            LossModel::None => Vec::new(),
+            LossModel::Bernoulli { p } => vec![*p],
+            LossModel::GilbertElliott {
+                p_good_to_bad,
+                p_bad_to_good,
+                loss_good,
+                loss_bad,
+            } => vec![*p_good_to_bad, *p_bad_to_good, *loss_good, *loss_bad],
+        }
+    }
+}
+
+/// Configuration of a `Channel`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChannelConfig {
+    /// Packet loss model
+    pub loss: LossModel,
+    /// Probability that a packet is held back by `reorder_delay`
+    pub reorder: f64,
+    /// Additional delay of the reordered packets
+    pub reorder_delay: Duration,
+    /// Probability that a packet is delivered twice
+    pub duplicate: f64,
+    /// Propagation delay of the packets
+    pub delay: Duration,
+    /// Maximum random delay added to each packet, uniformly distributed
+    pub jitter: Duration,
+    /// Bitrate of the link in bits/s, `None` for an unlimited bitrate
+    pub bitrate: Option<u64>,
+    /// Seed of the random generator, the same seed gives the same impairments
+    pub seed: u64,
+}
+
+impl Default for ChannelConfig {
+    fn default() -> Self {
+        Self {
+            loss: LossModel::None,
+            reorder: 0.0,
+            reorder_delay: Duration::from_millis(10),
+            duplicate: 0.0,
+            delay: Duration::ZERO,
+            jitter: Duration::ZERO,
+            bitrate: None,
+            seed: 0,
+        }
+    }
+}
+
+/// Statistics of a `Channel`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ChannelStats {
+    /// Number of packets sent to the channel
+    pub nb_sent: usize,
+    /// Number of packets lost
+    pub nb_lost: usize,
+    /// Number of packets duplicated
+    pub nb_duplicated: usize,
+    /// Number of packets held back by the reordering delay
+    pub nb_reordered: usize,
+    /// Number of packets delivered, duplicates included
+    pub nb_delivered: usize,
+}
+
+/// Unidirectional link that impairs the packets in virtual time
+///
+/// The packets are sent with `send()` and are available with `recv()` once the virtual
+/// time reaches their delivery time.
+#[derive(Debug)]
+pub struct Channel {
+    config: ChannelConfig,
+    rng: StdRng,
+    bad_state: bool,
+    busy_until: Option<SystemTime>,
+    queue: BTreeMap<(SystemTime, u64), Vec<u8>>,
+    seq: u64,
+    stats: ChannelStats,
+}
+
+impl Channel {
+    /// Create a new channel
+    pub fn new(config: ChannelConfig) -> Result<Self> {
+        let mut probabilities = config.loss.probabilities();
+        probabilities.extend([config.reorder, config.duplicate]);
+        if let Some(p) = probabilities.iter().find(|p| !(0.0..=1.0).contains(*p)) {
+            return Err(FluteError::new(format!("Invalid probability {}", p)));
+        }
+        if config.bitrate == Some(0) {
+            return Err(FluteError::new("Bitrate of the channel is 0"));
+        }
+
+        Ok(Self {
+            config,
+            rng: StdRng::seed_from_u64(config.seed),
+            bad_state: false,
+            busy_until: None,
+            queue: BTreeMap::new(),
+            seq: 0,
+            stats: ChannelStats::default(),
+        })
+    }
+
+    /// Configuration of the channel
+    pub fn config(&self) -> &ChannelConfig {
+        &self.config
+    }
+
+    /// Statistics of the channel
+    pub fn stats(&self) -> &ChannelStats {
+        &self.stats
+    }
+
+    /// Send a packet at the time `now`
+    ///
+    /// With a bitrate cap, the packet is transmitted after the packets that are
+    /// already on the link. Lost packets use the link as well.
+    pub fn send(&mut self, data: &[u8], now: SystemTime) {
+        self.stats.nb_sent += 1;
+
+        let mut departure = now;
+        if let Some(bitrate) = self.config.bitrate {
+            let start = self.busy_until.map_or(now, |busy| busy.max(now));
+            departure = start + transmission_time(data.len(), bitrate);
+            self.busy_until = Some(departure);
+        }
+
+        if self.is_lost() {
+            self.stats.nb_lost += 1;
+            return;
+        }
+
+        let mut deliveries = 1;
+        if self.draw(self.config.duplicate) {
+            self.stats.nb_duplicated += 1;
+            deliveries = 2;
+        }
+
+        for _ in 0..deliveries {
+            let mut delivery = departure + self.config.delay + self.jitter();
+            if self.draw(self.config.reorder) {
+                self.stats.nb_reordered += 1;
+                delivery += self.config.reorder_delay;
+            }
+            self.queue.insert((delivery, self.seq), data.to_vec());
+            self.seq += 1;
+        }
+    }
+
+    /// Receive the next packet delivered before or at the time `now`
+    ///
+    /// Return the delivery time and the packet
+    pub fn recv(&mut self, now: SystemTime) -> Option<(SystemTime, Vec<u8>)> {
+        let (delivery, _) = *self.queue.keys().next()?;
+        if delivery > now {
+            return None;
+        }
+        let ((delivery, _), data) = self.queue.pop_first()?;
+        self.stats.nb_delivered += 1;
+        Some((delivery, data))
+    }
+
+    /// Delivery time of the next packet
+    pub fn next_delivery(&self) -> Option<SystemTime> {
+        self.queue.keys().next().map(|(delivery, _)| *delivery)
+    }
+
+    /// Time at which the link has transmitted all the packets sent so far,
+    /// `None` without bitrate cap
+    pub fn busy_until(&self) -> Option<SystemTime> {
+        self.busy_until
+    }
+
+    /// Return true when no packet is waiting for its delivery
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    fn draw(&mut self, p: f64) -> bool {
+        p > 0.0 && self.rng.random_bool(p)
+    }
+
+    fn jitter(&mut self) -> Duration {
+        match self.config.jitter.is_zero() {
+            true => Duration::ZERO,
+            false => self.config.jitter.mul_f64(self.rng.random::<f64>()),
+        }
+    }
+
+    fn is_lost(&mut self) -> bool {
+        match self.config.loss {
+            LossModel::None => false,
+            LossModel::Bernoulli { p } => self.draw(p),
+            LossModel::GilbertElliott {
+                p_good_to_bad,
+                p_bad_to_good,
+                loss_good,
+                loss_bad,
+            } => {
+                let lost = match self.bad_state {
+                    true => self.draw(loss_bad),
+                    false => self.draw(loss_good),
+                };
+                let transition = match self.bad_state {
+                    true => p_bad_to_good,
+                    false => p_good_to_bad,
+                };
+                if self.draw(transition) {
+                    self.bad_state = !self.bad_state;
+                }
+                lost
+            }
+        }
+    }
+}
+
+fn transmission_time(len: usize, bitrate: u64) -> Duration {
+    let nanos = (len as u128 * 8 * 1_000_000_000) / bitrate as u128;
+    Duration::from_nanos(nanos as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use super::{Channel, ChannelConfig, LossModel};
+
+    fn loss_pattern(config: ChannelConfig, nb_packets: usize) -> Vec<bool> {
+        let mut channel = Channel::new(config).unwrap();
+        let now = SystemTime::UNIX_EPOCH;
+        (0..nb_packets)
+            .map(|_| {
+                channel.send(&[0u8; 10], now);
+                channel.recv(now).is_none()
+            })
+            .collect()
+    }
+
+    #[test]
+    pub fn test_channel_seed() {
+        crate::tests::init();
+        let config = ChannelConfig {
+            loss: LossModel::Bernoulli { p: 0.2 },
+            seed: 42,
+            ..Default::default()
+        };
+        let pattern = loss_pattern(config, 1000);
+        assert_eq!(pattern, loss_pattern(config, 1000));
+        assert_ne!(
+            pattern,
+            loss_pattern(ChannelConfig { seed: 43, ..config }, 1000)
+        );
+
+        let nb_lost = pattern.iter().filter(|lost| **lost).count();
+        assert!((150..250).contains(&nb_lost), "{} packets lost", nb_lost);
+    }
+
+    #[test]
+    pub fn test_channel_gilbert_elliott() {
+        crate::tests::init();
+        let loss = LossModel::gilbert(0.1, 5.0);
+        assert!((loss.average_loss() - 0.1).abs() < 1e-9);
+
+        let pattern = loss_pattern(
+            ChannelConfig {
+                loss,
+                seed: 1,
+                ..Default::default()
+            },
+            20000,
+        );
+        let nb_lost = pattern.iter().filter(|lost| **lost).count();
+        let nb_bursts = pattern.windows(2).filter(|w| !w[0] && w[1]).count().max(1);
+        let mean_burst = nb_lost as f64 / nb_bursts as f64;
+        assert!((1600..2400).contains(&nb_lost), "{} packets lost", nb_lost);
+        assert!(mean_burst > 3.5 && mean_burst < 6.5, "burst {}", mean_burst);
+    }
+
+    #[test]
+    pub fn test_channel_delivery() {
+        crate::tests::init();
+        let start = SystemTime::UNIX_EPOCH;
+        let mut channel = Channel::new(ChannelConfig {
+            delay: Duration::from_millis(10),
+            duplicate: 1.0,
+            bitrate: Some(8000),
+            ..Default::default()
+        })
+        .unwrap();
+
+        // 100 bytes at 8000 bits/s take 100ms on the link
+        channel.send(&[1u8; 100], start);
+        channel.send(&[2u8; 100], start);
+        assert_eq!(
+            channel.busy_until(),
+            Some(start + Duration::from_millis(200))
+        );
+        assert!(channel.recv(start + Duration::from_millis(109)).is_none());
+
+        let mut received = Vec::new();
+        while let Some((delivery, data)) = channel.recv(start + Duration::from_secs(1)) {
+            received.push((delivery.duration_since(start).unwrap().as_millis(), data[0]));
+        }
+        assert_eq!(received, vec![(110, 1), (110, 1), (210, 2), (210, 2)]);
+        assert!(channel.is_empty());
+        assert_eq!(channel.stats().nb_duplicated, 2);
+        assert_eq!(channel.stats().nb_delivered, 4);
+    }
+
+    #[test]
+    pub fn test_channel_invalid_config() {
+        crate::tests::init();
+        assert!(Channel::new(ChannelConfig {
+            loss: LossModel::Bernoulli { p: 1.5 },
+            ..Default::default()
+        })
+        .is_err());
+        assert!(Channel::new(ChannelConfig {
+            bitrate: Some(0),
+            ..Default::default()
+        })
+        .is_err());
+    }
+}
@@ -0,0 +1,195 @@
+//!
+//! Deterministic network simulator
+//!
+//! `simulate()` connects a `Sender` to a `MultiReceiver` through a `Channel` that impairs the
+//! packets: Bernoulli or Gilbert-Elliott (burst) losses, reordering, duplication, delay,
+//! jitter and bitrate cap. The simulation runs in virtual time and the impairments
+//! are drawn from a seeded random generator, so a run is reproducible. The session and
+//! object timeouts of the receiver use the local clock, see `simulate()`.
+//!
+//! It is used to test and compare the recovery of the FEC schemes under
+//! the loss patterns of a satellite or a mobile link.
+//!
+//! # Example
+//!
+//! ```
+//! use flute::core::{Oti, UDPEndpoint};
+//! use flute::receiver::{writer, MultiReceiver};
+//! use flute::sender::{Config, ObjectDesc, Sender};
+//! use flute::simulator::{simulate, ChannelConfig, LossModel, SimulatorConfig};
+//! use std::rc::Rc;
+//! use std::time::SystemTime;
+//!
+//! let start = SystemTime::now();
+//! let oti = Oti::new_raptorq(1400, 64, 32, 1, 4).unwrap();
+//! let endpoint = UDPEndpoint::new(None, "224.0.0.1".to_string(), 3400);
+//! let mut sender = Sender::new(endpoint, 1, &oti, &Config::default());
+//! let obj = ObjectDesc::create_from_buffer(vec![0u8; 100000], "application/octet-stream",
+//!     &url::Url::parse("file:///file.bin").unwrap(), 1, None, None, None, None,
+//!     flute::core::lct::Cenc::Null, true, None, true).unwrap();
+//! sender.add_object(0, obj).unwrap();
+//! sender.publish(start).unwrap();
+//!
+//! let output = Rc::new(writer::ObjectWriterBufferBuilder::new(true));
+//! let mut receiver = MultiReceiver::new(output.clone(), None, false);
+//!
+//! // 5% of losses, by bursts of 3 packets on average
+//! let config = SimulatorConfig {
+//!     channel: ChannelConfig {
+//!         loss: LossModel::gilbert(0.05, 3.0),
+//!         seed: 1,
+//!         ..Default::default()
+//!     },
+//!     start_time: Some(start),
+//!     ..Default::default()
+//! };
+//! let stats = simulate(&mut sender, &mut receiver, &config).unwrap();
+//! assert!(stats.finished);
+//! assert!(output.objects.borrow()[0].borrow().complete);
+//! ```
+//!
+
+mod channel;
+mod simulation;
+
+pub use channel::Channel;
+pub use channel::ChannelConfig;
+pub use channel::ChannelStats;
+pub use channel::LossModel;
+pub use simulation::simulate;
+pub use simulation::SimulatorConfig;
+pub use simulation::SimulatorStats;
+
+#[cfg(test)]
+mod tests {
+    use super::{simulate, ChannelConfig, LossModel, SimulatorConfig};
+    use crate::common::oti::Oti;
+    use crate::common::udpendpoint::UDPEndpoint;
+    use crate::receiver::{writer, MultiReceiver};
+    use crate::sender::{Config, ObjectDesc, Sender};
+    use std::rc::Rc;
+    use std::time::{Duration, SystemTime};
+
+    fn create_sender(oti: &Oti, start: SystemTime) -> Sender {
+        let endpoint = UDPEndpoint::new(None, "224.0.0.1".to_owned(), 3400);
+        let mut sender = Sender::new(endpoint, 1, oti, &Config::default());
+        let obj = ObjectDesc::create_from_buffer(
+            (0..50000u32).map(|i| i as u8).collect(),
+            "application/octet-stream",
+            &url::Url::parse("file:///file.bin").unwrap(),
+            1,
+            None,
+            None,
+            None,
+            None,
+            crate::core::lct::Cenc::Null,
+            true,
+            None,
+            true,
+        )
+        .unwrap();
+        sender.add_object(0, obj).unwrap();
+        sender.publish(start).unwrap();
+        sender
+    }
+
+    fn run(oti: &Oti, config: &SimulatorConfig) -> (super::SimulatorStats, bool) {
+        let start = config.start_time.unwrap();
+        let mut sender = create_sender(oti, start);
+        let output = Rc::new(writer::ObjectWriterBufferBuilder::new(true));
+        let mut receiver = MultiReceiver::new(output.clone(), None, false);
+        let stats = simulate(&mut sender, &mut receiver, config).unwrap();
+        let objects = output.objects.borrow();
+        let complete = objects.len() == 1 && objects[0].borrow().complete;
+        (stats, complete)
+    }
+
+    #[test]
+    pub fn test_simulator_deterministic() {
+        crate::tests::init();
+        let oti = Oti::new_reed_solomon_rs28(1400, 20, 10).unwrap();
+        let config = SimulatorConfig {
+            channel: ChannelConfig {
+                loss: LossModel::Bernoulli { p: 0.1 },
+                reorder: 0.1,
+                duplicate: 0.05,
+                jitter: Duration::from_millis(3),
+                seed: 7,
+                ..Default::default()
+            },
+            start_time: Some(SystemTime::now()),
+            ..Default::default()
+        };
+
+        let (stats, complete) = run(&oti, &config);
+        assert!(stats.finished);
+        assert!(complete);
+        assert!(stats.channel.nb_lost > 0);
+        assert!(stats.channel.nb_reordered > 0);
+        assert!(stats.channel.nb_duplicated > 0);
+        assert_eq!(
+            stats.channel.nb_delivered,
+            stats.nb_packets - stats.channel.nb_lost + stats.channel.nb_duplicated
+        );
+
+        let (stats2, _) = run(&oti, &config);
+        assert_eq!(stats.channel, stats2.channel);
+        assert_eq!(stats.duration, stats2.duration);
+    }
+
+    #[test]
+    pub fn test_simulator_no_code_loss() {
+        crate::tests::init();
+        let oti = Oti::new_no_code(1400, 64);
+        let config = SimulatorConfig {
+            channel: ChannelConfig {
+                loss: LossModel::gilbert(0.2, 3.0),
+                seed: 3,
+                ..Default::default()
+            },
+            start_time: Some(SystemTime::now()),
+            ..Default::default()
+        };
+        let (stats, complete) = run(&oti, &config);
+        assert!(stats.finished);
+        assert!(stats.channel.nb_lost > 0);
+        assert!(!complete);
+    }
+
+    #[test]
+    pub fn test_simulator_bitrate() {
+        crate::tests::init();
+        let oti = Oti::new_no_code(1400, 64);
+        let config = SimulatorConfig {
+            channel: ChannelConfig {
+                bitrate: Some(1_000_000),
+                delay: Duration::from_millis(250),
+                ..Default::default()
+            },
+            start_time: Some(SystemTime::now()),
+            packet_interval: Duration::ZERO,
+            ..Default::default()
+        };
+        let (stats, complete) = run(&oti, &config);
+        assert!(stats.finished);
+        assert!(complete);
+        // 50KB of data at 1 Mbit/s take at least 400ms, plus the propagation delay
+        assert!(stats.duration >= Duration::from_millis(650));
+        assert!(stats.duration < Duration::from_secs(1));
+    }
+
+    #[test]
+    pub fn test_simulator_max_duration() {
+        crate::tests::init();
+        let oti = Oti::new_no_code(1400, 64);
+        let config = SimulatorConfig {
+            start_time: Some(SystemTime::now()),
+            max_duration: Duration::from_millis(10),
+            ..Default::default()
+        };
+        let (stats, complete) = run(&oti, &config);
+        assert!(!stats.finished);
+        assert!(!complete);
+        assert!(stats.duration <= Duration::from_millis(10));
+    }
+}
@@ -0,0 +1,131 @@
+use std::time::{Duration, SystemTime};
+
+use super::channel::{Channel, ChannelConfig, ChannelStats};
+use crate::receiver::MultiReceiver;
+use crate::sender::Sender;
+use crate::tools::error::{FluteError, Result};
+
+/// Configuration of `simulate()`
+#[derive(Debug, Clone, Copy)]
+pub struct SimulatorConfig {
+    /// Channel between the sender and the receiver
+    pub channel: ChannelConfig,
+    /// Virtual time of the start of the simulation, `None` for the current time
+    pub start_time: Option<SystemTime>,
+    /// Interval between two packets of the sender.
+    /// With a bitrate cap, the sender also waits for the link to be available.
+    pub packet_interval: Duration,
+    /// Interval between two reads when the sender has no packet to transfer yet
+    pub idle_interval: Duration,
+    /// Interval between two calls to `MultiReceiver::cleanup()`
+    pub cleanup_interval: Duration,
+    /// The simulation stops after this duration of virtual time
+    pub max_duration: Duration,
+}
+
+impl Default for SimulatorConfig {
+    fn default() -> Self {
+        Self {
+            channel: ChannelConfig::default(),
+            start_time: None,
+            packet_interval: Duration::from_millis(1),
+            idle_interval: Duration::from_millis(10),
+            cleanup_interval: Duration::from_secs(1),
+            max_duration: Duration::from_secs(3600),
+        }
+    }
+}
+
+/// Statistics of a simulation
+#[derive(Debug, Clone, Default)]
+pub struct SimulatorStats {
+    /// Number of packets read from the sender
+    pub nb_packets: usize,
+    /// Number of packets rejected by the receiver
+    pub nb_errors: usize,
+    /// Statistics of the channel
+    pub channel: ChannelStats,
+    /// Duration of the simulation in virtual time
+    pub duration: Duration,
+    /// The sender has transferred all its objects and the channel is empty.
+    /// `false` when the simulation is stopped by `max_duration`.
+    pub finished: bool,
+}
+
+/// Transfer the objects of a `Sender` to a `MultiReceiver` through an impaired channel
+///
+/// The simulation runs in virtual time, nothing sleeps: the sender is read at the pace of
+/// `packet_interval` and each packet is pushed to the receiver with its delivery time.
+/// The simulation ends when the sender has no more object in its FDT and all the packets
+/// are delivered, or after `max_duration`.
+///
+/// The FDT expiration and the object cache of the receiver follow the virtual time.
+/// `Config::session_timeout` and `Config::object_timeout` of the receivers are measured
+/// with the local clock, so they depend on how long the run takes on the host, not on the virtual time.
+/// Disable them for a run that only depends on the virtual time and the seed of the channel.
+pub fn simulate(
+    sender: &mut Sender,
+    receiver: &mut MultiReceiver,
+    config: &SimulatorConfig,
+) -> Result<SimulatorStats> {
+    if config.idle_interval.is_zero() {
+        return Err(FluteError::new("Idle interval of the simulation is 0"));
+    }
+
+    let mut channel = Channel::new(config.channel)?;
+    let endpoint = sender.get_udp_endpoint().clone();
+    let start = config.start_time.unwrap_or_else(SystemTime::now);
+    let deadline = start + config.max_duration;
+
+    let mut stats = SimulatorStats::default();
+    let mut now = start;
+    let mut next_read = Some(start);
+    let mut last_cleanup = start;
+
+    loop {
+        while let Some((delivery, data)) = channel.recv(now) {
+            if let Err(e) = receiver.push(&endpoint, &data, delivery) {
+                log::warn!("Fail to push packet of {:?}: {:?}", endpoint, e);
+                stats.nb_errors += 1;
+            }
+        }
+
+        if now.duration_since(last_cleanup).unwrap_or_default() >= config.cleanup_interval {
+            receiver.cleanup(now);
+            last_cleanup = now;
+        }
+
+        if next_read.map_or(false, |next_read| next_read <= now) {
+            next_read = match sender.read(now) {
+                Some(pkt) => {
+                    channel.send(&pkt, now);
+                    stats.nb_packets += 1;
+                    let next = now + config.packet_interval;
+                    Some(channel.busy_until().map_or(next, |busy| busy.max(next)))
+                }
+                None if sender.get_objects_in_fdt().is_empty() => None,
+                None => Some(now + config.idle_interval),
+            };
+        }
+
+        let next = match (next_read, channel.next_delivery()) {
+            (Some(read), Some(delivery)) => read.min(delivery),
+            (Some(read), None) => read,
+            (None, Some(delivery)) => delivery,
+            (None, None) => {
+                stats.finished = true;
+                break;
+            }
+        };
+        if next > deadline {
+            log::warn!("Simulation stopped after {:?}", config.max_duration);
+            break;
+        }
+        now = next;
+    }
+
+    receiver.cleanup(now);
+    stats.channel = *channel.stats();
+    stats.duration = now.duration_since(start).unwrap_or_default();
+    Ok(stats)
+}
@@ -1,11 +1,47 @@
 use self::error::FluteError;
 use self::error::Result;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::SystemTime;
 
 /// Handle errors
 pub mod error;
 pub mod ringbuffer;
 
+/// Max number of names tried by `create_temp_file()`
+const MAX_TEMP_FILE_ATTEMPTS: u32 = 1000;
+
+static TEMP_FILE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Create a new file with a unique name, starting with `prefix`, inside `dir`.
+/// An existing file or symbolic link is never opened, another name is tried instead.
+pub(crate) fn create_temp_file(
+    dir: &std::path::Path,
+    prefix: &str,
+) -> Result<(std::path::PathBuf, std::fs::File)> {
+    for _ in 0..MAX_TEMP_FILE_ATTEMPTS {
+        let path = dir.join(format!(
+            "{}_{}_{}.bin",
+            prefix,
+            std::process::id(),
+            TEMP_FILE_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        match std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path)
+        {
+            Ok(file) => return Ok((path, file)),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Err(FluteError::new(format!(
+        "Fail to create a temporary file in {:?}",
+        dir
+    )))
+}
+
 /// Convert the `SystemTime`into NTP.
 pub fn system_time_to_ntp(time: SystemTime) -> Result<u64> {
     let duration = time
@@ -179,9 +179,9 @@ mod tests {
                         .push(&endpoint, data.as_ref().unwrap(), now)
                         .unwrap();
                 }
+                i += 1;
             }
             receiver.cleanup(now);
-            i += 1;
         }
     }
 
@@ -494,6 +494,112 @@ mod tests {
         );
     }
 
+    #[cfg(feature = "zstd")]
+    #[test]
+    pub fn test_receiver_cenc_zstd() {
+        init();
+        test_receiver_with_oti(
+            &Default::default(),
+            None,
+            false,
+            flute::core::lct::Cenc::Zstd,
+            true,
+            None,
+            100000,
+            false,
+            None,
+            true,
+        );
+    }
+
+    #[cfg(feature = "brotli")]
+    #[test]
+    pub fn test_receiver_cenc_brotli() {
+        init();
+        test_receiver_with_oti(
+            &Default::default(),
+            None,
+            false,
+            flute::core::lct::Cenc::Brotli,
+            true,
+            None,
+            100000,
+            false,
+            None,
+            true,
+        );
+    }
+
+    #[cfg(not(feature = "zstd"))]
+    #[test]
+    pub fn test_sender_cenc_zstd_unsupported() {
+        init();
+        let (buffer, content_location) = create_file_buffer(1000);
+        let result = sender::ObjectDesc::create_from_buffer(
+            buffer,
+            "application/octet-stream",
+            &content_location,
+            1,
+            None,
+            None,
+            None,
+            None,
+            flute::core::lct::Cenc::Zstd,
+            true,
+            None,
+            true,
+        );
+        assert!(result.is_err());
+        assert!(!flute::core::lct::Cenc::Zstd.is_supported());
+    }
+
+    #[test]
+    pub fn test_receiver_cenc_gzip_file_stream() {
+        init();
+
+        let content_type = "application/octet-stream";
+        let (buffer, content_location) = create_file_buffer(100000);
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(&buffer).unwrap();
+
+        let obj = sender::ObjectDesc::create_from_file(
+            file.path(),
+            Some(&content_location),
+            content_type,
+            false,
+            1,
+            None,
+            None,
+            None,
+            None,
+            flute::core::lct::Cenc::Gzip,
+            true,
+            None,
+            true,
+        )
+        .unwrap();
+        assert!(obj.content_length == buffer.len() as u64);
+        assert!(matches!(obj.source, sender::ObjectDataSource::Stream(_)));
+
+        let output = Rc::new(receiver::writer::ObjectWriterBufferBuilder::new(true));
+        let mut receiver = receiver::MultiReceiver::new(output.clone(), None, false);
+        let mut sender = create_sender(
+            vec![obj],
+            &Default::default(),
+            flute::core::lct::Cenc::Gzip,
+            None,
+        );
+        run(&mut sender, &mut receiver);
+
+        check_output(
+            &buffer,
+            content_location.as_str(),
+            content_type,
+            None,
+            &output,
+        );
+    }
+
     #[test]
     pub fn test_receiver_reed_solomon_gf28_under_specified() {
         crate::tests::init();
@@ -607,6 +713,84 @@ mod tests {
         );
     }
 
+    #[test]
+    pub fn test_receiver_raptorq_parallel_encoding() {
+        init();
+        let oti: flute::core::Oti = flute::core::Oti::new_raptorq(1400, 64, 20, 1, 4).unwrap();
+        test_receiver_with_oti(
+            &oti,
+            None,
+            false,
+            flute::core::lct::Cenc::Null,
+            true,
+            Some(sender::Config {
+                fec_encoding_threads: 4,
+                ..Default::default()
+            }),
+            1000000,
+            false,
+            None,
+            true,
+        );
+    }
+
+    #[test]
+    pub fn test_receiver_raptorq_parallel_encoding_low_memory_budget() {
+        init();
+        let oti: flute::core::Oti = flute::core::Oti::new_raptorq(1400, 64, 20, 1, 4).unwrap();
+        test_receiver_with_oti(
+            &oti,
+            None,
+            true,
+            flute::core::lct::Cenc::Null,
+            true,
+            Some(sender::Config {
+                fec_encoding_threads: 2,
+                fec_encoding_memory_budget: 1,
+                ..Default::default()
+            }),
+            1000000,
+            true,
+            None,
+            true,
+        );
+    }
+
+    #[test]
+    pub fn test_receiver_raptorq_parallel_decoding() {
+        init();
+        let oti: flute::core::Oti = flute::core::Oti::new_raptorq(1400, 64, 20, 1, 4).unwrap();
+        let content_type = "application/octet-stream";
+        let (obj, input_file_buffer) = create_object(
+            100000,
+            content_type,
+            flute::core::lct::Cenc::Null,
+            true,
+            None,
+            None,
+        );
+        let input_content_location = obj.content_location.clone();
+
+        let output = Rc::new(receiver::writer::ObjectWriterBufferBuilder::new(true));
+        let receiver_config = receiver::Config {
+            fec_decoding_threads: 2,
+            ..Default::default()
+        };
+        let mut receiver =
+            receiver::MultiReceiver::new(output.clone(), Some(receiver_config), false);
+
+        let mut sender = create_sender(vec![obj], &oti, flute::core::lct::Cenc::Null, None);
+        run_loss(&mut sender, &mut receiver);
+
+        check_output(
+            &input_file_buffer,
+            input_content_location.as_str(),
+            content_type,
+            None,
+            &output,
+        );
+    }
+
     #[test]
     pub fn test_receiver_raptorq_outband_fti() {
         crate::tests::init();
@@ -683,12 +867,14 @@ mod tests {
     }
 
     #[test]
-    pub fn test_receiver_expired_fdt() {
-        crate::tests::init();
-
-        let oti: flute::core::Oti = Default::default();
+    pub fn test_receiver_outband_fti_inband_tol() {
+        init();
+        let oti = flute::core::Oti {
+            inband_fti: false,
+            ..Default::default()
+        };
         let content_type = "application/octet-stream";
-        let (obj, _) = create_object(
+        let (mut obj, input_file_buffer) = create_object(
             100000,
             content_type,
             flute::core::lct::Cenc::Null,
@@ -696,144 +882,165 @@ mod tests {
             None,
             None,
         );
+        obj.set_inband_tol(true);
+        let input_content_location = obj.content_location.clone();
+
         let output = Rc::new(receiver::writer::ObjectWriterBufferBuilder::new(true));
         let mut receiver = receiver::MultiReceiver::new(output.clone(), None, false);
-        let mut sender = create_sender(
-            vec![obj],
-            &oti,
-            flute::core::lct::Cenc::Null,
-            Some(sender::Config {
-                fdt_duration: std::time::Duration::from_secs(30),
-                fdt_inband_sct: false,
-                ..Default::default()
-            }),
-        );
+        let mut sender = create_sender(vec![obj], &oti, flute::core::lct::Cenc::Null, None);
 
         let endpoint = UDPEndpoint::new(None, "224.0.0.1".to_owned(), 5000);
-
         loop {
-            let now_sender = std::time::SystemTime::now();
-            let data = sender.read(now_sender);
-            if data.is_none() {
+            let now = std::time::SystemTime::now();
+            let data = sender.read(now);
+            if data.is_none() && sender.get_objects_in_fdt().is_empty() {
                 break;
             }
 
-            // Simulate reception 60s later -> FDT should be expired
-            let now_receiver = std::time::SystemTime::now() + std::time::Duration::from_secs(60);
-            receiver
-                .push(&endpoint, data.as_ref().unwrap(), now_receiver)
-                .unwrap();
-            receiver.cleanup(now_receiver);
+            if let Some(data) = data {
+                let pkt = flute::core::alc::parse_alc_pkt(&data).unwrap();
+                if pkt.lct.toi != 0 {
+                    // Transfer length is carried by EXT_TOL instead of the FTI
+                    assert!(pkt.oti.is_none());
+                    assert_eq!(pkt.transfer_length, Some(input_file_buffer.len() as u64));
+                }
+                receiver.push(&endpoint, &data, now).unwrap();
+            }
+            receiver.cleanup(now);
         }
 
-        let nb_complete_objects = output
-            .as_ref()
-            .objects
-            .borrow()
-            .iter()
-            .filter(|&obj| obj.borrow().complete)
-            .count();
-
-        let nb_error_objects = output
-            .as_ref()
-            .objects
-            .borrow()
-            .iter()
-            .filter(|&obj| obj.borrow().error)
-            .count();
-
-        assert!(nb_complete_objects == 0);
-        assert!(nb_error_objects == 0);
-    }
-
-    #[test]
-    pub fn test_receiver_empty_file() {
-        init();
-        test_receiver_with_oti(
-            &flute::core::Oti::new_no_code(1400, 64),
-            None,
-            false,
-            flute::core::lct::Cenc::Null,
-            true,
-            None,
-            0,
-            false,
+        check_output(
+            &input_file_buffer,
+            input_content_location.as_str(),
+            content_type,
             None,
-            true,
+            &output,
         );
     }
 
     #[test]
-    fn test_priority_queues() {
+    pub fn test_receiver_skip_unknown_ext() {
+        init();
+        let oti: flute::core::Oti = Default::default();
         let content_type = "application/octet-stream";
-
-        let oti = flute::core::Oti::new_no_code(1400, 64);
-
-        let (high_priority_obj, high_priority_buffer) = create_object(
-            1024,
+        let (obj, input_file_buffer) = create_object(
+            100000,
             content_type,
             flute::core::lct::Cenc::Null,
             true,
-            Some(&oti),
             None,
-        );
-
-        let (low_priority_obj, low_priority_buffer) = create_object(
-            1024,
-            content_type,
-            flute::core::lct::Cenc::Null,
-            true,
-            Some(&oti),
             None,
         );
+        let input_content_location = obj.content_location.clone();
 
         let output = Rc::new(receiver::writer::ObjectWriterBufferBuilder::new(true));
         let mut receiver = receiver::MultiReceiver::new(output.clone(), None, false);
-
-        let mut sender_config: sender::Config = Default::default();
-        sender_config.set_priority_queue(PriorityQueue::HIGHEST, PriorityQueue::new(3));
-        sender_config.set_priority_queue(PriorityQueue::LOW, PriorityQueue::new(3));
+        let mut sender = create_sender(vec![obj], &oti, flute::core::lct::Cenc::Null, None);
 
         let endpoint = UDPEndpoint::new(None, "224.0.0.1".to_owned(), 5000);
-        let mut sender = Box::new(sender::Sender::new(endpoint, 1, &oti, &sender_config));
+        let mut nb_pkt = 0;
+        loop {
+            let now = std::time::SystemTime::now();
+            let data = sender.read(now);
+            if data.is_none() && sender.get_objects_in_fdt().is_empty() {
+                break;
+            }
 
-        sender
-            .add_object(PriorityQueue::LOW, low_priority_obj)
-            .unwrap();
-        sender
-            .add_object(PriorityQueue::HIGHEST, high_priority_obj)
-            .unwrap();
-        sender.publish(std::time::SystemTime::now()).unwrap();
-
-        run(&mut sender, &mut receiver);
+            if let Some(data) = data {
+                // Insert an EXT_NOP and an extension of another stack after the LCT header
+                let lct_len = (data[2] as usize) << 2;
+                let mut pkt = data[..lct_len].to_vec();
+                flute::core::lct::push_nop(&mut pkt, 1);
+                pkt.extend([200, 1, 2, 3]);
+                flute::core::lct::inc_hdr_len(&mut pkt, 1);
+                pkt.extend(&data[lct_len..]);
+
+                receiver.push(&endpoint, &pkt, now).unwrap();
+                nb_pkt += 1;
+                assert_eq!(receiver.nb_unknown_ext(), nb_pkt);
+            }
+            receiver.cleanup(now);
+        }
 
-        let output_session = output.objects.borrow();
-        assert!(output_session.len() == 2);
+        check_output(
+            &input_file_buffer,
+            input_content_location.as_str(),
+            content_type,
+            None,
+            &output,
+        );
+    }
 
-        // Verify that file transferred in high priority queue is received before file in low priority queue
+    struct TestLctExtensionWriter {
+        inner: receiver::writer::ObjectWriterBufferBuilder,
+        received: RefCell<Vec<flute::core::lct::LctExtension>>,
+    }
 
-        let high_priority_output_object = output_session[0].as_ref().borrow();
-        let high_priority_output_file_buffer: &[u8] = high_priority_output_object.data.as_ref();
+    impl receiver::writer::ObjectWriterBuilder for TestLctExtensionWriter {
+        fn new_object_writer(
+            &self,
+            endpoint: &UDPEndpoint,
+            tsi: &u64,
+            toi: &u128,
+            meta: &receiver::writer::ObjectMetadata,
+            now: std::time::SystemTime,
+        ) -> receiver::writer::ObjectWriterBuilderResult {
+            self.inner.new_object_writer(endpoint, tsi, toi, meta, now)
+        }
 
-        let low_priority_output_object = output_session[1].as_ref().borrow();
-        let low_priority_output_file_buffer: &[u8] = low_priority_output_object.data.as_ref();
+        fn update_cache_control(
+            &self,
+            endpoint: &UDPEndpoint,
+            tsi: &u64,
+            toi: &u128,
+            meta: &receiver::writer::ObjectMetadata,
+            now: std::time::SystemTime,
+        ) {
+            self.inner
+                .update_cache_control(endpoint, tsi, toi, meta, now)
+        }
 
-        assert!(high_priority_output_object.complete == true);
-        assert!(high_priority_output_object.error == false);
-        assert!(high_priority_output_file_buffer.eq(&high_priority_buffer));
+        fn fdt_received(
+            &self,
+            endpoint: &UDPEndpoint,
+            tsi: &u64,
+            fdt_xml: &str,
+            expires: std::time::SystemTime,
+            meta: &receiver::writer::ObjectMetadata,
+            transfer_duration: std::time::Duration,
+            now: std::time::SystemTime,
+            ext_time: Option<std::time::SystemTime>,
+        ) {
+            self.inner.fdt_received(
+                endpoint,
+                tsi,
+                fdt_xml,
+                expires,
+                meta,
+                transfer_duration,
+                now,
+                ext_time,
+            )
+        }
 
-        assert!(low_priority_output_object.complete == true);
-        assert!(low_priority_output_object.error == false);
-        assert!(low_priority_output_file_buffer.eq(&low_priority_buffer));
+        fn lct_extensions_received(
+            &self,
+            _endpoint: &UDPEndpoint,
+            _tsi: &u64,
+            _toi: &u128,
+            extensions: &[flute::core::lct::LctExtension],
+            _now: std::time::SystemTime,
+        ) {
+            self.received.borrow_mut().extend_from_slice(extensions);
+        }
     }
 
     #[test]
-    fn test_asign_toi_to_object() {
-        let content_type = "application/octet-stream";
+    pub fn test_receiver_lct_extensions() {
+        init();
         let oti: flute::core::Oti = Default::default();
-        let mut sender = create_sender(Vec::new(), &oti, flute::core::lct::Cenc::Null, None);
-        let toi = sender.allocate_toi();
-        let (mut obj, _) = create_object(
+        let content_type = "application/octet-stream";
+        let (mut obj, input_file_buffer) = create_object(
             100000,
             content_type,
             flute::core::lct::Cenc::Null,
@@ -841,21 +1048,275 @@ mod tests {
             None,
             None,
         );
-        let toi_value = toi.get();
-        obj.set_toi(toi);
-        let toi_result = sender.add_object(0, obj).unwrap();
-        assert!(toi_value == toi_result);
+        let tag = flute::core::lct::LctExtension::new(200, vec![0xCA, 0xFE, 0x01]).unwrap();
+        let other = flute::core::lct::LctExtension::new(100, vec![1, 2, 3, 4, 5, 6]).unwrap();
+        obj.set_lct_extensions(vec![tag.clone(), other]);
+        let input_content_location = obj.content_location.clone();
+
+        let output = Rc::new(TestLctExtensionWriter {
+            inner: receiver::writer::ObjectWriterBufferBuilder::new(true),
+            received: RefCell::new(Vec::new()),
+        });
+        let mut receiver = receiver::MultiReceiver::new(output.clone(), None, false);
+        assert!(receiver.register_lct_extension(192).is_err());
+        receiver.register_lct_extension(200).unwrap();
+
+        let mut sender = create_sender(vec![obj], &oti, flute::core::lct::Cenc::Null, None);
+        let endpoint = UDPEndpoint::new(None, "224.0.0.1".to_owned(), 5000);
+        let mut nb_pkt = 0;
+        loop {
+            let now = std::time::SystemTime::now();
+            let data = sender.read(now);
+            if data.is_none() && sender.get_objects_in_fdt().is_empty() {
+                break;
+            }
+
+            if let Some(data) = data {
+                let pkt = flute::core::alc::parse_alc_pkt(&data).unwrap();
+                receiver.push(&endpoint, &data, now).unwrap();
+                if pkt.lct.toi != 0 {
+                    nb_pkt += 1;
+                    // Only the extension that is not registered is unknown
+                    assert_eq!(receiver.nb_unknown_ext(), nb_pkt);
+                }
+            }
+            receiver.cleanup(now);
+        }
+
+        let received = output.received.borrow();
+        assert_eq!(received.len(), nb_pkt);
+        assert!(received.iter().all(|ext| *ext == tag));
+
+        check_output(
+            &input_file_buffer,
+            input_content_location.as_str(),
+            content_type,
+            None,
+            &output.inner,
+        );
+        let objects = output.inner.objects.borrow();
+        let meta = &objects[0].borrow().meta;
+        assert_eq!(meta.lct_extensions, Some(vec![tag.clone()]));
     }
 
     #[test]
-    pub fn test_receiver_disable_received_once() {
+    pub fn test_sender_lct_extensions_too_large() {
+        init();
+        let ext = flute::core::lct::LctExtension::new(100, vec![0; 398]).unwrap();
+        let endpoint = UDPEndpoint::new(None, "224.0.0.1".to_owned(), 5000);
+        let create_object = |lct_extensions: Vec<flute::core::lct::LctExtension>| {
+            let (mut obj, _) = create_object(
+                1000,
+                "application/octet-stream",
+                flute::core::lct::Cenc::Null,
+                true,
+                None,
+                None,
+            );
+            obj.set_lct_extensions(lct_extensions);
+            obj
+        };
+
+        let oti: flute::core::Oti = Default::default();
+        let mut sender = sender::Sender::new(endpoint.clone(), 1, &oti, &Default::default());
+        assert!(sender.add_object(0, create_object(vec![ext.clone()])).is_ok());
+        // The LCT header is limited to 1020 bytes
+        let obj = create_object(vec![ext.clone(), ext.clone(), ext.clone()]);
+        assert!(sender.add_object(0, obj).is_err());
+
+        // The packet does not fit in an IP packet with large encoding symbols
+        let oti = flute::core::Oti::new_no_code(65400, 10);
+        let mut sender = sender::Sender::new(endpoint, 1, &oti, &Default::default());
+        assert!(sender.add_object(0, create_object(vec![ext])).is_err());
+        assert!(sender.add_object(0, create_object(Vec::new())).is_ok());
+    }
+
+    struct TestEventListener {
+        object_events: Rc<RefCell<Vec<receiver::ObjectEvent>>>,
+        fdt_events: Rc<RefCell<Vec<receiver::FdtEvent>>>,
+    }
+
+    impl MultiReceiverListener for TestEventListener {
+        fn on_session_open(&self, _endpoint: &ReceiverEndpoint) {}
+
+        fn on_session_closed(&self, _endpoint: &ReceiverEndpoint) {}
+
+        fn on_object_event(&self, _endpoint: &ReceiverEndpoint, event: &receiver::ObjectEvent) {
+            self.object_events.borrow_mut().push(event.clone());
+        }
+
+        fn on_fdt_event(&self, _endpoint: &ReceiverEndpoint, event: &receiver::FdtEvent) {
+            self.fdt_events.borrow_mut().push(event.clone());
+        }
+    }
+
+    #[test]
+    pub fn test_receiver_events() {
+        init();
+        let oti: flute::core::Oti = Default::default();
+        let (obj, _) = create_object(
+            200000,
+            "application/octet-stream",
+            flute::core::lct::Cenc::Null,
+            true,
+            None,
+            None,
+        );
+        let content_location = obj.content_location.to_string();
+
+        let object_events = Rc::new(RefCell::new(Vec::new()));
+        let fdt_events = Rc::new(RefCell::new(Vec::new()));
+        let output = Rc::new(receiver::writer::ObjectWriterBufferBuilder::new(true));
+        let mut receiver = receiver::MultiReceiver::new(output.clone(), None, false);
+        receiver.add_listener(TestEventListener {
+            object_events: object_events.clone(),
+            fdt_events: fdt_events.clone(),
+        });
+
+        let mut sender = create_sender(vec![obj], &oti, flute::core::lct::Cenc::Null, None);
+        run(&mut sender, &mut receiver);
+
+        let fdt_events = fdt_events.borrow();
+        assert!(matches!(
+            fdt_events[0],
+            receiver::FdtEvent::NewInstance { nb_objects: 1, .. }
+        ));
+
+        let object_events = object_events.borrow();
+        let toi = match &object_events[0] {
+            receiver::ObjectEvent::Announced {
+                toi,
+                content_location: cl,
+            } => {
+                assert_eq!(*cl, content_location);
+                *toi
+            }
+            event => panic!("Unexpected event {:?}", event),
+        };
+        assert_eq!(
+            object_events[1],
+            receiver::ObjectEvent::ReceptionStarted { toi }
+        );
+
+        let progress: Vec<u64> = object_events
+            .iter()
+            .filter_map(|event| match event {
+                receiver::ObjectEvent::Progress {
+                    received_bytes,
+                    transfer_length,
+                    ..
+                } => {
+                    assert_eq!(*transfer_length, Some(200000));
+                    Some(*received_bytes)
+                }
+                _ => None,
+            })
+            .collect();
+        // Reported at each percent of the object
+        assert!(progress.len() > 50 && progress.len() <= 100);
+        assert!(progress.windows(2).all(|p| p[0] < p[1]));
+        assert!(*progress.last().unwrap() <= 200000);
+
+        assert_eq!(
+            object_events.last(),
+            Some(&receiver::ObjectEvent::Completed {
+                toi,
+                content_location: Some(content_location)
+            })
+        );
+    }
+
+    #[test]
+    pub fn test_receiver_session_events() {
+        init();
+        let oti: flute::core::Oti = Default::default();
+        let (obj, _) = create_object(
+            10000,
+            "application/octet-stream",
+            flute::core::lct::Cenc::Null,
+            true,
+            None,
+            None,
+        );
+        let endpoint = UDPEndpoint::new(None, "224.0.0.1".to_owned(), 5000);
+        let output = Rc::new(receiver::writer::ObjectWriterBufferBuilder::new(true));
+        let mut receiver = receiver::Receiver::new(&endpoint, 1, output.clone(), None);
+        receiver.enable_events();
+
+        let mut sender = create_sender(vec![obj], &oti, flute::core::lct::Cenc::Null, None);
+        let now = std::time::SystemTime::now();
+        while let Some(data) = sender.read(now) {
+            let alc = flute::core::alc::parse_alc_pkt(&data).unwrap();
+            receiver.push(&alc, now).unwrap();
+        }
+
+        let events = receiver.take_events();
+        assert!(matches!(
+            events[0],
+            receiver::ReceiverEvent::Fdt(receiver::FdtEvent::NewInstance { .. })
+        ));
+        assert!(events.iter().any(|event| matches!(
+            event,
+            receiver::ReceiverEvent::Object(receiver::ObjectEvent::Completed { .. })
+        )));
+        assert!(receiver.take_events().is_empty());
+    }
+
+    #[test]
+    pub fn test_receiver_events_interrupted() {
+        init();
+        let oti: flute::core::Oti = Default::default();
+        let (obj, _) = create_object(
+            200000,
+            "application/octet-stream",
+            flute::core::lct::Cenc::Null,
+            true,
+            None,
+            None,
+        );
+
+        let object_events = Rc::new(RefCell::new(Vec::new()));
+        let output = Rc::new(receiver::writer::ObjectWriterBufferBuilder::new(true));
+        let mut receiver = receiver::MultiReceiver::new(output.clone(), None, false);
+        receiver.add_listener(TestEventListener {
+            object_events: object_events.clone(),
+            fdt_events: Rc::new(RefCell::new(Vec::new())),
+        });
+
+        let mut sender = create_sender(vec![obj], &oti, flute::core::lct::Cenc::Null, None);
+        let endpoint = UDPEndpoint::new(None, "224.0.0.1".to_owned(), 5000);
+        let mut i = 0;
+        loop {
+            let now = std::time::SystemTime::now();
+            let data = match sender.read(now) {
+                Some(data) => data,
+                None => break,
+            };
+            let pkt = flute::core::alc::parse_alc_pkt(&data).unwrap();
+            i += 1;
+            // Drop a packet of the object without FEC, the object can not be reconstructed
+            if pkt.lct.toi != 0 && i == 10 {
+                continue;
+            }
+            receiver.push(&endpoint, &data, now).unwrap();
+        }
+
+        let object_events = object_events.borrow();
+        assert!(object_events
+            .iter()
+            .any(|event| matches!(event, receiver::ObjectEvent::Interrupted { .. })));
+        assert!(!object_events
+            .iter()
+            .any(|event| matches!(event, receiver::ObjectEvent::Completed { .. })));
+    }
+
+    #[test]
+    pub fn test_receiver_expired_fdt() {
         crate::tests::init();
 
-        let max_transfert_count = 5usize;
         let oti: flute::core::Oti = Default::default();
         let content_type = "application/octet-stream";
-
-        let (mut obj, _) = create_object(
+        let (obj, _) = create_object(
             100000,
             content_type,
             flute::core::lct::Cenc::Null,
@@ -863,14 +1324,18 @@ mod tests {
             None,
             None,
         );
-        obj.max_transfer_count = max_transfert_count as u32;
         let output = Rc::new(receiver::writer::ObjectWriterBufferBuilder::new(true));
-        let mut receiver_config = receiver::Config::default();
-        receiver_config.object_receive_once = false;
-        let mut receiver =
-            receiver::MultiReceiver::new(output.clone(), Some(receiver_config), false);
-
-        let mut sender = create_sender(vec![obj], &oti, flute::core::lct::Cenc::Null, None);
+        let mut receiver = receiver::MultiReceiver::new(output.clone(), None, false);
+        let mut sender = create_sender(
+            vec![obj],
+            &oti,
+            flute::core::lct::Cenc::Null,
+            Some(sender::Config {
+                fdt_duration: std::time::Duration::from_secs(30),
+                fdt_inband_sct: false,
+                ..Default::default()
+            }),
+        );
 
         let endpoint = UDPEndpoint::new(None, "224.0.0.1".to_owned(), 5000);
 
@@ -904,7 +1369,1010 @@ mod tests {
             .iter()
             .filter(|&obj| obj.borrow().error)
             .count();
-        assert!(nb_complete_objects == max_transfert_count);
+
+        assert!(nb_complete_objects == 0);
         assert!(nb_error_objects == 0);
     }
+
+    #[test]
+    pub fn test_receiver_empty_file() {
+        init();
+        test_receiver_with_oti(
+            &flute::core::Oti::new_no_code(1400, 64),
+            None,
+            false,
+            flute::core::lct::Cenc::Null,
+            true,
+            None,
+            0,
+            false,
+            None,
+            true,
+        );
+    }
+
+    #[test]
+    fn test_priority_queues() {
+        let content_type = "application/octet-stream";
+
+        let oti = flute::core::Oti::new_no_code(1400, 64);
+
+        let (high_priority_obj, high_priority_buffer) = create_object(
+            1024,
+            content_type,
+            flute::core::lct::Cenc::Null,
+            true,
+            Some(&oti),
+            None,
+        );
+
+        let (low_priority_obj, low_priority_buffer) = create_object(
+            1024,
+            content_type,
+            flute::core::lct::Cenc::Null,
+            true,
+            Some(&oti),
+            None,
+        );
+
+        let output = Rc::new(receiver::writer::ObjectWriterBufferBuilder::new(true));
+        let mut receiver = receiver::MultiReceiver::new(output.clone(), None, false);
+
+        let mut sender_config: sender::Config = Default::default();
+        sender_config.set_priority_queue(PriorityQueue::HIGHEST, PriorityQueue::new(3));
+        sender_config.set_priority_queue(PriorityQueue::LOW, PriorityQueue::new(3));
+
+        let endpoint = UDPEndpoint::new(None, "224.0.0.1".to_owned(), 5000);
+        let mut sender = Box::new(sender::Sender::new(endpoint, 1, &oti, &sender_config));
+
+        sender
+            .add_object(PriorityQueue::LOW, low_priority_obj)
+            .unwrap();
+        sender
+            .add_object(PriorityQueue::HIGHEST, high_priority_obj)
+            .unwrap();
+        sender.publish(std::time::SystemTime::now()).unwrap();
+
+        run(&mut sender, &mut receiver);
+
+        let output_session = output.objects.borrow();
+        assert!(output_session.len() == 2);
+
+        // Verify that file transferred in high priority queue is received before file in low priority queue
+
+        let high_priority_output_object = output_session[0].as_ref().borrow();
+        let high_priority_output_file_buffer: &[u8] = high_priority_output_object.data.as_ref();
+
+        let low_priority_output_object = output_session[1].as_ref().borrow();
+        let low_priority_output_file_buffer: &[u8] = low_priority_output_object.data.as_ref();
+
+        assert!(high_priority_output_object.complete == true);
+        assert!(high_priority_output_object.error == false);
+        assert!(high_priority_output_file_buffer.eq(&high_priority_buffer));
+
+        assert!(low_priority_output_object.complete == true);
+        assert!(low_priority_output_object.error == false);
+        assert!(low_priority_output_file_buffer.eq(&low_priority_buffer));
+    }
+
+    #[test]
+    fn test_asign_toi_to_object() {
+        let content_type = "application/octet-stream";
+        let oti: flute::core::Oti = Default::default();
+        let mut sender = create_sender(Vec::new(), &oti, flute::core::lct::Cenc::Null, None);
+        let toi = sender.allocate_toi();
+        let (mut obj, _) = create_object(
+            100000,
+            content_type,
+            flute::core::lct::Cenc::Null,
+            true,
+            None,
+            None,
+        );
+        let toi_value = toi.get();
+        obj.set_toi(toi);
+        let toi_result = sender.add_object(0, obj).unwrap();
+        assert!(toi_value == toi_result);
+    }
+
+    #[test]
+    pub fn test_receiver_disable_received_once() {
+        crate::tests::init();
+
+        let max_transfert_count = 5usize;
+        let oti: flute::core::Oti = Default::default();
+        let content_type = "application/octet-stream";
+
+        let (mut obj, _) = create_object(
+            100000,
+            content_type,
+            flute::core::lct::Cenc::Null,
+            true,
+            None,
+            None,
+        );
+        obj.max_transfer_count = max_transfert_count as u32;
+        let output = Rc::new(receiver::writer::ObjectWriterBufferBuilder::new(true));
+        let mut receiver_config = receiver::Config::default();
+        receiver_config.object_receive_once = false;
+        let mut receiver =
+            receiver::MultiReceiver::new(output.clone(), Some(receiver_config), false);
+
+        let mut sender = create_sender(vec![obj], &oti, flute::core::lct::Cenc::Null, None);
+
+        let endpoint = UDPEndpoint::new(None, "224.0.0.1".to_owned(), 5000);
+
+        loop {
+            let now_sender = std::time::SystemTime::now();
+            let data = sender.read(now_sender);
+            if data.is_none() {
+                break;
+            }
+
+            // Simulate reception 60s later -> FDT should be expired
+            let now_receiver = std::time::SystemTime::now() + std::time::Duration::from_secs(60);
+            receiver
+                .push(&endpoint, data.as_ref().unwrap(), now_receiver)
+                .unwrap();
+            receiver.cleanup(now_receiver);
+        }
+
+        let nb_complete_objects = output
+            .as_ref()
+            .objects
+            .borrow()
+            .iter()
+            .filter(|&obj| obj.borrow().complete)
+            .count();
+
+        let nb_error_objects = output
+            .as_ref()
+            .objects
+            .borrow()
+            .iter()
+            .filter(|&obj| obj.borrow().error)
+            .count();
+        assert!(nb_complete_objects == max_transfert_count);
+        assert!(nb_error_objects == 0);
+    }
+
+    fn test_receiver_fec_cache(fec_cache: sender::FecCache) {
+        init();
+
+        let max_transfert_count = 3usize;
+        let oti: flute::core::Oti = flute::core::Oti::new_raptorq(1400, 64, 20, 1, 4).unwrap();
+        let content_type = "application/octet-stream";
+
+        let (mut obj, buffer) = create_object(
+            200000,
+            content_type,
+            flute::core::lct::Cenc::Null,
+            true,
+            None,
+            None,
+        );
+        obj.max_transfer_count = max_transfert_count as u32;
+        obj.fec_cache = Some(fec_cache);
+        let output = Rc::new(receiver::writer::ObjectWriterBufferBuilder::new(true));
+        let mut receiver_config = receiver::Config::default();
+        receiver_config.object_receive_once = false;
+        let mut receiver =
+            receiver::MultiReceiver::new(output.clone(), Some(receiver_config), false);
+
+        let mut sender = create_sender(vec![obj], &oti, flute::core::lct::Cenc::Null, None);
+
+        let endpoint = UDPEndpoint::new(None, "224.0.0.1".to_owned(), 5000);
+
+        loop {
+            let now_sender = std::time::SystemTime::now();
+            let data = sender.read(now_sender);
+            if data.is_none() {
+                break;
+            }
+
+            let now_receiver = std::time::SystemTime::now() + std::time::Duration::from_secs(60);
+            receiver
+                .push(&endpoint, data.as_ref().unwrap(), now_receiver)
+                .unwrap();
+            receiver.cleanup(now_receiver);
+        }
+
+        let objects = output.as_ref().objects.borrow();
+        assert!(objects.len() == max_transfert_count);
+        for obj in objects.iter() {
+            let obj = obj.borrow();
+            assert!(obj.complete);
+            assert!(!obj.error);
+            assert!(obj.data == buffer);
+        }
+    }
+
+    #[test]
+    pub fn test_receiver_fec_cache_memory() {
+        test_receiver_fec_cache(sender::FecCache::Memory);
+    }
+
+    #[test]
+    pub fn test_receiver_fec_cache_file() {
+        test_receiver_fec_cache(sender::FecCache::File(std::env::temp_dir()));
+    }
+
+    #[test]
+    pub fn test_receiver_directory_sender() {
+        init();
+
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("sub")).unwrap();
+        let (buffer1, _) = create_file_buffer(10000);
+        let (buffer2, _) = create_file_buffer(20000);
+        std::fs::write(dir.path().join("file1.bin"), &buffer1).unwrap();
+        std::fs::write(dir.path().join("sub").join("file2.bin"), &buffer2).unwrap();
+
+        let endpoint = UDPEndpoint::new(None, "224.0.0.1".to_owned(), 5000);
+        let config = sender::DirectoryConfig {
+            path: dir.path().to_path_buf(),
+            base_url: url::Url::parse("http://localhost/data/").unwrap(),
+            ..Default::default()
+        };
+        let mut sender = sender::DirectorySender::new(
+            endpoint.clone(),
+            1,
+            &Default::default(),
+            &Default::default(),
+            config,
+        )
+        .unwrap();
+
+        let output = Rc::new(receiver::writer::ObjectWriterBufferBuilder::new(true));
+        let mut receiver = receiver::MultiReceiver::new(output.clone(), None, false);
+
+        loop {
+            let now = std::time::SystemTime::now();
+            let data = sender.read(now);
+            if data.is_none() {
+                break;
+            }
+            receiver
+                .push(&endpoint, data.as_ref().unwrap(), now)
+                .unwrap();
+            receiver.cleanup(now);
+        }
+
+        assert!(sender.nb_files() == 2);
+        let objects = output.as_ref().objects.borrow();
+        assert!(objects.len() == 2);
+        for obj in objects.iter() {
+            let obj = obj.borrow();
+            assert!(obj.complete);
+            assert!(!obj.error);
+            assert!(obj.meta.e_tag.is_some());
+            match obj.meta.content_location.as_str() {
+                "http://localhost/data/file1.bin" => assert!(obj.data == buffer1),
+                "http://localhost/data/sub/file2.bin" => assert!(obj.data == buffer2),
+                _ => panic!("Unexpected object {}", obj.meta.content_location),
+            }
+        }
+    }
+
+    #[test]
+    pub fn test_receiver_mirror() {
+        init();
+
+        let source_dir = tempfile::tempdir().unwrap();
+        let mirror_dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(source_dir.path().join("sub")).unwrap();
+        std::fs::write(source_dir.path().join("file1.txt"), b"file1").unwrap();
+        std::fs::write(source_dir.path().join("sub").join("file2.txt"), b"file2").unwrap();
+
+        let endpoint = UDPEndpoint::new(None, "224.0.0.1".to_owned(), 5000);
+        let config = sender::DirectoryConfig {
+            path: source_dir.path().to_path_buf(),
+            ..Default::default()
+        };
+        let mut sender = sender::DirectorySender::new(
+            endpoint.clone(),
+            1,
+            &Default::default(),
+            &Default::default(),
+            config,
+        )
+        .unwrap();
+
+        let writer = Rc::new(
+            receiver::writer::ObjectWriterMirrorBuilder::new(
+                mirror_dir.path(),
+                true,
+                receiver::writer::MirrorRemovePolicy::Delete,
+            )
+            .unwrap(),
+        );
+        let mut receiver = receiver::MultiReceiver::new(writer, None, false);
+
+        let mut transfer = |sender: &mut sender::DirectorySender| loop {
+            let now = std::time::SystemTime::now();
+            let data = sender.read(now);
+            if data.is_none() {
+                break;
+            }
+            receiver
+                .push(&endpoint, data.as_ref().unwrap(), now)
+                .unwrap();
+            receiver.cleanup(now);
+        };
+
+        transfer(&mut sender);
+        assert!(std::fs::read(mirror_dir.path().join("file1.txt")).unwrap() == b"file1");
+        assert!(
+            std::fs::read(mirror_dir.path().join("sub").join("file2.txt")).unwrap() == b"file2"
+        );
+
+        // Delete a file and update the other one
+        std::fs::remove_file(source_dir.path().join("file1.txt")).unwrap();
+        std::fs::write(source_dir.path().join("sub").join("file2.txt"), b"file2 v2").unwrap();
+        sender.scan(std::time::SystemTime::now()).unwrap();
+        transfer(&mut sender);
+
+        assert!(!mirror_dir.path().join("file1.txt").exists());
+        assert!(
+            std::fs::read(mirror_dir.path().join("sub").join("file2.txt")).unwrap() == b"file2 v2"
+        );
+        let nb_files = std::fs::read_dir(mirror_dir.path().join("sub"))
+            .unwrap()
+            .count();
+        assert!(nb_files == 1);
+    }
+
+    fn write_mpd(dir: &std::path::Path, start_number: u32, nb_segments: u32) {
+        let mpd = format!(
+            r#"<?xml version="1.0"?>
+<MPD xmlns="urn:mpeg:dash:schema:mpd:2011" type="dynamic">
+  <Period id="0">
+    <AdaptationSet mimeType="video/mp4">
+      <SegmentTemplate timescale="1000" initialization="$RepresentationID$/init.mp4" media="$RepresentationID$/$Number$.m4s" startNumber="{}">
+        <SegmentTimeline>
+          <S d="100" r="{}"/>
+        </SegmentTimeline>
+      </SegmentTemplate>
+      <Representation id="video" bandwidth="1000000"/>
+    </AdaptationSet>
+  </Period>
+</MPD>"#,
+            start_number,
+            nb_segments - 1
+        );
+        std::fs::write(dir.join("live.mpd"), mpd).unwrap();
+    }
+
+    #[test]
+    pub fn test_receiver_dash_segments() {
+        init();
+
+        let source_dir = tempfile::tempdir().unwrap();
+        let player_dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(source_dir.path().join("video")).unwrap();
+        std::fs::write(source_dir.path().join("video/init.mp4"), b"init").unwrap();
+        for number in 1..=4 {
+            let content = format!("segment {}", number);
+            std::fs::write(source_dir.path().join(format!("video/{}.m4s", number)), content)
+                .unwrap();
+        }
+        write_mpd(source_dir.path(), 1, 3);
+
+        let base_url = url::Url::parse("http://localhost/live/").unwrap();
+        let endpoint = UDPEndpoint::new(None, "224.0.0.1".to_owned(), 5000);
+        let config = sender::SegmentConfig {
+            manifest: source_dir.path().join("live.mpd"),
+            base_url: base_url.clone(),
+            ..Default::default()
+        };
+        let mut sender = sender::SegmentSender::new(
+            endpoint.clone(),
+            1,
+            &Default::default(),
+            &Default::default(),
+            config,
+        )
+        .unwrap();
+
+        let writer = Rc::new(
+            receiver::writer::ObjectWriterSegmentBuilder::new(
+                player_dir.path(),
+                Some(&base_url),
+                2,
+                true,
+            )
+            .unwrap(),
+        );
+        let mut receiver = receiver::MultiReceiver::new(writer, None, false);
+
+        let player = player_dir.path().to_path_buf();
+        let source = source_dir.path().to_path_buf();
+        let received = |segment: &str| {
+            player.join(segment).exists()
+                && std::fs::read(player.join("live.mpd")).ok()
+                    == std::fs::read(source.join("live.mpd")).ok()
+        };
+
+        // Media segments are paced by their target acquisition, the sender is read until the last segment is received
+        let mut transfer = |sender: &mut sender::SegmentSender, last_segment: &str| {
+            let start = std::time::Instant::now();
+            while !received(last_segment) {
+                assert!(start.elapsed() < std::time::Duration::from_secs(5));
+                let now = std::time::SystemTime::now();
+                match sender.read(now) {
+                    Some(data) => receiver.push(&endpoint, &data, now).unwrap(),
+                    None => std::thread::sleep(std::time::Duration::from_millis(1)),
+                }
+                receiver.cleanup(now);
+            }
+        };
+
+        transfer(&mut sender, "video/3.m4s");
+        assert!(std::fs::read(player.join("video/init.mp4")).unwrap() == b"init");
+        assert!(!player.join("video/1.m4s").exists());
+        assert!(std::fs::read(player.join("video/3.m4s")).unwrap() == b"segment 3");
+
+        // Live edge advances
+        write_mpd(source_dir.path(), 2, 3);
+        sender.ingest(std::time::SystemTime::now()).unwrap();
+        transfer(&mut sender, "video/4.m4s");
+        assert!(!player.join("video/2.m4s").exists());
+        assert!(std::fs::read(player.join("video/4.m4s")).unwrap() == b"segment 4");
+    }
+
+    #[test]
+    pub fn test_receiver_split_large_object() {
+        init();
+
+        let oti: flute::core::Oti = flute::core::Oti::new_raptorq(64, 4, 2, 1, 4).unwrap();
+        let (obj, buffer) = create_object(
+            200000,
+            "application/octet-stream",
+            flute::core::lct::Cenc::Null,
+            true,
+            None,
+            None,
+        );
+        let content_location = obj.content_location.to_string();
+        assert!(buffer.len() > oti.max_transfer_length());
+
+        let output = Rc::new(receiver::writer::ObjectWriterBufferBuilder::new(true));
+        let mut receiver = receiver::MultiReceiver::new(output.clone(), None, false);
+
+        let sender_config = sender::Config {
+            split_large_objects: true,
+            ..Default::default()
+        };
+        let mut sender = create_sender(
+            vec![obj],
+            &oti,
+            flute::core::lct::Cenc::Null,
+            Some(sender_config),
+        );
+        assert!(sender.nb_objects() > 1);
+
+        let endpoint = UDPEndpoint::new(None, "224.0.0.1".to_owned(), 5000);
+        loop {
+            let now = std::time::SystemTime::now();
+            let data = sender.read(now);
+            if data.is_none() {
+                break;
+            }
+            receiver
+                .push(&endpoint, data.as_ref().unwrap(), now)
+                .unwrap();
+            receiver.cleanup(now);
+        }
+
+        let objects = output.as_ref().objects.borrow();
+        assert!(objects.len() == 1);
+        let obj = objects[0].borrow();
+        assert!(obj.complete);
+        assert!(!obj.error);
+        assert!(obj.meta.content_location == content_location);
+        assert!(obj.meta.content_length == Some(buffer.len()));
+        assert!(obj.data == buffer);
+    }
+
+    #[test]
+    pub fn test_sender_compressed_stream_temp_dir() {
+        init();
+
+        let (buffer, content_location) = create_file_buffer(100000);
+        let dir = tempfile::tempdir().unwrap();
+        let nb_temp_files = || std::fs::read_dir(dir.path()).unwrap().count();
+
+        let obj = sender::ObjectDesc::create_from_compressed_stream(
+            Box::new(std::io::Cursor::new(buffer)),
+            "application/octet-stream",
+            &content_location,
+            sender::CompressedStreamOptions {
+                cenc: flute::core::lct::Cenc::Gzip,
+                temp_dir: Some(dir.path().to_path_buf()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert!(nb_temp_files() == 1);
+
+        drop(obj);
+        assert!(nb_temp_files() == 0);
+    }
+
+    fn test_receiver_live_stream(oti: &flute::core::Oti, length: usize, with_loss: bool) {
+        init();
+
+        let (buffer, content_location) = create_file_buffer(length);
+        let stream = sender::LiveStream::new();
+        let obj = sender::ObjectDesc::create_from_live_stream(
+            stream.clone(),
+            "text/plain",
+            &content_location,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+        let output = Rc::new(receiver::writer::ObjectWriterBufferBuilder::new(true));
+        let mut receiver = receiver::MultiReceiver::new(output.clone(), None, false);
+        let mut sender = create_sender(vec![obj], oti, flute::core::lct::Cenc::Null, None);
+
+        let endpoint = UDPEndpoint::new(None, "224.0.0.1".to_owned(), 5000);
+        let mut produced = 0;
+        let mut nb_tol = 0;
+        let mut i = 0u32;
+        loop {
+            // Produce the content of the object while it is being transferred
+            if i % 4 == 0 && produced < buffer.len() {
+                let end = (produced + 700).min(buffer.len());
+                stream.write(&buffer[produced..end]).unwrap();
+                produced = end;
+                if produced == buffer.len() {
+                    stream.finish();
+                }
+            }
+
+            let now = std::time::SystemTime::now();
+            let data = sender.read(now);
+            if data.is_none() && stream.is_finished() && sender.get_objects_in_fdt().is_empty() {
+                break;
+            }
+
+            if let Some(data) = data {
+                let alc = flute::core::alc::parse_alc_pkt(&data).unwrap();
+                if alc.lct.toi != 0 {
+                    if let Some(tol) = flute::core::lct::get_tol(&data, &alc.lct).unwrap() {
+                        nb_tol += 1;
+                        assert!(tol == buffer.len() as u64);
+                    }
+                }
+
+                if with_loss && (i & 7) == 0 {
+                    log::info!("ALC pkt {} is lost", i)
+                } else {
+                    receiver.push(&endpoint, &data, now).unwrap();
+                }
+            }
+            receiver.cleanup(now);
+            i += 1;
+        }
+
+        let objects = output.as_ref().objects.borrow();
+        assert!(objects.len() == 1);
+        let obj = objects[0].borrow();
+        assert!(obj.complete);
+        assert!(!obj.error);
+        assert!(obj.meta.content_location == content_location.to_string());
+        assert!(obj.data == buffer);
+        // The packets sent after the end of the live stream carry EXT_TOL
+        assert!(nb_tol > 0);
+    }
+
+    #[test]
+    pub fn test_receiver_live_stream_no_code() {
+        test_receiver_live_stream(&flute::core::Oti::new_no_code(1000, 4), 25000, false);
+    }
+
+    #[test]
+    pub fn test_receiver_live_stream_end_on_block_boundary() {
+        // The last packet does not carry the transfer length, it is received from the FDT
+        test_receiver_live_stream(&flute::core::Oti::new_no_code(1000, 4), 24000, false);
+    }
+
+    #[test]
+    pub fn test_receiver_live_stream_reed_solomon_gf28_loss() {
+        let oti = flute::core::Oti::new_reed_solomon_rs28(1000, 4, 4).unwrap();
+        test_receiver_live_stream(&oti, 25000, true);
+    }
+
+    #[test]
+    pub fn test_sender_live_stream_raptorq() {
+        init();
+        let oti = flute::core::Oti::new_raptorq(1000, 4, 4, 1, 4).unwrap();
+        let obj = sender::ObjectDesc::create_from_live_stream(
+            sender::LiveStream::new(),
+            "text/plain",
+            &url::Url::parse("file:///live.txt").unwrap(),
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+        let endpoint = UDPEndpoint::new(None, "224.0.0.1".to_owned(), 5000);
+        let mut sender = sender::Sender::new(endpoint, 1, &oti, &Default::default());
+        assert!(sender.add_object(0, obj).is_err());
+    }
+
+    fn test_sender_repair_symbols_rotation(oti: flute::core::Oti) {
+        init();
+
+        let max_transfert_count = 3usize;
+        let (mut obj, buffer) = create_object(
+            100000,
+            "application/octet-stream",
+            flute::core::lct::Cenc::Null,
+            true,
+            None,
+            None,
+        );
+        obj.max_transfer_count = max_transfert_count as u32;
+        let output = Rc::new(receiver::writer::ObjectWriterBufferBuilder::new(true));
+        let mut receiver_config = receiver::Config::default();
+        receiver_config.object_receive_once = false;
+        let mut receiver =
+            receiver::MultiReceiver::new(output.clone(), Some(receiver_config), false);
+        let mut sender = create_sender(vec![obj], &oti, flute::core::lct::Cenc::Null, None);
+        let endpoint = UDPEndpoint::new(None, "224.0.0.1".to_owned(), 5000);
+
+        let nb_source_symbols = num_integer::div_ceil(buffer.len(), 1400);
+        let mut symbols = std::collections::HashSet::new();
+        let mut nb_symbols = 0;
+        loop {
+            let now = std::time::SystemTime::now();
+            let data = match sender.read(now) {
+                Some(data) => data,
+                None => break,
+            };
+
+            let alc = flute::core::alc::parse_alc_pkt(&data).unwrap();
+            if alc.lct.toi != 0 {
+                let payload_id = flute::core::alc::parse_payload_id(&alc, &oti).unwrap();
+                symbols.insert((payload_id.sbn, payload_id.esi));
+                nb_symbols += 1;
+            }
+
+            let now_receiver = now + std::time::Duration::from_secs(60);
+            receiver.push(&endpoint, &data, now_receiver).unwrap();
+            receiver.cleanup(now_receiver);
+        }
+
+        // Only the source symbols are repeated, each transfer sends new repair symbols
+        assert!(nb_symbols - symbols.len() == (max_transfert_count - 1) * nb_source_symbols);
+
+        let objects = output.as_ref().objects.borrow();
+        assert!(objects.len() == max_transfert_count);
+        for obj in objects.iter() {
+            let obj = obj.borrow();
+            assert!(obj.complete);
+            assert!(!obj.error);
+            assert!(obj.data == buffer);
+        }
+    }
+
+    #[test]
+    pub fn test_sender_repair_symbols_rotation_raptorq() {
+        let oti = flute::core::Oti::new_raptorq(1400, 64, 20, 1, 4).unwrap();
+        test_sender_repair_symbols_rotation(oti);
+    }
+
+    #[test]
+    pub fn test_sender_repair_symbols_rotation_raptor() {
+        let oti = flute::core::Oti::new_raptor(1400, 64, 20, 1, 4).unwrap();
+        test_sender_repair_symbols_rotation(oti);
+    }
+
+    #[test]
+    pub fn test_receiver_planner_oti() {
+        init();
+        let mtu = 1500;
+        let transfer_length = 500000;
+        let endpoint = UDPEndpoint::new(None, "224.0.0.1".to_owned(), 5000);
+        let candidates = flute::core::planner::plan(
+            transfer_length as u64,
+            &endpoint,
+            mtu,
+            0.1,
+            flute::core::FECEncodingID::RaptorQ,
+        )
+        .unwrap();
+        let candidate = candidates.last().unwrap();
+        assert!(candidate.nb_blocks > 1);
+        let oti = candidate.oti.clone();
+
+        let (obj, buffer) = create_object(
+            transfer_length,
+            "application/octet-stream",
+            flute::core::lct::Cenc::Null,
+            true,
+            None,
+            None,
+        );
+        let output = Rc::new(receiver::writer::ObjectWriterBufferBuilder::new(true));
+        let mut receiver = receiver::MultiReceiver::new(output.clone(), None, false);
+        let mut sender = create_sender(vec![obj], &oti, flute::core::lct::Cenc::Null, None);
+
+        let mut nb_packets = 0;
+        loop {
+            let now = std::time::SystemTime::now();
+            let data = match sender.read(now) {
+                Some(data) => data,
+                None => break,
+            };
+
+            let header_length = flute::core::planner::ip_udp_header_length(&endpoint).unwrap();
+            assert!(data.len() + header_length <= mtu);
+            let alc = flute::core::alc::parse_alc_pkt(&data).unwrap();
+            if alc.lct.toi != 0 {
+                nb_packets += 1;
+            }
+            receiver.push(&endpoint, &data, now).unwrap();
+        }
+
+        assert!(nb_packets == candidate.nb_packets);
+        let objects = output.as_ref().objects.borrow();
+        assert!(objects.len() == 1);
+        let obj = objects[0].borrow();
+        assert!(obj.complete);
+        assert!(obj.data == buffer);
+    }
+
+    fn test_route(inband_efdt: bool) {
+        use flute::route;
+
+        init();
+        let endpoint = UDPEndpoint::new(None, "239.255.1.1".to_owned(), 5000);
+        let config = route::SenderConfig {
+            max_payload_size: 500,
+            inband_efdt,
+            file_template: Some("video/segment-$TOI$.m4s".to_string()),
+        };
+        let mut sender = route::RouteSender::new(10, &config);
+
+        let mut init = vec![0u8; 1200];
+        rand::rng().fill_bytes(&mut init);
+        let mut segment = vec![0u8; 4321];
+        rand::rng().fill_bytes(&mut segment);
+        let presentation_time = std::time::SystemTime::UNIX_EPOCH
+            + std::time::Duration::from_secs(1_700_000_000);
+
+        sender
+            .add_object(route::RouteObject::new(
+                init.clone(),
+                "video/init.mp4",
+                Some("video/mp4"),
+                route::CodePoint::InitSegmentTimelineChanged,
+                None,
+            ))
+            .unwrap();
+        let toi = sender
+            .add_object(route::RouteObject::new(
+                segment.clone(),
+                "video/segment-2.m4s",
+                None,
+                route::CodePoint::MediaSegmentFileMode,
+                Some(presentation_time),
+            ))
+            .unwrap();
+        assert!(toi == 2);
+
+        let stsid = route::Stsid::new(vec![route::Rs {
+            s_ip_addr: None,
+            d_ip_addr: Some(endpoint.destination_group_address.clone()),
+            d_port: Some(endpoint.port),
+            ls: vec![route::Ls {
+                tsi: 10,
+                bw: None,
+                start_time: None,
+                end_time: None,
+                src_flow: Some(route::SrcFlow {
+                    rt: Some(true),
+                    min_buff_size: None,
+                    efdt: match inband_efdt {
+                        true => None,
+                        false => Some(sender.efdt().clone()),
+                    },
+                    content_info: None,
+                    payload: vec![],
+                }),
+            }],
+        }]);
+        let stsid = route::Stsid::parse(&stsid.to_xml().unwrap()).unwrap();
+
+        let output = Rc::new(receiver::writer::ObjectWriterBufferBuilder::new(true));
+        let mut receiver =
+            route::RouteReceiver::new(output.clone(), Some(stsid), &Default::default());
+
+        let now = std::time::SystemTime::now();
+        let mut packets = Vec::new();
+        while let Some(pkt) = sender.read(now) {
+            packets.push(pkt);
+        }
+        assert!(sender.nb_objects() == 0);
+
+        // Byte ranges are received out of order, the in-band EFDT is received last
+        packets.reverse();
+        for pkt in &packets {
+            receiver.push(&endpoint, pkt, now).unwrap();
+        }
+
+        // Packets of channels that are not described by the S-TSID are ignored
+        let unknown = route::new_route_pkt(11, &1u128, 8, 0, 3, None, true, &[1, 2, 3]).unwrap();
+        receiver.push(&endpoint, &unknown, now).unwrap();
+
+        let objects = output.as_ref().objects.borrow();
+        assert!(objects.len() == 2);
+        let init_obj = objects
+            .iter()
+            .find(|o| o.borrow().meta.content_location == "video/init.mp4")
+            .unwrap()
+            .borrow();
+        assert!(init_obj.complete);
+        assert!(init_obj.data == init);
+        assert!(init_obj.meta.content_type.as_deref() == Some("video/mp4"));
+
+        let segment_obj = objects
+            .iter()
+            .find(|o| o.borrow().meta.content_location == "video/segment-2.m4s")
+            .unwrap()
+            .borrow();
+        assert!(segment_obj.complete);
+        assert!(segment_obj.data == segment);
+        assert!(segment_obj.meta.presentation_time == Some(presentation_time));
+    }
+
+    #[test]
+    pub fn test_route_inband_efdt() {
+        test_route(true);
+    }
+
+    #[test]
+    pub fn test_route_stsid_efdt() {
+        test_route(false);
+    }
+
+    #[test]
+    pub fn test_route_pkt_error() {
+        use flute::route;
+
+        init();
+        let mut sender = route::RouteSender::new(10, &Default::default());
+        // A presentation time before the UNIX epoch cannot be converted to NTP
+        let presentation_time =
+            std::time::SystemTime::UNIX_EPOCH - std::time::Duration::from_secs(60);
+        for presentation_time in [Some(presentation_time), None] {
+            sender
+                .add_object(route::RouteObject::new(
+                    vec![1, 2, 3],
+                    "video/segment.m4s",
+                    None,
+                    route::CodePoint::MediaSegmentFileMode,
+                    presentation_time,
+                ))
+                .unwrap();
+        }
+
+        let now = std::time::SystemTime::now();
+        let mut tois = Vec::new();
+        while let Some(pkt) = sender.read(now) {
+            let route_pkt = route::parse_route_pkt(&pkt).unwrap();
+            tois.push(route_pkt.lct.toi);
+        }
+
+        // The transfer that fails is skipped, the EFDT and the next object are sent
+        assert!(tois == vec![0, 2]);
+        assert!(sender.nb_objects() == 0);
+    }
+
+    #[test]
+    pub fn test_capture_replay() {
+        init();
+        let oti: flute::core::Oti = flute::core::Oti::new_raptorq(1400, 64, 20, 1, 4).unwrap();
+        let content_type = "application/octet-stream";
+        let (obj, input_file_buffer) = create_object(
+            100000,
+            content_type,
+            flute::core::lct::Cenc::Null,
+            true,
+            None,
+            None,
+        );
+        let input_content_location = obj.content_location.clone();
+        let mut sender = create_sender(vec![obj], &oti, flute::core::lct::Cenc::Null, None);
+
+        let mut capture = flute::capture::PcapngWriter::new(Vec::new()).unwrap();
+        let mut now = std::time::SystemTime::now();
+        let mut nb_packets = 0;
+        while let Some(pkt) = sender.read(now) {
+            capture
+                .write_packet(sender.get_udp_endpoint(), &pkt, now)
+                .unwrap();
+            now += std::time::Duration::from_millis(1);
+            nb_packets += 1;
+        }
+        let capture = capture.into_inner();
+
+        let output = Rc::new(receiver::writer::ObjectWriterBufferBuilder::new(true));
+        let mut receiver = receiver::MultiReceiver::new(output.clone(), None, false);
+        let mut reader = flute::capture::PcapReader::new(capture.as_slice()).unwrap();
+        let config = flute::capture::ReplayConfig {
+            speed: Some(100.0),
+            ..Default::default()
+        };
+        let stats = flute::capture::replay(&mut reader, &mut receiver, &config).unwrap();
+        assert_eq!(stats.nb_packets, nb_packets);
+        assert_eq!(stats.nb_errors, 0);
+        assert!(stats.last_timestamp.unwrap() > stats.first_timestamp.unwrap());
+
+        check_output(
+            &input_file_buffer,
+            input_content_location.as_str(),
+            content_type,
+            None,
+            &output,
+        );
+    }
+
+    #[test]
+    pub fn test_simulator_fec_recovery() {
+        init();
+        let otis = [
+            flute::core::Oti::new_reed_solomon_rs28(1400, 64, 20).unwrap(),
+            flute::core::Oti::new_reed_solomon_rs28_under_specified(1400, 64, 20).unwrap(),
+            flute::core::Oti::new_raptor(1400, 64, 20, 1, 4).unwrap(),
+            flute::core::Oti::new_raptorq(1400, 64, 20, 1, 4).unwrap(),
+        ];
+        let content_type = "application/octet-stream";
+
+        for oti in otis {
+            let (obj, input_file_buffer) = create_object(
+                100000,
+                content_type,
+                flute::core::lct::Cenc::Null,
+                true,
+                None,
+                None,
+            );
+            let input_content_location = obj.content_location.clone();
+            let mut sender = create_sender(vec![obj], &oti, flute::core::lct::Cenc::Null, None);
+            let output = Rc::new(receiver::writer::ObjectWriterBufferBuilder::new(true));
+            let mut receiver = receiver::MultiReceiver::new(output.clone(), None, false);
+
+            // 10% of losses by bursts of 2 packets, reordering and duplicates
+            let config = flute::simulator::SimulatorConfig {
+                channel: flute::simulator::ChannelConfig {
+                    loss: flute::simulator::LossModel::gilbert(0.1, 2.0),
+                    reorder: 0.05,
+                    duplicate: 0.02,
+                    jitter: std::time::Duration::from_millis(2),
+                    seed: 1234,
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            let stats = flute::simulator::simulate(&mut sender, &mut receiver, &config).unwrap();
+            log::info!("{:?} {:?}", oti.fec_encoding_id, stats);
+            assert!(stats.finished);
+            assert!(stats.channel.nb_lost > 0);
+
+            check_output(
+                &input_file_buffer,
+                input_content_location.as_str(),
+                content_type,
+                None,
+                &output,
+            );
+        }
+    }
 }